@@ -32,10 +32,24 @@ type AuthorizationPolicySpec struct {
 	// TargetRef references a resource to which the authorization policy applies.
 	TargetRef gatewayapiv1alpha2.PolicyTargetReference `json:"targetRef,omitempty"`
 
+	// TargetRefs references additional resources to which the authorization
+	// policy applies.
+	TargetRefs []gatewayapiv1alpha2.PolicyTargetReference `json:"targetRefs,omitempty"`
+
 	// RequiredAuthenticationRefs enumerates a set of required authentications
 	RequiredAuthenticationRefs []gatewayapiv1alpha2.PolicyTargetReference `json:"requiredAuthenticationRefs,omitempty"`
 }
 
+// Targets returns all of the resources to which the authorization policy
+// applies.
+func (s AuthorizationPolicySpec) Targets() []gatewayapiv1alpha2.PolicyTargetReference {
+	targets := make([]gatewayapiv1alpha2.PolicyTargetReference, 0, len(s.TargetRefs)+1)
+	if s.TargetRef.Kind != "" {
+		targets = append(targets, s.TargetRef)
+	}
+	return append(targets, s.TargetRefs...)
+}
+
 // +k8s:deepcopy-gen:interfaces=k8s.io/apimachinery/pkg/runtime.Object
 
 // AuthorizationPolicyList is a list of AuthorizationPolicy resources.
@@ -91,6 +91,13 @@ func (in *AuthorizationPolicyList) DeepCopyObject() runtime.Object {
 func (in *AuthorizationPolicySpec) DeepCopyInto(out *AuthorizationPolicySpec) {
 	*out = *in
 	in.TargetRef.DeepCopyInto(&out.TargetRef)
+	if in.TargetRefs != nil {
+		in, out := &in.TargetRefs, &out.TargetRefs
+		*out = make([]v1alpha2.PolicyTargetReference, len(*in))
+		for i := range *in {
+			(*in)[i].DeepCopyInto(&(*out)[i])
+		}
+	}
 	if in.RequiredAuthenticationRefs != nil {
 		in, out := &in.RequiredAuthenticationRefs, &out.RequiredAuthenticationRefs
 		*out = make([]v1alpha2.PolicyTargetReference, len(*in))
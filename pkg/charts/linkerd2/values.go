@@ -77,7 +77,7 @@ type (
 		DebugContainer   *DebugContainer   `json:"debugContainer"`
 		ProxyInjector    *ProxyInjector    `json:"proxyInjector"`
 		ProfileValidator *Webhook          `json:"profileValidator"`
-		PolicyValidator  *Webhook          `json:"policyValidator"`
+		PolicyValidator  *PolicyWebhook    `json:"policyValidator"`
 		NodeSelector     map[string]string `json:"nodeSelector"`
 		Tolerations      []interface{}     `json:"tolerations"`
 
@@ -322,6 +322,12 @@ type (
 		NamespaceSelector *metav1.LabelSelector `json:"namespaceSelector"`
 	}
 
+	// PolicyWebhook Helm variables for the policy admission webhooks
+	PolicyWebhook struct {
+		Webhook
+		Defaulting bool `json:"defaulting,omitempty"`
+	}
+
 	// TLS has a pair of PEM-encoded key and certificate variables used in the
 	// Helm templates
 	TLS struct {
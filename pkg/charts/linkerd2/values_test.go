@@ -251,7 +251,7 @@ func TestNewValues(t *testing.T) {
 
 		ProxyInjector:    &ProxyInjector{Webhook: Webhook{TLS: &TLS{}, NamespaceSelector: namespaceSelectorInjector}},
 		ProfileValidator: &Webhook{TLS: &TLS{}, NamespaceSelector: namespaceSelectorSimple},
-		PolicyValidator:  &Webhook{TLS: &TLS{}, NamespaceSelector: namespaceSelectorSimple},
+		PolicyValidator:  &PolicyWebhook{Webhook: Webhook{TLS: &TLS{}, NamespaceSelector: namespaceSelectorSimple}},
 	}
 
 	// pin the versions to ensure consistent test result.
@@ -98,21 +98,36 @@ func AuthorizationsForResource(ctx context.Context, k8sAPI *KubernetesAPI, names
 	allServersInNamespace := map[string]*serverv1beta2.ServerList{}
 
 	for _, p := range policies.Items {
-		target := p.Spec.TargetRef
-		if target.Kind == NamespaceKind && target.Group == K8sCoreAPIGroup {
-			serverList, ok := allServersInNamespace[p.Namespace]
-			if !ok {
-				serverList, err = k8sAPI.L5dCrdClient.ServerV1beta2().Servers(p.Namespace).List(ctx, metav1.ListOptions{})
+		for _, target := range p.Spec.Targets() {
+			if target.Kind == NamespaceKind && target.Group == K8sCoreAPIGroup {
+				serverList, ok := allServersInNamespace[p.Namespace]
+				if !ok {
+					serverList, err = k8sAPI.L5dCrdClient.ServerV1beta2().Servers(p.Namespace).List(ctx, metav1.ListOptions{})
+					if err != nil {
+						fmt.Fprintf(os.Stderr, "Failed to get Servers for Namespace/%s: %s\n", p.Namespace, err)
+						continue
+					}
+
+					allServersInNamespace[p.Namespace] = serverList
+				}
+
+				for _, server := range serverList.Items {
+					if serverIncludesPod(server, pods) {
+						results = append(results, Authorization{
+							Route:               "",
+							Server:              server.GetName(),
+							ServerAuthorization: "",
+							AuthorizationPolicy: p.GetName(),
+						})
+					}
+				}
+			} else if target.Kind == ServerKind && target.Group == PolicyAPIGroup {
+				server, err := k8sAPI.L5dCrdClient.ServerV1beta2().Servers(p.Namespace).Get(ctx, string(target.Name), metav1.GetOptions{})
 				if err != nil {
-					fmt.Fprintf(os.Stderr, "Failed to get Servers for Namespace/%s: %s\n", p.Namespace, err)
+					fmt.Fprintf(os.Stderr, "AuthorizationPolicy/%s targets Server/%s but we failed to get it: %s\n", p.Name, target.Name, err)
 					continue
 				}
-
-				allServersInNamespace[p.Namespace] = serverList
-			}
-
-			for _, server := range serverList.Items {
-				if serverIncludesPod(server, pods) {
+				if serverIncludesPod(*server, pods) {
 					results = append(results, Authorization{
 						Route:               "",
 						Server:              server.GetName(),
@@ -120,42 +135,28 @@ func AuthorizationsForResource(ctx context.Context, k8sAPI *KubernetesAPI, names
 						AuthorizationPolicy: p.GetName(),
 					})
 				}
-			}
-		} else if target.Kind == ServerKind && target.Group == PolicyAPIGroup {
-			server, err := k8sAPI.L5dCrdClient.ServerV1beta2().Servers(p.Namespace).Get(ctx, string(target.Name), metav1.GetOptions{})
-			if err != nil {
-				fmt.Fprintf(os.Stderr, "AuthorizationPolicy/%s targets Server/%s but we failed to get it: %s\n", p.Name, target.Name, err)
-				continue
-			}
-			if serverIncludesPod(*server, pods) {
-				results = append(results, Authorization{
-					Route:               "",
-					Server:              server.GetName(),
-					ServerAuthorization: "",
-					AuthorizationPolicy: p.GetName(),
-				})
-			}
-		} else if target.Kind == HTTPRouteKind && target.Group == PolicyAPIGroup {
-			route, err := k8sAPI.L5dCrdClient.PolicyV1alpha1().HTTPRoutes(p.Namespace).Get(ctx, string(target.Name), metav1.GetOptions{})
-			if err != nil {
-				fmt.Fprintf(os.Stderr, "AuthorizationPolicy/%s targets HTTPRoute/%s but we failed to get it: %s\n", p.Name, target.Name, err)
-				continue
-			}
-			for _, parent := range route.Spec.ParentRefs {
-				if parent.Kind != nil && *parent.Kind == ServerKind &&
-					parent.Group != nil && *parent.Group == PolicyAPIGroup {
-					server, err := k8sAPI.L5dCrdClient.ServerV1beta2().Servers(p.Namespace).Get(ctx, string(parent.Name), metav1.GetOptions{})
-					if err != nil {
-						fmt.Fprintf(os.Stderr, "HTTPRoute/%s belongs to Server/%s but we failed to get it: %s\n", target.Name, parent.Name, err)
-						continue
-					}
-					if serverIncludesPod(*server, pods) {
-						results = append(results, Authorization{
-							Route:               route.GetName(),
-							Server:              server.GetName(),
-							ServerAuthorization: "",
-							AuthorizationPolicy: p.GetName(),
-						})
+			} else if target.Kind == HTTPRouteKind && target.Group == PolicyAPIGroup {
+				route, err := k8sAPI.L5dCrdClient.PolicyV1alpha1().HTTPRoutes(p.Namespace).Get(ctx, string(target.Name), metav1.GetOptions{})
+				if err != nil {
+					fmt.Fprintf(os.Stderr, "AuthorizationPolicy/%s targets HTTPRoute/%s but we failed to get it: %s\n", p.Name, target.Name, err)
+					continue
+				}
+				for _, parent := range route.Spec.ParentRefs {
+					if parent.Kind != nil && *parent.Kind == ServerKind &&
+						parent.Group != nil && *parent.Group == PolicyAPIGroup {
+						server, err := k8sAPI.L5dCrdClient.ServerV1beta2().Servers(p.Namespace).Get(ctx, string(parent.Name), metav1.GetOptions{})
+						if err != nil {
+							fmt.Fprintf(os.Stderr, "HTTPRoute/%s belongs to Server/%s but we failed to get it: %s\n", target.Name, parent.Name, err)
+							continue
+						}
+						if serverIncludesPod(*server, pods) {
+							results = append(results, Authorization{
+								Route:               route.GetName(),
+								Server:              server.GetName(),
+								ServerAuthorization: "",
+								AuthorizationPolicy: p.GetName(),
+							})
+						}
 					}
 				}
 			}
@@ -0,0 +1,481 @@
+//! Evaluates inbound policies as a proxy would, so that tools may check how
+//! a client's connections and requests are handled without sending traffic.
+//!
+//! Policies are evaluated against an [`InboundServer`], as resolved for a
+//! workload's port by the controller's indexes. Tools that start from a set of
+//! resources may resolve the server by populating an index with them (see
+//! `linkerd-policy-controller-k8s-index`), so that the controller's exact
+//! resolution logic is applied before evaluation. For example:
+//!
+//! ```
+//! use linkerd_policy_controller_core::{
+//!     evaluate::{self, Client, ClientTls, Decision},
+//!     inbound::{AuthorizationRef, ClientAuthentication, ClientAuthorization, InboundServer},
+//!     inbound::{ProxyProtocol, ServerRef},
+//! };
+//!
+//! let server = InboundServer {
+//!     reference: ServerRef::Server("web".to_string()),
+//!     protocol: ProxyProtocol::Http1,
+//!     authorizations: Some((
+//!         AuthorizationRef::AuthorizationPolicy("web-clients".to_string()),
+//!         ClientAuthorization {
+//!             networks: vec!["10.0.0.0/8".parse::<ipnet::IpNet>().unwrap().into()],
+//!             authentication: ClientAuthentication::TlsAuthenticated(vec![
+//!                 "*.emojivoto.serviceaccount.identity.linkerd.cluster.local".parse().unwrap(),
+//!             ]),
+//!         },
+//!     ))
+//!     .into_iter()
+//!     .collect(),
+//!     http_routes: Default::default(),
+//! };
+//!
+//! let client = Client {
+//!     addr: "10.1.2.3".parse().unwrap(),
+//!     tls: ClientTls::Authenticated(
+//!         "default.emojivoto.serviceaccount.identity.linkerd.cluster.local".to_string(),
+//!     ),
+//! };
+//! let req = http::Request::get("/api/vote").body(()).unwrap();
+//! assert!(evaluate::request(&server, &client, &req).is_allowed());
+//!
+//! let client = Client { tls: ClientTls::None, ..client };
+//! assert!(matches!(evaluate::connection(&server, &client), Decision::Deny { .. }));
+//! ```
+
+use crate::{
+    identity_match::IdentityMatch,
+    inbound::{
+        AuthorizationRef, ClientAuthentication, ClientAuthorization, HttpRoute, HttpRouteRef,
+        InboundServer,
+    },
+    network_match::NetworkMatch,
+    routes::{compile_regex, HeaderMatch, HttpRouteMatch, PathMatch, QueryParamMatch},
+};
+use ahash::AHashMap as HashMap;
+use serde::Serialize;
+use std::{cmp::Ordering, net::IpAddr};
+
+/// Describes a client of an inbound server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Client {
+    /// The client's address, as observed by the server's proxy.
+    pub addr: IpAddr,
+    pub tls: ClientTls,
+}
+
+/// Describes the TLS that a client's connection uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientTls {
+    /// The connection is not secured by the mesh.
+    None,
+
+    /// The connection uses TLS without a client identity.
+    Unauthenticated,
+
+    /// The connection uses mutual TLS with the given client identity.
+    Authenticated(String),
+}
+
+/// The outcome of evaluating a policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+pub enum Decision {
+    /// The client is authorized. Connections are not matched to a route, so
+    /// only requests have a route.
+    Allow {
+        route: Option<HttpRouteRef>,
+        authorization: AuthorizationRef,
+    },
+
+    /// The client is not authorized.
+    Deny { route: Option<HttpRouteRef> },
+
+    /// The request does not match any of the server's routes.
+    NotFound,
+}
+
+// === impl Decision ===
+
+impl Decision {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allow { .. })
+    }
+}
+
+/// Evaluates the server's policy for a connection, as is done for
+/// connections that are not handled as HTTP (e.g. opaque connections).
+pub fn connection(server: &InboundServer, client: &Client) -> Decision {
+    match authorize(&server.authorizations, client) {
+        Some(authorization) => Decision::Allow {
+            route: None,
+            authorization: authorization.clone(),
+        },
+        None => Decision::Deny { route: None },
+    }
+}
+
+/// Evaluates the server's policy for an HTTP request.
+///
+/// The request is matched to a route following the Gateway API's precedence
+/// rules. It is authorized by either the route's authorizations or the
+/// server's, which apply to all of its routes.
+pub fn request<B>(server: &InboundServer, client: &Client, req: &http::Request<B>) -> Decision {
+    let default_route;
+    let routes = if server.http_routes.is_empty() {
+        default_route = HttpRoute::default();
+        vec![(None, &default_route)]
+    } else {
+        server
+            .http_routes
+            .iter()
+            .map(|(r, route)| (Some(r), route))
+            .collect()
+    };
+
+    let Some((route_ref, route)) = route_for(routes, req) else {
+        return Decision::NotFound;
+    };
+    let route_ref = route_ref.cloned();
+    match authorize(&route.authorizations, client)
+        .or_else(|| authorize(&server.authorizations, client))
+    {
+        Some(authorization) => Decision::Allow {
+            route: route_ref,
+            authorization: authorization.clone(),
+        },
+        None => Decision::Deny { route: route_ref },
+    }
+}
+
+/// Selects the route with the most specific match for the request. Ties are
+/// broken by route age and then by name.
+fn route_for<'r, B>(
+    routes: Vec<(Option<&'r HttpRouteRef>, &'r HttpRoute)>,
+    req: &http::Request<B>,
+) -> Option<(Option<&'r HttpRouteRef>, &'r HttpRoute)> {
+    let host = req
+        .uri()
+        .host()
+        .or_else(|| req.headers().get(http::header::HOST)?.to_str().ok())
+        .map(|h| h.rsplit_once(':').map_or(h, |(h, _port)| h));
+
+    routes
+        .into_iter()
+        .filter(|(_, route)| {
+            route.hostnames.is_empty()
+                || host.map_or(false, |h| route.hostnames.iter().any(|m| m.matches(h)))
+        })
+        .filter_map(|(r, route)| {
+            let specificity = route
+                .rules
+                .iter()
+                .flat_map(|rule| rule.matches.iter())
+                .filter(|m| matches(m, req))
+                .map(Specificity::of)
+                .max()?;
+            Some((specificity, r, route))
+        })
+        .max_by(|(a_spec, a_ref, a), (b_spec, b_ref, b)| {
+            a_spec.cmp(b_spec).then_with(|| {
+                // Older routes, and then routes that sort first, take
+                // precedence.
+                let by_ts = match (&a.creation_timestamp, &b.creation_timestamp) {
+                    (Some(a_ts), Some(b_ts)) => b_ts.cmp(a_ts),
+                    (None, None) => Ordering::Equal,
+                    (Some(_), None) => Ordering::Greater,
+                    (None, Some(_)) => Ordering::Less,
+                };
+                by_ts.then_with(|| b_ref.cmp(a_ref))
+            })
+        })
+        .map(|(_, r, route)| (r, route))
+}
+
+/// Orders matches by the Gateway API's precedence rules: exact paths, then the
+/// longest path prefixes, then method matches, then the most header matches,
+/// then the most query parameter matches.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Specificity {
+    exact_path: bool,
+    path_len: usize,
+    method: bool,
+    headers: usize,
+    query_params: usize,
+}
+
+impl Specificity {
+    fn of(m: &HttpRouteMatch) -> Self {
+        let (exact_path, path_len) = match &m.path {
+            Some(PathMatch::Exact(path)) => (true, path.len()),
+            Some(PathMatch::Prefix(prefix)) => (false, prefix.len()),
+            Some(PathMatch::Regex(_)) | None => (false, 0),
+        };
+        Self {
+            exact_path,
+            path_len,
+            method: m.method.is_some(),
+            headers: m.headers.len(),
+            query_params: m.query_params.len(),
+        }
+    }
+}
+
+fn matches<B>(m: &HttpRouteMatch, req: &http::Request<B>) -> bool {
+    let path = req.uri().path();
+    let path_matches = match &m.path {
+        None => true,
+        Some(PathMatch::Exact(exact)) => path == exact,
+        Some(PathMatch::Prefix(prefix)) => {
+            let prefix = prefix.trim_end_matches('/');
+            path.strip_prefix(prefix)
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+        }
+        Some(PathMatch::Regex(regex)) => full_match(regex.as_str(), path),
+    };
+
+    let headers_match = m.headers.iter().all(|h| match h {
+        HeaderMatch::Exact(name, value) => req.headers().get_all(name).iter().any(|v| v == value),
+        HeaderMatch::Regex(name, regex) => req
+            .headers()
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| full_match(regex.as_str(), v)),
+    });
+
+    let query = req.uri().query().unwrap_or_default();
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|p| p.split_once('='))
+            .find_map(|(k, v)| (k == name).then_some(v))
+    };
+    let query_params_match = m.query_params.iter().all(|q| match q {
+        QueryParamMatch::Exact(name, value) => param(name) == Some(value.as_str()),
+        QueryParamMatch::Regex(name, regex) => {
+            param(name).map_or(false, |v| full_match(regex.as_str(), v))
+        }
+    });
+
+    let method_matches = m
+        .method
+        .as_ref()
+        .map_or(true, |method| req.method() == method);
+
+    path_matches && headers_match && query_params_match && method_matches
+}
+
+/// Returns true if the pattern matches the entire value, as proxies apply
+/// route regular expressions.
+fn full_match(pattern: &str, value: &str) -> bool {
+    compile_regex(&format!("^(?:{pattern})$")).map_or(false, |re| re.is_match(value))
+}
+
+/// Returns the first of the authorizations, by reference, that permits the
+/// client.
+fn authorize<'a>(
+    authorizations: &'a HashMap<AuthorizationRef, ClientAuthorization>,
+    client: &Client,
+) -> Option<&'a AuthorizationRef> {
+    let mut permitted = authorizations
+        .iter()
+        .filter(|(_, authz)| permits(authz, client))
+        .map(|(r, _)| r)
+        .collect::<Vec<_>>();
+    permitted.sort_by_key(|r| r.to_string());
+    permitted.into_iter().next()
+}
+
+/// Returns true if the authorization permits the client.
+pub fn permits(authz: &ClientAuthorization, client: &Client) -> bool {
+    let in_network = authz.networks.is_empty()
+        || authz
+            .networks
+            .iter()
+            .any(|n| network_contains(n, client.addr));
+    in_network
+        && match (&authz.authentication, &client.tls) {
+            (ClientAuthentication::Unauthenticated, _) => true,
+            (ClientAuthentication::TlsUnauthenticated, ClientTls::None) => false,
+            (ClientAuthentication::TlsUnauthenticated, _) => true,
+            (ClientAuthentication::TlsAuthenticated(ids), ClientTls::Authenticated(id)) => {
+                ids.iter().any(|m| identity_matches(m, id))
+            }
+            (ClientAuthentication::TlsAuthenticated(_), _) => false,
+        }
+}
+
+fn network_contains(m: &NetworkMatch, addr: IpAddr) -> bool {
+    m.net.contains(&addr) && !m.except.iter().any(|e| e.contains(&addr))
+}
+
+fn identity_matches(m: &IdentityMatch, id: &str) -> bool {
+    match m {
+        IdentityMatch::Exact(exact) => exact == id,
+        IdentityMatch::Suffix(suffix) => {
+            let mut labels = id.rsplit('.');
+            suffix
+                .iter()
+                .rev()
+                .all(|s| labels.next().map_or(false, |l| l == s))
+                && labels.next().is_some()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        inbound::{HttpRouteRule, ProxyProtocol, ServerRef},
+        routes::{GroupKindName, Method},
+    };
+
+    fn route(name: &str, matches: Vec<HttpRouteMatch>) -> (HttpRouteRef, HttpRoute) {
+        (
+            HttpRouteRef::Linkerd(GroupKindName {
+                group: "policy.linkerd.io".into(),
+                kind: "HTTPRoute".into(),
+                name: name.into(),
+            }),
+            HttpRoute {
+                hostnames: vec![],
+                rules: vec![HttpRouteRule {
+                    matches,
+                    filters: vec![],
+                }]
+                .into(),
+                authorizations: Default::default(),
+                creation_timestamp: None,
+            },
+        )
+    }
+
+    fn path(path: PathMatch) -> HttpRouteMatch {
+        HttpRouteMatch {
+            path: Some(path),
+            headers: vec![],
+            query_params: vec![],
+            method: None,
+        }
+    }
+
+    fn server(http_routes: HashMap<HttpRouteRef, HttpRoute>) -> InboundServer {
+        InboundServer {
+            reference: ServerRef::Server("srv".to_string()),
+            protocol: ProxyProtocol::Http1,
+            authorizations: Some((
+                AuthorizationRef::AuthorizationPolicy("mesh".to_string()),
+                ClientAuthorization {
+                    networks: vec![],
+                    authentication: ClientAuthentication::TlsAuthenticated(vec![
+                        "*.ns-0.serviceaccount.identity.linkerd.cluster.local"
+                            .parse()
+                            .unwrap(),
+                    ]),
+                },
+            ))
+            .into_iter()
+            .collect(),
+            http_routes,
+        }
+    }
+
+    fn client(tls: ClientTls) -> Client {
+        Client {
+            addr: "10.0.0.1".parse().unwrap(),
+            tls,
+        }
+    }
+
+    fn get(uri: &str) -> http::Request<()> {
+        http::Request::get(uri).body(()).unwrap()
+    }
+
+    #[test]
+    fn authorizes_clients() {
+        let server = server(Default::default());
+        let meshed = client(ClientTls::Authenticated(
+            "sa.ns-0.serviceaccount.identity.linkerd.cluster.local".to_string(),
+        ));
+        assert_eq!(
+            connection(&server, &meshed),
+            Decision::Allow {
+                route: None,
+                authorization: AuthorizationRef::AuthorizationPolicy("mesh".to_string()),
+            }
+        );
+
+        let other_ns = client(ClientTls::Authenticated(
+            "sa.ns-1.serviceaccount.identity.linkerd.cluster.local".to_string(),
+        ));
+        assert_eq!(
+            connection(&server, &other_ns),
+            Decision::Deny { route: None }
+        );
+        assert_eq!(
+            connection(&server, &client(ClientTls::Unauthenticated)),
+            Decision::Deny { route: None }
+        );
+    }
+
+    #[test]
+    fn selects_most_specific_route() {
+        let mut routes = HashMap::default();
+        let (prefix_ref, prefix) = route("prefix", vec![path(PathMatch::Prefix("/api".into()))]);
+        routes.insert(prefix_ref.clone(), prefix);
+        let (exact_ref, mut exact) = route(
+            "exact",
+            vec![HttpRouteMatch {
+                method: Some(Method::POST),
+                ..path(PathMatch::Exact("/api/vote".into()))
+            }],
+        );
+        exact.authorizations.insert(
+            AuthorizationRef::AuthorizationPolicy("public".to_string()),
+            ClientAuthorization {
+                networks: vec![],
+                authentication: ClientAuthentication::Unauthenticated,
+            },
+        );
+        routes.insert(exact_ref.clone(), exact);
+        let server = server(routes);
+        let unmeshed = client(ClientTls::None);
+
+        let post = http::Request::post("/api/vote").body(()).unwrap();
+        assert_eq!(
+            request(&server, &unmeshed, &post),
+            Decision::Allow {
+                route: Some(exact_ref),
+                authorization: AuthorizationRef::AuthorizationPolicy("public".to_string()),
+            }
+        );
+        assert_eq!(
+            request(&server, &unmeshed, &get("/api/vote")),
+            Decision::Deny {
+                route: Some(prefix_ref.clone())
+            }
+        );
+        assert_eq!(
+            request(&server, &unmeshed, &get("/api/votes")),
+            Decision::Deny {
+                route: Some(prefix_ref)
+            }
+        );
+        assert_eq!(
+            request(&server, &unmeshed, &get("/apis")),
+            Decision::NotFound
+        );
+    }
+
+    #[test]
+    fn matches_identity_suffixes() {
+        let m = "*.example.com".parse().unwrap();
+        assert!(identity_matches(&m, "foo.example.com"));
+        assert!(!identity_matches(&m, "example.com"));
+        assert!(!identity_matches(&m, "foo.example.org"));
+        assert!(identity_matches(&"*".parse().unwrap(), "foo"));
+    }
+}
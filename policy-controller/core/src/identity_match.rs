@@ -1,7 +1,8 @@
+use serde::Serialize;
 use std::{convert::Infallible, fmt, str::FromStr};
 
 /// Matches a client's mesh identity.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum IdentityMatch {
     /// An exact match.
     Exact(String),
@@ -10,9 +10,10 @@ use ahash::AHashMap as HashMap;
 use anyhow::Result;
 use chrono::{offset::Utc, DateTime};
 use futures::prelude::*;
-use std::{pin::Pin, time::Duration};
+use serde::{Serialize, Serializer};
+use std::{fmt, pin::Pin, sync::Arc, time::Duration};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ServerRef {
     Default(&'static str),
     Server(String),
@@ -32,7 +33,7 @@ pub enum HttpRouteRef {
 }
 
 /// Describes how a proxy should handle inbound connections.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum ProxyProtocol {
     /// Indicates that the protocol should be discovered dynamically.
     Detect {
@@ -47,11 +48,15 @@ pub enum ProxyProtocol {
     Opaque,
 
     /// Indicates that connections should be handled as application-terminated TLS.
+    ///
+    /// Connections are passed through opaquely; the proxy reads the SNI from
+    /// the client's handshake itself. The proxy API's `Tls` protocol has no
+    /// fields, so nothing about SNI handling is configured by the controller.
     Tls,
 }
 
 /// Describes a class of authorized clients.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct ClientAuthorization {
     /// Limits which source networks this authorization applies to.
     pub networks: Vec<NetworkMatch>,
@@ -60,7 +65,7 @@ pub struct ClientAuthorization {
     pub authentication: ClientAuthentication,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ClientAuthentication {
     /// Indicates that clients need not be authenticated.
     Unauthenticated,
@@ -83,7 +88,7 @@ pub trait DiscoverInboundServer<T> {
 pub type InboundServerStream = Pin<Box<dyn Stream<Item = InboundServer> + Send + Sync + 'static>>;
 
 /// Inbound server configuration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct InboundServer {
     pub reference: ServerRef,
 
@@ -92,10 +97,12 @@ pub struct InboundServer {
     pub http_routes: HashMap<HttpRouteRef, HttpRoute>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct HttpRoute {
     pub hostnames: Vec<HostMatch>,
-    pub rules: Vec<HttpRouteRule>,
+
+    /// Rules are shared by the route's bindings to each of its servers.
+    pub rules: Arc<[HttpRouteRule]>,
     pub authorizations: HashMap<AuthorizationRef, ClientAuthorization>,
 
     /// This is required for ordering returned `HttpRoute`s by their creation
@@ -103,13 +110,13 @@ pub struct HttpRoute {
     pub creation_timestamp: Option<DateTime<Utc>>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct HttpRouteRule {
     pub matches: Vec<HttpRouteMatch>,
     pub filters: Vec<Filter>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Filter {
     RequestHeaderModifier(HeaderModifierFilter),
     ResponseHeaderModifier(HeaderModifierFilter),
@@ -117,6 +124,25 @@ pub enum Filter {
     FailureInjector(FailureInjectorFilter),
 }
 
+// === impl AuthorizationRef ===
+
+impl fmt::Display for AuthorizationRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default(name) => write!(f, "default:{name}"),
+            Self::ServerAuthorization(name) => write!(f, "serverauthorization:{name}"),
+            Self::AuthorizationPolicy(name) => write!(f, "authorizationpolicy:{name}"),
+        }
+    }
+}
+
+/// Serialized as a string so that references may be used as map keys.
+impl Serialize for AuthorizationRef {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
 // === impl InboundHttpRoute ===
 
 /// The default `InboundHttpRoute` used for any `InboundServer` that
@@ -133,7 +159,8 @@ impl Default for HttpRoute {
                     method: None,
                 }],
                 filters: vec![],
-            }],
+            }]
+            .into(),
             // Default routes do not have authorizations; the default policy's
             // authzs will be configured by the default `InboundServer`, not by
             // the route.
@@ -163,3 +190,19 @@ impl PartialOrd for HttpRouteRef {
         Some(self.cmp(other))
     }
 }
+
+impl fmt::Display for HttpRouteRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default(name) => write!(f, "default:{name}"),
+            Self::Linkerd(gkn) => gkn.fmt(f),
+        }
+    }
+}
+
+/// Serialized as a string so that references may be used as map keys.
+impl Serialize for HttpRouteRef {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
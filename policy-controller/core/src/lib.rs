@@ -1,11 +1,13 @@
 #![deny(warnings, rust_2018_idioms)]
 #![forbid(unsafe_code)]
 
+pub mod evaluate;
 mod identity_match;
 pub mod inbound;
 mod network_match;
 pub mod outbound;
 pub mod routes;
+mod serialize;
 
 pub use self::{identity_match::IdentityMatch, network_match::NetworkMatch};
 pub use ipnet::{IpNet, Ipv4Net, Ipv6Net};
@@ -1,7 +1,8 @@
 use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use serde::Serialize;
 use std::net::IpAddr;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct NetworkMatch {
     /// A network to match against.
     pub net: IpNet,
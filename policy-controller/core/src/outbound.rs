@@ -5,6 +5,7 @@ use ahash::AHashMap as HashMap;
 use anyhow::Result;
 use chrono::{offset::Utc, DateTime};
 use futures::prelude::*;
+use serde::Serialize;
 use std::{net::IpAddr, num::NonZeroU16, pin::Pin, time};
 
 /// Models outbound policy discovery.
@@ -15,6 +16,10 @@ pub trait DiscoverOutboundPolicy<T> {
     async fn watch_outbound_policy(&self, target: T) -> Result<Option<OutboundPolicyStream>>;
 
     fn lookup_ip(&self, addr: IpAddr, port: NonZeroU16, source_namespace: String) -> Option<T>;
+
+    /// Resolves a hostname that does not name a Service, e.g. an external
+    /// destination, to the Service whose routes match the hostname.
+    fn lookup_hostname(&self, host: &str, port: NonZeroU16, source_namespace: String) -> Option<T>;
 }
 
 pub type OutboundPolicyStream = Pin<Box<dyn Stream<Item = OutboundPolicy> + Send + Sync + 'static>>;
@@ -24,9 +29,14 @@ pub struct OutboundDiscoverTarget {
     pub service_namespace: String,
     pub service_port: NonZeroU16,
     pub source_namespace: String,
+
+    /// The endpoint address through which the target was resolved and the
+    /// UID of the pod that owned it at the time. Watches for such targets end
+    /// when the address is reassigned, so that clients resolve it again.
+    pub endpoint_owner: Option<(IpAddr, String)>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct OutboundPolicy {
     pub http_routes: HashMap<GroupKindNamespaceName, HttpRoute>,
     pub authority: String,
@@ -34,10 +44,33 @@ pub struct OutboundPolicy {
     pub namespace: String,
     pub port: NonZeroU16,
     pub opaque: bool,
+
+    /// The HTTP version that clients use, if the Service port declares it with
+    /// its `appProtocol`. When unset, the protocol is detected.
+    pub app_protocol: Option<AppProtocol>,
+
     pub accrual: Option<FailureAccrual>,
+
+    /// Bounds the retries that clients may issue to the Service, relative to
+    /// its request volume. This is not sent to proxies, since the outbound API
+    /// cannot yet describe it.
+    pub retry_budget: Option<RetryBudget>,
+
+    pub detect_timeout: time::Duration,
+
+    /// The backends, across clusters, among which traffic that is not routed
+    /// otherwise is distributed. When empty, the Service is the only default
+    /// backend.
+    pub cluster_backends: Vec<WeightedService>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum AppProtocol {
+    Http1,
+    Http2,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct HttpRoute {
     pub hostnames: Vec<HostMatch>,
     pub rules: Vec<HttpRouteRule>,
@@ -47,7 +80,7 @@ pub struct HttpRoute {
     pub creation_timestamp: Option<DateTime<Utc>>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct HttpRouteRule {
     pub matches: Vec<HttpRouteMatch>,
     pub backends: Vec<Backend>,
@@ -56,21 +89,21 @@ pub struct HttpRouteRule {
     pub filters: Vec<Filter>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Backend {
     Addr(WeightedAddr),
     Service(WeightedService),
     Invalid { weight: u32, message: String },
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct WeightedAddr {
     pub weight: u32,
     pub addr: IpAddr,
     pub port: NonZeroU16,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct WeightedService {
     pub weight: u32,
     pub authority: String,
@@ -79,23 +112,148 @@ pub struct WeightedService {
     pub port: NonZeroU16,
     pub filters: Vec<Filter>,
     pub exists: bool,
+
+    /// Whether the Service has any ready endpoints. This is false if the
+    /// Service does not exist.
+    pub ready: bool,
+
+    /// The zones in which the Service's ready endpoints are hinted to serve
+    /// traffic, or in which they are located when the EndpointSlice controller
+    /// has not set topology hints. Sorted and deduplicated.
+    pub zones: Vec<String>,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub enum FailureAccrual {
     Consecutive { max_failures: u32, backoff: Backoff },
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Limits retries to a proportion of requests, as a ServiceProfile's
+/// `retryBudget` does.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct RetryBudget {
+    /// The ratio of retries to original requests that may be issued, e.g. `0.2`
+    /// permits one retry for every five requests.
+    pub retry_ratio: f32,
+
+    /// The number of retries that may be issued each second regardless of the
+    /// ratio, so that services with little traffic may retry.
+    pub min_retries_per_second: u32,
+
+    /// The window over which requests are counted to enforce the ratio.
+    pub ttl: time::Duration,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub struct Backoff {
     pub min_penalty: time::Duration,
     pub max_penalty: time::Duration,
     pub jitter: f32,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Filter {
     RequestHeaderModifier(HeaderModifierFilter),
     ResponseHeaderModifier(HeaderModifierFilter),
     RequestRedirect(RequestRedirectFilter),
 }
+
+// === impl OutboundPolicy ===
+
+impl OutboundPolicy {
+    /// Scopes the policy to a single pod of its Service, addressed by the pod's
+    /// hostname as a StatefulSet's pods are (e.g. `web-0.web.ns.svc.cluster.local`).
+    ///
+    /// The policy's authority and the authorities of backends that refer to the
+    /// policy's own Service are qualified by the hostname, so that traffic
+    /// continues to target the addressed pod.
+    pub fn for_hostname(mut self, hostname: &str) -> Self {
+        self.authority = format!("{hostname}.{}", self.authority);
+        // Traffic to a specific pod is not distributed across clusters.
+        self.cluster_backends.clear();
+        let backends = self
+            .http_routes
+            .values_mut()
+            .flat_map(|route| route.rules.iter_mut())
+            .flat_map(|rule| rule.backends.iter_mut());
+        for backend in backends {
+            if let Backend::Service(svc) = backend {
+                if svc.name == self.name && svc.namespace == self.namespace {
+                    svc.authority = format!("{hostname}.{}", svc.authority);
+                }
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualifies_parent_backends_by_hostname() {
+        let port = NonZeroU16::new(8080).unwrap();
+        let backend = |name: &str| {
+            Backend::Service(WeightedService {
+                weight: 1,
+                authority: format!("{name}.ns.svc.cluster.local:8080"),
+                name: name.to_string(),
+                namespace: "ns".to_string(),
+                port,
+                filters: vec![],
+                exists: true,
+                ready: true,
+                zones: vec![],
+            })
+        };
+        let route = HttpRoute {
+            hostnames: vec![],
+            rules: vec![HttpRouteRule {
+                matches: vec![],
+                backends: vec![backend("web"), backend("other")],
+                request_timeout: None,
+                backend_request_timeout: None,
+                filters: vec![],
+            }],
+            creation_timestamp: None,
+        };
+        let gknn = GroupKindNamespaceName {
+            group: "policy.linkerd.io".into(),
+            kind: "HTTPRoute".into(),
+            namespace: "ns".into(),
+            name: "route".into(),
+        };
+        let policy = OutboundPolicy {
+            http_routes: Some((gknn.clone(), route)).into_iter().collect(),
+            authority: "web.ns.svc.cluster.local:8080".to_string(),
+            name: "web".to_string(),
+            namespace: "ns".to_string(),
+            port,
+            opaque: false,
+            app_protocol: None,
+            accrual: None,
+            retry_budget: None,
+            detect_timeout: time::Duration::from_secs(10),
+            cluster_backends: vec![],
+        }
+        .for_hostname("web-0");
+
+        assert_eq!(policy.authority, "web-0.web.ns.svc.cluster.local:8080");
+        let authorities = policy.http_routes[&gknn].rules[0]
+            .backends
+            .iter()
+            .map(|backend| match backend {
+                Backend::Service(svc) => svc.authority.as_str(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            authorities,
+            [
+                "web-0.web.ns.svc.cluster.local:8080",
+                "other.ns.svc.cluster.local:8080"
+            ]
+        );
+    }
+}
@@ -1,94 +1,218 @@
+use ahash::AHashMap as HashMap;
 use anyhow::Result;
 pub use http::{
     header::{HeaderName, HeaderValue},
     uri::Scheme,
     Method, StatusCode,
 };
+use indexmap::IndexMap;
+use parking_lot::{Mutex, RwLock};
 use regex::Regex;
-use std::{borrow::Cow, num::NonZeroU16};
+use serde::{Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    num::NonZeroU16,
+    sync::{Arc, OnceLock},
+};
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+/// The maximum number of compiled regular expressions retained by
+/// [`compile_regex`].
+const REGEX_CACHE_CAPACITY: usize = 1_000;
 
+/// Identifies a resource within a namespace.
+///
+/// Resource identifiers are copied into every policy that references the
+/// resource, so names are reference-counted rather than cloned. Groups and
+/// kinds are typically static.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct GroupKindName {
     pub group: Cow<'static, str>,
     pub kind: Cow<'static, str>,
-    pub name: Cow<'static, str>,
+    pub name: Arc<str>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct GroupKindNamespaceName {
     pub group: Cow<'static, str>,
     pub kind: Cow<'static, str>,
-    pub namespace: Cow<'static, str>,
-    pub name: Cow<'static, str>,
+    pub namespace: Arc<str>,
+    pub name: Arc<str>,
 }
 
+/// Records the routes that the indexes could not parse, so that the status
+/// controller may report why they are not accepted.
+///
+/// Each index parses routes independently, so rejections are recorded by the
+/// name of the index that rejected the route. A rejection only applies to the
+/// generation of the route that was rejected, and not to a route that has been
+/// recreated with the same name (and so has a new UID).
+#[derive(Clone, Debug, Default)]
+pub struct Quarantine(
+    Arc<RwLock<HashMap<GroupKindNamespaceName, BTreeMap<&'static str, Rejection>>>>,
+);
+
 #[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rejection {
+    pub uid: Option<String>,
+    pub generation: Option<i64>,
+    pub error: Arc<str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum HostMatch {
     Exact(String),
     Suffix { reverse_labels: Vec<String> },
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct HeaderModifierFilter {
+    #[serde(serialize_with = "crate::serialize::headers")]
     pub add: Vec<(HeaderName, HeaderValue)>,
+    #[serde(serialize_with = "crate::serialize::headers")]
     pub set: Vec<(HeaderName, HeaderValue)>,
+    #[serde(serialize_with = "crate::serialize::header_names")]
     pub remove: Vec<HeaderName>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct RequestRedirectFilter {
+    #[serde(serialize_with = "crate::serialize::display_opt")]
     pub scheme: Option<Scheme>,
     pub host: Option<String>,
     pub path: Option<PathModifier>,
     pub port: Option<NonZeroU16>,
+    #[serde(serialize_with = "crate::serialize::status_opt")]
     pub status: Option<StatusCode>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct FailureInjectorFilter {
+    #[serde(serialize_with = "crate::serialize::status")]
     pub status: StatusCode,
     pub message: String,
     pub ratio: Ratio,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum PathModifier {
     Full(String),
     Prefix(String),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Ratio {
     pub numerator: u32,
     pub denominator: u32,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct HttpRouteMatch {
     pub path: Option<PathMatch>,
     pub headers: Vec<HeaderMatch>,
     pub query_params: Vec<QueryParamMatch>,
+    #[serde(serialize_with = "crate::serialize::display_opt")]
     pub method: Option<Method>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum PathMatch {
     Exact(String),
     Prefix(String),
-    Regex(Regex),
+    Regex(#[serde(serialize_with = "crate::serialize::display")] Regex),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum HeaderMatch {
-    Exact(HeaderName, HeaderValue),
-    Regex(HeaderName, Regex),
+    Exact(
+        #[serde(serialize_with = "crate::serialize::display")] HeaderName,
+        #[serde(serialize_with = "crate::serialize::header_value")] HeaderValue,
+    ),
+    Regex(
+        #[serde(serialize_with = "crate::serialize::display")] HeaderName,
+        #[serde(serialize_with = "crate::serialize::display")] Regex,
+    ),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum QueryParamMatch {
     Exact(String, String),
-    Regex(String, Regex),
+    Regex(
+        String,
+        #[serde(serialize_with = "crate::serialize::display")] Regex,
+    ),
+}
+
+/// Compiles a regular expression, reusing a previously compiled expression
+/// with the same pattern if one exists.
+///
+/// Many routes may use the same patterns. Clones of a compiled expression share
+/// its program, so routes that use a cached expression do not each hold a copy.
+/// When the cache reaches its capacity, the least recently used expression is
+/// evicted.
+pub fn compile_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    static CACHE: OnceLock<Mutex<RegexCache>> = OnceLock::new();
+
+    CACHE
+        .get_or_init(|| Mutex::new(RegexCache::new(REGEX_CACHE_CAPACITY)))
+        .lock()
+        .get_or_compile(pattern)
+}
+
+/// A least-recently-used cache of compiled regular expressions, ordered from
+/// least to most recently used.
+#[derive(Debug)]
+struct RegexCache {
+    capacity: usize,
+    by_pattern: IndexMap<String, Regex, ahash::RandomState>,
+}
+
+// === impl RegexCache ===
+
+impl RegexCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            by_pattern: IndexMap::default(),
+        }
+    }
+
+    fn get_or_compile(&mut self, pattern: &str) -> Result<Regex, regex::Error> {
+        if let Some((idx, _, regex)) = self.by_pattern.get_full(pattern) {
+            let regex = regex.clone();
+            let last = self.by_pattern.len() - 1;
+            self.by_pattern.move_index(idx, last);
+            return Ok(regex);
+        }
+
+        let regex = Regex::new(pattern)?;
+        if self.by_pattern.len() >= self.capacity {
+            self.by_pattern.shift_remove_index(0);
+        }
+        self.by_pattern.insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+}
+
+// === impl HostMatch ===
+
+impl HostMatch {
+    /// Returns true if the hostname matches. Hostnames are compared
+    /// case-insensitively, and a wildcard matches one or more leading labels.
+    pub fn matches(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.');
+        match self {
+            Self::Exact(name) => name.eq_ignore_ascii_case(host),
+            Self::Suffix { reverse_labels } => {
+                let mut labels = host.rsplit('.');
+                reverse_labels
+                    .iter()
+                    .all(|l| labels.next().map_or(false, |h| l.eq_ignore_ascii_case(h)))
+                    && labels.next().map_or(false, |h| !h.is_empty())
+            }
+        }
+    }
 }
 
 // === impl GroupKindName ===
@@ -109,6 +233,23 @@ impl PartialOrd for GroupKindName {
     }
 }
 
+impl fmt::Display for GroupKindName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.group.is_empty() {
+            write!(f, "{}/{}", self.kind, self.name)
+        } else {
+            write!(f, "{}.{}/{}", self.kind, self.group, self.name)
+        }
+    }
+}
+
+/// Serialized as a string so that names may be used as map keys.
+impl Serialize for GroupKindName {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
 impl GroupKindName {
     pub fn eq_ignore_ascii_case(&self, other: &Self) -> bool {
         self.group.eq_ignore_ascii_case(&other.group)
@@ -126,6 +267,76 @@ impl GroupKindName {
     }
 }
 
+// === impl GroupKindNamespaceName ===
+
+impl fmt::Display for GroupKindNamespaceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.group.is_empty() {
+            write!(f, "{}/{}/{}", self.kind, self.namespace, self.name)
+        } else {
+            write!(
+                f,
+                "{}.{}/{}/{}",
+                self.kind, self.group, self.namespace, self.name
+            )
+        }
+    }
+}
+
+/// Serialized as a string so that names may be used as map keys.
+impl Serialize for GroupKindNamespaceName {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+// === impl Quarantine ===
+
+impl Quarantine {
+    pub fn insert(&self, index: &'static str, route: GroupKindNamespaceName, rejection: Rejection) {
+        self.0
+            .write()
+            .entry(route)
+            .or_default()
+            .insert(index, rejection);
+    }
+
+    pub fn remove(&self, index: &'static str, route: &GroupKindNamespaceName) {
+        let mut routes = self.0.write();
+        if let Some(rejections) = routes.get_mut(route) {
+            rejections.remove(index);
+            if rejections.is_empty() {
+                routes.remove(route);
+            }
+        }
+    }
+
+    pub fn retain(&self, index: &'static str, mut f: impl FnMut(&GroupKindNamespaceName) -> bool) {
+        self.0.write().retain(|route, rejections| {
+            if !f(route) {
+                rejections.remove(index);
+            }
+            !rejections.is_empty()
+        });
+    }
+
+    /// Returns the error with which the given version of a route was
+    /// rejected, if any index rejected it.
+    pub fn error(
+        &self,
+        route: &GroupKindNamespaceName,
+        uid: Option<&str>,
+        generation: Option<i64>,
+    ) -> Option<Arc<str>> {
+        self.0
+            .read()
+            .get(route)?
+            .values()
+            .find(|r| r.uid.as_deref() == uid && r.generation == generation)
+            .map(|r| r.error.clone())
+    }
+}
+
 // === impl PathMatch ===
 
 impl PartialEq for PathMatch {
@@ -143,7 +354,7 @@ impl Eq for PathMatch {}
 
 impl PathMatch {
     pub fn regex(s: &str) -> Result<Self> {
-        Ok(Self::Regex(Regex::new(s)?))
+        Ok(Self::Regex(compile_regex(s)?))
     }
 }
 
@@ -174,3 +385,49 @@ impl PartialEq for QueryParamMatch {
 }
 
 impl Eq for QueryParamMatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_regex_reuses_patterns() {
+        let a = compile_regex("/foo/[0-9]+").unwrap();
+        let b = compile_regex("/foo/[0-9]+").unwrap();
+        assert_eq!(a.as_str(), b.as_str());
+        assert!(b.is_match("/foo/123"));
+
+        assert!(compile_regex("/foo/[").is_err());
+    }
+
+    #[test]
+    fn regex_cache_evicts_least_recently_used() {
+        let mut cache = RegexCache::new(2);
+        cache.get_or_compile("a").unwrap();
+        cache.get_or_compile("b").unwrap();
+        // Using `a` makes `b` the least recently used pattern.
+        cache.get_or_compile("a").unwrap();
+        cache.get_or_compile("c").unwrap();
+        assert_eq!(
+            cache.by_pattern.keys().collect::<Vec<_>>(),
+            ["a", "c"],
+            "the least recently used pattern must be evicted"
+        );
+    }
+
+    #[test]
+    fn host_matches() {
+        let exact = HostMatch::Exact("api.example.com".to_string());
+        assert!(exact.matches("api.example.com"));
+        assert!(exact.matches("API.example.com."));
+        assert!(!exact.matches("v1.api.example.com"));
+
+        let suffix = HostMatch::Suffix {
+            reverse_labels: vec!["com".to_string(), "example".to_string()],
+        };
+        assert!(suffix.matches("api.example.com"));
+        assert!(suffix.matches("v1.api.example.com"));
+        assert!(!suffix.matches("example.com"));
+        assert!(!suffix.matches("api.example.org"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Helpers for serializing policy types that do not implement `Serialize`.
+//!
+//! Policies are serialized for diagnostics, so values are rendered in their
+//! human-readable forms.
+
+use crate::routes::{HeaderName, HeaderValue, StatusCode};
+use serde::{ser::SerializeSeq, Serializer};
+use std::fmt;
+
+pub(crate) fn display<T: fmt::Display, S: Serializer>(v: &T, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(v)
+}
+
+pub(crate) fn display_opt<T: fmt::Display, S: Serializer>(
+    v: &Option<T>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match v {
+        Some(v) => s.collect_str(v),
+        None => s.serialize_none(),
+    }
+}
+
+pub(crate) fn header_names<S: Serializer>(names: &[HeaderName], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(names.iter().map(HeaderName::as_str))
+}
+
+pub(crate) fn header_value<S: Serializer>(v: &HeaderValue, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&String::from_utf8_lossy(v.as_bytes()))
+}
+
+pub(crate) fn headers<S: Serializer>(
+    headers: &[(HeaderName, HeaderValue)],
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = s.serialize_seq(Some(headers.len()))?;
+    for (name, value) in headers {
+        seq.serialize_element(&(name.as_str(), String::from_utf8_lossy(value.as_bytes())))?;
+    }
+    seq.end()
+}
+
+pub(crate) fn status<S: Serializer>(status: &StatusCode, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u16(status.as_u16())
+}
+
+pub(crate) fn status_opt<S: Serializer>(
+    status: &Option<StatusCode>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match status {
+        Some(status) => s.serialize_u16(status.as_u16()),
+        None => s.serialize_none(),
+    }
+}
@@ -0,0 +1,15 @@
+#![no_main]
+
+#[cfg(fuzzing)]
+use libfuzzer_sys::fuzz_target;
+
+#[cfg(fuzzing)]
+fuzz_target!(|data: &[u8]| {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    rt.block_on(linkerd_policy_controller::admission_fuzz_logic::fuzz_entry(
+        data,
+    ));
+});
@@ -0,0 +1,27 @@
+#![no_main]
+
+#[cfg(fuzzing)]
+use libfuzzer_sys::fuzz_target;
+
+#[cfg(fuzzing)]
+fuzz_target!(|data: &[u8]| {
+    use k8s_gateway_api::HttpRouteFilter;
+    use linkerd_policy_controller_k8s_index::http_route;
+
+    match serde_json::from_slice::<HttpRouteFilter>(data) {
+        Ok(HttpRouteFilter::RequestHeaderModifier {
+            request_header_modifier,
+        }) => {
+            let _ = http_route::header_modifier(request_header_modifier);
+        }
+        Ok(HttpRouteFilter::ResponseHeaderModifier {
+            response_header_modifier,
+        }) => {
+            let _ = http_route::header_modifier(response_header_modifier);
+        }
+        Ok(HttpRouteFilter::RequestRedirect { request_redirect }) => {
+            let _ = http_route::req_redirect(request_redirect);
+        }
+        _ => {}
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+#[cfg(fuzzing)]
+use libfuzzer_sys::fuzz_target;
+
+#[cfg(fuzzing)]
+fuzz_target!(|data: &[u8]| {
+    use linkerd_policy_controller_k8s_index::http_route;
+
+    if let Ok(m) = serde_json::from_slice::<k8s_gateway_api::HttpRouteMatch>(data) {
+        let _ = http_route::try_match(m);
+    }
+});
@@ -0,0 +1,273 @@
+//! Benchmarks serving policies over gRPC: converting indexed policies to their
+//! protobuf representation and encoding the response.
+//!
+//! Compare against a saved baseline to detect regressions, e.g.:
+//!
+//! ```text
+//! cargo bench -p linkerd-policy-controller-grpc --bench encode -- --save-baseline main
+//! cargo bench -p linkerd-policy-controller-grpc --bench encode -- --baseline main
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use linkerd2_proxy_api::{
+    inbound::{inbound_server_policies_server::InboundServerPolicies, PortSpec},
+    outbound::{outbound_policies_server::OutboundPolicies, traffic_spec, TrafficSpec},
+};
+use linkerd_policy_controller_core::{
+    inbound::{self, DiscoverInboundServer, InboundServer, InboundServerStream},
+    outbound::{self, DiscoverOutboundPolicy, OutboundDiscoverTarget, OutboundPolicyStream},
+    routes::{self, GroupKindName, GroupKindNamespaceName},
+    IdentityMatch, IpNet, NetworkMatch,
+};
+use linkerd_policy_controller_grpc::{
+    capabilities::Capabilities,
+    inbound::InboundPolicyServer,
+    limits::{WatchLimits, WatchMetrics},
+    metrics::StreamMetrics,
+    outbound::OutboundPolicyServer,
+    workload::Workload,
+};
+use prometheus_client::registry::Registry;
+use prost::Message;
+use std::{net::IpAddr, num::NonZeroU16, time};
+
+/// The numbers of routes in the policies that are served.
+const ROUTES: [usize; 3] = [1, 10, 100];
+
+const PORT: u16 = 8080;
+
+/// Serves a fixed policy.
+#[derive(Clone, Debug)]
+struct Fixed<T>(T);
+
+fn inbound(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("inbound");
+    for routes in ROUTES {
+        let (drain_tx, drain) = drain::channel();
+        let mut prom = Registry::default();
+        let server = InboundPolicyServer::new(
+            Fixed(mk_inbound_server(routes)),
+            vec!["10.0.0.0/8".parse().unwrap()],
+            mk_limits(&mut prom),
+            StreamMetrics::register(&mut prom),
+            Capabilities::register(&mut prom),
+            drain,
+        );
+        group.bench_with_input(
+            BenchmarkId::new("get_port", routes),
+            &server,
+            |b, server| {
+                b.iter(|| {
+                    let req = tonic::Request::new(PortSpec {
+                        workload: "ns-0:pod-0".to_string(),
+                        port: PORT.into(),
+                    });
+                    let rsp = rt.block_on(server.get_port(req)).unwrap();
+                    rsp.into_inner().encode_to_vec()
+                })
+            },
+        );
+        drop(drain_tx);
+    }
+    group.finish();
+}
+
+fn outbound(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("outbound");
+    for routes in ROUTES {
+        let (drain_tx, drain) = drain::channel();
+        let mut prom = Registry::default();
+        let server = OutboundPolicyServer::new(
+            Fixed(mk_outbound_policy(routes)),
+            "cluster.local",
+            mk_limits(&mut prom),
+            StreamMetrics::register(&mut prom),
+            Capabilities::register(&mut prom),
+            drain,
+        );
+        group.bench_with_input(BenchmarkId::new("get", routes), &server, |b, server| {
+            b.iter(|| {
+                let req = tonic::Request::new(TrafficSpec {
+                    source_workload: "ns-0:pod-0".to_string(),
+                    target: Some(traffic_spec::Target::Authority(format!(
+                        "svc-0.ns-0.svc.cluster.local:{PORT}"
+                    ))),
+                });
+                let rsp = rt.block_on(server.get(req)).unwrap();
+                rsp.into_inner().encode_to_vec()
+            })
+        });
+        drop(drain_tx);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, inbound, outbound);
+criterion_main!(benches);
+
+fn mk_limits(prom: &mut Registry) -> WatchLimits {
+    WatchLimits::new(
+        1000,
+        time::Duration::from_secs(30),
+        WatchMetrics::register(prom),
+    )
+}
+
+fn mk_inbound_server(routes: usize) -> InboundServer {
+    let authorizations = (0..3)
+        .map(|i| {
+            (
+                inbound::AuthorizationRef::AuthorizationPolicy(format!("authz-{i}")),
+                inbound::ClientAuthorization {
+                    networks: vec![NetworkMatch::from("10.0.0.0/8".parse::<IpNet>().unwrap())],
+                    authentication: inbound::ClientAuthentication::TlsAuthenticated(vec![
+                        IdentityMatch::Exact(format!(
+                            "sa-{i}.ns-0.serviceaccount.identity.linkerd.cluster.local"
+                        )),
+                    ]),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let http_routes = (0..routes)
+        .map(|i| {
+            let route = inbound::HttpRoute {
+                hostnames: vec![],
+                rules: vec![inbound::HttpRouteRule {
+                    matches: mk_matches(i),
+                    filters: vec![],
+                }]
+                .into(),
+                authorizations: authorizations.iter().cloned().collect(),
+                creation_timestamp: None,
+            };
+            let gkn = GroupKindName {
+                group: "policy.linkerd.io".into(),
+                kind: "HTTPRoute".into(),
+                name: format!("route-{i}").into(),
+            };
+            (inbound::HttpRouteRef::Linkerd(gkn), route)
+        })
+        .collect();
+
+    InboundServer {
+        reference: inbound::ServerRef::Server("srv-0".to_string()),
+        protocol: inbound::ProxyProtocol::Http1,
+        authorizations: authorizations.into_iter().collect(),
+        http_routes,
+    }
+}
+
+fn mk_outbound_policy(routes: usize) -> outbound::OutboundPolicy {
+    let port = NonZeroU16::new(PORT).unwrap();
+    let http_routes = (0..routes)
+        .map(|i| {
+            let backend = outbound::Backend::Service(outbound::WeightedService {
+                weight: 1,
+                authority: format!("backend-{i}.ns-0.svc.cluster.local:{PORT}"),
+                name: format!("backend-{i}"),
+                namespace: "ns-0".to_string(),
+                port,
+                filters: vec![],
+                exists: true,
+                ready: true,
+                zones: vec![],
+            });
+            let route = outbound::HttpRoute {
+                hostnames: vec![],
+                rules: vec![outbound::HttpRouteRule {
+                    matches: mk_matches(i),
+                    backends: vec![backend],
+                    request_timeout: Some(time::Duration::from_secs(10)),
+                    backend_request_timeout: None,
+                    filters: vec![],
+                }],
+                creation_timestamp: None,
+            };
+            let gknn = GroupKindNamespaceName {
+                group: "policy.linkerd.io".into(),
+                kind: "HTTPRoute".into(),
+                namespace: "ns-0".into(),
+                name: format!("route-{i}").into(),
+            };
+            (gknn, route)
+        })
+        .collect();
+
+    outbound::OutboundPolicy {
+        http_routes,
+        authority: format!("svc-0.ns-0.svc.cluster.local:{PORT}"),
+        name: "svc-0".to_string(),
+        namespace: "ns-0".to_string(),
+        port,
+        opaque: false,
+        app_protocol: None,
+        accrual: None,
+        retry_budget: None,
+        detect_timeout: time::Duration::from_secs(10),
+        cluster_backends: vec![],
+    }
+}
+
+fn mk_matches(i: usize) -> Vec<routes::HttpRouteMatch> {
+    vec![routes::HttpRouteMatch {
+        path: Some(routes::PathMatch::Prefix(format!("/api/v{i}"))),
+        headers: vec![routes::HeaderMatch::Regex(
+            routes::HeaderName::from_static("x-version"),
+            routes::compile_regex("v[0-9]+").unwrap(),
+        )],
+        query_params: vec![],
+        method: Some(routes::Method::GET),
+    }]
+}
+
+// === impl Fixed ===
+
+#[async_trait::async_trait]
+impl DiscoverInboundServer<(Workload, NonZeroU16)> for Fixed<InboundServer> {
+    async fn get_inbound_server(
+        &self,
+        _: (Workload, NonZeroU16),
+    ) -> anyhow::Result<Option<InboundServer>> {
+        Ok(Some(self.0.clone()))
+    }
+
+    async fn watch_inbound_server(
+        &self,
+        _: (Workload, NonZeroU16),
+    ) -> anyhow::Result<Option<InboundServerStream>> {
+        Ok(None)
+    }
+}
+
+#[async_trait::async_trait]
+impl DiscoverOutboundPolicy<OutboundDiscoverTarget> for Fixed<outbound::OutboundPolicy> {
+    async fn get_outbound_policy(
+        &self,
+        _: OutboundDiscoverTarget,
+    ) -> anyhow::Result<Option<outbound::OutboundPolicy>> {
+        Ok(Some(self.0.clone()))
+    }
+
+    async fn watch_outbound_policy(
+        &self,
+        _: OutboundDiscoverTarget,
+    ) -> anyhow::Result<Option<OutboundPolicyStream>> {
+        Ok(None)
+    }
+
+    fn lookup_ip(&self, _: IpAddr, _: NonZeroU16, _: String) -> Option<OutboundDiscoverTarget> {
+        None
+    }
+
+    fn lookup_hostname(&self, _: &str, _: NonZeroU16, _: String) -> Option<OutboundDiscoverTarget> {
+        None
+    }
+}
@@ -0,0 +1,150 @@
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use tonic::metadata::{AsciiMetadataValue, MetadataMap};
+
+/// The metadata key used to exchange the set of policy features supported by
+/// clients and served by this controller.
+///
+/// The value is a comma-separated list of feature names. The controller
+/// includes the features it serves in the response metadata of every policy
+/// lookup; clients may include the features that they support in their request
+/// metadata. Clients that do not advertise any features are assumed to predate
+/// feature negotiation.
+pub const METADATA_KEY: &str = "l5d-policy-features";
+
+/// Clients that support this feature may be sent only the authorizations of
+/// an inbound server when nothing else about it has changed. See
+/// [`crate::inbound`].
+pub const AUTHORIZATION_DELTAS: &str = "inbound-authorization-deltas";
+
+/// The policy features served by this controller.
+pub const FEATURES: &[&str] = &[
+    "inbound-http-routes",
+    "outbound-http-routes",
+    "request-header-modifier",
+    "response-header-modifier",
+    "request-redirect",
+    "failure-injector",
+    "failure-accrual",
+    "route-timeouts",
+    "external-workloads",
+    "detect-timeout",
+    AUTHORIZATION_DELTAS,
+];
+
+type ClientLabels = [(&'static str, &'static str); 2];
+
+/// Advertises the features served by this controller and records whether
+/// clients participate in feature negotiation.
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    served: AsciiMetadataValue,
+    requests: Family<ClientLabels, Counter>,
+}
+
+// === impl Capabilities ===
+
+impl Capabilities {
+    pub fn register(prom: &mut Registry) -> Self {
+        let requests = Family::default();
+        prom.register(
+            "policy_requests",
+            "Count of policy lookups by whether the client advertised its supported features",
+            requests.clone(),
+        );
+        Self {
+            requests,
+            ..Default::default()
+        }
+    }
+
+    /// Records the features advertised by a client, returning the names of
+    /// those features.
+    pub(crate) fn client<'m>(&self, api: &'static str, metadata: &'m MetadataMap) -> Vec<&'m str> {
+        let features = metadata
+            .get_all(METADATA_KEY)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>();
+        let client = if features.is_empty() {
+            "legacy"
+        } else {
+            "negotiated"
+        };
+        self.requests
+            .get_or_create(&[("api", api), ("client", client)])
+            .inc();
+        tracing::trace!(?features, "Client features");
+        features
+    }
+
+    /// Includes the features served by this controller in a response's
+    /// metadata.
+    pub(crate) fn advertise<T>(&self, mut rsp: tonic::Response<T>) -> tonic::Response<T> {
+        rsp.metadata_mut().insert(METADATA_KEY, self.served.clone());
+        rsp
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            served: FEATURES
+                .join(",")
+                .parse()
+                .expect("feature names must be valid metadata"),
+            requests: Family::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advertises_features() {
+        let rsp = Capabilities::default().advertise(tonic::Response::new(()));
+        assert_eq!(
+            rsp.metadata().get(METADATA_KEY).unwrap(),
+            &FEATURES.join(",")
+        );
+    }
+
+    #[test]
+    fn client_features() {
+        let caps = Capabilities::default();
+
+        let md = MetadataMap::new();
+        assert!(caps.client("inbound", &md).is_empty());
+
+        let mut md = MetadataMap::new();
+        md.insert(
+            METADATA_KEY,
+            "failure-accrual, route-timeouts,".parse().unwrap(),
+        );
+        md.append(METADATA_KEY, "detect-timeout".parse().unwrap());
+        assert_eq!(
+            caps.client("inbound", &md),
+            ["failure-accrual", "route-timeouts", "detect-timeout"]
+        );
+
+        assert_eq!(
+            caps.requests
+                .get_or_create(&[("api", "inbound"), ("client", "legacy")])
+                .get(),
+            1
+        );
+        assert_eq!(
+            caps.requests
+                .get_or_create(&[("api", "inbound"), ("client", "negotiated")])
+                .get(),
+            1
+        );
+    }
+}
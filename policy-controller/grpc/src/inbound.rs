@@ -1,4 +1,20 @@
-use crate::{routes, workload::Workload};
+//! The inbound policy API.
+//!
+//! Watches send a complete server on every update, unless the client
+//! advertises the [`AUTHORIZATION_DELTAS`] feature. Then, an update that only
+//! changes the server's authorizations is sent as a delta: a server with no
+//! protocol, whose authorizations replace those previously sent. The protocol
+//! is always set on complete updates, so clients distinguish deltas by its
+//! absence.
+
+use crate::{
+    capabilities::{Capabilities, AUTHORIZATION_DELTAS},
+    limits::{SendError, WatchLimits, WatchPermit},
+    metrics::{LookupRecorder, StreamMetrics, StreamRecorder},
+    routes,
+    staleness::Staleness,
+    workload::{self, Workload},
+};
 use futures::prelude::*;
 use linkerd2_proxy_api::{
     self as api,
@@ -18,13 +34,18 @@ use linkerd_policy_controller_core::{
 };
 use maplit::*;
 use std::{num::NonZeroU16, str::FromStr, sync::Arc};
+use tokio::sync::watch;
 use tracing::trace;
 
 #[derive(Clone, Debug)]
 pub struct InboundPolicyServer<T> {
     discover: T,
     drain: drain::Watch,
-    cluster_networks: Arc<[IpNet]>,
+    cluster_networks: watch::Receiver<Arc<[IpNet]>>,
+    limits: WatchLimits,
+    metrics: StreamMetrics,
+    capabilities: Capabilities,
+    staleness: Staleness,
 }
 
 // === impl InboundPolicyServer ===
@@ -33,18 +54,50 @@ impl<T> InboundPolicyServer<T>
 where
     T: DiscoverInboundServer<(Workload, NonZeroU16)> + Send + Sync + 'static,
 {
-    pub fn new(discover: T, cluster_networks: Vec<IpNet>, drain: drain::Watch) -> Self {
+    pub fn new(
+        discover: T,
+        cluster_networks: Vec<IpNet>,
+        limits: WatchLimits,
+        metrics: StreamMetrics,
+        capabilities: Capabilities,
+        drain: drain::Watch,
+    ) -> Self {
         Self {
             discover,
             drain,
-            cluster_networks: cluster_networks.into(),
+            cluster_networks: watch::channel(cluster_networks.into()).1,
+            limits,
+            metrics,
+            capabilities,
+            staleness: Staleness::default(),
         }
     }
 
+    /// Serves authorizations that apply to cluster networks using networks
+    /// that may change at runtime. Active watches are updated when the
+    /// networks change.
+    pub fn with_cluster_networks(mut self, networks: watch::Receiver<Arc<[IpNet]>>) -> Self {
+        self.cluster_networks = networks;
+        self
+    }
+
+    /// Marks responses that are served while the index is stale.
+    pub fn with_staleness(mut self, staleness: Staleness) -> Self {
+        self.staleness = staleness;
+        self
+    }
+
     pub fn svc(self) -> InboundServerPoliciesServer<Self> {
         InboundServerPoliciesServer::new(self)
     }
 
+    /// Builds a response, including the metadata that describes how it is
+    /// served.
+    fn respond<R>(&self, rsp: R) -> tonic::Response<R> {
+        self.staleness
+            .mark(self.capabilities.advertise(tonic::Response::new(rsp)))
+    }
+
     fn check_target(
         &self,
         proto::PortSpec { workload, port }: proto::PortSpec,
@@ -64,11 +117,19 @@ impl<T> InboundServerPolicies for InboundPolicyServer<T>
 where
     T: DiscoverInboundServer<(Workload, NonZeroU16)> + Send + Sync + 'static,
 {
+    #[tracing::instrument(
+        skip_all,
+        fields(workload = %req.get_ref().workload, port = req.get_ref().port),
+    )]
     async fn get_port(
         &self,
         req: tonic::Request<proto::PortSpec>,
     ) -> Result<tonic::Response<proto::Server>, tonic::Status> {
-        let target = self.check_target(req.into_inner())?;
+        let mut lookup = self.metrics.lookup("inbound");
+        self.capabilities.client("inbound", req.metadata());
+        let target = self
+            .check_target(req.into_inner())
+            .map_err(|s| lookup.failed(s))?;
 
         // Lookup the configuration for an inbound port. If the pod hasn't (yet)
         // been indexed, return a Not Found error.
@@ -76,30 +137,61 @@ where
             .discover
             .get_inbound_server(target)
             .await
-            .map_err(|e| tonic::Status::internal(format!("lookup failed: {}", e)))?
-            .ok_or_else(|| tonic::Status::not_found("unknown server"))?;
+            .map_err(|e| tonic::Status::internal(format!("lookup failed: {}", e)))
+            .and_then(|s| s.ok_or_else(|| tonic::Status::not_found("unknown server")))
+            .map_err(|s| lookup.failed(s))?;
 
-        Ok(tonic::Response::new(to_server(&s, &self.cluster_networks)))
+        let rsp = to_server(&s, &self.cluster_networks.borrow());
+        lookup.responded();
+        Ok(self.respond(rsp))
     }
 
     type WatchPortStream = BoxWatchStream;
 
+    #[tracing::instrument(
+        skip_all,
+        fields(workload = %req.get_ref().workload, port = req.get_ref().port),
+    )]
     async fn watch_port(
         &self,
         req: tonic::Request<proto::PortSpec>,
     ) -> Result<tonic::Response<BoxWatchStream>, tonic::Status> {
-        let target = self.check_target(req.into_inner())?;
+        let mut lookup = self.metrics.lookup("inbound");
+        let permit = self
+            .limits
+            .acquire(req.remote_addr())
+            .map_err(|s| lookup.failed(s))?;
+        let deltas = self
+            .capabilities
+            .client("inbound", req.metadata())
+            .contains(&AUTHORIZATION_DELTAS);
+        let target = self
+            .check_target(req.into_inner())
+            .map_err(|s| lookup.failed(s))?;
+        let kind = match target.0.kind {
+            workload::Kind::Pod(_) => "pod",
+            workload::Kind::External(_) | workload::Kind::ExternalIdentity(_) => {
+                "external_workload"
+            }
+        };
         let drain = self.drain.clone();
         let rx = self
             .discover
             .watch_inbound_server(target)
             .await
-            .map_err(|e| tonic::Status::internal(format!("lookup failed: {}", e)))?
-            .ok_or_else(|| tonic::Status::not_found("unknown server"))?;
-        Ok(tonic::Response::new(response_stream(
+            .map_err(|e| tonic::Status::internal(format!("lookup failed: {}", e)))
+            .and_then(|rx| rx.ok_or_else(|| tonic::Status::not_found("unknown server")))
+            .map_err(|s| lookup.failed(s))?;
+        let stream = self.metrics.stream("inbound", kind);
+        Ok(self.respond(response_stream(
             drain,
             rx,
             self.cluster_networks.clone(),
+            deltas,
+            permit,
+            lookup,
+            stream,
+            tracing::Span::current(),
         )))
     }
 }
@@ -107,34 +199,112 @@ where
 type BoxWatchStream =
     std::pin::Pin<Box<dyn Stream<Item = Result<proto::Server, tonic::Status>> + Send + Sync>>;
 
+#[allow(clippy::too_many_arguments)]
 fn response_stream(
     drain: drain::Watch,
     mut rx: InboundServerStream,
-    cluster_networks: Arc<[IpNet]>,
+    mut cluster_networks: watch::Receiver<Arc<[IpNet]>>,
+    deltas: bool,
+    permit: WatchPermit,
+    lookup: LookupRecorder,
+    mut stream: StreamRecorder,
+    span: tracing::Span,
 ) -> BoxWatchStream {
-    Box::pin(async_stream::try_stream! {
+    let (mut tx, updates) = permit.channel();
+    let mut lookup = Some(lookup);
+    tokio::spawn(async move {
         tokio::pin! {
             let shutdown = drain.signaled();
         }
 
+        // The networks are fixed once their sender is dropped.
+        let mut networks_fixed = false;
+        let mut current = None;
+        let mut last_sent = None;
         loop {
-            tokio::select! {
+            let update = tokio::select! {
                 // When the port is updated with a new server, update the server watch.
                 res = rx.next() => match res {
                     Some(s) => {
-                        yield to_server(&s, &cluster_networks);
+                        let networks = cluster_networks.borrow_and_update().clone();
+                        let update = tracing::info_span!(parent: &span, "publish")
+                            .in_scope(|| to_server(&s, &networks));
+                        current = Some(s);
+                        if let Some(mut lookup) = lookup.take() {
+                            lookup.responded();
+                        }
+                        next_update(update, &mut last_sent, deltas)
+                    }
+                    None => {
+                        stream.closed("removed");
+                        return;
                     }
-                    None => return,
                 },
 
+                // When the cluster's networks change, republish the current
+                // server so that its authorizations reflect the new networks.
+                res = cluster_networks.changed(), if !networks_fixed => {
+                    if res.is_err() {
+                        networks_fixed = true;
+                        continue;
+                    }
+                    let Some(s) = current.as_ref() else { continue };
+                    let networks = cluster_networks.borrow_and_update().clone();
+                    let update = tracing::info_span!(parent: &span, "publish")
+                        .in_scope(|| to_server(s, &networks));
+                    next_update(update, &mut last_sent, deltas)
+                }
+
                 // If the server starts shutting down, close the stream so that it doesn't hold the
                 // server open.
                 _ = (&mut shutdown) => {
+                    stream.closed("shutdown");
                     return;
                 }
+
+                _ = tx.closed() => return,
+            };
+
+            match tx.send(update).await {
+                Ok(()) => stream.sent(),
+                Err(SendError::Lagging) => {
+                    stream.closed("lagging");
+                    return;
+                }
+                Err(SendError::Closed) => return,
             }
         }
-    })
+    });
+    Box::pin(updates)
+}
+
+/// Returns the update to send for a server, recording it as the last server
+/// sent. If deltas are enabled and only the server's authorizations have
+/// changed since the last update, only the authorizations are sent.
+fn next_update(
+    server: proto::Server,
+    last_sent: &mut Option<proto::Server>,
+    deltas: bool,
+) -> proto::Server {
+    let update = match last_sent.as_ref() {
+        Some(last)
+            if deltas
+                && last.protocol == server.protocol
+                && last.server_ips == server.server_ips
+                && last.labels == server.labels =>
+        {
+            trace!("Sending authorizations delta");
+            proto::Server {
+                protocol: None,
+                authorizations: server.authorizations.clone(),
+                labels: server.labels.clone(),
+                ..Default::default()
+            }
+        }
+        _ => server.clone(),
+    };
+    *last_sent = Some(server);
+    update
 }
 
 fn to_server(srv: &InboundServer, cluster_networks: &[IpNet]) -> proto::Server {
@@ -386,14 +556,15 @@ fn to_http_route(
         .collect();
 
     let rules = rules
-        .into_iter()
+        .iter()
         .map(
             |HttpRouteRule { matches, filters }| proto::http_route::Rule {
                 matches: matches
-                    .into_iter()
+                    .iter()
+                    .cloned()
                     .map(routes::http::convert_match)
                     .collect(),
-                filters: filters.into_iter().filter_map(convert_filter).collect(),
+                filters: filters.iter().cloned().filter_map(convert_filter).collect(),
             },
         )
         .collect();
@@ -427,3 +598,56 @@ fn convert_filter(filter: Filter) -> Option<proto::http_route::Filter> {
 
     kind.map(|kind| proto::http_route::Filter { kind: Some(kind) })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_server(protocol: proto::proxy_protocol::Kind, authzs: &[&str]) -> proto::Server {
+        proto::Server {
+            protocol: Some(proto::ProxyProtocol {
+                kind: Some(protocol),
+            }),
+            authorizations: authzs
+                .iter()
+                .map(|name| proto::Authz {
+                    labels: convert_args!(hashmap!("name" => *name)),
+                    ..Default::default()
+                })
+                .collect(),
+            labels: convert_args!(hashmap!("name" => "srv")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn authorization_deltas() {
+        let opaque = || proto::proxy_protocol::Kind::Opaque(proto::proxy_protocol::Opaque {});
+        let tls = || proto::proxy_protocol::Kind::Tls(proto::proxy_protocol::Tls {});
+        let mut last_sent = None;
+
+        let first = mk_server(opaque(), &["a"]);
+        assert_eq!(next_update(first.clone(), &mut last_sent, true), first);
+
+        let update = next_update(mk_server(opaque(), &["a", "b"]), &mut last_sent, true);
+        assert_eq!(update.protocol, None, "authorization changes are deltas");
+        assert_eq!(update.authorizations.len(), 2);
+        assert_eq!(update.labels, first.labels);
+
+        let changed = mk_server(tls(), &["a", "b"]);
+        assert_eq!(
+            next_update(changed.clone(), &mut last_sent, true),
+            changed,
+            "protocol changes are sent in full"
+        );
+
+        let mut last_sent = None;
+        next_update(first, &mut last_sent, false);
+        let update = mk_server(opaque(), &["a", "b"]);
+        assert_eq!(
+            next_update(update.clone(), &mut last_sent, false),
+            update,
+            "deltas are only sent to clients that support them"
+        );
+    }
+}
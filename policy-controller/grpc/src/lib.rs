@@ -3,6 +3,10 @@
 
 mod routes;
 
+pub mod capabilities;
 pub mod inbound;
+pub mod limits;
+pub mod metrics;
 pub mod outbound;
+pub mod staleness;
 pub mod workload;
@@ -0,0 +1,416 @@
+use ahash::AHashMap as HashMap;
+use futures::prelude::*;
+use parking_lot::Mutex;
+use prometheus_client::{metrics::counter::Counter, registry::Registry};
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::{
+        mpsc::{self, error::SendTimeoutError},
+        OwnedSemaphorePermit, Semaphore,
+    },
+};
+use tonic::transport::server::Connected;
+
+/// Updates that take longer than this to be consumed by a client are counted
+/// as lagging.
+const LAG_THRESHOLD: time::Duration = time::Duration::from_secs(1);
+
+/// Bounds the resources that a single client connection may consume by
+/// watching policies.
+///
+/// Each connection may hold at most `max_per_connection` concurrent watches.
+/// At most one pending update is buffered per stream; when a client fails to
+/// consume an update within `lag_timeout`, the stream is reset so that it may
+/// be re-established with the latest state.
+#[derive(Clone, Debug)]
+pub struct WatchLimits {
+    max_per_connection: usize,
+    lag_timeout: time::Duration,
+    active: Arc<Mutex<HashMap<SocketAddr, usize>>>,
+    metrics: WatchMetrics,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WatchMetrics {
+    rejected: Counter,
+    lagged: Counter,
+    resets: Counter,
+}
+
+/// Holds a connection's watch slot for the lifetime of a response stream.
+#[derive(Debug)]
+pub(crate) struct WatchPermit {
+    client: Option<SocketAddr>,
+    lag_timeout: time::Duration,
+    active: Arc<Mutex<HashMap<SocketAddr, usize>>>,
+    metrics: WatchMetrics,
+}
+
+/// Sends a watch's updates to its client, holding the watch's permit.
+///
+/// Updates are produced by a task that is decoupled from the response stream,
+/// so that the lag timeout is enforced while an update is pending, even when
+/// the client never polls the stream again.
+#[derive(Debug)]
+pub(crate) struct WatchSender<T> {
+    tx: mpsc::Sender<T>,
+    reset: Arc<AtomicBool>,
+    permit: WatchPermit,
+}
+
+/// The response stream of a watch, yielding the updates sent by its
+/// [`WatchSender`].
+///
+/// If the sender is dropped because the client lagged, the stream fails after
+/// the buffered update has been consumed.
+#[derive(Debug)]
+pub(crate) struct WatchReceiver<T> {
+    rx: mpsc::Receiver<T>,
+    reset: Arc<AtomicBool>,
+}
+
+/// Indicates why an update could not be sent to a watch's client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SendError {
+    /// The client did not consume the previous update within the lag timeout.
+    Lagging,
+
+    /// The client dropped the response stream.
+    Closed,
+}
+
+// === impl WatchLimits ===
+
+impl WatchLimits {
+    pub fn new(
+        max_per_connection: usize,
+        lag_timeout: time::Duration,
+        metrics: WatchMetrics,
+    ) -> Self {
+        Self {
+            max_per_connection,
+            lag_timeout,
+            active: Default::default(),
+            metrics,
+        }
+    }
+
+    /// Acquires a watch slot for the given client connection.
+    ///
+    /// Connections without a known remote address are not limited.
+    pub(crate) fn acquire(&self, client: Option<SocketAddr>) -> Result<WatchPermit, tonic::Status> {
+        if let Some(addr) = client {
+            let mut active = self.active.lock();
+            let watches = active.entry(addr).or_default();
+            if *watches >= self.max_per_connection {
+                self.metrics.rejected.inc();
+                tracing::info!(client.addr = %addr, watches, "Too many concurrent watches");
+                return Err(tonic::Status::resource_exhausted(format!(
+                    "too many concurrent watches (limit {})",
+                    self.max_per_connection
+                )));
+            }
+            *watches += 1;
+        }
+
+        Ok(WatchPermit {
+            client,
+            lag_timeout: self.lag_timeout,
+            active: self.active.clone(),
+            metrics: self.metrics.clone(),
+        })
+    }
+}
+
+// === impl WatchMetrics ===
+
+impl WatchMetrics {
+    pub fn register(prom: &mut Registry) -> Self {
+        let rejected = Counter::default();
+        prom.register(
+            "watch_rejected",
+            "Count of watches rejected because the client connection has too many active watches",
+            rejected.clone(),
+        );
+
+        let lagged = Counter::default();
+        prom.register(
+            "watch_lagged_updates",
+            "Count of updates that were not consumed by the client within one second",
+            lagged.clone(),
+        );
+
+        let resets = Counter::default();
+        prom.register(
+            "watch_resets",
+            "Count of watches reset because the client did not consume an update within the lag timeout",
+            resets.clone(),
+        );
+
+        Self {
+            rejected,
+            lagged,
+            resets,
+        }
+    }
+}
+
+// === impl WatchPermit ===
+
+impl WatchPermit {
+    /// Creates a channel through which at most one pending update is buffered
+    /// for the client.
+    pub(crate) fn channel<T>(self) -> (WatchSender<T>, WatchReceiver<T>) {
+        let (tx, rx) = mpsc::channel(1);
+        let reset = Arc::new(AtomicBool::new(false));
+        let tx = WatchSender {
+            tx,
+            reset: reset.clone(),
+            permit: self,
+        };
+        (tx, WatchReceiver { rx, reset })
+    }
+}
+
+impl Drop for WatchPermit {
+    fn drop(&mut self) {
+        if let Some(addr) = self.client {
+            let mut active = self.active.lock();
+            if let Some(watches) = active.get_mut(&addr) {
+                *watches -= 1;
+                if *watches == 0 {
+                    active.remove(&addr);
+                }
+            }
+        }
+    }
+}
+
+// === impl WatchSender ===
+
+impl<T> WatchSender<T> {
+    /// Sends an update to the client once it has consumed the previous update,
+    /// failing if the client does not do so within the lag timeout.
+    ///
+    /// When the client lags, the watch should be dropped so that its permit is
+    /// released.
+    pub(crate) async fn send(&mut self, update: T) -> Result<(), SendError> {
+        let WatchPermit {
+            client,
+            lag_timeout,
+            ref metrics,
+            ..
+        } = self.permit;
+        let start = time::Instant::now();
+        match self.tx.send_timeout(update, lag_timeout).await {
+            Ok(()) => {
+                if start.elapsed() >= LAG_THRESHOLD {
+                    metrics.lagged.inc();
+                }
+                Ok(())
+            }
+            Err(SendTimeoutError::Timeout(_)) => {
+                metrics.resets.inc();
+                tracing::info!(client.addr = ?client, ?lag_timeout, "Resetting lagging watch");
+                self.reset.store(true, Ordering::Release);
+                Err(SendError::Lagging)
+            }
+            Err(SendTimeoutError::Closed(_)) => Err(SendError::Closed),
+        }
+    }
+
+    /// Completes when the client has dropped the response stream.
+    pub(crate) async fn closed(&self) {
+        self.tx.closed().await
+    }
+}
+
+// === impl WatchReceiver ===
+
+impl<T> Stream for WatchReceiver<T> {
+    type Item = Result<T, tonic::Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match futures::ready!(self.rx.poll_recv(cx)) {
+            Some(update) => Poll::Ready(Some(Ok(update))),
+            None if self.reset.swap(false, Ordering::AcqRel) => Poll::Ready(Some(Err(
+                tonic::Status::resource_exhausted("client did not consume updates in time"),
+            ))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// A server connection that holds one of a limited number of connection
+/// slots until it is dropped.
+#[derive(Debug)]
+pub struct LimitedConnection<IO> {
+    io: IO,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Limits the number of connections that may be open concurrently.
+///
+/// New connections are not accepted from `incoming` until a slot is available,
+/// so excess connections are left in the listener's backlog rather than being
+/// served. Limits beyond [`Semaphore::MAX_PERMITS`] are clamped.
+pub fn limit_connections<I, IO, E>(
+    incoming: I,
+    max_connections: usize,
+) -> impl Stream<Item = Result<LimitedConnection<IO>, E>>
+where
+    I: Stream<Item = Result<IO, E>> + Unpin,
+{
+    let semaphore = Arc::new(Semaphore::new(max_connections.min(Semaphore::MAX_PERMITS)));
+    stream::unfold(incoming, move |mut incoming| {
+        let semaphore = semaphore.clone();
+        async move {
+            // The semaphore is never closed.
+            let permit = semaphore.acquire_owned().await.ok()?;
+            let conn = incoming.next().await?.map(|io| LimitedConnection {
+                io,
+                _permit: permit,
+            });
+            Some((conn, incoming))
+        }
+    })
+}
+
+// === impl LimitedConnection ===
+
+impl<IO: Connected> Connected for LimitedConnection<IO> {
+    type ConnectInfo = IO::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.io.connect_info()
+    }
+}
+
+impl<IO: AsyncRead + Unpin> AsyncRead for LimitedConnection<IO> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for LimitedConnection<IO> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_watches_per_connection() {
+        let limits = WatchLimits::new(2, time::Duration::from_secs(10), Default::default());
+        let a = Some(SocketAddr::from(([192, 0, 2, 1], 4143)));
+        let b = Some(SocketAddr::from(([192, 0, 2, 2], 4143)));
+
+        let a0 = limits.acquire(a).expect("first watch must be permitted");
+        let _a1 = limits.acquire(a).expect("second watch must be permitted");
+        assert!(limits.acquire(a).is_err(), "third watch must be rejected");
+        assert!(
+            limits.acquire(b).is_ok(),
+            "other connections are unaffected"
+        );
+        assert!(
+            limits.acquire(None).is_ok(),
+            "unknown clients are unlimited"
+        );
+
+        drop(a0);
+        assert!(limits.acquire(a).is_ok(), "released slots may be reused");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resets_stalled_watches() {
+        let limits = WatchLimits::new(1, time::Duration::from_secs(10), Default::default());
+        let client = Some(SocketAddr::from(([192, 0, 2, 1], 4143)));
+        let (mut tx, mut rx) = limits.acquire(client).unwrap().channel();
+
+        assert_eq!(tx.send(0).await, Ok(()));
+        assert_eq!(rx.next().await.unwrap().unwrap(), 0);
+
+        // The client stops consuming updates, so the second update remains
+        // buffered and the third cannot be sent.
+        assert_eq!(tx.send(1).await, Ok(()));
+        assert_eq!(tx.send(2).await, Err(SendError::Lagging));
+        drop(tx);
+        assert!(
+            limits.acquire(client).is_ok(),
+            "the permit must be released when the client lags"
+        );
+
+        assert_eq!(rx.next().await.unwrap().unwrap(), 1);
+        let status = rx.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(rx.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_watches_dropped_by_clients() {
+        let limits = WatchLimits::new(1, time::Duration::from_secs(10), Default::default());
+        let (mut tx, rx) = limits.acquire(None).unwrap().channel();
+        drop(rx);
+        tx.closed().await;
+        assert_eq!(tx.send(0).await, Err(SendError::Closed));
+    }
+
+    #[tokio::test]
+    async fn limits_connections() {
+        let incoming = stream::iter((0..3).map(Ok::<_, ()>));
+        let mut conns = Box::pin(limit_connections(incoming, 2));
+
+        let c0 = conns.next().await.unwrap().unwrap();
+        let _c1 = conns.next().await.unwrap().unwrap();
+        assert!(
+            conns.next().now_or_never().is_none(),
+            "connections must not be accepted beyond the limit"
+        );
+
+        drop(c0);
+        let c2 = conns.next().await.unwrap().unwrap();
+        assert_eq!(c2.io, 2);
+    }
+}
@@ -0,0 +1,258 @@
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::Histogram},
+    registry::{Registry, Unit},
+};
+use std::time;
+
+type StreamLabels = [(&'static str, &'static str); 2];
+type CloseLabels = [(&'static str, &'static str); 3];
+type LookupLabels = [(&'static str, &'static str); 2];
+type HistogramFamily = Family<StreamLabels, Histogram, fn() -> Histogram>;
+
+/// Describes the activity of policy watch streams, labeled by the API serving
+/// the stream and the kind of resource being watched, and the latency of
+/// policy lookups.
+#[derive(Clone, Debug)]
+pub struct StreamMetrics {
+    active: Family<StreamLabels, Gauge>,
+    updates: Family<StreamLabels, Counter>,
+    stream_updates: HistogramFamily,
+    duration: HistogramFamily,
+    closed: Family<CloseLabels, Counter>,
+    lookup_duration: Family<LookupLabels, Histogram, fn() -> Histogram>,
+}
+
+/// Records the lifetime of a single watch stream.
+///
+/// Recorders should only be created once a watch's lookup has succeeded, so
+/// that failed lookups are not counted as opened streams; they are recorded by
+/// a [`LookupRecorder`] instead.
+///
+/// When the recorder is dropped, the stream is considered closed. Unless
+/// another reason is set, streams are assumed to have been closed by the
+/// client.
+#[derive(Debug)]
+pub(crate) struct StreamRecorder {
+    labels: StreamLabels,
+    start: time::Instant,
+    updates: u64,
+    reason: &'static str,
+    metrics: StreamMetrics,
+}
+
+/// Records the time taken to serve the initial response to a single lookup.
+///
+/// For watches, the initial response is the first update sent on the stream.
+/// If the recorder is dropped before a result is recorded, the lookup is
+/// considered to have been canceled by the client.
+#[derive(Debug)]
+pub(crate) struct LookupRecorder {
+    api: &'static str,
+    start: time::Instant,
+    recorded: bool,
+    metrics: StreamMetrics,
+}
+
+// === impl StreamMetrics ===
+
+impl StreamMetrics {
+    pub fn register(prom: &mut Registry) -> Self {
+        let active = Family::default();
+        prom.register(
+            "watch_streams",
+            "The number of active watch streams",
+            active.clone(),
+        );
+
+        let updates = Family::default();
+        prom.register(
+            "watch_updates",
+            "Count of updates sent on watch streams",
+            updates.clone(),
+        );
+
+        let stream_updates = HistogramFamily::new_with_constructor(|| {
+            Histogram::new([1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 500.0, 1000.0].into_iter())
+        });
+        prom.register(
+            "watch_stream_updates",
+            "Histogram of the number of updates sent on each closed watch stream",
+            stream_updates.clone(),
+        );
+
+        let duration = HistogramFamily::new_with_constructor(|| {
+            Histogram::new(
+                [
+                    1.0, 10.0, 60.0, 300.0, 900.0, 1800.0, 3600.0, 21600.0, 86400.0,
+                ]
+                .into_iter(),
+            )
+        });
+        prom.register_with_unit(
+            "watch_stream_duration",
+            "Histogram of the lifetimes of closed watch streams",
+            Unit::Seconds,
+            duration.clone(),
+        );
+
+        let closed = Family::default();
+        prom.register(
+            "watch_streams_closed",
+            "Count of closed watch streams by the reason they were closed",
+            closed.clone(),
+        );
+
+        let lookup_duration = Family::<_, _, fn() -> Histogram>::new_with_constructor(|| {
+            Histogram::new(
+                [
+                    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+                ]
+                .into_iter(),
+            )
+        });
+        prom.register_with_unit(
+            "lookup_duration",
+            "Histogram of the time taken to serve the initial response to a policy lookup, \
+             including the time spent waiting to read the index",
+            Unit::Seconds,
+            lookup_duration.clone(),
+        );
+
+        Self {
+            active,
+            updates,
+            stream_updates,
+            duration,
+            closed,
+            lookup_duration,
+        }
+    }
+
+    pub(crate) fn lookup(&self, api: &'static str) -> LookupRecorder {
+        LookupRecorder {
+            api,
+            start: time::Instant::now(),
+            recorded: false,
+            metrics: self.clone(),
+        }
+    }
+
+    pub(crate) fn stream(&self, api: &'static str, kind: &'static str) -> StreamRecorder {
+        let labels = [("api", api), ("kind", kind)];
+        self.active.get_or_create(&labels).inc();
+        StreamRecorder {
+            labels,
+            start: time::Instant::now(),
+            updates: 0,
+            reason: "client",
+            metrics: self.clone(),
+        }
+    }
+}
+
+// === impl StreamRecorder ===
+
+impl StreamRecorder {
+    pub(crate) fn sent(&mut self) {
+        self.updates += 1;
+        self.metrics.updates.get_or_create(&self.labels).inc();
+    }
+
+    /// Records that the server closed the stream for the given reason.
+    pub(crate) fn closed(&mut self, reason: &'static str) {
+        self.reason = reason;
+    }
+}
+
+impl Drop for StreamRecorder {
+    fn drop(&mut self) {
+        let Self {
+            labels,
+            start,
+            updates,
+            reason,
+            ref metrics,
+        } = *self;
+        metrics.active.get_or_create(&labels).dec();
+        metrics
+            .stream_updates
+            .get_or_create(&labels)
+            .observe(updates as f64);
+        metrics
+            .duration
+            .get_or_create(&labels)
+            .observe(start.elapsed().as_secs_f64());
+        let [api, kind] = labels;
+        metrics
+            .closed
+            .get_or_create(&[api, kind, ("reason", reason)])
+            .inc();
+    }
+}
+
+// === impl LookupRecorder ===
+
+impl LookupRecorder {
+    /// Records that the initial response has been produced.
+    pub(crate) fn responded(&mut self) {
+        self.record("ok");
+    }
+
+    /// Records that the lookup failed, returning the status for convenience.
+    pub(crate) fn failed(&mut self, status: tonic::Status) -> tonic::Status {
+        self.record(match status.code() {
+            tonic::Code::InvalidArgument => "invalid_argument",
+            tonic::Code::NotFound => "not_found",
+            tonic::Code::ResourceExhausted => "resource_exhausted",
+            _ => "error",
+        });
+        status
+    }
+
+    fn record(&mut self, result: &'static str) {
+        if std::mem::replace(&mut self.recorded, true) {
+            return;
+        }
+        self.metrics
+            .lookup_duration
+            .get_or_create(&[("api", self.api), ("result", result)])
+            .observe(self.start.elapsed().as_secs_f64());
+    }
+}
+
+impl Drop for LookupRecorder {
+    fn drop(&mut self) {
+        self.record("canceled");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_lookup_results() {
+        let mut prom = Registry::default();
+        let metrics = StreamMetrics::register(&mut prom);
+
+        let mut lookup = metrics.lookup("inbound");
+        lookup.responded();
+        // Only the first result is recorded.
+        let _ = lookup.failed(tonic::Status::internal("late"));
+        drop(lookup);
+        let mut lookup = metrics.lookup("outbound");
+        let _ = lookup.failed(tonic::Status::not_found("unknown server"));
+        drop(metrics.lookup("outbound"));
+
+        let mut text = String::new();
+        prometheus_client::encoding::text::encode(&mut text, &prom).unwrap();
+        for expected in [
+            r#"lookup_duration_seconds_count{api="inbound",result="ok"} 1"#,
+            r#"lookup_duration_seconds_count{api="outbound",result="not_found"} 1"#,
+            r#"lookup_duration_seconds_count{api="outbound",result="canceled"} 1"#,
+        ] {
+            assert!(text.contains(expected), "{expected} not in {text}");
+        }
+        assert!(!text.contains(r#"result="error""#), "{text}");
+    }
+}
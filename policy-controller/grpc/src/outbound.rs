@@ -1,4 +1,11 @@
-use crate::{routes, workload};
+use crate::{
+    capabilities::Capabilities,
+    limits::{SendError, WatchLimits, WatchPermit},
+    metrics::{LookupRecorder, StreamMetrics, StreamRecorder},
+    routes,
+    staleness::Staleness,
+    workload,
+};
 use futures::prelude::*;
 use linkerd2_proxy_api::{
     self as api, destination,
@@ -10,8 +17,8 @@ use linkerd2_proxy_api::{
 };
 use linkerd_policy_controller_core::{
     outbound::{
-        Backend, DiscoverOutboundPolicy, Filter, HttpRoute, HttpRouteRule, OutboundDiscoverTarget,
-        OutboundPolicy, OutboundPolicyStream,
+        AppProtocol, Backend, DiscoverOutboundPolicy, Filter, HttpRoute, HttpRouteRule,
+        OutboundDiscoverTarget, OutboundPolicy, OutboundPolicyStream,
     },
     routes::GroupKindNamespaceName,
 };
@@ -22,6 +29,10 @@ pub struct OutboundPolicyServer<T> {
     index: T,
     // Used to parse named addresses into <svc>.<ns>.svc.<cluster-domain>.
     cluster_domain: Arc<str>,
+    limits: WatchLimits,
+    metrics: StreamMetrics,
+    capabilities: Capabilities,
+    staleness: Staleness,
     drain: drain::Watch,
 }
 
@@ -29,19 +40,44 @@ impl<T> OutboundPolicyServer<T>
 where
     T: DiscoverOutboundPolicy<OutboundDiscoverTarget> + Send + Sync + 'static,
 {
-    pub fn new(discover: T, cluster_domain: impl Into<Arc<str>>, drain: drain::Watch) -> Self {
+    pub fn new(
+        discover: T,
+        cluster_domain: impl Into<Arc<str>>,
+        limits: WatchLimits,
+        metrics: StreamMetrics,
+        capabilities: Capabilities,
+        drain: drain::Watch,
+    ) -> Self {
         Self {
             index: discover,
             cluster_domain: cluster_domain.into(),
+            limits,
+            metrics,
+            capabilities,
+            staleness: Staleness::default(),
             drain,
         }
     }
 
+    /// Marks responses that are served while the index is stale.
+    pub fn with_staleness(mut self, staleness: Staleness) -> Self {
+        self.staleness = staleness;
+        self
+    }
+
     pub fn svc(self) -> OutboundPoliciesServer<Self> {
         OutboundPoliciesServer::new(self)
     }
 
-    fn lookup(&self, spec: outbound::TrafficSpec) -> Result<OutboundDiscoverTarget, tonic::Status> {
+    /// Builds a response, including the metadata that describes how it is
+    /// served.
+    fn respond<R>(&self, rsp: R) -> tonic::Response<R> {
+        self.staleness
+            .mark(self.capabilities.advertise(tonic::Response::new(rsp)))
+    }
+
+    /// Resolves the target of a lookup.
+    fn lookup(&self, spec: outbound::TrafficSpec) -> Result<Lookup, tonic::Status> {
         let target = spec
             .target
             .ok_or_else(|| tonic::Status::invalid_argument("target is required"))?;
@@ -49,14 +85,7 @@ where
         let target = match target {
             outbound::traffic_spec::Target::Addr(target) => target,
             outbound::traffic_spec::Target::Authority(auth) => {
-                return self.lookup_authority(&auth).map(
-                    |(service_namespace, service_name, service_port)| OutboundDiscoverTarget {
-                        service_name,
-                        service_namespace,
-                        service_port,
-                        source_namespace,
-                    },
-                )
+                return self.lookup_authority(&auth, source_namespace);
             }
         };
 
@@ -75,96 +104,177 @@ where
                 tonic::Status::invalid_argument(format!("failed to parse target addr: {error}"))
             })?;
 
-        self.index
+        let target = self
+            .index
             .lookup_ip(addr, port, source_namespace)
-            .ok_or_else(|| tonic::Status::not_found("No such service"))
+            .ok_or_else(|| tonic::Status::not_found("No such service"))?;
+        let kind = if target.endpoint_owner.is_some() {
+            "endpoint"
+        } else {
+            "service"
+        };
+        Ok(Lookup {
+            target,
+            hostname: None,
+            kind,
+        })
     }
 
+    /// Parses an authority of the form `<name>.<namespace>.svc.<domain>`, or
+    /// `<hostname>.<name>.<namespace>.svc.<domain>` when it addresses a single
+    /// pod of the Service (as a StatefulSet's pods are addressed).
+    ///
+    /// Other authorities, e.g. of external destinations, are resolved to the
+    /// Service whose routes match the authority's host.
     fn lookup_authority(
         &self,
         authority: &str,
-    ) -> Result<(String, String, NonZeroU16), tonic::Status> {
+        source_namespace: String,
+    ) -> Result<Lookup, tonic::Status> {
         let auth = authority
             .parse::<http::uri::Authority>()
             .map_err(|_| tonic::Status::invalid_argument("invalid authority"))?;
 
-        let mut host = auth.host();
+        let host = auth.host().trim_end_matches('.');
         if host.is_empty() {
             return Err(tonic::Status::invalid_argument(
                 "authority must have a host",
             ));
         }
 
-        host = host
-            .trim_end_matches('.')
-            .trim_end_matches(&*self.cluster_domain);
-
-        let mut parts = host.split('.');
-        let invalid = {
-            let domain = &self.cluster_domain;
-            move || {
-                tonic::Status::not_found(format!(
-                    "authority must be of the form <name>.<namespace>.svc.{domain}",
-                ))
-            }
-        };
-        let name = parts.next().ok_or_else(invalid)?;
-        let namespace = parts.next().ok_or_else(invalid)?;
-        if parts.next() != Some("svc") {
-            return Err(invalid());
-        };
-
         let port = auth
             .port_u16()
             .and_then(|p| NonZeroU16::try_from(p).ok())
             .unwrap_or_else(|| 80.try_into().unwrap());
 
-        Ok((namespace.to_string(), name.to_string(), port))
+        let parts = host
+            .trim_end_matches(&*self.cluster_domain)
+            .split('.')
+            .collect::<Vec<_>>();
+        let (hostname, name, namespace) = match parts[..] {
+            [name, namespace, "svc", ..] => (None, name, namespace),
+            [hostname, name, namespace, "svc", ..] => (Some(hostname.to_string()), name, namespace),
+            _ => {
+                let target = self
+                    .index
+                    .lookup_hostname(host, port, source_namespace)
+                    .ok_or_else(|| {
+                        tonic::Status::not_found(format!(
+                            "authority must be of the form <name>.<namespace>.svc.{} or match the hostname of a route",
+                            self.cluster_domain,
+                        ))
+                    })?;
+                return Ok(Lookup {
+                    target,
+                    hostname: None,
+                    kind: "hostname",
+                });
+            }
+        };
+
+        let target = OutboundDiscoverTarget {
+            service_name: name.to_string(),
+            service_namespace: namespace.to_string(),
+            service_port: port,
+            source_namespace,
+            endpoint_owner: None,
+        };
+        Ok(Lookup {
+            target,
+            hostname,
+            kind: "service",
+        })
     }
 }
 
+/// The target resolved for a lookup.
+struct Lookup {
+    target: OutboundDiscoverTarget,
+
+    /// The hostname of the pod addressed by the target, if the target
+    /// addresses a single pod of a Service.
+    hostname: Option<String>,
+
+    /// Describes how the target was resolved, labeling its watch streams.
+    kind: &'static str,
+}
+
 #[async_trait::async_trait]
 impl<T> OutboundPolicies for OutboundPolicyServer<T>
 where
     T: DiscoverOutboundPolicy<OutboundDiscoverTarget> + Send + Sync + 'static,
 {
+    #[tracing::instrument(skip_all, fields(source = %req.get_ref().source_workload))]
     async fn get(
         &self,
         req: tonic::Request<outbound::TrafficSpec>,
     ) -> Result<tonic::Response<outbound::OutboundPolicy>, tonic::Status> {
-        let service = self.lookup(req.into_inner())?;
+        let mut lookup = self.metrics.lookup("outbound");
+        self.capabilities.client("outbound", req.metadata());
+        let Lookup {
+            target, hostname, ..
+        } = self
+            .lookup(req.into_inner())
+            .map_err(|s| lookup.failed(s))?;
 
         let policy = self
             .index
-            .get_outbound_policy(service)
+            .get_outbound_policy(target)
             .await
             .map_err(|error| {
                 tonic::Status::internal(format!("failed to get outbound policy: {error}"))
-            })?;
+            })
+            .and_then(|policy| policy.ok_or_else(|| tonic::Status::not_found("No such policy")))
+            .map_err(|s| lookup.failed(s))?;
 
-        if let Some(policy) = policy {
-            Ok(tonic::Response::new(to_service(policy)))
-        } else {
-            Err(tonic::Status::not_found("No such policy"))
-        }
+        let policy = match hostname {
+            Some(hostname) => policy.for_hostname(&hostname),
+            None => policy,
+        };
+        let rsp = to_service(policy);
+        lookup.responded();
+        Ok(self.respond(rsp))
     }
 
     type WatchStream = BoxWatchStream;
 
+    #[tracing::instrument(skip_all, fields(source = %req.get_ref().source_workload))]
     async fn watch(
         &self,
         req: tonic::Request<outbound::TrafficSpec>,
     ) -> Result<tonic::Response<BoxWatchStream>, tonic::Status> {
-        let service = self.lookup(req.into_inner())?;
+        let mut lookup = self.metrics.lookup("outbound");
+        let permit = self
+            .limits
+            .acquire(req.remote_addr())
+            .map_err(|s| lookup.failed(s))?;
+        self.capabilities.client("outbound", req.metadata());
+        let Lookup {
+            target,
+            hostname,
+            kind,
+        } = self
+            .lookup(req.into_inner())
+            .map_err(|s| lookup.failed(s))?;
         let drain = self.drain.clone();
 
         let rx = self
             .index
-            .watch_outbound_policy(service)
+            .watch_outbound_policy(target)
             .await
-            .map_err(|e| tonic::Status::internal(format!("lookup failed: {e}")))?
-            .ok_or_else(|| tonic::Status::not_found("unknown server"))?;
-        Ok(tonic::Response::new(response_stream(drain, rx)))
+            .map_err(|e| tonic::Status::internal(format!("lookup failed: {e}")))
+            .and_then(|rx| rx.ok_or_else(|| tonic::Status::not_found("unknown server")))
+            .map_err(|s| lookup.failed(s))?;
+        let stream = self.metrics.stream("outbound", kind);
+        Ok(self.respond(response_stream(
+            drain,
+            rx,
+            hostname,
+            permit,
+            lookup,
+            stream,
+            tracing::Span::current(),
+        )))
     }
 }
 
@@ -172,30 +282,65 @@ type BoxWatchStream = std::pin::Pin<
     Box<dyn Stream<Item = Result<outbound::OutboundPolicy, tonic::Status>> + Send + Sync>,
 >;
 
-fn response_stream(drain: drain::Watch, mut rx: OutboundPolicyStream) -> BoxWatchStream {
-    Box::pin(async_stream::try_stream! {
+fn response_stream(
+    drain: drain::Watch,
+    mut rx: OutboundPolicyStream,
+    hostname: Option<String>,
+    permit: WatchPermit,
+    lookup: LookupRecorder,
+    mut stream: StreamRecorder,
+    span: tracing::Span,
+) -> BoxWatchStream {
+    let (mut tx, updates) = permit.channel();
+    let mut lookup = Some(lookup);
+    tokio::spawn(async move {
         tokio::pin! {
             let shutdown = drain.signaled();
         }
 
         loop {
-            tokio::select! {
+            let update = tokio::select! {
                 // When the port is updated with a new server, update the server watch.
                 res = rx.next() => match res {
                     Some(policy) => {
-                        yield to_service(policy);
+                        let policy = match hostname.as_deref() {
+                            Some(hostname) => policy.for_hostname(hostname),
+                            None => policy,
+                        };
+                        let update = tracing::info_span!(parent: &span, "publish")
+                            .in_scope(|| to_service(policy));
+                        if let Some(mut lookup) = lookup.take() {
+                            lookup.responded();
+                        }
+                        update
+                    }
+                    None => {
+                        stream.closed("removed");
+                        return;
                     }
-                    None => return,
                 },
 
                 // If the server starts shutting down, close the stream so that it doesn't hold the
                 // server open.
                 _ = (&mut shutdown) => {
+                    stream.closed("shutdown");
                     return;
                 }
+
+                _ = tx.closed() => return,
+            };
+
+            match tx.send(update).await {
+                Ok(()) => stream.sent(),
+                Err(SendError::Lagging) => {
+                    stream.closed("lagging");
+                    return;
+                }
+                Err(SendError::Closed) => return,
             }
         }
-    })
+    });
+    Box::pin(updates)
 }
 
 fn to_service(outbound: OutboundPolicy) -> outbound::OutboundPolicy {
@@ -229,6 +374,9 @@ fn to_service(outbound: OutboundPolicy) -> outbound::OutboundPolicy {
             http_routes = vec![default_outbound_http_route(backend.clone())];
         }
 
+        // `outbound.retry_budget` is deliberately not encoded: the outbound
+        // API (linkerd2-proxy-api 0.13) has no field for retry budgets, so a
+        // Service's budget is only exposed by the controller's debug endpoints.
         let accrual =
             outbound
                 .accrual
@@ -256,26 +404,43 @@ fn to_service(outbound: OutboundPolicy) -> outbound::OutboundPolicy {
                     }),
                 });
 
-        linkerd2_proxy_api::outbound::proxy_protocol::Kind::Detect(
-            outbound::proxy_protocol::Detect {
-                timeout: Some(
-                    time::Duration::from_secs(10)
-                        .try_into()
-                        .expect("failed to convert detect timeout to protobuf"),
-                ),
-                opaque: Some(outbound::proxy_protocol::Opaque {
-                    routes: vec![default_outbound_opaq_route(backend)],
-                }),
-                http1: Some(outbound::proxy_protocol::Http1 {
-                    routes: http_routes.clone(),
-                    failure_accrual: accrual.clone(),
-                }),
-                http2: Some(outbound::proxy_protocol::Http2 {
+        // If the Service port declares its protocol, it need not be detected.
+        match outbound.app_protocol {
+            Some(AppProtocol::Http1) => linkerd2_proxy_api::outbound::proxy_protocol::Kind::Http1(
+                outbound::proxy_protocol::Http1 {
                     routes: http_routes,
                     failure_accrual: accrual,
-                }),
-            },
-        )
+                },
+            ),
+            Some(AppProtocol::Http2) => linkerd2_proxy_api::outbound::proxy_protocol::Kind::Http2(
+                outbound::proxy_protocol::Http2 {
+                    routes: http_routes,
+                    failure_accrual: accrual,
+                },
+            ),
+            None => linkerd2_proxy_api::outbound::proxy_protocol::Kind::Detect(
+                outbound::proxy_protocol::Detect {
+                    timeout: outbound
+                        .detect_timeout
+                        .try_into()
+                        .map_err(|error| {
+                            tracing::warn!(%error, "failed to convert protocol detect timeout to protobuf")
+                        })
+                        .ok(),
+                    opaque: Some(outbound::proxy_protocol::Opaque {
+                        routes: vec![default_outbound_opaq_route(backend)],
+                    }),
+                    http1: Some(outbound::proxy_protocol::Http1 {
+                        routes: http_routes.clone(),
+                        failure_accrual: accrual.clone(),
+                    }),
+                    http2: Some(outbound::proxy_protocol::Http2 {
+                        routes: http_routes,
+                        failure_accrual: accrual,
+                    }),
+                },
+            ),
+        }
     };
 
     let metadata = Metadata {
@@ -1,7 +1,7 @@
 use linkerd2_proxy_api::{http_route as proto, http_types};
 use linkerd_policy_controller_core::routes::{
     FailureInjectorFilter, HeaderMatch, HeaderModifierFilter, HostMatch, HttpRouteMatch, PathMatch,
-    PathModifier, QueryParamMatch, RequestRedirectFilter,
+    PathModifier, QueryParamMatch, RequestRedirectFilter, StatusCode,
 };
 
 pub(crate) fn convert_host_match(h: HostMatch) -> proto::HostMatch {
@@ -88,7 +88,7 @@ pub(crate) fn convert_redirect_filter(
             }),
         }),
         port: port.map(u16::from).map(u32::from).unwrap_or_default(),
-        status: u32::from(status.unwrap_or_default().as_u16()),
+        status: u32::from(status.unwrap_or(StatusCode::FOUND).as_u16()),
     }
 }
 
@@ -166,3 +166,45 @@ pub(crate) mod http {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd2_proxy_api::http_types::http_method;
+
+    #[test]
+    fn encodes_extension_methods() {
+        let convert = |method: &str| {
+            http::convert_match(HttpRouteMatch {
+                method: Some(method.parse().unwrap()),
+                headers: vec![],
+                path: None,
+                query_params: vec![],
+            })
+            .method
+            .and_then(|m| m.r#type)
+        };
+        assert!(matches!(
+            convert("GET"),
+            Some(http_method::Type::Registered(_))
+        ));
+        assert_eq!(
+            convert("PROPFIND"),
+            Some(http_method::Type::Unregistered("PROPFIND".to_string()))
+        );
+    }
+
+    #[test]
+    fn redirects_default_to_found() {
+        let redirect = convert_redirect_filter(RequestRedirectFilter {
+            scheme: None,
+            host: None,
+            path: None,
+            port: None,
+            status: None,
+        });
+        assert_eq!(redirect.status, 302);
+        assert_eq!(redirect.port, 0, "the request's port is preserved");
+        assert_eq!(redirect.host, "", "the request's host is preserved");
+    }
+}
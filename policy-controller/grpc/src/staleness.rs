@@ -0,0 +1,58 @@
+use std::{fmt, sync::Arc, time};
+use tonic::metadata::AsciiMetadataValue;
+
+/// The metadata key used to mark policy responses that are served from a stale
+/// index.
+///
+/// The value is the number of seconds since the index was last known to be
+/// up-to-date with the Kubernetes API. Watch responses are marked when the
+/// watch is opened; the updates sent on an open stream carry no metadata, so
+/// clients that need to know whether later updates are stale must reopen their
+/// watches.
+pub const METADATA_KEY: &str = "l5d-policy-stale-age";
+
+/// Reports how long the index has been stale, if its resource watches are
+/// failing.
+#[derive(Clone, Default)]
+pub struct Staleness(Option<Arc<dyn Fn() -> Option<time::Duration> + Send + Sync>>);
+
+// === impl Staleness ===
+
+impl Staleness {
+    pub fn new(staleness: impl Fn() -> Option<time::Duration> + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(staleness)))
+    }
+
+    /// Marks a response as stale if the index is currently stale.
+    pub(crate) fn mark<T>(&self, mut rsp: tonic::Response<T>) -> tonic::Response<T> {
+        if let Some(age) = self.0.as_ref().and_then(|staleness| staleness()) {
+            let age = AsciiMetadataValue::from(age.as_secs());
+            rsp.metadata_mut().insert(METADATA_KEY, age);
+        }
+        rsp
+    }
+}
+
+impl fmt::Debug for Staleness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Staleness").field(&self.0.is_some()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_stale_responses() {
+        let rsp = Staleness::default().mark(tonic::Response::new(()));
+        assert!(rsp.metadata().get(METADATA_KEY).is_none());
+
+        let rsp = Staleness::new(|| None).mark(tonic::Response::new(()));
+        assert!(rsp.metadata().get(METADATA_KEY).is_none());
+
+        let rsp = Staleness::new(|| Some(time::Duration::from_millis(12_500)))
+            .mark(tonic::Response::new(()));
+        assert_eq!(rsp.metadata().get(METADATA_KEY).unwrap(), "12");
+    }
+}
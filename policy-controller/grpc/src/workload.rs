@@ -5,6 +5,10 @@ use std::str::FromStr;
 pub enum Kind {
     #[serde(rename = "external_workload")]
     External(String),
+    /// An external workload identified by its mesh identity rather than by
+    /// name.
+    #[serde(rename = "external_workload_identity")]
+    ExternalIdentity(String),
     #[serde(rename = "pod")]
     Pod(String),
 }
@@ -78,6 +82,18 @@ mod tests {
         assert_eq!(expected, Workload::from_str(input).expect("should parse"));
     }
 
+    #[test]
+    fn parse_new_format_external_identity() {
+        let input = r#"{"ns":"my-namespace", "external_workload_identity":"vm.my-namespace.serviceaccount.identity.linkerd.cluster.local"}"#;
+        let expected: Workload = Workload {
+            namespace: "my-namespace".to_string(),
+            kind: Kind::ExternalIdentity(
+                "vm.my-namespace.serviceaccount.identity.linkerd.cluster.local".to_string(),
+            ),
+        };
+        assert_eq!(expected, Workload::from_str(input).expect("should parse"));
+    }
+
     #[test]
     fn errors_invalid_new_format() {
         let input = r#"{"ns":"my-namespace", "nonsense":"my-external"}"#;
@@ -0,0 +1,633 @@
+//! Snapshot tests for the policies that are served to proxies.
+//!
+//! Each fixture is served through the gRPC API and the response is compared
+//! against a golden file in `tests/snapshots`, so that changes to the encoding
+//! of policies are caught in review. When a change is intended, regenerate the
+//! golden files with:
+//!
+//! ```text
+//! UPDATE_SNAPSHOTS=1 cargo test -p linkerd-policy-controller-grpc --test snapshots
+//! ```
+
+use linkerd2_proxy_api::{
+    inbound::{inbound_server_policies_server::InboundServerPolicies, Authz, PortSpec},
+    outbound::{outbound_policies_server::OutboundPolicies, traffic_spec, TrafficSpec},
+};
+use linkerd_policy_controller_core::{
+    inbound::{self, DiscoverInboundServer, InboundServer, InboundServerStream},
+    outbound::{self, DiscoverOutboundPolicy, OutboundDiscoverTarget, OutboundPolicyStream},
+    routes::{self, GroupKindName, GroupKindNamespaceName},
+    IdentityMatch, IpNet, NetworkMatch,
+};
+use linkerd_policy_controller_grpc::{
+    capabilities::Capabilities,
+    inbound::InboundPolicyServer,
+    limits::{WatchLimits, WatchMetrics},
+    metrics::StreamMetrics,
+    outbound::OutboundPolicyServer,
+    workload::Workload,
+};
+use prometheus_client::registry::Registry;
+use std::{collections::BTreeMap, fmt, net::IpAddr, num::NonZeroU16, path::PathBuf, time};
+
+const PORT: u16 = 8080;
+
+/// Serves a fixed policy.
+#[derive(Clone, Debug)]
+struct Fixed<T>(T);
+
+#[tokio::test(flavor = "current_thread")]
+async fn inbound_default() {
+    let server = InboundServer {
+        reference: inbound::ServerRef::Default("all-unauthenticated"),
+        protocol: inbound::ProxyProtocol::Detect {
+            timeout: time::Duration::from_secs(10),
+        },
+        authorizations: Some((
+            inbound::AuthorizationRef::Default("all-unauthenticated"),
+            inbound::ClientAuthorization {
+                networks: vec![net("0.0.0.0/0"), net("::/0")],
+                authentication: inbound::ClientAuthentication::Unauthenticated,
+            },
+        ))
+        .into_iter()
+        .collect(),
+        http_routes: Some((
+            inbound::HttpRouteRef::Default("default"),
+            inbound::HttpRoute {
+                hostnames: vec![],
+                rules: vec![inbound::HttpRouteRule {
+                    matches: vec![path_prefix("/")],
+                    filters: vec![],
+                }]
+                .into(),
+                authorizations: Default::default(),
+                creation_timestamp: None,
+            },
+        ))
+        .into_iter()
+        .collect(),
+    };
+    assert_inbound_snapshot("inbound_default", server).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn inbound_authorizations() {
+    let server = InboundServer {
+        reference: inbound::ServerRef::Server("srv-0".to_string()),
+        protocol: inbound::ProxyProtocol::Http2,
+        authorizations: [
+            (
+                inbound::AuthorizationRef::AuthorizationPolicy("authz-0".to_string()),
+                inbound::ClientAuthorization {
+                    networks: vec![NetworkMatch {
+                        net: "10.0.0.0/8".parse().unwrap(),
+                        except: vec!["10.0.1.0/24".parse().unwrap()],
+                    }],
+                    authentication: inbound::ClientAuthentication::TlsAuthenticated(vec![
+                        IdentityMatch::Exact(
+                            "sa-0.ns-0.serviceaccount.identity.linkerd.cluster.local".to_string(),
+                        ),
+                        IdentityMatch::Suffix(vec![
+                            "ns-1".to_string(),
+                            "serviceaccount".to_string(),
+                            "identity".to_string(),
+                            "linkerd".to_string(),
+                            "cluster".to_string(),
+                            "local".to_string(),
+                        ]),
+                    ]),
+                },
+            ),
+            (
+                inbound::AuthorizationRef::ServerAuthorization("saz-0".to_string()),
+                inbound::ClientAuthorization {
+                    networks: vec![net("192.168.0.0/16")],
+                    authentication: inbound::ClientAuthentication::TlsUnauthenticated,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        http_routes: Default::default(),
+    };
+    assert_inbound_snapshot("inbound_authorizations", server).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn inbound_http_routes() {
+    let authorizations = [(
+        inbound::AuthorizationRef::AuthorizationPolicy("authz-route".to_string()),
+        inbound::ClientAuthorization {
+            networks: vec![net("10.0.0.0/8")],
+            authentication: inbound::ClientAuthentication::TlsAuthenticated(vec![
+                IdentityMatch::Exact(
+                    "sa-0.ns-0.serviceaccount.identity.linkerd.cluster.local".to_string(),
+                ),
+            ]),
+        },
+    )]
+    .into_iter()
+    .collect::<ahash::AHashMap<_, _>>();
+
+    let route_0 = inbound::HttpRoute {
+        hostnames: vec![
+            routes::HostMatch::Exact("app.example.com".to_string()),
+            routes::HostMatch::Suffix {
+                reverse_labels: vec!["com".to_string(), "example".to_string()],
+            },
+        ],
+        rules: vec![inbound::HttpRouteRule {
+            matches: vec![routes::HttpRouteMatch {
+                path: Some(routes::PathMatch::Exact("/api".to_string())),
+                headers: vec![
+                    routes::HeaderMatch::Exact(
+                        routes::HeaderName::from_static("x-env"),
+                        routes::HeaderValue::from_static("prod"),
+                    ),
+                    routes::HeaderMatch::Regex(
+                        routes::HeaderName::from_static("x-version"),
+                        routes::compile_regex("v[0-9]+").unwrap(),
+                    ),
+                ],
+                query_params: vec![
+                    routes::QueryParamMatch::Exact("debug".to_string(), "true".to_string()),
+                    routes::QueryParamMatch::Regex(
+                        "user".to_string(),
+                        routes::compile_regex("[a-z]+").unwrap(),
+                    ),
+                ],
+                method: Some(routes::Method::POST),
+            }],
+            filters: vec![
+                inbound::Filter::RequestHeaderModifier(routes::HeaderModifierFilter {
+                    add: vec![(
+                        routes::HeaderName::from_static("x-added"),
+                        routes::HeaderValue::from_static("1"),
+                    )],
+                    set: vec![(
+                        routes::HeaderName::from_static("x-set"),
+                        routes::HeaderValue::from_static("2"),
+                    )],
+                    remove: vec![routes::HeaderName::from_static("x-removed")],
+                }),
+                inbound::Filter::FailureInjector(routes::FailureInjectorFilter {
+                    status: routes::StatusCode::SERVICE_UNAVAILABLE,
+                    message: "injected".to_string(),
+                    ratio: routes::Ratio {
+                        numerator: 1,
+                        denominator: 10,
+                    },
+                }),
+            ],
+        }]
+        .into(),
+        authorizations: authorizations.clone(),
+        creation_timestamp: None,
+    };
+
+    let route_1 = inbound::HttpRoute {
+        hostnames: vec![],
+        rules: vec![inbound::HttpRouteRule {
+            matches: vec![routes::HttpRouteMatch {
+                path: Some(routes::PathMatch::Regex(
+                    routes::compile_regex("/v[0-9]+/.*").unwrap(),
+                )),
+                headers: vec![],
+                query_params: vec![],
+                method: None,
+            }],
+            filters: vec![
+                inbound::Filter::ResponseHeaderModifier(routes::HeaderModifierFilter {
+                    add: vec![],
+                    set: vec![(
+                        routes::HeaderName::from_static("cache-control"),
+                        routes::HeaderValue::from_static("no-store"),
+                    )],
+                    remove: vec![],
+                }),
+                inbound::Filter::RequestRedirect(routes::RequestRedirectFilter {
+                    scheme: Some(routes::Scheme::HTTPS),
+                    host: Some("secure.example.com".to_string()),
+                    path: Some(routes::PathModifier::Prefix("/v2".to_string())),
+                    port: NonZeroU16::new(8443),
+                    status: Some(routes::StatusCode::MOVED_PERMANENTLY),
+                }),
+            ],
+        }]
+        .into(),
+        authorizations,
+        creation_timestamp: None,
+    };
+
+    let server = InboundServer {
+        reference: inbound::ServerRef::Server("srv-0".to_string()),
+        protocol: inbound::ProxyProtocol::Http1,
+        authorizations: Default::default(),
+        http_routes: [
+            (
+                inbound::HttpRouteRef::Linkerd(route_gkn("route-1")),
+                route_1,
+            ),
+            (
+                inbound::HttpRouteRef::Linkerd(route_gkn("route-0")),
+                route_0,
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    };
+    assert_inbound_snapshot("inbound_http_routes", server).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn inbound_opaque() {
+    let server = InboundServer {
+        reference: inbound::ServerRef::Server("srv-0".to_string()),
+        protocol: inbound::ProxyProtocol::Opaque,
+        authorizations: Default::default(),
+        http_routes: Default::default(),
+    };
+    assert_inbound_snapshot("inbound_opaque", server).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn inbound_tls() {
+    let server = InboundServer {
+        reference: inbound::ServerRef::Server("srv-0".to_string()),
+        protocol: inbound::ProxyProtocol::Tls,
+        authorizations: Default::default(),
+        http_routes: Default::default(),
+    };
+    assert_inbound_snapshot("inbound_tls", server).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn outbound_default() {
+    assert_outbound_snapshot("outbound_default", mk_outbound_policy()).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn outbound_opaque() {
+    let policy = outbound::OutboundPolicy {
+        opaque: true,
+        ..mk_outbound_policy()
+    };
+    assert_outbound_snapshot("outbound_opaque", policy).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn outbound_http_routes() {
+    let route_0 = outbound::HttpRoute {
+        hostnames: vec![],
+        rules: vec![outbound::HttpRouteRule {
+            matches: vec![path_prefix("/api")],
+            backends: vec![
+                outbound::Backend::Service(mk_backend("backend-0", 90)),
+                outbound::Backend::Addr(outbound::WeightedAddr {
+                    weight: 10,
+                    addr: "10.1.2.3".parse().unwrap(),
+                    port: NonZeroU16::new(PORT).unwrap(),
+                }),
+            ],
+            request_timeout: Some(time::Duration::from_secs(10)),
+            backend_request_timeout: Some(time::Duration::from_secs(2)),
+            filters: vec![outbound::Filter::RequestHeaderModifier(
+                routes::HeaderModifierFilter {
+                    add: vec![],
+                    set: vec![(
+                        routes::HeaderName::from_static("x-route"),
+                        routes::HeaderValue::from_static("route-0"),
+                    )],
+                    remove: vec![],
+                },
+            )],
+        }],
+        creation_timestamp: None,
+    };
+
+    let route_1 = outbound::HttpRoute {
+        hostnames: vec![],
+        rules: vec![outbound::HttpRouteRule {
+            matches: vec![routes::HttpRouteMatch {
+                path: None,
+                headers: vec![],
+                query_params: vec![],
+                method: Some(routes::Method::GET),
+            }],
+            backends: vec![
+                outbound::Backend::Service(outbound::WeightedService {
+                    filters: vec![outbound::Filter::ResponseHeaderModifier(
+                        routes::HeaderModifierFilter {
+                            add: vec![(
+                                routes::HeaderName::from_static("x-backend"),
+                                routes::HeaderValue::from_static("backend-1"),
+                            )],
+                            set: vec![],
+                            remove: vec![],
+                        },
+                    )],
+                    ..mk_backend("backend-1", 1)
+                }),
+                outbound::Backend::Service(outbound::WeightedService {
+                    exists: false,
+                    ready: false,
+                    ..mk_backend("backend-missing", 1)
+                }),
+                outbound::Backend::Invalid {
+                    weight: 1,
+                    message: "invalid backend".to_string(),
+                },
+            ],
+            request_timeout: None,
+            backend_request_timeout: None,
+            filters: vec![outbound::Filter::RequestRedirect(
+                routes::RequestRedirectFilter {
+                    scheme: None,
+                    host: None,
+                    path: Some(routes::PathModifier::Full("/moved".to_string())),
+                    port: None,
+                    status: Some(routes::StatusCode::FOUND),
+                },
+            )],
+        }],
+        creation_timestamp: None,
+    };
+
+    let policy = outbound::OutboundPolicy {
+        http_routes: [
+            (route_gknn("route-1"), route_1),
+            (route_gknn("route-0"), route_0),
+        ]
+        .into_iter()
+        .collect(),
+        app_protocol: Some(outbound::AppProtocol::Http2),
+        accrual: Some(outbound::FailureAccrual::Consecutive {
+            max_failures: 7,
+            backoff: outbound::Backoff {
+                min_penalty: time::Duration::from_secs(1),
+                max_penalty: time::Duration::from_secs(60),
+                jitter: 0.5,
+            },
+        }),
+        ..mk_outbound_policy()
+    };
+    assert_outbound_snapshot("outbound_http_routes", policy).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn outbound_cluster_backends() {
+    let policy = outbound::OutboundPolicy {
+        cluster_backends: vec![
+            mk_backend("svc-0", 3),
+            outbound::WeightedService {
+                authority: format!("svc-0-west.ns-0.svc.cluster.local:{PORT}"),
+                ..mk_backend("svc-0-west", 1)
+            },
+        ],
+        ..mk_outbound_policy()
+    };
+    assert_outbound_snapshot("outbound_cluster_backends", policy).await;
+}
+
+async fn assert_inbound_snapshot(name: &str, server: InboundServer) {
+    let (_drain_tx, drain) = drain::channel();
+    let mut prom = Registry::default();
+    let svc = InboundPolicyServer::new(
+        Fixed(server),
+        vec!["10.0.0.0/8".parse().unwrap()],
+        mk_limits(&mut prom),
+        StreamMetrics::register(&mut prom),
+        Capabilities::register(&mut prom),
+        drain,
+    );
+    let req = tonic::Request::new(PortSpec {
+        workload: "ns-0:pod-0".to_string(),
+        port: PORT.into(),
+    });
+    let mut rsp = svc.get_port(req).await.unwrap().into_inner();
+
+    // Authorizations are not ordered.
+    sort_authzs(&mut rsp.authorizations);
+    let routes = match rsp.protocol.as_mut().and_then(|p| p.kind.as_mut()) {
+        Some(linkerd2_proxy_api::inbound::proxy_protocol::Kind::Detect(detect)) => {
+            Some(&mut detect.http_routes)
+        }
+        Some(linkerd2_proxy_api::inbound::proxy_protocol::Kind::Http1(http)) => {
+            Some(&mut http.routes)
+        }
+        Some(linkerd2_proxy_api::inbound::proxy_protocol::Kind::Http2(http)) => {
+            Some(&mut http.routes)
+        }
+        _ => None,
+    };
+    for route in routes.into_iter().flatten() {
+        sort_authzs(&mut route.authorizations);
+    }
+
+    assert_snapshot(name, rsp);
+}
+
+async fn assert_outbound_snapshot(name: &str, policy: outbound::OutboundPolicy) {
+    let (_drain_tx, drain) = drain::channel();
+    let mut prom = Registry::default();
+    let svc = OutboundPolicyServer::new(
+        Fixed(policy),
+        "cluster.local",
+        mk_limits(&mut prom),
+        StreamMetrics::register(&mut prom),
+        Capabilities::register(&mut prom),
+        drain,
+    );
+    let req = tonic::Request::new(TrafficSpec {
+        source_workload: "ns-0:pod-0".to_string(),
+        target: Some(traffic_spec::Target::Authority(format!(
+            "svc-0.ns-0.svc.cluster.local:{PORT}"
+        ))),
+    });
+    let rsp = svc.get(req).await.unwrap().into_inner();
+    assert_snapshot(name, rsp);
+}
+
+/// Compares a response against its golden file, or overwrites the golden file
+/// if `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, rsp: impl fmt::Debug) {
+    let actual = sort_maps(&format!("{rsp:#?}\n"));
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!(
+            "failed to read {}: {error}; set UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    if actual != expected {
+        let diff = expected
+            .lines()
+            .zip(actual.lines())
+            .enumerate()
+            .find(|(_, (e, a))| e != a)
+            .map(|(i, (e, a))| format!("line {}:\n  expected: {e}\n  actual:   {a}", i + 1))
+            .unwrap_or_else(|| "responses differ in length".to_string());
+        panic!(
+            "response does not match {}\n{diff}\nset UPDATE_SNAPSHOTS=1 if the change is intended",
+            path.display()
+        );
+    }
+}
+
+fn sort_authzs(authzs: &mut [Authz]) {
+    authzs.sort_by_cached_key(|authz| authz.labels.clone().into_iter().collect::<BTreeMap<_, _>>());
+}
+
+/// Protobuf maps are unordered, so the entries of each map in a rendered
+/// response are sorted. Maps are rendered as `field: {`, whereas messages are
+/// rendered with their type name.
+fn sort_maps(rendered: &str) -> String {
+    let mut out = Vec::new();
+    let mut lines = rendered.lines();
+    while let Some(line) = lines.next() {
+        out.push(line.to_string());
+        let trimmed = line.trim_start();
+        let is_map = trimmed
+            .strip_suffix(": {")
+            .map_or(false, |field| !field.contains(' '));
+        if !is_map {
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+        let mut entries = Vec::new();
+        for entry in lines.by_ref() {
+            if entry.len() - entry.trim_start().len() == indent {
+                entries.sort();
+                out.append(&mut entries);
+                out.push(entry.to_string());
+                break;
+            }
+            entries.push(entry.to_string());
+        }
+    }
+    out.join("\n") + "\n"
+}
+
+fn mk_limits(prom: &mut Registry) -> WatchLimits {
+    WatchLimits::new(
+        1000,
+        time::Duration::from_secs(30),
+        WatchMetrics::register(prom),
+    )
+}
+
+fn mk_outbound_policy() -> outbound::OutboundPolicy {
+    outbound::OutboundPolicy {
+        http_routes: Default::default(),
+        authority: format!("svc-0.ns-0.svc.cluster.local:{PORT}"),
+        name: "svc-0".to_string(),
+        namespace: "ns-0".to_string(),
+        port: NonZeroU16::new(PORT).unwrap(),
+        opaque: false,
+        app_protocol: None,
+        accrual: None,
+        retry_budget: None,
+        detect_timeout: time::Duration::from_secs(10),
+        cluster_backends: vec![],
+    }
+}
+
+fn mk_backend(name: &str, weight: u32) -> outbound::WeightedService {
+    outbound::WeightedService {
+        weight,
+        authority: format!("{name}.ns-0.svc.cluster.local:{PORT}"),
+        name: name.to_string(),
+        namespace: "ns-0".to_string(),
+        port: NonZeroU16::new(PORT).unwrap(),
+        filters: vec![],
+        exists: true,
+        ready: true,
+        zones: vec![],
+    }
+}
+
+fn route_gkn(name: &str) -> GroupKindName {
+    GroupKindName {
+        group: "policy.linkerd.io".into(),
+        kind: "HTTPRoute".into(),
+        name: name.into(),
+    }
+}
+
+fn route_gknn(name: &str) -> GroupKindNamespaceName {
+    GroupKindNamespaceName {
+        group: "policy.linkerd.io".into(),
+        kind: "HTTPRoute".into(),
+        namespace: "ns-0".into(),
+        name: name.into(),
+    }
+}
+
+fn path_prefix(prefix: &str) -> routes::HttpRouteMatch {
+    routes::HttpRouteMatch {
+        path: Some(routes::PathMatch::Prefix(prefix.to_string())),
+        headers: vec![],
+        query_params: vec![],
+        method: None,
+    }
+}
+
+fn net(cidr: &str) -> NetworkMatch {
+    cidr.parse::<IpNet>().unwrap().into()
+}
+
+// === impl Fixed ===
+
+#[async_trait::async_trait]
+impl DiscoverInboundServer<(Workload, NonZeroU16)> for Fixed<InboundServer> {
+    async fn get_inbound_server(
+        &self,
+        _: (Workload, NonZeroU16),
+    ) -> anyhow::Result<Option<InboundServer>> {
+        Ok(Some(self.0.clone()))
+    }
+
+    async fn watch_inbound_server(
+        &self,
+        _: (Workload, NonZeroU16),
+    ) -> anyhow::Result<Option<InboundServerStream>> {
+        Ok(None)
+    }
+}
+
+#[async_trait::async_trait]
+impl DiscoverOutboundPolicy<OutboundDiscoverTarget> for Fixed<outbound::OutboundPolicy> {
+    async fn get_outbound_policy(
+        &self,
+        _: OutboundDiscoverTarget,
+    ) -> anyhow::Result<Option<outbound::OutboundPolicy>> {
+        Ok(Some(self.0.clone()))
+    }
+
+    async fn watch_outbound_policy(
+        &self,
+        _: OutboundDiscoverTarget,
+    ) -> anyhow::Result<Option<OutboundPolicyStream>> {
+        Ok(None)
+    }
+
+    fn lookup_ip(&self, _: IpAddr, _: NonZeroU16, _: String) -> Option<OutboundDiscoverTarget> {
+        None
+    }
+
+    fn lookup_hostname(&self, _: &str, _: NonZeroU16, _: String) -> Option<OutboundDiscoverTarget> {
+        None
+    }
+}
@@ -18,8 +18,15 @@ pub enum ParseError {
 
     #[error("invalid floating-point number: {}", .0)]
     NotANumber(#[from] std::num::ParseFloatError),
+
+    #[error("duration out of range")]
+    OutOfRange,
 }
 
+/// Go durations are int64 nanoseconds, so larger durations cannot be
+/// represented.
+const MAX: Duration = Duration::from_nanos(i64::MAX as u64);
+
 const EXPECTED_UNITS: &str = "expected one of 'ns', 'us', '\u{00b5}s', 'ms', 's', 'm', or 'h'";
 
 impl From<Duration> for K8sDuration {
@@ -85,7 +92,10 @@ impl FromStr for K8sDuration {
                 "h" => MINUTE * 60,
                 _ => return Err(ParseError::InvalidUnit),
             };
-            Ok(base.mul_f64(val))
+            Duration::try_from_secs_f64(base.as_secs_f64() * val)
+                .ok()
+                .filter(|d| *d <= MAX)
+                .ok_or(ParseError::OutOfRange)
         }
 
         // Go durations are signed. Rust durations aren't. So we need to ignore
@@ -107,7 +117,10 @@ impl FromStr for K8sDuration {
                     s = "";
                     rest
                 };
-                total += duration_from_units(val, unit)?;
+                total = total
+                    .checked_add(duration_from_units(val, unit)?)
+                    .filter(|d| *d <= MAX)
+                    .ok_or(ParseError::OutOfRange)?;
             } else if s == "0" {
                 return Ok(K8sDuration {
                     duration: Duration::from_secs(0),
@@ -351,4 +364,18 @@ mod tests {
             assert_eq!(&dbg!(parsed), expected);
         }
     }
+
+    #[test]
+    fn rejects_overflowing_durations() {
+        for input in [
+            "9223372036855s",
+            "6000000000000000h",
+            "5000000000s5000000000s",
+        ] {
+            assert_eq!(
+                dbg!(input).parse::<K8sDuration>(),
+                Err(ParseError::OutOfRange)
+            );
+        }
+    }
 }
@@ -71,6 +71,15 @@ impl Selector {
         }
     }
 
+    /// Returns the labels that must be set, with the given values, on all
+    /// selected resources.
+    pub fn required_labels(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.match_labels
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
     pub fn matches(&self, labels: &Labels) -> bool {
         for expr in self.match_expressions.iter().flatten() {
             if !expr.matches(labels.as_ref()) {
@@ -11,6 +11,22 @@ use super::{LocalTargetRef, NamespacedTargetRef};
 )]
 #[serde(rename_all = "camelCase")]
 pub struct AuthorizationPolicySpec {
-    pub target_ref: LocalTargetRef,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_ref: Option<LocalTargetRef>,
+
+    /// Additional resources to which the policy applies, so that targets with
+    /// the same authentication requirements may share a policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_refs: Option<Vec<LocalTargetRef>>,
+
     pub required_authentication_refs: Vec<NamespacedTargetRef>,
 }
+
+impl AuthorizationPolicySpec {
+    /// Returns all of the policy's targets: its `targetRef` and `targetRefs`.
+    pub fn targets(&self) -> impl Iterator<Item = &LocalTargetRef> {
+        self.target_ref
+            .iter()
+            .chain(self.target_refs.iter().flatten())
+    }
+}
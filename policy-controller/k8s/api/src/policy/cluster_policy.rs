@@ -0,0 +1,40 @@
+use super::network::Cidr;
+use crate::duration::K8sDuration;
+
+/// Configures cluster-wide inbound policy defaults, overriding those
+/// configured on the controller's command line.
+///
+/// Only the resource named `default` is honored.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    kube::CustomResource,
+    serde::Deserialize,
+    serde::Serialize,
+    schemars::JsonSchema,
+)]
+#[kube(
+    group = "policy.linkerd.io",
+    version = "v1alpha1",
+    kind = "ClusterPolicy"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterPolicySpec {
+    /// The default inbound policy for workloads that do not override it,
+    /// e.g. `all-unauthenticated`.
+    pub default_inbound_policy: Option<String>,
+
+    /// The networks from which probes are authorized.
+    pub probe_networks: Option<Vec<Cidr>>,
+
+    /// The protocol detection timeout for inbound servers that do not
+    /// override it.
+    pub detect_timeout: Option<K8sDuration>,
+}
+
+impl ClusterPolicy {
+    /// The name of the resource that configures the cluster's defaults.
+    pub const NAME: &'static str = "default";
+}
@@ -52,6 +52,10 @@ pub struct HttpRouteSpec {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct HttpRouteRule {
+    /// Name is the name of the route rule. When the defaulting webhook is
+    /// enabled, it defaults to `rule-<index>`.
+    pub name: Option<String>,
+
     /// Matches define conditions used for matching the rule against incoming
     /// HTTP requests. Each match is independent, i.e. this rule will be matched
     /// if **any** one of the matches is satisfied.
@@ -251,6 +255,16 @@ where
     super::targets_kind::<T>(parent_ref.group.as_deref(), kind)
 }
 
+/// Returns true if the parent reference targets a Gateway. Unlike other kinds,
+/// a Gateway reference's group defaults to `gateway.networking.k8s.io`.
+pub fn parent_ref_targets_gateway(parent_ref: &ParentReference) -> bool {
+    parent_ref.kind.as_deref() == Some("Gateway")
+        && parent_ref
+            .group
+            .as_deref()
+            .map_or(true, |g| g == "gateway.networking.k8s.io")
+}
+
 pub fn backend_ref_targets_kind<T>(backend_ref: &BackendObjectReference) -> bool
 where
     T: kube::Resource,
@@ -21,4 +21,18 @@ use super::NamespacedTargetRef;
 pub struct MeshTLSAuthenticationSpec {
     pub identities: Option<Vec<String>>,
     pub identity_refs: Option<Vec<NamespacedTargetRef>>,
+    pub remote_identity_refs: Option<Vec<RemoteIdentityRef>>,
+}
+
+/// References a `ServiceAccount` or `Namespace` in a linked cluster. Its
+/// identity is formatted with the linked cluster's trust domain.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+pub struct RemoteIdentityRef {
+    /// The name of the linked cluster.
+    pub cluster: String,
+
+    #[serde(flatten)]
+    pub target: NamespacedTargetRef,
 }
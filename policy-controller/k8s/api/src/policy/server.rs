@@ -1,4 +1,5 @@
 use super::super::labels;
+use crate::duration::K8sDuration;
 use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -18,6 +19,11 @@ pub struct ServerSpec {
     pub selector: Selector,
     pub port: Port,
     pub proxy_protocol: Option<ProxyProtocol>,
+
+    /// The protocol detection timeout for connections to this server, when
+    /// its protocol is detected. Supersedes the
+    /// `config.linkerd.io/protocol-detect-timeout` annotation.
+    pub detect_timeout: Option<K8sDuration>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
@@ -0,0 +1,372 @@
+//! Benchmarks the inbound and outbound indexes as they process churn in a large
+//! synthetic cluster: pods scaling up and down, route edits, and `Server`
+//! changes.
+//!
+//! Every workload in the cluster holds a watch on its policy, so that each
+//! benchmark includes the cost of publishing updates to subscribers.
+//!
+//! Compare against a saved baseline to detect regressions, e.g.:
+//!
+//! ```text
+//! cargo bench -p linkerd-policy-controller-k8s-index --bench index -- --save-baseline main
+//! cargo bench -p linkerd-policy-controller-k8s-index --bench index -- --baseline main
+//! ```
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use kubert::index::IndexNamespacedResource;
+use linkerd_policy_controller_core::{
+    inbound::InboundServer, outbound::OutboundPolicy, POLICY_CONTROLLER_NAME,
+};
+use linkerd_policy_controller_k8s_api::{
+    self as k8s,
+    api::core::v1::{Container, ContainerPort, PodSpec, ServicePort, ServiceSpec},
+    gateway,
+    policy::{self, httproute as route, server::Port},
+};
+use linkerd_policy_controller_k8s_index::{inbound, outbound, ClusterInfo, DefaultPolicy};
+use std::{sync::Arc, time};
+use tokio::sync::watch;
+
+const NAMESPACES: usize = 50;
+const PODS_PER_NS: usize = 100;
+const SERVERS_PER_NS: usize = 10;
+const SERVICES_PER_NS: usize = 10;
+const PORT: u16 = 8080;
+
+/// A synthetic cluster and the watches held on its policies.
+struct Cluster<I, T> {
+    index: I,
+    _watches: Vec<watch::Receiver<T>>,
+}
+
+fn inbound(c: &mut Criterion) {
+    let mut group = c.benchmark_group("inbound");
+
+    let cluster = mk_inbound();
+    let mut i = 0;
+    group.bench_function("pod_scale", |b| {
+        b.iter(|| {
+            i += 1;
+            let name = format!("scaled-{i}");
+            let mut index = cluster.index.write();
+            index.apply(mk_pod("ns-0", &name, i % SERVERS_PER_NS));
+            IndexNamespacedResource::<k8s::Pod>::delete(&mut *index, "ns-0".to_string(), name);
+        })
+    });
+
+    let cluster = mk_inbound();
+    let mut generation = 1;
+    group.bench_function("route_edit", |b| {
+        b.iter_batched(
+            || {
+                generation += 1;
+                let mut route = mk_server_route("ns-0", "route-0", "srv-0");
+                route.metadata.generation = Some(generation);
+                set_path(&mut route, &format!("/v{generation}"));
+                route
+            },
+            |route| cluster.index.write().apply(route),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let cluster = mk_inbound();
+    let mut http2 = false;
+    group.bench_function("server_edit", |b| {
+        b.iter_batched(
+            || {
+                http2 = !http2;
+                let mut server = mk_server("ns-0", 0);
+                if http2 {
+                    server.spec.proxy_protocol = Some(policy::server::ProxyProtocol::Http2);
+                }
+                server
+            },
+            |server| cluster.index.write().apply(server),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn outbound(c: &mut Criterion) {
+    let mut group = c.benchmark_group("outbound");
+
+    let cluster = mk_outbound();
+    let mut generation = 1;
+    group.bench_function("route_edit", |b| {
+        b.iter_batched(
+            || {
+                generation += 1;
+                let mut route = mk_service_route("ns-0", "route-0", "svc-0");
+                route.metadata.generation = Some(generation);
+                set_path(&mut route, &format!("/v{generation}"));
+                route
+            },
+            |route| cluster.index.write().apply(route),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let cluster = mk_outbound();
+    let mut opaque = false;
+    group.bench_function("service_edit", |b| {
+        b.iter_batched(
+            || {
+                opaque = !opaque;
+                let mut svc = mk_service("ns-0", 0);
+                if opaque {
+                    svc.metadata.annotations = Some(
+                        Some((
+                            "config.linkerd.io/opaque-ports".to_string(),
+                            PORT.to_string(),
+                        ))
+                        .into_iter()
+                        .collect(),
+                    );
+                }
+                svc
+            },
+            |svc| cluster.index.write().apply(svc),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, inbound, outbound);
+criterion_main!(benches);
+
+fn mk_cluster_info() -> ClusterInfo {
+    ClusterInfo {
+        networks: vec!["10.0.0.0/8".parse().unwrap()],
+        control_plane_ns: "linkerd".to_string(),
+        identity_domain: "cluster.local".into(),
+        dns_domain: "cluster.local".into(),
+        default_policy: DefaultPolicy::Allow {
+            authenticated_only: false,
+            cluster_only: true,
+        },
+        default_detect_timeout: time::Duration::from_secs(10),
+        default_opaque_ports: Default::default(),
+        probe_networks: vec![],
+        max_authorizations_per_server: None,
+        max_routes_per_parent: None,
+        max_backends_per_route: None,
+        meshed_pods_only: false,
+        remote_identity_domains: Default::default(),
+    }
+}
+
+fn mk_inbound() -> Cluster<inbound::SharedIndex, InboundServer> {
+    let index = inbound::Index::shared(mk_cluster_info());
+    let mut watches = Vec::with_capacity(NAMESPACES * PODS_PER_NS);
+    for n in 0..NAMESPACES {
+        let ns = format!("ns-{n}");
+        let mut idx = index.write();
+        for s in 0..SERVERS_PER_NS {
+            idx.apply(mk_server(&ns, s));
+            idx.apply(mk_server_route(
+                &ns,
+                &format!("route-{s}"),
+                &format!("srv-{s}"),
+            ));
+        }
+        for p in 0..PODS_PER_NS {
+            let name = format!("pod-{p}");
+            idx.apply(mk_pod(&ns, &name, p % SERVERS_PER_NS));
+            let rx = idx
+                .pod_server_rx(&ns, &name, PORT.try_into().unwrap())
+                .expect("pod must exist");
+            watches.push(rx);
+        }
+    }
+    Cluster {
+        index,
+        _watches: watches,
+    }
+}
+
+fn mk_outbound() -> Cluster<outbound::SharedIndex, OutboundPolicy> {
+    let index = outbound::Index::shared(Arc::new(mk_cluster_info()));
+    let mut watches = Vec::with_capacity(NAMESPACES * SERVICES_PER_NS);
+    for n in 0..NAMESPACES {
+        let ns = format!("ns-{n}");
+        let mut idx = index.write();
+        for s in 0..SERVICES_PER_NS {
+            let name = format!("svc-{s}");
+            idx.apply(mk_service(&ns, s));
+            idx.apply(mk_service_route(&ns, &format!("route-{s}"), &name));
+            let rx = idx
+                .outbound_policy_rx(name, ns.clone(), PORT.try_into().unwrap(), ns.clone())
+                .expect("service must exist");
+            watches.push(rx);
+        }
+    }
+    Cluster {
+        index,
+        _watches: watches,
+    }
+}
+
+fn mk_meta(ns: &str, name: &str) -> k8s::ObjectMeta {
+    k8s::ObjectMeta {
+        namespace: Some(ns.to_string()),
+        name: Some(name.to_string()),
+        generation: Some(1),
+        ..Default::default()
+    }
+}
+
+fn mk_pod(ns: &str, name: &str, app: usize) -> k8s::Pod {
+    let mut metadata = mk_meta(ns, name);
+    metadata.labels = Some(
+        Some(("app".to_string(), format!("app-{app}")))
+            .into_iter()
+            .collect(),
+    );
+    k8s::Pod {
+        metadata,
+        spec: Some(PodSpec {
+            containers: vec![Container {
+                name: "app".to_string(),
+                ports: Some(vec![ContainerPort {
+                    container_port: PORT.into(),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn mk_server(ns: &str, i: usize) -> policy::Server {
+    let app = format!("app-{i}");
+    policy::Server {
+        metadata: mk_meta(ns, &format!("srv-{i}")),
+        spec: policy::ServerSpec {
+            port: Port::Number(PORT.try_into().unwrap()),
+            selector: policy::server::Selector::Pod(
+                Some(("app".to_string(), app)).into_iter().collect(),
+            ),
+            proxy_protocol: Some(policy::server::ProxyProtocol::Http1),
+            detect_timeout: None,
+        },
+    }
+}
+
+fn mk_service(ns: &str, i: usize) -> k8s::Service {
+    k8s::Service {
+        metadata: mk_meta(ns, &format!("svc-{i}")),
+        spec: Some(ServiceSpec {
+            cluster_ip: Some(format!("10.{}.{}.1", i, ns.len())),
+            ports: Some(vec![ServicePort {
+                port: PORT.into(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn mk_server_route(ns: &str, name: &str, server: &str) -> policy::HttpRoute {
+    let parent = gateway::ParentReference {
+        group: Some("policy.linkerd.io".to_string()),
+        kind: Some("Server".to_string()),
+        namespace: None,
+        name: server.to_string(),
+        section_name: None,
+        port: None,
+    };
+    mk_route(ns, name, parent, None)
+}
+
+fn mk_service_route(ns: &str, name: &str, service: &str) -> policy::HttpRoute {
+    let parent = gateway::ParentReference {
+        group: Some("core".to_string()),
+        kind: Some("Service".to_string()),
+        namespace: Some(ns.to_string()),
+        name: service.to_string(),
+        section_name: None,
+        port: Some(PORT),
+    };
+    let backend = gateway::HttpBackendRef {
+        backend_ref: Some(gateway::BackendRef {
+            weight: None,
+            inner: gateway::BackendObjectReference {
+                group: Some("core".to_string()),
+                kind: Some("Service".to_string()),
+                namespace: Some(ns.to_string()),
+                name: service.to_string(),
+                port: Some(PORT),
+            },
+        }),
+        filters: None,
+    };
+    mk_route(ns, name, parent, Some(backend))
+}
+
+fn mk_route(
+    ns: &str,
+    name: &str,
+    parent: gateway::ParentReference,
+    backend: Option<gateway::HttpBackendRef>,
+) -> policy::HttpRoute {
+    policy::HttpRoute {
+        metadata: mk_meta(ns, name),
+        spec: route::HttpRouteSpec {
+            inner: gateway::CommonRouteSpec {
+                parent_refs: Some(vec![parent.clone()]),
+            },
+            hostnames: None,
+            rules: Some(vec![route::HttpRouteRule {
+                name: None,
+                matches: Some(vec![gateway::HttpRouteMatch {
+                    path: Some(gateway::HttpPathMatch::PathPrefix {
+                        value: "/".to_string(),
+                    }),
+                    headers: Some(vec![gateway::HttpHeaderMatch::RegularExpression {
+                        name: "x-version".to_string(),
+                        value: "v[0-9]+".to_string(),
+                    }]),
+                    query_params: None,
+                    method: Some("GET".to_string()),
+                }]),
+                filters: None,
+                backend_refs: backend.map(|b| vec![b]),
+                timeouts: None,
+            }]),
+        },
+        status: Some(route::HttpRouteStatus {
+            inner: gateway::RouteStatus {
+                parents: vec![gateway::RouteParentStatus {
+                    parent_ref: parent,
+                    controller_name: POLICY_CONTROLLER_NAME.to_string(),
+                    conditions: vec![k8s::Condition {
+                        last_transition_time: k8s::Time(chrono::DateTime::<chrono::Utc>::MIN_UTC),
+                        message: "".to_string(),
+                        observed_generation: None,
+                        reason: "Accepted".to_string(),
+                        status: "True".to_string(),
+                        type_: "Accepted".to_string(),
+                    }],
+                }],
+            },
+        }),
+    }
+}
+
+fn set_path(route: &mut policy::HttpRoute, path: &str) {
+    for rule in route.spec.rules.iter_mut().flatten() {
+        for m in rule.matches.iter_mut().flatten() {
+            m.path = Some(gateway::HttpPathMatch::PathPrefix {
+                value: path.to_string(),
+            });
+        }
+    }
+}
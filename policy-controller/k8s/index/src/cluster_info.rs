@@ -1,9 +1,16 @@
-use std::num::NonZeroU16;
+use std::{collections::BTreeMap, num::NonZeroU16};
 
 use crate::{ports::PortSet, DefaultPolicy};
-use linkerd_policy_controller_core::IpNet;
+use linkerd_policy_controller_core::{IdentityMatch, IpNet};
+use linkerd_policy_controller_k8s_api::duration::K8sDuration;
 use tokio::time;
 
+/// Overrides the protocol detection timeout for a `Server` or `Service`.
+pub const DETECT_TIMEOUT_ANNOTATION: &str = "config.linkerd.io/protocol-detect-timeout";
+
+/// The largest protocol detection timeout that may be set by an annotation.
+pub const MAX_DETECT_TIMEOUT: time::Duration = time::Duration::from_secs(60 * 60);
+
 /// Holds cluster metadata.
 #[derive(Clone, Debug)]
 pub struct ClusterInfo {
@@ -32,9 +39,100 @@ pub struct ClusterInfo {
 
     /// The networks that probes are expected to be from.
     pub probe_networks: Vec<IpNet>,
+
+    /// The maximum number of authorizations that may apply to a single
+    /// `Server`. Excess authorizations are ignored.
+    pub max_authorizations_per_server: Option<usize>,
+
+    /// The maximum number of routes that may be attached to a single
+    /// `Server`. The newest routes in excess of the limit are ignored.
+    pub max_routes_per_parent: Option<usize>,
+
+    /// The maximum number of backends that a route attached to a `Server` may
+    /// reference. Routes in excess of the limit are ignored.
+    pub max_backends_per_route: Option<usize>,
+
+    /// Whether only meshed pods are indexed. Pods without a proxy are never
+    /// served policies, so they need not be held in memory.
+    pub meshed_pods_only: bool,
+
+    /// The mesh identity trust domains of linked clusters, by cluster name.
+    pub remote_identity_domains: BTreeMap<String, String>,
+}
+
+/// A client identity as configured on a resource.
+///
+/// Identities that refer to `ServiceAccount`s or `Namespace`s are formatted
+/// with the cluster's identity configuration only when policies are computed,
+/// so that changes to the trust domain apply to resources that have already
+/// been indexed.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum IdentityRef {
+    Match(IdentityMatch),
+    ServiceAccount {
+        namespace: String,
+        name: String,
+    },
+    Namespace(String),
+    RemoteServiceAccount {
+        cluster: String,
+        namespace: String,
+        name: String,
+    },
+    RemoteNamespace {
+        cluster: String,
+        name: String,
+    },
 }
 
 impl ClusterInfo {
+    /// Formats an identity with the cluster's identity configuration. Returns
+    /// `None` if the identity refers to a cluster whose trust domain is not
+    /// known.
+    pub(crate) fn identity_match(&self, id: &IdentityRef) -> Option<IdentityMatch> {
+        let id = match id {
+            IdentityRef::Match(id) => id.clone(),
+            IdentityRef::ServiceAccount { namespace, name } => {
+                IdentityMatch::Exact(self.service_account_identity(namespace, name))
+            }
+            IdentityRef::Namespace(ns) => match self.namespace_identity(ns).parse() {
+                Ok(id) => id,
+                Err(e) => match e {},
+            },
+            IdentityRef::RemoteServiceAccount {
+                cluster,
+                namespace,
+                name,
+            } => {
+                let domain = self.remote_identity_domain(cluster)?;
+                IdentityMatch::Exact(format!(
+                    "{}.{}.serviceaccount.identity.{}.{}",
+                    name, namespace, self.control_plane_ns, domain
+                ))
+            }
+            IdentityRef::RemoteNamespace { cluster, name } => {
+                let domain = self.remote_identity_domain(cluster)?;
+                let id = format!(
+                    "*.{}.serviceaccount.identity.{}.{}",
+                    name, self.control_plane_ns, domain
+                );
+                match id.parse() {
+                    Ok(id) => id,
+                    Err(e) => match e {},
+                }
+            }
+        };
+        Some(id)
+    }
+
+    fn remote_identity_domain(&self, cluster: &str) -> Option<&str> {
+        let domain = self.remote_identity_domains.get(cluster);
+        if domain.is_none() {
+            tracing::debug!(%cluster, "No trust domain is configured for linked cluster");
+        }
+        domain.map(String::as_str)
+    }
+
     pub(crate) fn service_account_identity(&self, ns: &str, sa: &str) -> String {
         format!(
             "{}.{}.serviceaccount.identity.{}.{}",
@@ -52,4 +150,34 @@ impl ClusterInfo {
     pub(crate) fn service_dns_authority(&self, ns: &str, svc: &str, port: NonZeroU16) -> String {
         format!("{}.{}.svc.{}:{port}", svc, ns, self.dns_domain)
     }
+
+    /// Returns the protocol detection timeout configured by a resource's
+    /// annotations, falling back to the cluster-wide default if the annotation
+    /// is absent or invalid.
+    pub(crate) fn detect_timeout(&self, annotations: &BTreeMap<String, String>) -> time::Duration {
+        detect_timeout_annotation(annotations).unwrap_or(self.default_detect_timeout)
+    }
+}
+
+/// Returns the protocol detection timeout configured by a resource's
+/// annotations, if the annotation is present and valid.
+pub(crate) fn detect_timeout_annotation(
+    annotations: &BTreeMap<String, String>,
+) -> Option<time::Duration> {
+    let value = annotations.get(DETECT_TIMEOUT_ANNOTATION)?;
+    match value.parse::<K8sDuration>() {
+        Ok(timeout) if timeout.is_negative() => {
+            tracing::warn!(%value, "Ignoring negative protocol detection timeout");
+            None
+        }
+        Ok(timeout) if time::Duration::from(timeout) > MAX_DETECT_TIMEOUT => {
+            tracing::warn!(%value, max = ?MAX_DETECT_TIMEOUT, "Ignoring protocol detection timeout that exceeds the maximum");
+            None
+        }
+        Ok(timeout) => Some(timeout.into()),
+        Err(error) => {
+            tracing::warn!(%error, %value, "Invalid protocol detection timeout");
+            None
+        }
+    }
 }
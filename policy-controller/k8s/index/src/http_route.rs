@@ -1,9 +1,12 @@
+use ahash::AHashMap as HashMap;
 use anyhow::{anyhow, bail, Result};
 use k8s_gateway_api as api;
 use kube::{Resource, ResourceExt};
-use linkerd_policy_controller_core::routes::{self, GroupKindName, GroupKindNamespaceName};
+use linkerd_policy_controller_core::routes::{
+    self, GroupKindName, GroupKindNamespaceName, Quarantine, Rejection,
+};
 use linkerd_policy_controller_k8s_api::policy;
-use std::num::NonZeroU16;
+use std::{num::NonZeroU16, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub(crate) enum HttpRouteResource {
@@ -30,6 +33,13 @@ impl HttpRouteResource {
         }
     }
 
+    pub(crate) fn annotations(&self) -> &std::collections::BTreeMap<String, String> {
+        match self {
+            HttpRouteResource::Linkerd(route) => route.annotations(),
+            HttpRouteResource::Gateway(route) => route.annotations(),
+        }
+    }
+
     pub(crate) fn inner(&self) -> &api::CommonRouteSpec {
         match self {
             HttpRouteResource::Linkerd(route) => &route.spec.inner,
@@ -52,6 +62,172 @@ impl HttpRouteResource {
                 .namespaced(route.namespace().expect("Route must have namespace")),
         }
     }
+
+    pub(crate) fn metadata(&self) -> &kube::api::ObjectMeta {
+        match self {
+            HttpRouteResource::Linkerd(route) => &route.metadata,
+            HttpRouteResource::Gateway(route) => &route.metadata,
+        }
+    }
+}
+
+/// Caches the artifacts parsed from route resources (matches, filters, and
+/// hostnames) so that they are not parsed again when a route is re-applied
+/// without changes to its spec, e.g. when only its status has been updated.
+///
+/// Entries are keyed by the route's `metadata.uid` and `metadata.generation`.
+/// The API server increments the generation on each change to a resource's
+/// spec, and a route that is deleted and recreated with the same name has a new
+/// UID, so that its generation may start over.
+///
+/// Routes that fail to parse are quarantined: they are not parsed again (nor
+/// is the error logged again) until their spec changes. Rejections are
+/// recorded in a [`Quarantine`] shared with the status controller, if one is
+/// set, so that the error is reported in the route's status.
+#[derive(Debug)]
+pub(crate) struct ParseCache<T> {
+    by_route: HashMap<GroupKindNamespaceName, (Version, Arc<T>)>,
+    rejected: HashMap<GroupKindNamespaceName, Rejection>,
+    quarantine: Option<(&'static str, Quarantine)>,
+}
+
+/// Identifies a version of a route's spec.
+#[derive(Debug, PartialEq, Eq)]
+struct Version {
+    uid: Option<String>,
+    generation: i64,
+}
+
+/// The error returned when a route could not be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error("{error}")]
+pub(crate) struct Rejected {
+    error: Arc<str>,
+
+    /// Whether the route had already been rejected at this generation, in
+    /// which case the error has already been reported.
+    pub(crate) quarantined: bool,
+}
+
+// === impl ParseCache ===
+
+impl<T> Default for ParseCache<T> {
+    fn default() -> Self {
+        Self {
+            by_route: HashMap::default(),
+            rejected: HashMap::default(),
+            quarantine: None,
+        }
+    }
+}
+
+impl<T> ParseCache<T> {
+    /// Records the routes rejected by this cache, on behalf of the named
+    /// index, in the given quarantine.
+    pub(crate) fn set_quarantine(&mut self, index: &'static str, quarantine: Quarantine) {
+        for (route, rejection) in &self.rejected {
+            quarantine.insert(index, route.clone(), rejection.clone());
+        }
+        self.quarantine = Some((index, quarantine));
+    }
+
+    /// Returns the artifacts parsed from the current generation of a route,
+    /// parsing the route if it has not been parsed at that generation.
+    ///
+    /// Routes without a generation are always parsed.
+    pub(crate) fn get_or_parse(
+        &mut self,
+        key: GroupKindNamespaceName,
+        meta: &kube::api::ObjectMeta,
+        parse: impl FnOnce() -> Result<T>,
+    ) -> Result<Arc<T>, Rejected> {
+        let generation = meta.generation;
+        let version = generation.map(|generation| Version {
+            uid: meta.uid.clone(),
+            generation,
+        });
+        if let Some(generation) = generation {
+            if let Some((cached, parsed)) = self.by_route.get(&key) {
+                if Some(cached) == version.as_ref() {
+                    return Ok(parsed.clone());
+                }
+            }
+            if let Some(rejection) = self.rejected.get(&key) {
+                if rejection.uid == meta.uid && rejection.generation == Some(generation) {
+                    return Err(Rejected {
+                        error: rejection.error.clone(),
+                        quarantined: true,
+                    });
+                }
+            }
+        }
+        match parse() {
+            Ok(parsed) => {
+                let parsed = Arc::new(parsed);
+                if self.rejected.remove(&key).is_some() {
+                    if let Some((index, quarantine)) = &self.quarantine {
+                        quarantine.remove(index, &key);
+                    }
+                }
+                match version {
+                    Some(version) => {
+                        self.by_route.insert(key, (version, parsed.clone()));
+                    }
+                    None => {
+                        self.by_route.remove(&key);
+                    }
+                }
+                Ok(parsed)
+            }
+            Err(error) => {
+                let rejection = Rejection {
+                    uid: meta.uid.clone(),
+                    generation,
+                    error: format!("{error:#}").into(),
+                };
+                self.by_route.remove(&key);
+                if let Some((index, quarantine)) = &self.quarantine {
+                    quarantine.insert(index, key.clone(), rejection.clone());
+                }
+                let error = rejection.error.clone();
+                self.rejected.insert(key, rejection);
+                Err(Rejected {
+                    error,
+                    quarantined: false,
+                })
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &GroupKindNamespaceName) {
+        self.by_route.remove(key);
+        if self.rejected.remove(key).is_some() {
+            if let Some((index, quarantine)) = &self.quarantine {
+                quarantine.remove(index, key);
+            }
+        }
+    }
+
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&GroupKindNamespaceName) -> bool) {
+        self.by_route.retain(|key, _| f(key));
+        self.rejected.retain(|key, _| f(key));
+        if let Some((index, quarantine)) = &self.quarantine {
+            quarantine.retain(index, |key| f(key));
+        }
+    }
+
+    /// Returns the routes that failed to parse when they were last applied.
+    pub(crate) fn rejected(&self) -> impl Iterator<Item = &GroupKindNamespaceName> {
+        self.rejected.keys()
+    }
+}
+
+/// Returns true if the error indicates that the route was already rejected at
+/// its current generation, so that the error need not be logged again.
+pub(crate) fn is_quarantined(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<Rejected>()
+        .map_or(false, |r| r.quarantined)
 }
 
 pub fn try_match(
@@ -98,8 +274,7 @@ pub fn path_match(path_match: api::HttpPathMatch) -> Result<routes::PathMatch> {
             }
             api::HttpPathMatch::Exact { value } => Ok(routes::PathMatch::Exact(value)),
             api::HttpPathMatch::PathPrefix { value } => Ok(routes::PathMatch::Prefix(value)),
-            api::HttpPathMatch::RegularExpression { value } => value
-                .parse()
+            api::HttpPathMatch::RegularExpression { value } => routes::compile_regex(&value)
                 .map(routes::PathMatch::Regex)
                 .map_err(Into::into),
         }
@@ -124,9 +299,10 @@ pub fn header_match(header_match: api::HttpHeaderMatch) -> Result<routes::Header
         api::HttpHeaderMatch::Exact { name, value } => {
             Ok(routes::HeaderMatch::Exact(name.parse()?, value.parse()?))
         }
-        api::HttpHeaderMatch::RegularExpression { name, value } => {
-            Ok(routes::HeaderMatch::Regex(name.parse()?, value.parse()?))
-        }
+        api::HttpHeaderMatch::RegularExpression { name, value } => Ok(routes::HeaderMatch::Regex(
+            name.parse()?,
+            routes::compile_regex(&value)?,
+        )),
     }
 }
 
@@ -135,9 +311,9 @@ pub fn query_param_match(query_match: api::HttpQueryParamMatch) -> Result<routes
         api::HttpQueryParamMatch::Exact { name, value } => {
             Ok(routes::QueryParamMatch::Exact(name, value))
         }
-        api::HttpQueryParamMatch::RegularExpression { name, value } => {
-            Ok(routes::QueryParamMatch::Regex(name, value.parse()?))
-        }
+        api::HttpQueryParamMatch::RegularExpression { name, value } => Ok(
+            routes::QueryParamMatch::Regex(name, routes::compile_regex(&value)?),
+        ),
     }
 }
 
@@ -163,6 +339,12 @@ pub fn header_modifier(
     })
 }
 
+/// Converts a RequestRedirect filter, applying the Gateway API's defaults:
+///
+/// - When a scheme is set without a port, the scheme's well-known port is
+///   used. When neither is set, the request's port is preserved.
+/// - When no hostname is set, the request's host is preserved.
+/// - When no status code is set, a 302 is returned.
 pub fn req_redirect(
     api::HttpRequestRedirectFilter {
         scheme,
@@ -172,15 +354,51 @@ pub fn req_redirect(
         status_code,
     }: api::HttpRequestRedirectFilter,
 ) -> Result<routes::RequestRedirectFilter> {
+    let scheme = scheme.as_deref().map(redirect_scheme).transpose()?;
+    let port = match port {
+        Some(port) => Some(
+            NonZeroU16::try_from(port)
+                .map_err(|_| anyhow!("RequestRedirect filters may not redirect to port 0"))?,
+        ),
+        None => scheme.as_ref().map(|scheme| {
+            let port = if *scheme == routes::Scheme::HTTPS {
+                443
+            } else {
+                80
+            };
+            NonZeroU16::new(port).unwrap()
+        }),
+    };
+    let status = match status_code {
+        Some(code) => redirect_status(code)?,
+        None => routes::StatusCode::FOUND,
+    };
     Ok(routes::RequestRedirectFilter {
-        scheme: scheme.as_deref().map(TryInto::try_into).transpose()?,
-        host: hostname,
+        scheme,
+        host: hostname.filter(|h| !h.is_empty()),
         path: path.map(path_modifier).transpose()?,
-        port: port.and_then(|p| NonZeroU16::try_from(p).ok()),
-        status: status_code.map(routes::StatusCode::try_from).transpose()?,
+        port,
+        status: Some(status),
     })
 }
 
+fn redirect_scheme(scheme: &str) -> Result<routes::Scheme> {
+    match scheme {
+        "http" => Ok(routes::Scheme::HTTP),
+        "https" => Ok(routes::Scheme::HTTPS),
+        scheme => {
+            bail!("RequestRedirect filters only support the http and https schemes; got {scheme:?}")
+        }
+    }
+}
+
+fn redirect_status(code: u16) -> Result<routes::StatusCode> {
+    match code {
+        301 | 302 | 303 | 307 | 308 => Ok(routes::StatusCode::from_u16(code)?),
+        code => bail!("RequestRedirect filters only support the 301, 302, 303, 307, and 308 status codes; got {code}"),
+    }
+}
+
 fn path_modifier(path_modifier: api::HttpPathModifier) -> Result<routes::PathModifier> {
     use api::HttpPathModifier::*;
     match path_modifier {
@@ -227,3 +445,171 @@ pub(crate) fn gkn_for_gateway_http_route(name: String) -> GroupKindName {
         name: name.into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gknn(name: &str) -> GroupKindNamespaceName {
+        gkn_for_gateway_http_route(name.to_string()).namespaced("ns-0".to_string())
+    }
+
+    fn meta(uid: &str, generation: Option<i64>) -> kube::api::ObjectMeta {
+        kube::api::ObjectMeta {
+            uid: Some(uid.to_string()),
+            generation,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_cache_reuses_generations() {
+        let mut cache = ParseCache::<usize>::default();
+        let mut parses = 0;
+        let mut parse = |uid, gen| {
+            cache
+                .get_or_parse(gknn("route"), &meta(uid, gen), || {
+                    parses += 1;
+                    Ok(parses)
+                })
+                .map(|parsed| *parsed)
+        };
+
+        assert_eq!(parse("uid-0", Some(1)).unwrap(), 1);
+        assert_eq!(
+            parse("uid-0", Some(1)).unwrap(),
+            1,
+            "unchanged routes are not reparsed"
+        );
+        assert_eq!(parse("uid-0", Some(2)).unwrap(), 2);
+        assert_eq!(
+            parse("uid-1", Some(2)).unwrap(),
+            3,
+            "recreated routes are reparsed"
+        );
+        assert_eq!(
+            parse("uid-1", None).unwrap(),
+            4,
+            "routes without a generation are always parsed"
+        );
+        assert_eq!(parse("uid-1", None).unwrap(), 5);
+
+        cache.remove(&gknn("route"));
+        assert!(cache.by_route.is_empty());
+
+        assert!(cache
+            .get_or_parse(gknn("route"), &meta("uid-1", Some(1)), || Err(anyhow!(
+                "invalid"
+            )))
+            .is_err());
+        assert!(
+            cache.by_route.is_empty(),
+            "routes that fail to parse are not cached"
+        );
+    }
+
+    #[test]
+    fn req_redirect_defaults() {
+        let redirect = |scheme: Option<&str>, port, status_code| {
+            req_redirect(api::HttpRequestRedirectFilter {
+                scheme: scheme.map(Into::into),
+                hostname: None,
+                path: None,
+                port,
+                status_code,
+            })
+        };
+
+        let r = redirect(None, None, None).unwrap();
+        assert_eq!(r.port, None, "the request's port is preserved");
+        assert_eq!(r.host, None, "the request's host is preserved");
+        assert_eq!(r.status, Some(routes::StatusCode::FOUND));
+
+        let r = redirect(Some("https"), None, Some(301)).unwrap();
+        assert_eq!(r.port, NonZeroU16::new(443));
+        assert_eq!(r.status, Some(routes::StatusCode::MOVED_PERMANENTLY));
+        let r = redirect(Some("http"), None, None).unwrap();
+        assert_eq!(r.port, NonZeroU16::new(80));
+        let r = redirect(Some("https"), Some(8443), Some(308)).unwrap();
+        assert_eq!(r.port, NonZeroU16::new(8443));
+
+        assert!(redirect(Some("ftp"), None, None).is_err());
+        assert!(redirect(None, Some(0), None).is_err());
+        assert!(redirect(None, None, Some(200)).is_err());
+    }
+
+    #[test]
+    fn matches_extension_methods() {
+        let mk = |method: &str| api::HttpRouteMatch {
+            method: Some(method.to_string()),
+            ..Default::default()
+        };
+        for method in ["PROPFIND", "PURGE", "x-custom"] {
+            let m = try_match(mk(method)).expect("extension methods must be accepted");
+            assert_eq!(m.method.unwrap().as_str(), method);
+        }
+        assert!(
+            try_match(mk("GET /")).is_err(),
+            "methods must be valid tokens"
+        );
+    }
+
+    #[test]
+    fn parse_cache_quarantines_rejected_generations() {
+        let quarantine = Quarantine::default();
+        let mut cache = ParseCache::<usize>::default();
+        cache.set_quarantine("test", quarantine.clone());
+        let mut parses = 0;
+        let mut parse = |uid, gen, valid| {
+            cache.get_or_parse(gknn("route"), &meta(uid, gen), || {
+                parses += 1;
+                if valid {
+                    Ok(parses)
+                } else {
+                    Err(anyhow!("invalid"))
+                }
+            })
+        };
+
+        let rejected = parse("uid-0", Some(1), false).unwrap_err();
+        assert!(!rejected.quarantined);
+        assert_eq!(
+            quarantine
+                .error(&gknn("route"), Some("uid-0"), Some(1))
+                .as_deref(),
+            Some("invalid")
+        );
+
+        let rejected = parse("uid-0", Some(1), false).unwrap_err();
+        assert!(rejected.quarantined, "rejected generations are quarantined");
+        assert_eq!(rejected.to_string(), "invalid");
+
+        let rejected = parse("uid-1", Some(1), false).unwrap_err();
+        assert!(
+            !rejected.quarantined,
+            "recreated routes are reparsed, even if their deletion was missed"
+        );
+        assert_eq!(
+            quarantine.error(&gknn("route"), Some("uid-0"), Some(1)),
+            None
+        );
+
+        assert_eq!(
+            *parse("uid-1", Some(2), true).unwrap(),
+            3,
+            "changed routes are reparsed"
+        );
+        assert_eq!(
+            quarantine.error(&gknn("route"), Some("uid-1"), Some(1)),
+            None
+        );
+
+        assert!(parse("uid-1", Some(3), false).is_err());
+        cache.remove(&gknn("route"));
+        assert_eq!(
+            quarantine.error(&gknn("route"), Some("uid-1"), Some(3)),
+            None
+        );
+        assert_eq!(parses, 4);
+    }
+}
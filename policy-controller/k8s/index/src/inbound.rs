@@ -1,4 +1,5 @@
 pub mod authorization_policy;
+mod cluster_policy;
 mod http_route;
 pub mod index;
 mod meshtls_authentication;
@@ -7,7 +8,7 @@ mod server;
 pub mod server_authorization;
 mod workload;
 
-pub use index::{metrics, Index, SharedIndex};
+pub use index::{dump, metrics, Index, Lookup, SharedIndex};
 
 #[cfg(test)]
 mod tests;
@@ -8,7 +8,7 @@ use linkerd_policy_controller_k8s_api::{
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct Spec {
-    pub target: Target,
+    pub targets: Vec<Target>,
     pub authentications: Vec<AuthenticationTarget>,
 }
 
@@ -45,7 +45,14 @@ impl TryFrom<k8s::policy::AuthorizationPolicySpec> for Spec {
     type Error = anyhow::Error;
 
     fn try_from(ap: k8s::policy::AuthorizationPolicySpec) -> Result<Self> {
-        let target = target(ap.target_ref)?;
+        let targets = ap
+            .targets()
+            .cloned()
+            .map(target)
+            .collect::<Result<Vec<_>>>()?;
+        if targets.is_empty() {
+            anyhow::bail!("authorization policy must have a target");
+        }
 
         let authentications = ap
             .required_authentication_refs
@@ -54,7 +61,7 @@ impl TryFrom<k8s::policy::AuthorizationPolicySpec> for Spec {
             .collect::<Result<Vec<_>>>()?;
 
         Ok(Self {
-            target,
+            targets,
             authentications,
         })
     }
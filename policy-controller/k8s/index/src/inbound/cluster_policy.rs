@@ -0,0 +1,56 @@
+use crate::{ClusterInfo, DefaultPolicy, MAX_DETECT_TIMEOUT};
+use anyhow::{bail, Result};
+use linkerd_policy_controller_core::IpNet;
+use linkerd_policy_controller_k8s_api::policy::ClusterPolicy;
+use std::time;
+
+/// The cluster-wide inbound defaults that may be overridden by a
+/// `ClusterPolicy`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Defaults {
+    pub default_policy: DefaultPolicy,
+    pub probe_networks: Vec<IpNet>,
+    pub detect_timeout: time::Duration,
+}
+
+impl Defaults {
+    pub(crate) fn from_cluster(cluster: &ClusterInfo) -> Self {
+        Self {
+            default_policy: cluster.default_policy,
+            probe_networks: cluster.probe_networks.clone(),
+            detect_timeout: cluster.default_detect_timeout,
+        }
+    }
+
+    /// Returns these defaults with the overrides configured by the policy.
+    pub(crate) fn with_policy(&self, policy: ClusterPolicy) -> Result<Self> {
+        let spec = policy.spec;
+        let default_policy = match spec.default_inbound_policy {
+            Some(policy) => policy.parse()?,
+            None => self.default_policy,
+        };
+        let probe_networks = match spec.probe_networks {
+            Some(nets) => nets.into_iter().map(Into::into).collect(),
+            None => self.probe_networks.clone(),
+        };
+        let detect_timeout = match spec.detect_timeout {
+            Some(timeout) if timeout.is_negative() => bail!("negative detect timeout"),
+            Some(timeout) if time::Duration::from(timeout) > MAX_DETECT_TIMEOUT => {
+                bail!("detect timeout exceeds the maximum of {MAX_DETECT_TIMEOUT:?}")
+            }
+            Some(timeout) => timeout.into(),
+            None => self.detect_timeout,
+        };
+        Ok(Self {
+            default_policy,
+            probe_networks,
+            detect_timeout,
+        })
+    }
+
+    pub(crate) fn apply(self, cluster: &mut ClusterInfo) {
+        cluster.default_policy = self.default_policy;
+        cluster.probe_networks = self.probe_networks;
+        cluster.default_detect_timeout = self.detect_timeout;
+    }
+}
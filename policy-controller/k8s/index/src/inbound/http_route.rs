@@ -3,24 +3,65 @@ use ahash::AHashMap as HashMap;
 use anyhow::{bail, Error, Result};
 use k8s_gateway_api as api;
 use linkerd_policy_controller_core::inbound::{Filter, HttpRoute, HttpRouteRule};
-use linkerd_policy_controller_core::routes::{HttpRouteMatch, Method};
+use linkerd_policy_controller_core::routes::{HostMatch, HttpRouteMatch, Method};
 use linkerd_policy_controller_core::POLICY_CONTROLLER_NAME;
 use linkerd_policy_controller_k8s_api::{
     self as k8s, gateway,
     policy::{httproute as policy, Server},
 };
-use std::fmt;
+use std::{fmt, num::NonZeroU16, sync::Arc};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RouteBinding {
     pub parents: Vec<ParentRef>,
     pub route: HttpRoute,
     pub statuses: Vec<Status>,
+
+    /// The number of backends referenced by the route's rules. Inbound routes
+    /// do not use their backends, but routes that reference too many backends
+    /// are not accepted.
+    pub backends: usize,
+}
+
+/// The hostnames and rules parsed from an HTTPRoute's spec.
+#[derive(Debug)]
+pub struct ParsedRoute {
+    hostnames: Vec<HostMatch>,
+    rules: Arc<[HttpRouteRule]>,
+    backends: usize,
+}
+
+/// An HTTPRoute resource that may be bound to inbound servers.
+///
+/// Parsing a route's spec is separated from binding it so that the parsed
+/// route may be reused while the route's generation is unchanged.
+pub trait RouteResource: kube::Resource<DynamicType = ()> {
+    fn parse(&self) -> Result<ParsedRoute>;
+
+    fn bind(self, parsed: &ParsedRoute) -> Result<RouteBinding>;
 }
 
+/// The label that identifies the Gateway to which an ingress pod belongs.
+pub const GATEWAY_NAME_LABEL: &str = "gateway.networking.k8s.io/gateway-name";
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ParentRef {
     Server(String),
+
+    /// A Gateway in the route's namespace. The route applies to the servers
+    /// of meshed ingress pods that are labeled with the Gateway's name, on the
+    /// ports that match the referenced listener.
+    Gateway {
+        name: String,
+
+        /// The name of the referenced listener, which must be a name of the
+        /// server's port.
+        section_name: Option<String>,
+
+        /// The port of the referenced listener, which must be the server's
+        /// port.
+        port: Option<u16>,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -56,89 +97,138 @@ impl TryFrom<api::HttpRoute> for RouteBinding {
     type Error = Error;
 
     fn try_from(route: api::HttpRoute) -> Result<Self, Self::Error> {
-        let route_ns = route.metadata.namespace.as_deref();
-        let creation_timestamp = route.metadata.creation_timestamp.map(|k8s::Time(t)| t);
-        let parents = ParentRef::collect_from(route_ns, route.spec.inner.parent_refs)?;
-        let hostnames = route
-            .spec
-            .hostnames
-            .into_iter()
-            .flatten()
-            .map(http_route::host_match)
-            .collect();
+        let parsed = route.parse()?;
+        route.bind(&parsed)
+    }
+}
 
-        let rules = route
+impl TryFrom<policy::HttpRoute> for RouteBinding {
+    type Error = Error;
+
+    fn try_from(route: policy::HttpRoute) -> Result<Self, Self::Error> {
+        let parsed = route.parse()?;
+        route.bind(&parsed)
+    }
+}
+
+impl RouteResource for api::HttpRoute {
+    fn parse(&self) -> Result<ParsedRoute> {
+        let rules = self
             .spec
             .rules
-            .into_iter()
+            .iter()
             .flatten()
-            .map(
-                |api::HttpRouteRule {
-                     matches,
-                     filters,
-                     backend_refs: _,
-                 }| Self::try_rule(matches, filters, Self::try_gateway_filter),
-            )
+            .map(|rule| {
+                RouteBinding::try_rule(
+                    rule.matches.clone(),
+                    rule.filters.clone(),
+                    RouteBinding::try_gateway_filter,
+                )
+            })
             .collect::<Result<_>>()?;
+        let backends = self
+            .spec
+            .rules
+            .iter()
+            .flatten()
+            .flat_map(|rule| rule.backend_refs.iter().flatten())
+            .filter(|backend| backend.backend_ref.is_some())
+            .count();
+
+        Ok(ParsedRoute::new(
+            self.spec.hostnames.as_deref(),
+            rules,
+            backends,
+        ))
+    }
 
-        let statuses = route
+    fn bind(self, parsed: &ParsedRoute) -> Result<RouteBinding> {
+        let route_ns = self.metadata.namespace.as_deref();
+        let parents = ParentRef::collect_from(route_ns, self.spec.inner.parent_refs)?;
+        let statuses = self
             .status
             .map_or_else(Vec::new, |status| Status::collect_from(status.inner));
-
-        Ok(RouteBinding {
-            parents,
-            route: HttpRoute {
-                hostnames,
-                rules,
-                authorizations: HashMap::default(),
-                creation_timestamp,
-            },
-            statuses,
-        })
+        Ok(parsed.bind(parents, statuses, self.metadata.creation_timestamp))
     }
 }
 
-impl TryFrom<policy::HttpRoute> for RouteBinding {
-    type Error = Error;
-
-    fn try_from(route: policy::HttpRoute) -> Result<Self, Self::Error> {
-        let route_ns = route.metadata.namespace.as_deref();
-        let creation_timestamp = route.metadata.creation_timestamp.map(|k8s::Time(t)| t);
-        let parents = ParentRef::collect_from(route_ns, route.spec.inner.parent_refs)?;
-        let hostnames = route
+impl RouteResource for policy::HttpRoute {
+    fn parse(&self) -> Result<ParsedRoute> {
+        let rules = self
             .spec
-            .hostnames
-            .into_iter()
+            .rules
+            .iter()
             .flatten()
-            .map(http_route::host_match)
-            .collect();
-
-        let rules = route
+            .map(|rule| {
+                RouteBinding::try_rule(
+                    rule.matches.clone(),
+                    rule.filters.clone(),
+                    RouteBinding::try_policy_filter,
+                )
+            })
+            .collect::<Result<_>>()?;
+        let backends = self
             .spec
             .rules
-            .into_iter()
+            .iter()
             .flatten()
-            .map(
-                |policy::HttpRouteRule {
-                     matches, filters, ..
-                 }| { Self::try_rule(matches, filters, Self::try_policy_filter) },
-            )
-            .collect::<Result<_>>()?;
+            .flat_map(|rule| rule.backend_refs.iter().flatten())
+            .filter(|backend| backend.backend_ref.is_some())
+            .count();
+
+        Ok(ParsedRoute::new(
+            self.spec.hostnames.as_deref(),
+            rules,
+            backends,
+        ))
+    }
 
-        let statuses = route
+    fn bind(self, parsed: &ParsedRoute) -> Result<RouteBinding> {
+        let route_ns = self.metadata.namespace.as_deref();
+        let parents = ParentRef::collect_from(route_ns, self.spec.inner.parent_refs)?;
+        let statuses = self
             .status
             .map_or_else(Vec::new, |status| Status::collect_from(status.inner));
+        Ok(parsed.bind(parents, statuses, self.metadata.creation_timestamp))
+    }
+}
+
+impl ParsedRoute {
+    fn new(
+        hostnames: Option<&[api::Hostname]>,
+        rules: Vec<HttpRouteRule>,
+        backends: usize,
+    ) -> Self {
+        let hostnames = hostnames
+            .into_iter()
+            .flatten()
+            .cloned()
+            .map(http_route::host_match)
+            .collect();
+        Self {
+            hostnames,
+            rules: rules.into(),
+            backends,
+        }
+    }
 
-        Ok(RouteBinding {
+    fn bind(
+        &self,
+        parents: Vec<ParentRef>,
+        statuses: Vec<Status>,
+        creation_timestamp: Option<k8s::Time>,
+    ) -> RouteBinding {
+        RouteBinding {
             parents,
             route: HttpRoute {
-                hostnames,
-                rules,
+                hostnames: self.hostnames.clone(),
+                rules: self.rules.clone(),
                 authorizations: HashMap::default(),
-                creation_timestamp,
+                creation_timestamp: creation_timestamp.map(|k8s::Time(t)| t),
             },
             statuses,
-        })
+            backends: self.backends,
+        }
     }
 }
 
@@ -152,8 +242,34 @@ impl RouteBinding {
 
     #[inline]
     pub fn accepted_by_server(&self, name: &str) -> bool {
+        self.accepted_by(&ParentRef::Server(name.to_string()))
+    }
+
+    /// Returns true if the route is attached to a port of a Gateway's ingress
+    /// pods, i.e. it references a listener of the Gateway whose port and
+    /// section name (if set) match the port, and the policy controller has
+    /// accepted the route on that listener.
+    pub fn attaches_to_gateway(&self, name: &str, port: NonZeroU16, port_names: &[&str]) -> bool {
+        self.parents.iter().any(|parent| match parent {
+            ParentRef::Gateway {
+                name: gateway,
+                section_name,
+                port: listener_port,
+            } => {
+                gateway == name
+                    && listener_port.map_or(true, |p| p == port.get())
+                    && section_name
+                        .as_deref()
+                        .map_or(true, |s| port_names.contains(&s))
+                    && self.accepted_by(parent)
+            }
+            ParentRef::Server(_) => false,
+        })
+    }
+
+    fn accepted_by(&self, parent: &ParentRef) -> bool {
         self.statuses.iter().any(|status| {
-            status.parent == ParentRef::Server(name.to_string())
+            status.parent == *parent
                 && status
                     .conditions
                     .iter()
@@ -290,6 +406,21 @@ impl ParentRef {
         route_ns: Option<&str>,
         parent_ref: api::ParentReference,
     ) -> Option<Result<Self, InvalidParentRef>> {
+        // Gateways in other namespaces are skipped, since their pods are
+        // indexed separately from the route.
+        if policy::parent_ref_targets_gateway(&parent_ref) {
+            if parent_ref.name.is_empty()
+                || (parent_ref.namespace.is_some() && parent_ref.namespace.as_deref() != route_ns)
+            {
+                return None;
+            }
+            return Some(Ok(ParentRef::Gateway {
+                name: parent_ref.name,
+                section_name: parent_ref.section_name,
+                port: parent_ref.port,
+            }));
+        }
+
         // Skip parent refs that don't target a `Server` resource.
         if !policy::parent_ref_targets_kind::<Server>(&parent_ref) || parent_ref.name.is_empty() {
             return None;
@@ -323,18 +454,28 @@ impl Status {
         status
             .parents
             .iter()
-            .filter(|status| status.controller_name == POLICY_CONTROLLER_NAME)
             .filter_map(Self::from_parent_status)
             .collect::<Vec<_>>()
     }
 
     fn from_parent_status(status: &gateway::RouteParentStatus) -> Option<Self> {
-        // Only match parent statuses that belong to resources of
-        // `kind: Server`.
-        match status.parent_ref.kind.as_deref() {
-            Some("Server") => (),
-            _ => return None,
+        // Only match parent statuses that the policy controller writes for
+        // resources of `kind: Server` or for Gateways. Statuses that a
+        // Gateway's own controller writes are ignored.
+        if status.controller_name != POLICY_CONTROLLER_NAME {
+            return None;
         }
+        let parent = if policy::parent_ref_targets_gateway(&status.parent_ref) {
+            ParentRef::Gateway {
+                name: status.parent_ref.name.to_string(),
+                section_name: status.parent_ref.section_name.clone(),
+                port: status.parent_ref.port,
+            }
+        } else if status.parent_ref.kind.as_deref() == Some("Server") {
+            ParentRef::Server(status.parent_ref.name.to_string())
+        } else {
+            return None;
+        };
 
         let conditions = status
             .conditions
@@ -359,10 +500,7 @@ impl Status {
             })
             .collect();
 
-        Some(Status {
-            parent: ParentRef::Server(status.parent_ref.name.to_string()),
-            conditions,
-        })
+        Some(Status { parent, conditions })
     }
 }
 
@@ -1,17 +1,23 @@
 //! Keeps track of `Pod`, `Server`, and `ServerAuthorization` resources to
 //! provide a dynamic server configuration for all known ports on all pods.
 //!
-//! The `Index` type exposes a single public method: `Index::pod_server_rx`,
-//! which is used to lookup pod/ports (i.e. by the gRPC API). Otherwise, it
-//! implements `kubert::index::IndexNamespacedResource` for the indexed
-//! kubernetes resources.
+//! The `Index` type implements `kubert::index::IndexNamespacedResource` for the
+//! indexed kubernetes resources. Lookups (i.e. by the gRPC API) are served by a
+//! `Lookup` handle, which may be used without holding the index's lock.
+//!
+//! Each namespace's state is locked independently, so that processing updates
+//! in one namespace does not block lookups in another.
 
 use super::{
-    authorization_policy, http_route::RouteBinding, meshtls_authentication, network_authentication,
-    server, server_authorization, workload,
+    authorization_policy, cluster_policy,
+    http_route::{ParsedRoute, RouteBinding, RouteResource, GATEWAY_NAME_LABEL},
+    meshtls_authentication, network_authentication, server, server_authorization, workload,
 };
 use crate::{
-    http_route::{gkn_for_gateway_http_route, gkn_for_linkerd_http_route, gkn_for_resource},
+    http_route::{
+        gkn_for_gateway_http_route, gkn_for_linkerd_http_route, gkn_for_resource, is_quarantined,
+        ParseCache,
+    },
     ports::{PortHasher, PortMap, PortSet},
     ClusterInfo, DefaultPolicy,
 };
@@ -22,22 +28,27 @@ use linkerd_policy_controller_core::{
         AuthorizationRef, ClientAuthentication, ClientAuthorization, HttpRoute, HttpRouteRef,
         HttpRouteRule, InboundServer, ProxyProtocol, ServerRef,
     },
-    routes::{GroupKindName, HttpRouteMatch, Method, PathMatch},
-    IdentityMatch, Ipv4Net, Ipv6Net, NetworkMatch,
+    routes::{
+        GroupKindName, GroupKindNamespaceName, HttpRouteMatch, Method, PathMatch, Quarantine,
+    },
+    IdentityMatch, IpNet, Ipv4Net, Ipv6Net, NetworkMatch,
 };
 use linkerd_policy_controller_k8s_api::{
     self as k8s, policy::server::Port, policy::server::Selector, ResourceExt,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::{
-    collections::{hash_map::Entry, BTreeSet},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet},
     num::NonZeroU16,
     sync::Arc,
+    time::SystemTime,
 };
 use tokio::sync::watch;
 use tracing::info_span;
 
+pub mod dump;
 pub mod metrics;
+mod selection;
 
 pub type SharedIndex = Arc<RwLock<Index>>;
 
@@ -47,15 +58,33 @@ pub type SharedIndex = Arc<RwLock<Index>>;
 #[derive(Debug)]
 pub struct Index {
     cluster_info: Arc<ClusterInfo>,
+    /// The defaults configured on the command line, which apply unless they
+    /// are overridden by a `ClusterPolicy`.
+    configured_defaults: cluster_policy::Defaults,
     namespaces: NamespaceIndex,
-    authentications: AuthenticationNsIndex,
+    authentications: Arc<RwLock<AuthenticationNsIndex>>,
+    parsed_routes: ParseCache<ParsedRoute>,
+}
+
+/// Serves lookups against an `Index` without contending with the index's
+/// updates in other namespaces.
+#[derive(Clone, Debug)]
+pub struct Lookup {
+    namespaces: NamespaceIndex,
+    authentications: Arc<RwLock<AuthenticationNsIndex>>,
 }
 
 /// Holds all `Pod`, `Server`, and `ServerAuthorization` indices by-namespace.
-#[derive(Debug)]
+///
+/// The map of namespaces is only locked exclusively while namespaces are added
+/// or removed; each namespace is guarded by its own lock. To avoid deadlocks,
+/// the map's lock must not be acquired while a namespace's lock is held.
+#[derive(Clone, Debug)]
 struct NamespaceIndex {
-    cluster_info: Arc<ClusterInfo>,
-    by_ns: HashMap<String, Namespace>,
+    /// Shared with lookups, so that defaults created on lookup reflect updates
+    /// to the cluster's configuration.
+    cluster_info: Arc<RwLock<Arc<ClusterInfo>>>,
+    by_ns: Arc<RwLock<HashMap<String, Arc<Mutex<Namespace>>>>>,
 }
 
 /// Holds all `NetworkAuthentication` and `MeshTLSAuthentication` indices by-namespace.
@@ -80,6 +109,7 @@ struct Namespace {
 struct PodIndex {
     namespace: String,
     by_name: HashMap<String, Pod>,
+    labels: selection::WorkloadLabels,
 }
 
 /// Holds a single pod's data with the server watches for all known ports.
@@ -108,6 +138,16 @@ struct Pod {
     /// aware of the probe ports and the expected paths on which probes are
     /// expected.
     probes: PortMap<BTreeSet<String>>,
+
+    /// The time after which the pod is deleted, if its deletion has been
+    /// requested.
+    ///
+    /// A terminating pod continues to run until its grace period expires, so
+    /// its policy is served until then. If the pod's deletion is not observed
+    /// by then (e.g. because its node is unreachable), lookups fail as though
+    /// the pod had already been removed, so that a pod that reuses its name is
+    /// not served stale policy.
+    deletion_deadline: Option<SystemTime>,
 }
 
 /// Holds the state of a single port on a workload (e.g. a pod or an external
@@ -128,6 +168,10 @@ struct WorkloadPortServer {
 struct ExternalWorkloadIndex {
     namespace: String,
     by_name: HashMap<String, ExternalWorkload>,
+    labels: selection::WorkloadLabels,
+
+    /// The name of the external workload that holds each mesh identity.
+    by_identity: HashMap<String, String>,
 }
 
 /// Holds data for a single external workload, with server watches for all known
@@ -139,12 +183,19 @@ struct ExternalWorkloadIndex {
 struct ExternalWorkload {
     meta: workload::Meta,
 
+    /// The mesh identity with which the workload is enrolled.
+    identity: String,
+
     // The workload's named container ports. Used by `Server` port selectors.
     //
     // A workload will not have multiple ports with the same name, e.g. two
     // `admin-http` ports pointing to different numerical values.
     port_names: HashMap<String, NonZeroU16>,
 
+    /// The TCP ports declared in the workload's spec. These are served default
+    /// policies eagerly, rather than only once they are discovered.
+    ports: PortSet,
+
     /// All known TCP server ports.
     port_servers: PortMap<WorkloadPortServer>,
 }
@@ -156,6 +207,7 @@ struct PolicyIndex {
     cluster_info: Arc<ClusterInfo>,
 
     servers: HashMap<String, server::Server>,
+    server_labels: selection::ServerLabels,
     server_authorizations: HashMap<String, server_authorization::ServerAuthz>,
 
     authorization_policies: HashMap<String, authorization_policy::Spec>,
@@ -168,6 +220,38 @@ struct AuthenticationIndex {
     network: HashMap<String, network_authentication::Spec>,
 }
 
+/// Describes the workloads that must be reindexed after a change to a
+/// namespace's policy resources.
+#[derive(Copy, Clone, Debug)]
+enum Scope<'s> {
+    /// All workloads in the namespace.
+    All,
+
+    /// Workloads that are selected by, or have ports bound to, the named
+    /// server.
+    Server(&'s str),
+
+    /// Workloads that have ports bound to a server.
+    ///
+    /// Changes to policy resources other than servers do not change which
+    /// ports are selected by servers, so workloads that only use default
+    /// policies are not affected.
+    Bound,
+}
+
+/// Caches the policy resolved for each server during a reindex, so that it is
+/// computed once per server rather than once per selected workload port.
+///
+/// Servers that have no routes use default routes, which depend on the probe
+/// paths of each port, so only routes from route resources are cached.
+type ServerCache<'p> = HashMap<
+    &'p str,
+    (
+        HashMap<AuthorizationRef, ClientAuthorization>,
+        HashMap<HttpRouteRef, HttpRoute>,
+    ),
+>;
+
 struct NsUpdate<K, T> {
     added: Vec<(K, T)>,
     removed: HashSet<K>,
@@ -179,39 +263,55 @@ impl Index {
     pub fn shared(cluster_info: impl Into<Arc<ClusterInfo>>) -> SharedIndex {
         let cluster_info = cluster_info.into();
         Arc::new(RwLock::new(Self {
+            configured_defaults: cluster_policy::Defaults::from_cluster(&cluster_info),
             cluster_info: cluster_info.clone(),
             namespaces: NamespaceIndex {
-                cluster_info,
-                by_ns: HashMap::default(),
+                cluster_info: Arc::new(RwLock::new(cluster_info)),
+                by_ns: Default::default(),
             },
-            authentications: AuthenticationNsIndex::default(),
+            authentications: Default::default(),
+            parsed_routes: ParseCache::default(),
         }))
     }
 
+    /// Removes namespaces that no longer hold any resources, returning the
+    /// number of namespaces that were removed.
+    ///
+    /// Namespaces are ordinarily removed when their last resource is deleted,
+    /// so this only sweeps up state that was retained unexpectedly.
+    pub fn gc(&self) -> usize {
+        let mut removed = 0;
+        for (namespace, ns) in self.namespaces.all_named() {
+            if ns.lock().is_empty() && self.namespaces.remove_if_empty(namespace) {
+                removed += 1;
+            }
+        }
+
+        let mut authns = self.authentications.write();
+        let before = authns.by_ns.len();
+        authns.by_ns.retain(|_, ns| !ns.is_empty());
+        removed + before - authns.by_ns.len()
+    }
+
+    /// Returns a handle that serves lookups against this index.
+    pub fn lookup(&self) -> Lookup {
+        Lookup {
+            namespaces: self.namespaces.clone(),
+            authentications: self.authentications.clone(),
+        }
+    }
+
     /// Obtains a pod:port's server receiver.
     ///
     /// An error is returned if the pod is not found. If the port is not found,
     /// a default is server is created.
     pub fn pod_server_rx(
-        &mut self,
+        &self,
         namespace: &str,
         pod: &str,
         port: NonZeroU16,
     ) -> Result<watch::Receiver<InboundServer>> {
-        let ns = self
-            .namespaces
-            .by_ns
-            .get_mut(namespace)
-            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
-        let pod = ns
-            .pods
-            .by_name
-            .get_mut(pod)
-            .ok_or_else(|| anyhow::anyhow!("pod {}.{} not found", pod, namespace))?;
-        Ok(pod
-            .port_server_or_default(port, &self.cluster_info)
-            .watch
-            .subscribe())
+        self.lookup().pod_server_rx(namespace, pod, port)
     }
 
     /// Obtains an external_workload:port's server receiver.
@@ -219,90 +319,194 @@ impl Index {
     /// An error is returned if the external workload is not found. If the port
     /// is not found, a default server is created.
     pub fn external_workload_server_rx(
-        &mut self,
+        &self,
         namespace: &str,
         workload: &str,
         port: NonZeroU16,
     ) -> Result<watch::Receiver<InboundServer>> {
-        let ns = self
-            .namespaces
-            .by_ns
-            .get_mut(namespace)
-            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
-        let external_workload =
-            ns.external_workloads
-                .by_name
-                .get_mut(workload)
-                .ok_or_else(|| {
-                    anyhow::anyhow!("external workload {}.{} not found", workload, namespace)
-                })?;
-        Ok(external_workload
-            .port_server_or_default(port, &self.cluster_info)
-            .watch
-            .subscribe())
+        self.lookup()
+            .external_workload_server_rx(namespace, workload, port)
+    }
+
+    /// Obtains the server receiver for a port on the external workload that
+    /// is enrolled with the given mesh identity.
+    ///
+    /// An error is returned if no external workload in the namespace has the
+    /// identity. If the port is not found, a default server is created.
+    pub fn external_workload_identity_server_rx(
+        &self,
+        namespace: &str,
+        identity: &str,
+        port: NonZeroU16,
+    ) -> Result<watch::Receiver<InboundServer>> {
+        self.lookup()
+            .external_workload_identity_server_rx(namespace, identity, port)
+    }
+
+    /// Resolves the policy configured by a `Server` resource, independently of
+    /// the workloads that it selects.
+    ///
+    /// Returns `None` if the server is not found.
+    pub fn server(&self, namespace: &str, name: &str) -> Option<InboundServer> {
+        self.lookup().server(namespace, name)
     }
 
-    fn ns_with_reindex(&mut self, namespace: String, f: impl FnOnce(&mut Namespace) -> bool) {
+    fn ns_with_reindex(
+        &mut self,
+        namespace: String,
+        scope: Scope<'_>,
+        f: impl FnOnce(&mut Namespace) -> bool,
+    ) {
         self.namespaces
-            .get_with_reindex(namespace, &self.authentications, f)
+            .get_with_reindex(namespace, &self.authentications.read(), scope, f)
     }
 
     fn ns_or_default_with_reindex(
         &mut self,
         namespace: String,
+        scope: Scope<'_>,
         f: impl FnOnce(&mut Namespace) -> bool,
     ) {
-        self.namespaces
-            .get_or_default_with_reindex(namespace, &self.authentications, f)
+        self.namespaces.get_or_default_with_reindex(
+            namespace,
+            &self.authentications.read(),
+            scope,
+            f,
+        )
+    }
+
+    /// Updates the networks that contain the cluster's pods, recomputing the
+    /// default policies that only authorize clients in the cluster.
+    pub fn set_cluster_networks(&mut self, networks: Vec<IpNet>) {
+        if self.cluster_info.networks == networks {
+            return;
+        }
+        tracing::info!(?networks, "Cluster networks changed");
+        self.update_cluster_info(|cluster| cluster.networks = networks);
+    }
+
+    /// Updates the mesh identity trust domain, recomputing authorizations for
+    /// clients identified by `ServiceAccount` or `Namespace`.
+    pub fn set_identity_domain(&mut self, domain: String) {
+        if self.cluster_info.identity_domain == domain {
+            return;
+        }
+        tracing::info!(%domain, "Identity trust domain changed");
+        self.update_cluster_info(|cluster| cluster.identity_domain = domain);
+    }
+
+    /// Updates the trust domains of linked clusters, recomputing authorizations
+    /// for clients in those clusters.
+    pub fn set_remote_identity_domains(&mut self, domains: BTreeMap<String, String>) {
+        if self.cluster_info.remote_identity_domains == domains {
+            return;
+        }
+        tracing::info!(?domains, "Linked cluster trust domains changed");
+        self.update_cluster_info(|cluster| cluster.remote_identity_domains = domains);
+    }
+
+    /// Records the routes that this index fails to parse in the given
+    /// quarantine.
+    pub fn set_quarantine(&mut self, quarantine: Quarantine) {
+        self.parsed_routes.set_quarantine("inbound", quarantine);
     }
 
+    /// Updates the cluster-wide inbound defaults.
+    fn set_defaults(&mut self, defaults: cluster_policy::Defaults) {
+        if cluster_policy::Defaults::from_cluster(&self.cluster_info) == defaults {
+            return;
+        }
+        tracing::info!(?defaults, "Cluster defaults changed");
+        self.update_cluster_info(|cluster| defaults.apply(cluster));
+    }
+
+    /// Replaces the cluster's configuration and reindexes every workload, so
+    /// that servers are rebuilt from the new configuration.
+    fn update_cluster_info(&mut self, update: impl FnOnce(&mut ClusterInfo)) {
+        let mut cluster_info = (*self.cluster_info).clone();
+        update(&mut cluster_info);
+        let cluster_info = Arc::new(cluster_info);
+        self.cluster_info = cluster_info.clone();
+        *self.namespaces.cluster_info.write() = cluster_info.clone();
+
+        let authns = self.authentications.read();
+        for ns in self.namespaces.all() {
+            let mut ns = ns.lock();
+            ns.policy.cluster_info = cluster_info.clone();
+            ns.reindex(&authns, Scope::All);
+        }
+    }
+
+    /// Reindexes workloads in all namespaces after a change to authentication
+    /// resources, which may be referenced across namespaces.
     fn reindex_all(&mut self) {
         tracing::debug!("Reindexing all namespaces");
-        for ns in self.namespaces.by_ns.values_mut() {
-            ns.reindex(&self.authentications);
+        let authns = self.authentications.read();
+        for ns in self.namespaces.all() {
+            ns.lock().reindex(&authns, Scope::Bound);
         }
     }
 
-    fn apply_route<R>(&mut self, route: R)
-    where
-        R: ResourceExt<DynamicType = ()>,
-        RouteBinding: TryFrom<R>,
-        <RouteBinding as TryFrom<R>>::Error: std::fmt::Display,
-    {
+    /// Binds a route, reusing the route's parsed spec if the route has already
+    /// been parsed at its current generation.
+    fn bind_route<R: RouteResource>(
+        &mut self,
+        gknn: GroupKindNamespaceName,
+        route: R,
+    ) -> Result<RouteBinding> {
+        let parsed = self
+            .parsed_routes
+            .get_or_parse(gknn, route.meta(), || route.parse())?;
+        route.bind(&parsed)
+    }
+
+    fn apply_route<R: RouteResource>(&mut self, route: R) {
         let ns = route.namespace().expect("HttpRoute must have a namespace");
         let name = route.name_unchecked();
         let gkn = gkn_for_resource(&route);
         let _span = info_span!("apply", %ns, %name).entered();
 
-        let route_binding = match route.try_into() {
+        let route_binding = match self.bind_route(gkn.clone().namespaced(ns.clone()), route) {
             Ok(binding) => binding,
+            Err(error) if is_quarantined(&error) => {
+                tracing::debug!(%ns, %name, %error, "Ignoring quarantined HTTPRoute");
+                return;
+            }
             Err(error) => {
                 tracing::info!(%ns, %name, %error, "Ignoring HTTPRoute");
                 return;
             }
         };
 
-        self.ns_or_default_with_reindex(ns, |ns| ns.policy.update_http_route(gkn, route_binding))
+        self.ns_or_default_with_reindex(ns, Scope::Bound, |ns| {
+            ns.policy.update_http_route(gkn, route_binding)
+        })
     }
 
-    fn reset_route<R>(&mut self, routes: Vec<R>, deleted: HashMap<String, HashSet<String>>)
-    where
-        R: ResourceExt<DynamicType = ()>,
-        RouteBinding: TryFrom<R>,
-        <RouteBinding as TryFrom<R>>::Error: std::fmt::Display,
-    {
+    fn reset_route<R: RouteResource>(
+        &mut self,
+        routes: Vec<R>,
+        deleted: HashMap<String, HashSet<String>>,
+    ) {
         let _span = info_span!("reset").entered();
 
         // Aggregate all of the updates by namespace so that we only reindex
         // once per namespace.
         type Ns = NsUpdate<GroupKindName, RouteBinding>;
         let mut updates_by_ns = HashMap::<String, Ns>::default();
+        let mut live = HashSet::<GroupKindNamespaceName>::default();
         for route in routes.into_iter() {
             let namespace = route.namespace().expect("HttpRoute must be namespaced");
             let name = route.name_unchecked();
             let gkn = gkn_for_resource(&route);
-            let route_binding = match route.try_into() {
+            let gknn = gkn.clone().namespaced(namespace.clone());
+            live.insert(gknn.clone());
+            let route_binding = match self.bind_route(gknn, route) {
                 Ok(binding) => binding,
+                Err(error) if is_quarantined(&error) => {
+                    tracing::debug!(ns = %namespace, %name, %error, "Ignoring quarantined HTTPRoute");
+                    continue;
+                }
                 Err(error) => {
                     tracing::info!(ns = %namespace, %name, %error, "Ignoring HTTPRoute");
                     continue;
@@ -326,13 +530,18 @@ impl Index {
             updates_by_ns.entry(ns).or_default().removed = removed;
         }
 
+        // Forget the parsed specs of routes of this kind that no longer exist.
+        let (group, kind) = (R::group(&()), R::kind(&()));
+        self.parsed_routes
+            .retain(|gknn| gknn.group != group || gknn.kind != kind || live.contains(gknn));
+
         for (namespace, Ns { added, removed }) in updates_by_ns.into_iter() {
             if added.is_empty() {
                 // If there are no live resources in the namespace, we do not
                 // want to create a default namespace instance, we just want to
                 // clear out all resources for the namespace (and then drop the
                 // whole namespace, if necessary).
-                self.ns_with_reindex(namespace, |ns| {
+                self.ns_with_reindex(namespace, Scope::Bound, |ns| {
                     ns.policy.http_routes.clear();
                     true
                 });
@@ -340,7 +549,7 @@ impl Index {
                 // Otherwise, we take greater care to reindex only when the
                 // state actually changed. The vast majority of resets will see
                 // no actual data change.
-                self.ns_or_default_with_reindex(namespace, |ns| {
+                self.ns_or_default_with_reindex(namespace, Scope::Bound, |ns| {
                     let mut changed = !removed.is_empty();
                     for gkn in removed.into_iter() {
                         ns.policy.http_routes.remove(&gkn);
@@ -356,7 +565,11 @@ impl Index {
 
     fn delete_route(&mut self, ns: String, gkn: GroupKindName) {
         let _span = info_span!("delete", %ns, route = ?gkn).entered();
-        self.ns_with_reindex(ns, |ns| ns.policy.http_routes.remove(&gkn).is_some())
+        self.parsed_routes
+            .remove(&gkn.clone().namespaced(ns.clone()));
+        self.ns_with_reindex(ns, Scope::Bound, |ns| {
+            ns.policy.http_routes.remove(&gkn).is_some()
+        })
     }
 }
 
@@ -376,16 +589,54 @@ impl kubert::index::IndexNamespacedResource<k8s::Pod> for Index {
             .as_ref()
             .map(workload::pod_http_probes)
             .unwrap_or_default();
+        let proxy_ports = pod
+            .spec
+            .as_ref()
+            .map(workload::pod_proxy_ports)
+            .unwrap_or_default();
 
-        let meta = workload::Meta::from_metadata(pod.metadata);
+        // Pods that have completed no longer run a proxy, so they are removed
+        // from the index (completing any open watches) rather than continuing
+        // to be served until they are deleted.
+        if workload::pod_completed(&pod) {
+            tracing::debug!("Pod has completed");
+            self.namespaces
+                .get_with_removal(namespace, |ns| ns.pods.remove(&name));
+            return;
+        }
+
+        // Pods without a proxy are not served policies, so they may be omitted
+        // from the index. A pod that is no longer meshed is removed.
+        if self.cluster_info.meshed_pods_only && !workload::pod_meshed(&pod) {
+            tracing::debug!("Pod is not meshed");
+            self.namespaces
+                .get_with_removal(namespace, |ns| ns.pods.remove(&name));
+            return;
+        }
+
+        let deletion_deadline = pod
+            .metadata
+            .deletion_timestamp
+            .as_ref()
+            .map(|k8s::Time(t)| SystemTime::from(*t));
+        let mut meta = workload::Meta::from_metadata(pod.metadata);
+        meta.settings.proxy_ports = proxy_ports;
 
         // Add or update the pod. If the pod was not already present in the
         // index with the same metadata, index it against the policy resources,
         // updating its watches.
+        let authns = self.authentications.read();
         let ns = self.namespaces.get_or_default(namespace);
-        match ns.pods.update(name, meta, port_names, probes) {
-            Ok(None) => {}
-            Ok(Some(pod)) => pod.reindex_servers(&ns.policy, &self.authentications),
+        let mut ns = ns.lock();
+        let ns = &mut *ns;
+        match ns
+            .pods
+            .update(name.clone(), meta, port_names, probes, deletion_deadline)
+        {
+            Ok(false) => {}
+            Ok(true) => ns
+                .pods
+                .reindex_pod(&name, &ns.policy, &authns, &mut Default::default()),
             Err(error) => {
                 tracing::error!(%error, "Illegal pod update");
             }
@@ -394,15 +645,11 @@ impl kubert::index::IndexNamespacedResource<k8s::Pod> for Index {
 
     fn delete(&mut self, ns: String, name: String) {
         tracing::debug!(%ns, %name, "delete");
-        if let Entry::Occupied(mut ns) = self.namespaces.by_ns.entry(ns) {
-            // Once the pod is removed, there's nothing else to update. Any open
-            // watches will complete.  No other parts of the index need to be
-            // updated.
-            if ns.get_mut().pods.by_name.remove(&name).is_some() && ns.get().is_empty() {
-                tracing::debug!(namespace = ns.key(), "Removing empty namespace index");
-                ns.remove();
-            }
-        }
+        // Once the pod is removed, there's nothing else to update. Any open
+        // watches will complete.  No other parts of the index need to be
+        // updated.
+        self.namespaces
+            .get_with_removal(ns, |ns| ns.pods.remove(&name));
     }
 
     // Since apply only reindexes a single pod at a time, there's no need to
@@ -416,21 +663,34 @@ impl kubert::index::IndexNamespacedResource<k8s::external_workload::ExternalWork
         let _span = info_span!("apply", %ns, %name).entered();
 
         // Extract ports and settings.
-        // Note: external workloads do not have any probe paths to synthesise
-        // default policies for.
+        // Note: external workloads do not declare probes, so only the probe
+        // paths listed in their annotations are authorized.
         let port_names = workload::external_tcp_ports_by_name(&ext_workload.spec);
+        let ports = workload::external_tcp_ports(&ext_workload.spec);
+        let identity = ext_workload.spec.mesh_tls.identity;
         let meta = workload::Meta::from_metadata(ext_workload.metadata);
 
         // Add or update the workload.
         //
         // If the resource is present in the index, but its metadata has
         // changed, then it means the watches need to get an update.
+        let authns = self.authentications.read();
         let ns = self.namespaces.get_or_default(ns);
-        match ns.external_workloads.update(name, meta, port_names) {
+        let mut ns = ns.lock();
+        let ns = &mut *ns;
+        match ns
+            .external_workloads
+            .update(name.clone(), identity, meta, port_names, ports)
+        {
             // No update
-            Ok(None) => {}
+            Ok(false) => {}
             // Update, so re-index
-            Ok(Some(workload)) => workload.reindex_servers(&ns.policy, &self.authentications),
+            Ok(true) => ns.external_workloads.reindex_workload(
+                &name,
+                &ns.policy,
+                &authns,
+                &mut Default::default(),
+            ),
             Err(error) => {
                 tracing::error!(%error, "Illegal external workload update");
             }
@@ -439,21 +699,11 @@ impl kubert::index::IndexNamespacedResource<k8s::external_workload::ExternalWork
 
     fn delete(&mut self, ns: String, name: String) {
         tracing::debug!(%ns, %name, "delete");
-        if let Entry::Occupied(mut ns) = self.namespaces.by_ns.entry(ns) {
-            // Once the external workload is removed, there's nothing else to
-            // update. Any open watches will complete. No other parts of the
-            // index need to be updated.
-            if ns
-                .get_mut()
-                .external_workloads
-                .by_name
-                .remove(&name)
-                .is_some()
-                && ns.get().is_empty()
-            {
-                ns.remove();
-            }
-        }
+        // Once the external workload is removed, there's nothing else to
+        // update. Any open watches will complete. No other parts of the
+        // index need to be updated.
+        self.namespaces
+            .get_with_removal(ns, |ns| ns.external_workloads.remove(&name));
     }
 
     // Since apply only reindexes a single external workload at a time, there's no need to
@@ -466,13 +716,18 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::Server> for Index {
         let name = srv.name_unchecked();
         let _span = info_span!("apply", %ns, %name).entered();
 
-        let server = server::Server::from_resource(srv, &self.cluster_info);
-        self.ns_or_default_with_reindex(ns, |ns| ns.policy.update_server(name, server))
+        let server = server::Server::from_resource(srv);
+        let scope = name.clone();
+        self.ns_or_default_with_reindex(ns, Scope::Server(&scope), |ns| {
+            ns.policy.update_server(name, server)
+        })
     }
 
     fn delete(&mut self, ns: String, name: String) {
         let _span = info_span!("delete", %ns, %name).entered();
-        self.ns_with_reindex(ns, |ns| ns.policy.servers.remove(&name).is_some())
+        self.ns_with_reindex(ns, Scope::Server(&name), |ns| {
+            ns.policy.remove_server(&name)
+        })
     }
 
     fn reset(&mut self, srvs: Vec<k8s::policy::Server>, deleted: HashMap<String, HashSet<String>>) {
@@ -485,7 +740,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::Server> for Index {
         for srv in srvs.into_iter() {
             let namespace = srv.namespace().expect("server must be namespaced");
             let name = srv.name_unchecked();
-            let server = server::Server::from_resource(srv, &self.cluster_info);
+            let server = server::Server::from_resource(srv);
             updates_by_ns
                 .entry(namespace)
                 .or_default()
@@ -502,18 +757,18 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::Server> for Index {
                 // want to create a default namespace instance, we just want to
                 // clear out all resources for the namespace (and then drop the
                 // whole namespace, if necessary).
-                self.ns_with_reindex(namespace, |ns| {
-                    ns.policy.servers.clear();
+                self.ns_with_reindex(namespace, Scope::All, |ns| {
+                    ns.policy.clear_servers();
                     true
                 });
             } else {
                 // Otherwise, we take greater care to reindex only when the
                 // state actually changed. The vast majority of resets will see
                 // no actual data change.
-                self.ns_or_default_with_reindex(namespace, |ns| {
+                self.ns_or_default_with_reindex(namespace, Scope::All, |ns| {
                     let mut changed = !removed.is_empty();
                     for name in removed.into_iter() {
-                        ns.policy.servers.remove(&name);
+                        ns.policy.remove_server(&name);
                     }
                     for (name, server) in added.into_iter() {
                         changed = ns.policy.update_server(name, server) || changed;
@@ -531,8 +786,8 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::ServerAuthorization> fo
         let name = saz.name_unchecked();
         let _span = info_span!("apply", %ns, %name).entered();
 
-        match server_authorization::ServerAuthz::from_resource(saz, &self.cluster_info) {
-            Ok(meta) => self.ns_or_default_with_reindex(ns, move |ns| {
+        match server_authorization::ServerAuthz::from_resource(saz) {
+            Ok(meta) => self.ns_or_default_with_reindex(ns, Scope::Bound, move |ns| {
                 ns.policy.update_server_authz(name, meta)
             }),
             Err(error) => tracing::error!(%error, "Illegal server authorization update"),
@@ -541,7 +796,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::ServerAuthorization> fo
 
     fn delete(&mut self, ns: String, name: String) {
         let _span = info_span!("delete", %ns, %name).entered();
-        self.ns_with_reindex(ns, |ns| {
+        self.ns_with_reindex(ns, Scope::Bound, |ns| {
             ns.policy.server_authorizations.remove(&name).is_some()
         })
     }
@@ -562,7 +817,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::ServerAuthorization> fo
                 .namespace()
                 .expect("serverauthorization must be namespaced");
             let name = saz.name_unchecked();
-            match server_authorization::ServerAuthz::from_resource(saz, &self.cluster_info) {
+            match server_authorization::ServerAuthz::from_resource(saz) {
                 Ok(saz) => updates_by_ns
                     .entry(namespace)
                     .or_default()
@@ -583,7 +838,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::ServerAuthorization> fo
                 // want to create a default namespace instance, we just want to
                 // clear out all resources for the namespace (and then drop the
                 // whole namespace, if necessary).
-                self.ns_with_reindex(namespace, |ns| {
+                self.ns_with_reindex(namespace, Scope::Bound, |ns| {
                     ns.policy.server_authorizations.clear();
                     true
                 });
@@ -591,7 +846,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::ServerAuthorization> fo
                 // Otherwise, we take greater care to reindex only when the
                 // state actually changed. The vast majority of resets will see
                 // no actual data change.
-                self.ns_or_default_with_reindex(namespace, |ns| {
+                self.ns_or_default_with_reindex(namespace, Scope::Bound, |ns| {
                     let mut changed = !removed.is_empty();
                     for name in removed.into_iter() {
                         ns.policy.server_authorizations.remove(&name);
@@ -620,13 +875,15 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::AuthorizationPolicy> fo
             }
         };
 
-        self.ns_or_default_with_reindex(ns, |ns| ns.policy.update_authz_policy(name, spec))
+        self.ns_or_default_with_reindex(ns, Scope::Bound, |ns| {
+            ns.policy.update_authz_policy(name, spec)
+        })
     }
 
     fn delete(&mut self, ns: String, ap: String) {
         let _span = info_span!("delete", %ns, %ap).entered();
         tracing::trace!(name = %ap, "Delete");
-        self.ns_with_reindex(ns, |ns| {
+        self.ns_with_reindex(ns, Scope::Bound, |ns| {
             ns.policy.authorization_policies.remove(&ap).is_some()
         })
     }
@@ -669,7 +926,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::AuthorizationPolicy> fo
                 // want to create a default namespace instance, we just want to
                 // clear out all resources for the namespace (and then drop the
                 // whole namespace, if necessary).
-                self.ns_with_reindex(namespace, |ns| {
+                self.ns_with_reindex(namespace, Scope::Bound, |ns| {
                     ns.policy.authorization_policies.clear();
                     true
                 });
@@ -677,7 +934,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::AuthorizationPolicy> fo
                 // Otherwise, we take greater care to reindex only when the
                 // state actually changed. The vast majority of resets will see
                 // no actual data change.
-                self.ns_or_default_with_reindex(namespace, |ns| {
+                self.ns_or_default_with_reindex(namespace, Scope::Bound, |ns| {
                     let mut changed = !removed.is_empty();
                     for name in removed.into_iter() {
                         ns.policy.authorization_policies.remove(&name);
@@ -700,8 +957,7 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::MeshTLSAuthentication>
         let name = authn.name_unchecked();
         let _span = info_span!("apply", %ns, %name).entered();
 
-        let spec = match meshtls_authentication::Spec::try_from_resource(authn, &self.cluster_info)
-        {
+        let spec = match meshtls_authentication::Spec::try_from_resource(authn) {
             Ok(spec) => spec,
             Err(error) => {
                 tracing::warn!(%error, "Invalid MeshTLSAuthentication");
@@ -709,7 +965,8 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::MeshTLSAuthentication>
             }
         };
 
-        if self.authentications.update_meshtls(ns, name, spec) {
+        let changed = self.authentications.write().update_meshtls(ns, name, spec);
+        if changed {
             self.reindex_all();
         }
     }
@@ -717,12 +974,18 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::MeshTLSAuthentication>
     fn delete(&mut self, ns: String, name: String) {
         let _span = info_span!("delete", %ns, %name).entered();
 
-        if let Entry::Occupied(mut ns) = self.authentications.by_ns.entry(ns) {
-            tracing::debug!("Deleting MeshTLSAuthentication");
-            ns.get_mut().network.remove(&name);
-            if ns.get().is_empty() {
-                ns.remove();
+        let found = match self.authentications.write().by_ns.entry(ns) {
+            Entry::Occupied(mut ns) => {
+                tracing::debug!("Deleting MeshTLSAuthentication");
+                ns.get_mut().meshtls.remove(&name);
+                if ns.get().is_empty() {
+                    ns.remove();
+                }
+                true
             }
+            Entry::Vacant(_) => false,
+        };
+        if found {
             self.reindex_all();
         } else {
             tracing::warn!("Namespace already deleted!");
@@ -743,22 +1006,23 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::MeshTLSAuthentication>
                 .namespace()
                 .expect("meshtlsauthentication must be namespaced");
             let name = authn.name_unchecked();
-            let spec = match meshtls_authentication::Spec::try_from_resource(
-                authn,
-                &self.cluster_info,
-            ) {
+            let spec = match meshtls_authentication::Spec::try_from_resource(authn) {
                 Ok(spec) => spec,
                 Err(error) => {
                     tracing::warn!(ns = %namespace, %name, %error, "Invalid MeshTLSAuthentication");
                     continue;
                 }
             };
-            changed = self.authentications.update_meshtls(namespace, name, spec) || changed;
+            changed = self
+                .authentications
+                .write()
+                .update_meshtls(namespace, name, spec)
+                || changed;
         }
         for (namespace, names) in deleted.into_iter() {
-            if let Entry::Occupied(mut ns) = self.authentications.by_ns.entry(namespace) {
+            if let Entry::Occupied(mut ns) = self.authentications.write().by_ns.entry(namespace) {
                 for name in names.into_iter() {
-                    ns.get_mut().meshtls.remove(&name);
+                    changed = ns.get_mut().meshtls.remove(&name).is_some() || changed;
                 }
                 if ns.get().is_empty() {
                     ns.remove();
@@ -786,7 +1050,8 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::NetworkAuthentication>
             }
         };
 
-        if self.authentications.update_network(ns, name, spec) {
+        let changed = self.authentications.write().update_network(ns, name, spec);
+        if changed {
             self.reindex_all();
         }
     }
@@ -794,13 +1059,19 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::NetworkAuthentication>
     fn delete(&mut self, ns: String, name: String) {
         let _span = info_span!("delete", %ns, %name).entered();
 
-        if let Entry::Occupied(mut ns) = self.authentications.by_ns.entry(ns) {
-            tracing::debug!("Deleting MeshTLSAuthentication");
+        let found = match self.authentications.write().by_ns.entry(ns) {
+            Entry::Occupied(mut ns) => {
+                tracing::debug!("Deleting NetworkAuthentication");
 
-            ns.get_mut().network.remove(&name);
-            if ns.get().is_empty() {
-                ns.remove();
+                ns.get_mut().network.remove(&name);
+                if ns.get().is_empty() {
+                    ns.remove();
+                }
+                true
             }
+            Entry::Vacant(_) => false,
+        };
+        if found {
             self.reindex_all();
         } else {
             tracing::warn!("Namespace already deleted!");
@@ -828,12 +1099,16 @@ impl kubert::index::IndexNamespacedResource<k8s::policy::NetworkAuthentication>
                     return;
                 }
             };
-            changed = self.authentications.update_network(namespace, name, spec) || changed;
+            changed = self
+                .authentications
+                .write()
+                .update_network(namespace, name, spec)
+                || changed;
         }
         for (namespace, names) in deleted.into_iter() {
-            if let Entry::Occupied(mut ns) = self.authentications.by_ns.entry(namespace) {
+            if let Entry::Occupied(mut ns) = self.authentications.write().by_ns.entry(namespace) {
                 for name in names.into_iter() {
-                    ns.get_mut().meshtls.remove(&name);
+                    changed = ns.get_mut().network.remove(&name).is_some() || changed;
                 }
                 if ns.get().is_empty() {
                     ns.remove();
@@ -885,51 +1160,295 @@ impl kubert::index::IndexNamespacedResource<k8s_gateway_api::HttpRoute> for Inde
     }
 }
 
+impl kubert::index::IndexClusterResource<k8s::policy::ClusterPolicy> for Index {
+    fn apply(&mut self, policy: k8s::policy::ClusterPolicy) {
+        let name = policy.name_unchecked();
+        let _span = info_span!("apply", %name).entered();
+        if name != k8s::policy::ClusterPolicy::NAME {
+            tracing::warn!(
+                "Ignoring ClusterPolicy; only the {:?} policy is used",
+                k8s::policy::ClusterPolicy::NAME
+            );
+            return;
+        }
+
+        match self.configured_defaults.with_policy(policy) {
+            Ok(defaults) => self.set_defaults(defaults),
+            Err(error) => tracing::warn!(%error, "Invalid ClusterPolicy"),
+        }
+    }
+
+    fn delete(&mut self, name: String) {
+        let _span = info_span!("delete", %name).entered();
+        if name == k8s::policy::ClusterPolicy::NAME {
+            self.set_defaults(self.configured_defaults.clone());
+        }
+    }
+}
+
 // === impl NemspaceIndex ===
 
 impl NamespaceIndex {
-    fn get_or_default(&mut self, ns: String) -> &mut Namespace {
+    fn get(&self, ns: &str) -> Option<Arc<Mutex<Namespace>>> {
+        self.by_ns.read().get(ns).cloned()
+    }
+
+    fn cluster_info(&self) -> Arc<ClusterInfo> {
+        self.cluster_info.read().clone()
+    }
+
+    fn get_or_default(&self, ns: String) -> Arc<Mutex<Namespace>> {
+        if let Some(ns) = self.get(&ns) {
+            return ns;
+        }
         self.by_ns
+            .write()
             .entry(ns.clone())
-            .or_insert_with(|| Namespace::new(ns, self.cluster_info.clone()))
+            .or_insert_with(|| Arc::new(Mutex::new(Namespace::new(ns, self.cluster_info()))))
+            .clone()
+    }
+
+    /// Returns all namespaces, so that they may be locked in turn without
+    /// holding the map's lock.
+    fn all(&self) -> Vec<Arc<Mutex<Namespace>>> {
+        self.by_ns.read().values().cloned().collect()
+    }
+
+    fn all_named(&self) -> Vec<(String, Arc<Mutex<Namespace>>)> {
+        self.by_ns
+            .read()
+            .iter()
+            .map(|(name, ns)| (name.clone(), ns.clone()))
+            .collect()
     }
 
     /// Gets the given namespace and, if it exists, passes it to the given
-    /// function. If the function returns true, all pods in the namespace are
-    /// reindexed; or, if the function returns false and the namespace is empty,
-    /// it is removed from the index.
+    /// function. If the function returns true and the namespace is empty, it
+    /// is removed from the index.
+    fn get_with_removal(&self, namespace: String, f: impl FnOnce(&mut Namespace) -> bool) {
+        if let Some(ns) = self.get(&namespace) {
+            let changed = f(&mut ns.lock());
+            if changed {
+                self.remove_if_empty(namespace);
+            }
+        }
+    }
+
+    /// Gets the given namespace and, if it exists, passes it to the given
+    /// function. If the function returns true, the workloads in the given scope
+    /// are reindexed; or, if the function returns false and the namespace is
+    /// empty, it is removed from the index.
     fn get_with_reindex(
-        &mut self,
+        &self,
         namespace: String,
         authns: &AuthenticationNsIndex,
+        scope: Scope<'_>,
         f: impl FnOnce(&mut Namespace) -> bool,
     ) {
-        if let Entry::Occupied(mut ns) = self.by_ns.entry(namespace) {
-            if f(ns.get_mut()) {
-                if ns.get().is_empty() {
-                    tracing::debug!(namespace = ns.key(), "Removing empty namespace index");
-                    ns.remove();
-                } else {
-                    ns.get_mut().reindex(authns);
-                }
+        let Some(ns) = self.get(&namespace) else {
+            return;
+        };
+        let mut ns = ns.lock();
+        if f(&mut ns) {
+            if ns.is_empty() {
+                drop(ns);
+                self.remove_if_empty(namespace);
+            } else {
+                ns.reindex(authns, scope);
             }
         }
     }
 
     /// Gets the given namespace (or creates it) and passes it to the given
-    /// function. If the function returns true, all pods in the namespace are
-    /// reindexed.
+    /// function. If the function returns true, the workloads in the given scope
+    /// are reindexed; or, if the namespace is left empty, it is removed from
+    /// the index.
     fn get_or_default_with_reindex(
-        &mut self,
+        &self,
         namespace: String,
         authns: &AuthenticationNsIndex,
+        scope: Scope<'_>,
         f: impl FnOnce(&mut Namespace) -> bool,
     ) {
-        let ns = self.get_or_default(namespace);
-        if f(ns) {
-            ns.reindex(authns);
+        let ns = self.get_or_default(namespace.clone());
+        let mut ns = ns.lock();
+        let changed = f(&mut ns);
+        if ns.is_empty() {
+            drop(ns);
+            self.remove_if_empty(namespace);
+        } else if changed {
+            ns.reindex(authns, scope);
         }
     }
+
+    /// Removes the given namespace if it is empty, returning true if it was
+    /// removed.
+    fn remove_if_empty(&self, namespace: String) -> bool {
+        let mut by_ns = self.by_ns.write();
+        if let Entry::Occupied(ns) = by_ns.entry(namespace) {
+            if ns.get().lock().is_empty() {
+                tracing::debug!(namespace = ns.key(), "Removing empty namespace index");
+                ns.remove();
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// === impl Lookup ===
+
+impl Lookup {
+    /// Obtains a pod:port's server receiver.
+    ///
+    /// An error is returned if the pod is not found. If the port is not found,
+    /// a default is server is created.
+    pub fn pod_server_rx(
+        &self,
+        namespace: &str,
+        pod: &str,
+        port: NonZeroU16,
+    ) -> Result<watch::Receiver<InboundServer>> {
+        let ns = self
+            .namespaces
+            .get(namespace)
+            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
+        let mut ns = ns.lock();
+        let pod = ns
+            .pods
+            .by_name
+            .get_mut(pod)
+            .filter(|pod| !pod.is_deleted(SystemTime::now()))
+            .ok_or_else(|| anyhow::anyhow!("pod {}.{} not found", pod, namespace))?;
+        Ok(pod
+            .port_server_or_default(port, &self.namespaces.cluster_info())
+            .watch
+            .subscribe())
+    }
+
+    /// Resolves the server for a pod:port without creating index state for
+    /// it.
+    ///
+    /// An error is returned if the pod is not found. If the port is not found,
+    /// the default server is returned.
+    pub fn pod_server(
+        &self,
+        namespace: &str,
+        pod: &str,
+        port: NonZeroU16,
+    ) -> Result<InboundServer> {
+        let ns = self
+            .namespaces
+            .get(namespace)
+            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
+        let ns = ns.lock();
+        let pod = ns
+            .pods
+            .by_name
+            .get(pod)
+            .filter(|pod| !pod.is_deleted(SystemTime::now()))
+            .ok_or_else(|| anyhow::anyhow!("pod {}.{} not found", pod, namespace))?;
+        Ok(pod.port_server(port, &self.namespaces.cluster_info()))
+    }
+
+    /// Obtains an external_workload:port's server receiver.
+    ///
+    /// An error is returned if the external workload is not found. If the port
+    /// is not found, a default server is created.
+    pub fn external_workload_server_rx(
+        &self,
+        namespace: &str,
+        workload: &str,
+        port: NonZeroU16,
+    ) -> Result<watch::Receiver<InboundServer>> {
+        let ns = self
+            .namespaces
+            .get(namespace)
+            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
+        let mut ns = ns.lock();
+        let external_workload =
+            ns.external_workloads
+                .by_name
+                .get_mut(workload)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("external workload {}.{} not found", workload, namespace)
+                })?;
+        Ok(external_workload
+            .port_server_or_default(port, &self.namespaces.cluster_info())
+            .watch
+            .subscribe())
+    }
+
+    /// Resolves the server for an external_workload:port without creating
+    /// index state for it.
+    ///
+    /// An error is returned if the external workload is not found. If the port
+    /// is not found, the default server is returned.
+    pub fn external_workload_server(
+        &self,
+        namespace: &str,
+        workload: &str,
+        port: NonZeroU16,
+    ) -> Result<InboundServer> {
+        let ns = self
+            .namespaces
+            .get(namespace)
+            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
+        let ns = ns.lock();
+        let external_workload = ns.external_workloads.by_name.get(workload).ok_or_else(|| {
+            anyhow::anyhow!("external workload {}.{} not found", workload, namespace)
+        })?;
+        Ok(external_workload.port_server(port, &self.namespaces.cluster_info()))
+    }
+
+    /// Obtains the server receiver for a port on the external workload that
+    /// is enrolled with the given mesh identity.
+    ///
+    /// An error is returned if no external workload in the namespace has the
+    /// identity. If the port is not found, a default server is created.
+    pub fn external_workload_identity_server_rx(
+        &self,
+        namespace: &str,
+        identity: &str,
+        port: NonZeroU16,
+    ) -> Result<watch::Receiver<InboundServer>> {
+        let ns = self
+            .namespaces
+            .get(namespace)
+            .ok_or_else(|| anyhow::anyhow!("namespace not found: {}", namespace))?;
+        let mut ns = ns.lock();
+        let workloads = &mut ns.external_workloads;
+        let external_workload = workloads
+            .by_identity
+            .get(identity)
+            .and_then(|name| workloads.by_name.get_mut(name))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "external workload with identity {} not found in {}",
+                    identity,
+                    namespace
+                )
+            })?;
+        Ok(external_workload
+            .port_server_or_default(port, &self.namespaces.cluster_info())
+            .watch
+            .subscribe())
+    }
+
+    /// Resolves the policy configured by a `Server` resource, independently of
+    /// the workloads that it selects.
+    ///
+    /// Returns `None` if the server is not found.
+    pub fn server(&self, namespace: &str, name: &str) -> Option<InboundServer> {
+        let authns = self.authentications.read();
+        let ns = self.namespaces.get(namespace)?;
+        let ns = ns.lock();
+        let server = ns.policy.servers.get(name)?;
+        Some(
+            ns.policy
+                .inbound_server(name.to_string(), server, &authns, std::iter::empty()),
+        )
+    }
 }
 
 // === impl Namespace ===
@@ -940,15 +1459,19 @@ impl Namespace {
             pods: PodIndex {
                 namespace: namespace.clone(),
                 by_name: HashMap::default(),
+                labels: Default::default(),
             },
             external_workloads: ExternalWorkloadIndex {
                 namespace: namespace.clone(),
                 by_name: HashMap::default(),
+                labels: Default::default(),
+                by_identity: HashMap::default(),
             },
             policy: PolicyIndex {
                 namespace,
                 cluster_info,
                 servers: HashMap::default(),
+                server_labels: Default::default(),
                 server_authorizations: HashMap::default(),
                 authorization_policies: HashMap::default(),
                 http_routes: HashMap::default(),
@@ -963,9 +1486,11 @@ impl Namespace {
     }
 
     #[inline]
-    fn reindex(&mut self, authns: &AuthenticationNsIndex) {
-        self.pods.reindex(&self.policy, authns);
-        self.external_workloads.reindex(&self.policy, authns);
+    fn reindex(&mut self, authns: &AuthenticationNsIndex, scope: Scope<'_>) {
+        let mut cache = ServerCache::default();
+        self.pods.reindex(&self.policy, authns, scope, &mut cache);
+        self.external_workloads
+            .reindex(&self.policy, authns, scope, &mut cache);
     }
 }
 
@@ -983,14 +1508,19 @@ impl PodIndex {
         meta: workload::Meta,
         port_names: HashMap<String, PortSet>,
         probes: PortMap<BTreeSet<String>>,
-    ) -> Result<Option<&mut Pod>> {
-        let pod = match self.by_name.entry(name.clone()) {
-            Entry::Vacant(entry) => entry.insert(Pod {
-                meta,
-                port_names,
-                port_servers: PortMap::default(),
-                probes,
-            }),
+        deletion_deadline: Option<SystemTime>,
+    ) -> Result<bool> {
+        match self.by_name.entry(name.clone()) {
+            Entry::Vacant(entry) => {
+                self.labels.insert(&name, &meta.labels);
+                entry.insert(Pod {
+                    meta,
+                    port_names,
+                    port_servers: PortMap::default(),
+                    probes,
+                    deletion_deadline,
+                });
+            }
 
             Entry::Occupied(entry) => {
                 let pod = entry.into_mut();
@@ -1001,25 +1531,83 @@ impl PodIndex {
                     bail!("pod {} port names must not change", name);
                 }
 
+                // The deletion deadline does not affect the pod's policy, so
+                // it does not require the pod to be reindexed.
+                pod.deletion_deadline = deletion_deadline;
+
                 // If there aren't meaningful changes, then don't bother doing
                 // any more work.
                 if pod.meta == meta {
                     tracing::debug!(pod = %name, "No changes");
-                    return Ok(None);
+                    return Ok(false);
                 }
                 tracing::debug!(pod = %name, "Updating");
+                if pod.meta.labels != meta.labels {
+                    self.labels.remove(&name, &pod.meta.labels);
+                    self.labels.insert(&name, &meta.labels);
+                }
                 pod.meta = meta;
-                pod
             }
-        };
-        Ok(Some(pod))
+        }
+        Ok(true)
     }
 
-    fn reindex(&mut self, policy: &PolicyIndex, authns: &AuthenticationNsIndex) {
+    /// Removes a pod from the index, returning true if it was present.
+    fn remove(&mut self, name: &str) -> bool {
+        match self.by_name.remove(name) {
+            Some(pod) => {
+                self.labels.remove(name, &pod.meta.labels);
+                self.labels
+                    .bind(name, pod.bound_servers(), Default::default());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reindexes the pods that may be affected by changes in the given scope.
+    fn reindex<'p>(
+        &mut self,
+        policy: &'p PolicyIndex,
+        authns: &AuthenticationNsIndex,
+        scope: Scope<'_>,
+        cache: &mut ServerCache<'p>,
+    ) {
         let _span = info_span!("reindex", ns = %self.namespace).entered();
-        for (name, pod) in self.by_name.iter_mut() {
+        let names = match scope {
+            Scope::All => None,
+            Scope::Bound => Some(self.labels.bound()),
+            Scope::Server(name) => {
+                let selector = policy
+                    .servers
+                    .get(name)
+                    .and_then(|srv| match &srv.selector {
+                        Selector::Pod(selector) => Some(selector),
+                        Selector::ExternalWorkload(_) => None,
+                    });
+                self.labels.candidates(name, selector)
+            }
+        };
+        let names = names.unwrap_or_else(|| self.by_name.keys().cloned().collect());
+        for name in names {
+            self.reindex_pod(&name, policy, authns, cache);
+        }
+    }
+
+    /// Determines the policies for ports on the named pod, updating the
+    /// servers to which the pod is bound.
+    fn reindex_pod<'p>(
+        &mut self,
+        name: &str,
+        policy: &'p PolicyIndex,
+        authns: &AuthenticationNsIndex,
+        cache: &mut ServerCache<'p>,
+    ) {
+        if let Some(pod) = self.by_name.get_mut(name) {
             let _span = info_span!("pod", pod = %name).entered();
-            pod.reindex_servers(policy, authns);
+            let prior = pod.bound_servers();
+            pod.reindex_servers(policy, authns, cache);
+            self.labels.bind(name, prior, pod.bound_servers());
         }
     }
 }
@@ -1027,8 +1615,29 @@ impl PodIndex {
 // === impl Pod ===
 
 impl Pod {
+    /// Returns true if the pod's deletion deadline has passed.
+    fn is_deleted(&self, now: SystemTime) -> bool {
+        self.deletion_deadline
+            .map_or(false, |deadline| deadline <= now)
+    }
+
+    /// Returns the names of the servers to which the pod's ports are bound.
+    fn bound_servers(&self) -> HashSet<String> {
+        bound_servers(&self.port_servers)
+    }
+
+    /// Returns the paths on which a port is probed.
+    fn probe_paths(&self, port: NonZeroU16) -> impl Iterator<Item = &str> {
+        pod_probe_paths(&self.probes, &self.meta.settings, port)
+    }
+
     /// Determines the policies for ports on this pod.
-    fn reindex_servers(&mut self, policy: &PolicyIndex, authentications: &AuthenticationNsIndex) {
+    fn reindex_servers<'p>(
+        &mut self,
+        policy: &'p PolicyIndex,
+        authentications: &AuthenticationNsIndex,
+        cache: &mut ServerCache<'p>,
+    ) {
         // Keep track of the ports that are already known in the pod so that, after applying server
         // matches, we can ensure remaining ports are set to the default policy.
         let mut unmatched_ports = self.port_servers.keys().copied().collect::<PortSet>();
@@ -1043,7 +1652,7 @@ impl Pod {
             std::hash::BuildHasherDefault::<PortHasher>::default(),
         );
 
-        for (srvname, server) in policy.servers.iter() {
+        for (srvname, server) in policy.selecting_servers(&self.meta.labels) {
             if let Selector::Pod(pod_selector) = &server.selector {
                 if pod_selector.matches(&self.meta.labels) {
                     for port in self.select_ports(&server.port_ref).into_iter() {
@@ -1059,15 +1668,34 @@ impl Pod {
                             continue;
                         }
 
-                        let s = policy.inbound_server(
-                            srvname.clone(),
+                        let mut s = policy.cached_inbound_server(
+                            cache,
+                            srvname,
                             server,
                             authentications,
-                            self.probes
-                                .get(&port)
-                                .into_iter()
-                                .flatten()
-                                .map(|p| p.as_str()),
+                            self.probe_paths(port),
+                        );
+                        if let Some(gateway) = self.meta.labels.as_ref().get(GATEWAY_NAME_LABEL) {
+                            let port_names = self
+                                .port_names
+                                .iter()
+                                .filter(|(_, ports)| ports.contains(&port))
+                                .map(|(name, _)| name.as_str())
+                                .collect::<Vec<_>>();
+                            policy.add_gateway_routes(
+                                &mut s,
+                                gateway,
+                                port,
+                                &port_names,
+                                authentications,
+                            );
+                        }
+                        add_gateway_probe_route(&mut s.http_routes, &self.meta.settings, port);
+                        add_proxy_admin_probe_route(
+                            &mut s.http_routes,
+                            &self.meta.settings,
+                            port,
+                            &policy.cluster_info,
                         );
                         self.update_server(port, srvname, s);
 
@@ -1134,11 +1762,7 @@ impl Pod {
         let server = PolicyIndex::default_inbound_server(
             port,
             &self.meta.settings,
-            self.probes
-                .get(&port)
-                .into_iter()
-                .flatten()
-                .map(|p| p.as_str()),
+            self.probe_paths(port),
             config,
         );
         match self.port_servers.entry(port) {
@@ -1193,17 +1817,27 @@ impl Pod {
                 let (watch, _) = watch::channel(PolicyIndex::default_inbound_server(
                     port,
                     &self.meta.settings,
-                    self.probes
-                        .get(&port)
-                        .into_iter()
-                        .flatten()
-                        .map(|p| p.as_str()),
+                    pod_probe_paths(&self.probes, &self.meta.settings, port),
                     config,
                 ));
                 entry.insert(WorkloadPortServer { name: None, watch })
             }
         }
     }
+
+    /// Returns the port's current server, or the default server if the port
+    /// is not indexed, without indexing it.
+    fn port_server(&self, port: NonZeroU16, config: &ClusterInfo) -> InboundServer {
+        match self.port_servers.get(&port) {
+            Some(server) => server.watch.borrow().clone(),
+            None => PolicyIndex::default_inbound_server(
+                port,
+                &self.meta.settings,
+                pod_probe_paths(&self.probes, &self.meta.settings, port),
+                config,
+            ),
+        }
+    }
 }
 
 // === impl ExternalWorkloadIndex ===
@@ -1225,65 +1859,155 @@ impl ExternalWorkloadIndex {
     fn update(
         &mut self,
         name: String,
+        identity: String,
         meta: workload::Meta,
         port_names: HashMap<String, NonZeroU16>,
-    ) -> Result<Option<&mut ExternalWorkload>> {
-        let workload = match self.by_name.entry(name.clone()) {
-            Entry::Vacant(entry) => entry.insert(ExternalWorkload {
-                meta,
-                port_names,
-                port_servers: PortMap::default(),
-            }),
+        ports: PortSet,
+    ) -> Result<bool> {
+        match self.by_name.entry(name.clone()) {
+            Entry::Vacant(entry) => {
+                self.labels.insert(&name, &meta.labels);
+                self.by_identity.insert(identity.clone(), name.clone());
+                entry.insert(ExternalWorkload {
+                    meta,
+                    identity,
+                    port_names,
+                    ports,
+                    port_servers: PortMap::default(),
+                });
+            }
             Entry::Occupied(entry) => {
                 let workload = entry.into_mut();
 
-                if workload.meta == meta && workload.port_names == port_names {
+                if workload.meta == meta
+                    && workload.identity == identity
+                    && workload.port_names == port_names
+                    && workload.ports == ports
+                {
                     tracing::debug!(external_workload = %name, "No changes");
-                    return Ok(None);
+                    return Ok(false);
+                }
+
+                if workload.identity != identity {
+                    tracing::trace!(external_workload = %name, "Updating workload's identity");
+                    if self.by_identity.get(&workload.identity) == Some(&name) {
+                        self.by_identity.remove(&workload.identity);
+                    }
+                    self.by_identity.insert(identity.clone(), name.clone());
+                    workload.identity = identity;
                 }
 
                 if workload.meta != meta {
                     tracing::trace!(external_workload = %name, "Updating workload's metadata");
+                    if workload.meta.labels != meta.labels {
+                        self.labels.remove(&name, &workload.meta.labels);
+                        self.labels.insert(&name, &meta.labels);
+                    }
                     workload.meta = meta;
                 }
 
-                if workload.port_names != port_names {
+                if workload.port_names != port_names || workload.ports != ports {
                     tracing::trace!(external_workload = %name, "Updating workload's ports");
                     workload.port_names = port_names;
+                    workload.ports = ports;
                 }
 
                 tracing::debug!(external_workload = %name, "Updating");
-                workload
             }
-        };
-        Ok(Some(workload))
+        }
+        Ok(true)
+    }
+
+    /// Removes an external workload from the index, returning true if it was
+    /// present.
+    fn remove(&mut self, name: &str) -> bool {
+        match self.by_name.remove(name) {
+            Some(workload) => {
+                if self.by_identity.get(&workload.identity).map(String::as_str) == Some(name) {
+                    self.by_identity.remove(&workload.identity);
+                }
+                self.labels.remove(name, &workload.meta.labels);
+                self.labels
+                    .bind(name, workload.bound_servers(), Default::default());
+                true
+            }
+            None => false,
+        }
     }
 
     /// For each external workload in a namespace, re-compute the server and
     /// authorization policy states to determine if new changes need to be
     /// pushed to clients.
-    fn reindex(&mut self, policy: &PolicyIndex, authns: &AuthenticationNsIndex) {
+    fn reindex<'p>(
+        &mut self,
+        policy: &'p PolicyIndex,
+        authns: &AuthenticationNsIndex,
+        scope: Scope<'_>,
+        cache: &mut ServerCache<'p>,
+    ) {
         let _span = info_span!("reindex", ns = %self.namespace).entered();
-        for (name, ext_workload) in self.by_name.iter_mut() {
+        let names = match scope {
+            Scope::All => None,
+            Scope::Bound => Some(self.labels.bound()),
+            Scope::Server(name) => {
+                let selector = policy
+                    .servers
+                    .get(name)
+                    .and_then(|srv| match &srv.selector {
+                        Selector::ExternalWorkload(selector) => Some(selector),
+                        Selector::Pod(_) => None,
+                    });
+                self.labels.candidates(name, selector)
+            }
+        };
+        let names = names.unwrap_or_else(|| self.by_name.keys().cloned().collect());
+        for name in names {
+            self.reindex_workload(&name, policy, authns, cache);
+        }
+    }
+
+    /// Determines the policies for ports on the named workload, updating the
+    /// servers to which the workload is bound.
+    fn reindex_workload<'p>(
+        &mut self,
+        name: &str,
+        policy: &'p PolicyIndex,
+        authns: &AuthenticationNsIndex,
+        cache: &mut ServerCache<'p>,
+    ) {
+        if let Some(workload) = self.by_name.get_mut(name) {
             let _span = info_span!("external_workload", external_workload = %name).entered();
-            ext_workload.reindex_servers(policy, authns);
+            let prior = workload.bound_servers();
+            workload.reindex_servers(policy, authns, cache);
+            self.labels.bind(name, prior, workload.bound_servers());
         }
     }
 }
 
 impl ExternalWorkload {
+    /// Returns the names of the servers to which the workload's ports are
+    /// bound.
+    fn bound_servers(&self) -> HashSet<String> {
+        bound_servers(&self.port_servers)
+    }
+
     /// Determines the policies for ports on this workload
-    fn reindex_servers(&mut self, policy: &PolicyIndex, authentications: &AuthenticationNsIndex) {
-        // Keep track of ports that are already known so that they may receive
-        // default policies if they are still not selected by a server.
-        //
-        // TODO (matei): we could eagerly fill this out if we require users to
-        // always document a workload's ports. i.e. right now a non-named port
-        // will be lazily discovered only when traffic is sent, we could eagerly
-        // set policies for it (but I feel like for now, this should be similar in
-        // behaviour to a pod)
-        //
-        let mut unmatched_ports = self.port_servers.keys().copied().collect::<PortSet>();
+    fn reindex_servers<'p>(
+        &mut self,
+        policy: &'p PolicyIndex,
+        authentications: &AuthenticationNsIndex,
+        cache: &mut ServerCache<'p>,
+    ) {
+        // Keep track of ports that are already known, or that are declared in
+        // the workload's spec, so that they may receive default policies if
+        // they are still not selected by a server. Undeclared ports are
+        // discovered lazily, as they are for pods.
+        let mut unmatched_ports = self
+            .port_servers
+            .keys()
+            .chain(self.ports.iter())
+            .copied()
+            .collect::<PortSet>();
 
         // Keep track of which ports have been matched with servers so that we
         // can detect when more than one server matches a single port.
@@ -1292,7 +2016,7 @@ impl ExternalWorkload {
             std::hash::BuildHasherDefault::<PortHasher>::default(),
         );
 
-        for (srvname, server) in policy.servers.iter() {
+        for (srvname, server) in policy.selecting_servers(&self.meta.labels) {
             if let Selector::ExternalWorkload(selector) = &server.selector {
                 if selector.matches(&self.meta.labels) {
                     // Each server selects exactly one port on an
@@ -1319,11 +2043,12 @@ impl ExternalWorkload {
                         continue;
                     }
 
-                    let s = policy.inbound_server(
-                        srvname.clone(),
+                    let s = policy.cached_inbound_server(
+                        cache,
+                        srvname,
                         server,
                         authentications,
-                        Vec::new().into_iter(),
+                        self.meta.settings.probe_paths(port),
                     );
 
                     self.update_server(port, srvname, s);
@@ -1387,11 +2112,12 @@ impl ExternalWorkload {
 
     /// Updates a workload-port to use a given named server.
     fn set_default_server(&mut self, port: NonZeroU16, config: &ClusterInfo) {
-        // Create a default server policy, without authorising any probe paths
+        // Create a default server policy, authorizing only the probe paths
+        // listed in the workload's annotations.
         let server = PolicyIndex::default_inbound_server(
             port,
             &self.meta.settings,
-            Vec::new().into_iter(),
+            self.meta.settings.probe_paths(port),
             config,
         );
         match self.port_servers.entry(port) {
@@ -1445,13 +2171,27 @@ impl ExternalWorkload {
                 let (watch, _) = watch::channel(PolicyIndex::default_inbound_server(
                     port,
                     &self.meta.settings,
-                    Vec::new().into_iter(),
+                    self.meta.settings.probe_paths(port),
                     config,
                 ));
                 entry.insert(WorkloadPortServer { name: None, watch })
             }
         }
     }
+
+    /// Returns the port's current server, or the default server if the port
+    /// is not indexed, without indexing it.
+    fn port_server(&self, port: NonZeroU16, config: &ClusterInfo) -> InboundServer {
+        match self.port_servers.get(&port) {
+            Some(server) => server.watch.borrow().clone(),
+            None => PolicyIndex::default_inbound_server(
+                port,
+                &self.meta.settings,
+                self.meta.settings.probe_paths(port),
+                config,
+            ),
+        }
+    }
 }
 
 // === impl PolicyIndex ===
@@ -1468,6 +2208,7 @@ impl PolicyIndex {
     fn update_server(&mut self, name: String, server: server::Server) -> bool {
         match self.servers.entry(name.clone()) {
             Entry::Vacant(entry) => {
+                self.server_labels.insert(&name, server_selector(&server));
                 entry.insert(server);
             }
             Entry::Occupied(entry) => {
@@ -1477,12 +2218,42 @@ impl PolicyIndex {
                     return false;
                 }
                 tracing::debug!(server = %name, "updating");
+                self.server_labels.remove(&name, server_selector(srv));
+                self.server_labels.insert(&name, server_selector(&server));
                 *srv = server;
             }
         }
         true
     }
 
+    /// Removes a server from the index, returning true if it was present.
+    fn remove_server(&mut self, name: &str) -> bool {
+        match self.servers.remove(name) {
+            Some(server) => {
+                self.server_labels.remove(name, server_selector(&server));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn clear_servers(&mut self) {
+        self.servers.clear();
+        self.server_labels.clear();
+    }
+
+    /// Returns the servers that may select a workload with the given labels,
+    /// ordered by name.
+    fn selecting_servers<'p>(
+        &'p self,
+        labels: &k8s::Labels,
+    ) -> impl Iterator<Item = (&'p String, &'p server::Server)> + 'p {
+        self.server_labels
+            .candidates(labels)
+            .into_iter()
+            .filter_map(|name| self.servers.get_key_value(name))
+    }
+
     fn update_server_authz(
         &mut self,
         name: String,
@@ -1533,8 +2304,10 @@ impl PolicyIndex {
             }
         };
 
+        // Only meshed clients may use the proxy's control (tap) server, so it
+        // requires identity by default.
         let mut policy = settings.default_policy.unwrap_or(config.default_policy);
-        if settings.require_id_ports.contains(&port) {
+        if settings.require_id_ports.contains(&port) || settings.proxy_ports.control == Some(port) {
             if let DefaultPolicy::Allow {
                 ref mut authenticated_only,
                 ..
@@ -1546,7 +2319,9 @@ impl PolicyIndex {
 
         let authorizations = policy.default_authzs(config);
 
-        let http_routes = config.default_inbound_http_routes(probe_paths);
+        let mut http_routes = config.default_inbound_http_routes(probe_paths);
+        add_gateway_probe_route(&mut http_routes, settings, port);
+        add_proxy_admin_probe_route(&mut http_routes, settings, port, config);
 
         InboundServer {
             reference: ServerRef::Default(policy.as_str()),
@@ -1563,14 +2338,42 @@ impl PolicyIndex {
         authentications: &AuthenticationNsIndex,
         probe_paths: impl Iterator<Item = &'p str>,
     ) -> InboundServer {
-        tracing::trace!(%name, ?server, "Creating inbound server");
-        let authorizations = self.client_authzs(&name, server, authentications);
-        let http_routes = self.http_routes(&name, authentications, probe_paths);
+        self.cached_inbound_server(
+            &mut ServerCache::default(),
+            &name,
+            server,
+            authentications,
+            probe_paths,
+        )
+    }
+
+    /// Resolves the policy for a server, reusing the server's authorizations
+    /// and routes if they have already been resolved during this reindex.
+    fn cached_inbound_server<'p, 'a>(
+        &'p self,
+        cache: &mut ServerCache<'p>,
+        name: &'p str,
+        server: &server::Server,
+        authentications: &AuthenticationNsIndex,
+        probe_paths: impl Iterator<Item = &'a str>,
+    ) -> InboundServer {
+        let (authorizations, routes) = cache.entry(name).or_insert_with(|| {
+            tracing::trace!(%name, ?server, "Creating inbound server");
+            (
+                self.client_authzs(name, server, authentications),
+                self.http_routes(name, authentications),
+            )
+        });
+        let http_routes = if routes.is_empty() {
+            self.cluster_info.default_inbound_http_routes(probe_paths)
+        } else {
+            routes.clone()
+        };
 
         InboundServer {
-            reference: ServerRef::Server(name),
-            authorizations,
-            protocol: server.protocol.clone(),
+            reference: ServerRef::Server(name.to_string()),
+            authorizations: authorizations.clone(),
+            protocol: server.protocol(&self.cluster_info),
             http_routes,
         }
     }
@@ -1586,33 +2389,30 @@ impl PolicyIndex {
             if saz.server_selector.selects(server_name, &server.labels) {
                 authzs.insert(
                     AuthorizationRef::ServerAuthorization(name.to_string()),
-                    saz.authz.clone(),
+                    saz.client_authz(&self.cluster_info),
                 );
             }
         }
 
         for (name, spec) in self.authorization_policies.iter() {
-            // Skip the policy if it doesn't apply to the server.
-            match &spec.target {
-                authorization_policy::Target::Server(name) => {
-                    if name != server_name {
-                        tracing::trace!(
-                            ns = %self.namespace,
-                            authorizationpolicy = %name,
-                            server = %server_name,
-                            target = %name,
-                            "AuthorizationPolicy does not target server",
-                        );
-                        continue;
-                    }
-                }
-                authorization_policy::Target::Namespace => {}
-                authorization_policy::Target::HttpRoute(_) => {
-                    // Policies which target HttpRoutes will be attached to
-                    // the route authorizations and should not be included in
-                    // the server authorizations.
-                    continue;
-                }
+            // Skip the policy if it doesn't apply to the server. Policies
+            // which target HttpRoutes are attached to the route
+            // authorizations and are not included in the server
+            // authorizations.
+            let targets_server = spec.targets.iter().any(|target| match target {
+                authorization_policy::Target::Server(target) => target == server_name,
+                authorization_policy::Target::Namespace => true,
+                authorization_policy::Target::HttpRoute(_) => false,
+            });
+            if !targets_server {
+                tracing::trace!(
+                    ns = %self.namespace,
+                    authorizationpolicy = %name,
+                    server = %server_name,
+                    targets = ?spec.targets,
+                    "AuthorizationPolicy does not target server",
+                );
+                continue;
             }
 
             tracing::trace!(
@@ -1640,6 +2440,26 @@ impl PolicyIndex {
             authzs.insert(reference, authz);
         }
 
+        if let Some(limit) = self.cluster_info.max_authorizations_per_server {
+            if authzs.len() > limit {
+                // Order the authorizations so that the same authorizations
+                // are ignored each time the server is indexed.
+                let mut refs = authzs.keys().cloned().collect::<Vec<_>>();
+                refs.sort_by(|a, b| authz_order(a).cmp(&authz_order(b)));
+                let excess = refs.split_off(limit);
+                tracing::warn!(
+                    ns = %self.namespace,
+                    server = %server_name,
+                    %limit,
+                    ignored = excess.len(),
+                    "Server has too many authorizations; ignoring excess",
+                );
+                for reference in excess {
+                    authzs.remove(&reference);
+                }
+            }
+        }
+
         authzs
     }
 
@@ -1652,18 +2472,18 @@ impl PolicyIndex {
 
         for (name, spec) in &self.authorization_policies {
             // Skip the policy if it doesn't apply to the route.
-            match &spec.target {
-                authorization_policy::Target::HttpRoute(n) if n.eq_ignore_ascii_case(gkn) => {}
-                _ => {
-                    tracing::trace!(
-                        ns = %self.namespace,
-                        authorizationpolicy = %name,
-                        route = ?gkn,
-                        target = ?spec.target,
-                        "AuthorizationPolicy does not target HttpRoute",
-                    );
-                    continue;
-                }
+            let targets_route = spec.targets.iter().any(|target| {
+                matches!(target, authorization_policy::Target::HttpRoute(n) if n.eq_ignore_ascii_case(gkn))
+            });
+            if !targets_route {
+                tracing::trace!(
+                    ns = %self.namespace,
+                    authorizationpolicy = %name,
+                    route = ?gkn,
+                    targets = ?spec.targets,
+                    "AuthorizationPolicy does not target HttpRoute",
+                );
+                continue;
             }
 
             tracing::trace!(
@@ -1694,27 +2514,87 @@ impl PolicyIndex {
         authzs
     }
 
-    fn http_routes<'p>(
+    /// Returns the routes that are attached to a server by route resources.
+    ///
+    /// Routes in excess of the configured limits are not accepted by the
+    /// status controller, so they are ignored here rather than waiting for
+    /// their statuses to be updated. Routes are ranked as they are by the
+    /// status controller: by their creation time and then by their name.
+    fn http_routes(
         &self,
         server_name: &str,
         authentications: &AuthenticationNsIndex,
-        probe_paths: impl Iterator<Item = &'p str>,
     ) -> HashMap<HttpRouteRef, HttpRoute> {
-        let routes = self
+        let mut routes = self
             .http_routes
             .iter()
             .filter(|(_, route)| route.selects_server(server_name))
-            .filter(|(_, route)| route.accepted_by_server(server_name))
+            .collect::<Vec<_>>();
+
+        if let Some(limit) = self.cluster_info.max_routes_per_parent {
+            if routes.len() > limit {
+                routes.sort_by(|(a_gkn, a), (b_gkn, b)| {
+                    (a.route.creation_timestamp, *a_gkn).cmp(&(b.route.creation_timestamp, *b_gkn))
+                });
+                let excess = routes.split_off(limit);
+                tracing::warn!(
+                    ns = %self.namespace,
+                    server = %server_name,
+                    %limit,
+                    ignored = excess.len(),
+                    "Server has too many routes; ignoring excess",
+                );
+            }
+        }
+
+        let max_backends = self.cluster_info.max_backends_per_route;
+        routes.retain(|(_, route)| {
+            route.accepted_by_server(server_name)
+                && max_backends.map_or(true, |limit| route.backends <= limit)
+        });
+        self.bind_routes(routes, authentications)
+    }
+
+    /// Adds the routes attached to a Gateway's listener on the given port to
+    /// the server of one of the Gateway's ingress pods. The Gateway's routes
+    /// replace the server's default routes.
+    fn add_gateway_routes(
+        &self,
+        server: &mut InboundServer,
+        gateway: &str,
+        port: NonZeroU16,
+        port_names: &[&str],
+        authentications: &AuthenticationNsIndex,
+    ) {
+        let routes = self.bind_routes(
+            self.http_routes
+                .iter()
+                .filter(|(_, route)| route.attaches_to_gateway(gateway, port, port_names)),
+            authentications,
+        );
+        if routes.is_empty() {
+            return;
+        }
+        server
+            .http_routes
+            .retain(|r, _| !matches!(r, HttpRouteRef::Default(_)));
+        server.http_routes.extend(routes);
+    }
+
+    /// Returns the given routes with their authorizations.
+    fn bind_routes<'r>(
+        &self,
+        routes: impl IntoIterator<Item = (&'r GroupKindName, &'r RouteBinding)>,
+        authentications: &AuthenticationNsIndex,
+    ) -> HashMap<HttpRouteRef, HttpRoute> {
+        routes
+            .into_iter()
             .map(|(gkn, route)| {
                 let mut route = route.route.clone();
                 route.authorizations = self.route_client_authzs(gkn, authentications);
                 (HttpRouteRef::Linkerd(gkn.clone()), route)
             })
-            .collect::<HashMap<_, _>>();
-        if !routes.is_empty() {
-            return routes;
-        }
-        self.cluster_info.default_inbound_http_routes(probe_paths)
+            .collect()
     }
 
     fn policy_client_authz(
@@ -1745,11 +2625,11 @@ impl PolicyIndex {
                                 namespace
                             )
                         })?;
-                    tracing::trace!(ids = ?authn.matches, "Found MeshTLSAuthentication");
+                    let ids = authn.matches(&self.cluster_info);
+                    tracing::trace!(?ids, "Found MeshTLSAuthentication");
                     if identities.is_some() {
                         bail!("policy must not include multiple MeshTLSAuthentications");
                     }
-                    let ids = authn.matches.clone();
                     identities = Some(ids);
                 }
                 AuthenticationTarget::ServiceAccount {
@@ -1918,10 +2798,20 @@ impl ClusterInfo {
         // the server.
         routes.insert(HttpRouteRef::Default("default"), HttpRoute::default());
 
+        if let Some(probe_route) = self.probe_route(probe_paths) {
+            routes.insert(HttpRouteRef::Default("probe"), probe_route);
+        }
+
+        routes
+    }
+
+    /// Builds a route that authorizes probes of the given paths from the
+    /// configured probe networks.
+    fn probe_route<'p>(&self, probe_paths: impl Iterator<Item = &'p str>) -> Option<HttpRoute> {
         // If there are no probe networks, there are no probe routes to
         // authorize.
         if self.probe_networks.is_empty() {
-            return routes;
+            return None;
         }
 
         // Generate an `Exact` path match for each probe path defined on the
@@ -1937,7 +2827,7 @@ impl ClusterInfo {
 
         // If there are no matches, then are no probe routes to authorize.
         if matches.is_empty() {
-            return routes;
+            return None;
         }
 
         // Probes are authorized on the configured probe networks only.
@@ -1955,17 +2845,119 @@ impl ClusterInfo {
         ))
         .collect();
 
-        let probe_route = HttpRoute {
+        Some(HttpRoute {
             hostnames: Vec::new(),
             rules: vec![HttpRouteRule {
                 matches,
                 filters: Vec::new(),
-            }],
+            }]
+            .into(),
             authorizations,
             creation_timestamp: None,
-        };
-        routes.insert(HttpRouteRef::Default("probe"), probe_route);
+        })
+    }
+}
 
-        routes
+/// Returns the paths on which a pod's port is probed: those declared by the
+/// pod's containers followed by any additional paths from its annotations.
+fn pod_probe_paths<'p>(
+    probes: &'p PortMap<BTreeSet<String>>,
+    settings: &'p workload::Settings,
+    port: NonZeroU16,
+) -> impl Iterator<Item = &'p str> {
+    let declared = probes.get(&port);
+    declared.into_iter().flatten().map(String::as_str).chain(
+        settings
+            .probe_paths(port)
+            .filter(move |path| !declared.map_or(false, |d| d.contains(*path))),
+    )
+}
+
+/// Authorizes the probes that linked clusters send to a multicluster gateway
+/// on the given port, regardless of the server's other authorizations.
+///
+/// Probes traverse the gateway's public address, so their source networks are
+/// not known.
+fn add_gateway_probe_route(
+    routes: &mut HashMap<HttpRouteRef, HttpRoute>,
+    settings: &workload::Settings,
+    port: NonZeroU16,
+) {
+    let path = match &settings.gateway_probe {
+        Some((probe_port, path)) if *probe_port == port => path,
+        _ => return,
+    };
+
+    let authorizations = std::iter::once((
+        AuthorizationRef::Default("gateway-probe"),
+        ClientAuthorization {
+            networks: vec![
+                "0.0.0.0/0".parse::<IpNet>().unwrap().into(),
+                "::/0".parse::<IpNet>().unwrap().into(),
+            ],
+            authentication: ClientAuthentication::Unauthenticated,
+        },
+    ))
+    .collect();
+
+    let probe_route = HttpRoute {
+        hostnames: Vec::new(),
+        rules: vec![HttpRouteRule {
+            matches: vec![HttpRouteMatch {
+                path: Some(PathMatch::Exact(path.clone())),
+                headers: vec![],
+                query_params: vec![],
+                method: Some(Method::GET),
+            }],
+            filters: Vec::new(),
+        }]
+        .into(),
+        authorizations,
+        creation_timestamp: None,
+    };
+    routes.insert(HttpRouteRef::Default("gateway-probe"), probe_route);
+}
+
+/// Authorizes the proxy's own probe endpoints on its admin port, so that
+/// kubelet probes of the proxy continue to succeed when a `Server` with routes
+/// restricts access to the admin server.
+fn add_proxy_admin_probe_route(
+    routes: &mut HashMap<HttpRouteRef, HttpRoute>,
+    settings: &workload::Settings,
+    port: NonZeroU16,
+    config: &ClusterInfo,
+) {
+    if settings.proxy_ports.admin != Some(port) {
+        return;
+    }
+
+    // The default routes already authorize the probe paths declared on the
+    // port, but a server's own routes replace them.
+    if let Entry::Vacant(entry) = routes.entry(HttpRouteRef::Default("probe")) {
+        if let Some(route) = config.probe_route(workload::PROXY_ADMIN_PROBE_PATHS.into_iter()) {
+            entry.insert(route);
+        }
+    }
+}
+
+/// Returns the names of the servers to which a workload's ports are bound.
+fn bound_servers(port_servers: &PortMap<WorkloadPortServer>) -> HashSet<String> {
+    port_servers
+        .values()
+        .filter_map(|ps| ps.name.clone())
+        .collect()
+}
+
+fn authz_order(reference: &AuthorizationRef) -> (u8, &str) {
+    match reference {
+        AuthorizationRef::Default(name) => (0, name),
+        AuthorizationRef::ServerAuthorization(name) => (1, name),
+        AuthorizationRef::AuthorizationPolicy(name) => (2, name),
+    }
+}
+
+fn server_selector(server: &server::Server) -> &k8s::labels::Selector {
+    match &server.selector {
+        Selector::Pod(selector) | Selector::ExternalWorkload(selector) => selector,
     }
 }
@@ -0,0 +1,117 @@
+use super::{super::http_route::ParentRef, Lookup, PortMap, WorkloadPortServer};
+use linkerd_policy_controller_core::{
+    inbound::{HttpRoute, InboundServer},
+    routes::GroupKindName,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, num::NonZeroU16};
+
+/// The contents of a single namespace's inbound index.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Namespace {
+    /// The policy configured by each `Server`, independently of the workloads
+    /// that it selects.
+    pub servers: BTreeMap<String, InboundServer>,
+
+    pub http_routes: BTreeMap<GroupKindName, Route>,
+
+    /// The server bound to each known port of each pod. Ports that use the
+    /// default policy are bound to no server.
+    pub pods: BTreeMap<String, BTreeMap<NonZeroU16, Option<String>>>,
+
+    /// The server bound to each known port of each external workload.
+    pub external_workloads: BTreeMap<String, BTreeMap<NonZeroU16, Option<String>>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Route {
+    /// The servers to which the route is attached.
+    pub parents: Vec<String>,
+    #[serde(flatten)]
+    pub route: HttpRoute,
+}
+
+/// Returns the contents of every namespace or, if one is given, of a single
+/// namespace.
+///
+/// Namespaces are locked one at a time, so the result is not a consistent
+/// snapshot of the whole index if it is updated concurrently.
+pub fn dump(lookup: &Lookup, namespace: Option<&str>) -> BTreeMap<String, Namespace> {
+    let namespaces = match namespace {
+        Some(ns) => lookup
+            .namespaces
+            .get(ns)
+            .map(|index| vec![(ns.to_string(), index)])
+            .unwrap_or_default(),
+        None => lookup.namespaces.all_named(),
+    };
+
+    let authns = lookup.authentications.read();
+    namespaces
+        .into_iter()
+        .map(|(name, index)| {
+            let index = index.lock();
+            let servers = index
+                .policy
+                .servers
+                .iter()
+                .map(|(name, server)| {
+                    let server = index.policy.inbound_server(
+                        name.clone(),
+                        server,
+                        &authns,
+                        std::iter::empty(),
+                    );
+                    (name.clone(), server)
+                })
+                .collect();
+            let http_routes = index
+                .policy
+                .http_routes
+                .iter()
+                .map(|(gkn, binding)| {
+                    let parents = binding
+                        .parents
+                        .iter()
+                        .map(|parent| match parent {
+                            ParentRef::Server(name) => name.clone(),
+                            ParentRef::Gateway { name, .. } => format!("gateway/{name}"),
+                        })
+                        .collect();
+                    let route = Route {
+                        parents,
+                        route: binding.route.clone(),
+                    };
+                    (gkn.clone(), route)
+                })
+                .collect();
+            let pods = index
+                .pods
+                .by_name
+                .iter()
+                .map(|(name, pod)| (name.clone(), bindings(&pod.port_servers)))
+                .collect();
+            let external_workloads = index
+                .external_workloads
+                .by_name
+                .iter()
+                .map(|(name, workload)| (name.clone(), bindings(&workload.port_servers)))
+                .collect();
+            let ns = Namespace {
+                servers,
+                http_routes,
+                pods,
+                external_workloads,
+            };
+            (name, ns)
+        })
+        .collect()
+}
+
+fn bindings(ports: &PortMap<WorkloadPortServer>) -> BTreeMap<NonZeroU16, Option<String>> {
+    ports
+        .iter()
+        .map(|(port, server)| (*port, server.name.clone()))
+        .collect()
+}
@@ -2,16 +2,44 @@ use prometheus_client::{
     collector::Collector,
     encoding::{DescriptorEncoder, EncodeMetric},
     metrics::{gauge::ConstGauge, MetricType},
-    registry::Registry,
+    registry::{Registry, Unit},
 };
 
-use super::SharedIndex;
+use super::{Lookup, PortMap, SharedIndex, WorkloadPortServer};
+use linkerd_policy_controller_core::inbound::ServerRef;
+use std::{borrow::Cow, collections::BTreeMap};
 
 #[derive(Debug)]
-struct Instrumented(SharedIndex);
+struct Instrumented {
+    lookup: Lookup,
+    index: SharedIndex,
+}
+
+/// The sizes of a single namespace's indexes.
+struct NsSizes<'n> {
+    namespace: &'n str,
+    pods: usize,
+    external_workloads: usize,
+    servers: usize,
+    server_authorizations: usize,
+    authorization_policies: usize,
+    http_routes: usize,
+    routes_by_kind: BTreeMap<(Cow<'static, str>, Cow<'static, str>), usize>,
+    empty: bool,
+
+    /// The estimated bytes held by the servers published for the namespace's
+    /// workload ports.
+    published_bytes: usize,
+
+    /// The number of ports on each workload that are not selected by any
+    /// server, by the kind and name of the workload and the default policy
+    /// that applies.
+    default_ports: BTreeMap<(&'static str, String, &'static str), u32>,
+}
 
 pub fn register(reg: &mut Registry, index: SharedIndex) {
-    reg.register_collector(Box::new(Instrumented(index)));
+    let lookup = index.read().lookup();
+    reg.register_collector(Box::new(Instrumented { lookup, index }));
 }
 
 impl Collector for Instrumented {
@@ -19,7 +47,62 @@ impl Collector for Instrumented {
         &self,
         mut encoder: DescriptorEncoder<'_>,
     ) -> std::prelude::v1::Result<(), std::fmt::Error> {
-        let this = self.0.read();
+        // Namespaces are locked one at a time, so that encoding metrics does
+        // not block index updates.
+        let namespaces = self.lookup.namespaces.all_named();
+        let sizes = namespaces
+            .iter()
+            .map(|(namespace, index)| {
+                let index = index.lock();
+                let mut routes_by_kind = BTreeMap::new();
+                for gkn in index.policy.http_routes.keys() {
+                    *routes_by_kind
+                        .entry((gkn.group.clone(), gkn.kind.clone()))
+                        .or_default() += 1;
+                }
+                let published_bytes = index
+                    .pods
+                    .by_name
+                    .values()
+                    .map(|pod| &pod.port_servers)
+                    .chain(
+                        index
+                            .external_workloads
+                            .by_name
+                            .values()
+                            .map(|workload| &workload.port_servers),
+                    )
+                    .flat_map(|ports| ports.values())
+                    .map(|server| crate::size::estimate_bytes(&*server.watch.borrow()))
+                    .sum();
+                let mut default_ports = BTreeMap::new();
+                for (name, pod) in &index.pods.by_name {
+                    count_default_ports(&mut default_ports, "pod", name, &pod.port_servers);
+                }
+                for (name, workload) in &index.external_workloads.by_name {
+                    count_default_ports(
+                        &mut default_ports,
+                        "external_workload",
+                        name,
+                        &workload.port_servers,
+                    );
+                }
+                NsSizes {
+                    namespace,
+                    pods: index.pods.by_name.len(),
+                    external_workloads: index.external_workloads.by_name.len(),
+                    servers: index.policy.servers.len(),
+                    server_authorizations: index.policy.server_authorizations.len(),
+                    authorization_policies: index.policy.authorization_policies.len(),
+                    http_routes: index.policy.http_routes.len(),
+                    routes_by_kind,
+                    empty: index.is_empty(),
+                    published_bytes,
+                    default_ports,
+                }
+            })
+            .collect::<Vec<_>>();
+        let authentications = self.lookup.authentications.read();
 
         let mut meshtls_authn_encoder = encoder.encode_descriptor(
             "meshtls_authentication_index_size",
@@ -27,7 +110,7 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, auth) in &this.authentications.by_ns {
+        for (ns, auth) in &authentications.by_ns {
             let labels = [("namespace", ns.as_str())];
             let meshtls_authn = ConstGauge::new(auth.meshtls.len() as u32);
             let meshtls_authn_encoder = meshtls_authn_encoder.encode_family(&labels)?;
@@ -40,7 +123,7 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, auth) in &this.authentications.by_ns {
+        for (ns, auth) in &authentications.by_ns {
             let labels = [("namespace", ns.as_str())];
             let network_authn = ConstGauge::new(auth.network.len() as u32);
             let network_authn_encoder = network_authn_encoder.encode_family(&labels)?;
@@ -53,9 +136,9 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, index) in &this.namespaces.by_ns {
-            let labels = [("namespace", ns.as_str())];
-            let pods = ConstGauge::new(index.pods.by_name.len() as u32);
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let pods = ConstGauge::new(ns.pods as u32);
             let pods_encoder = pods_encoder.encode_family(&labels)?;
             pods.encode(pods_encoder)?;
         }
@@ -66,9 +149,9 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, index) in &this.namespaces.by_ns {
-            let labels = [("namespace", ns.as_str())];
-            let external_workloads = ConstGauge::new(index.external_workloads.by_name.len() as u32);
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let external_workloads = ConstGauge::new(ns.external_workloads as u32);
             let external_workloads_encoder = external_workloads_encoder.encode_family(&labels)?;
             external_workloads.encode(external_workloads_encoder)?;
         }
@@ -79,9 +162,9 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, index) in &this.namespaces.by_ns {
-            let labels = [("namespace", ns.as_str())];
-            let servers = ConstGauge::new(index.policy.servers.len() as u32);
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let servers = ConstGauge::new(ns.servers as u32);
             let servers_encoder = servers_encoder.encode_family(&labels)?;
             servers.encode(servers_encoder)?;
         }
@@ -92,9 +175,9 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, index) in &this.namespaces.by_ns {
-            let labels = [("namespace", ns.as_str())];
-            let server_authz = ConstGauge::new(index.policy.server_authorizations.len() as u32);
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let server_authz = ConstGauge::new(ns.server_authorizations as u32);
             let server_authz_encoder = server_authz_encoder.encode_family(&labels)?;
             server_authz.encode(server_authz_encoder)?;
         }
@@ -105,9 +188,9 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, index) in &this.namespaces.by_ns {
-            let labels = [("namespace", ns.as_str())];
-            let authz_policies = ConstGauge::new(index.policy.authorization_policies.len() as u32);
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let authz_policies = ConstGauge::new(ns.authorization_policies as u32);
             let authz_policies_encoder = authz_policies_encoder.encode_family(&labels)?;
             authz_policies.encode(authz_policies_encoder)?;
         }
@@ -118,12 +201,108 @@ impl Collector for Instrumented {
             None,
             MetricType::Gauge,
         )?;
-        for (ns, index) in &this.namespaces.by_ns {
-            let labels = [("namespace", ns.as_str())];
-            let http_routes = ConstGauge::new(index.policy.http_routes.len() as u32);
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let http_routes = ConstGauge::new(ns.http_routes as u32);
             let http_routes_encoder = http_routes_encoder.encode_family(&labels)?;
             http_routes.encode(http_routes_encoder)?;
         }
+
+        let mut routes_encoder = encoder.encode_descriptor(
+            "route_index_size",
+            "The number of routes in index by group and kind",
+            None,
+            MetricType::Gauge,
+        )?;
+        for ns in &sizes {
+            for ((group, kind), routes) in &ns.routes_by_kind {
+                let labels = [
+                    ("namespace", ns.namespace),
+                    ("group", group.as_ref()),
+                    ("kind", kind.as_ref()),
+                ];
+                let routes = ConstGauge::new(*routes as u32);
+                let routes_encoder = routes_encoder.encode_family(&labels)?;
+                routes.encode(routes_encoder)?;
+            }
+        }
+
+        let empty_encoder = encoder.encode_descriptor(
+            "empty_namespaces",
+            "The number of namespaces retained in index without any resources",
+            None,
+            MetricType::Gauge,
+        )?;
+        let empty = sizes.iter().filter(|ns| ns.empty).count()
+            + authentications
+                .by_ns
+                .values()
+                .filter(|auth| auth.is_empty())
+                .count();
+        ConstGauge::new(empty as u32).encode(empty_encoder)?;
+
+        let mut published_encoder = encoder.encode_descriptor(
+            "published_servers",
+            "An estimate of the memory held by the servers published for workload ports, from \
+             their encoded size",
+            Some(&Unit::Bytes),
+            MetricType::Gauge,
+        )?;
+        for ns in &sizes {
+            let labels = [("namespace", ns.namespace)];
+            let published_encoder = published_encoder.encode_family(&labels)?;
+            ConstGauge::new(ns.published_bytes as i64).encode(published_encoder)?;
+        }
+
+        let mut default_ports_encoder = encoder.encode_descriptor(
+            "default_policy_ports",
+            "The number of known ports on each workload that are not selected by any server, and \
+             so are served by a default policy",
+            None,
+            MetricType::Gauge,
+        )?;
+        for ns in &sizes {
+            for ((kind, name, policy), ports) in &ns.default_ports {
+                let labels = [
+                    ("namespace", ns.namespace),
+                    ("kind", kind),
+                    ("name", name.as_str()),
+                    ("policy", policy),
+                ];
+                let default_ports_encoder = default_ports_encoder.encode_family(&labels)?;
+                ConstGauge::new(*ports).encode(default_ports_encoder)?;
+            }
+        }
+
+        let mut rejected = BTreeMap::<_, u32>::new();
+        for gknn in self.index.read().parsed_routes.rejected() {
+            *rejected.entry(gknn.namespace.clone()).or_default() += 1;
+        }
+        let mut rejected_encoder = encoder.encode_descriptor(
+            "rejected_routes",
+            "The number of routes that are quarantined because they could not be converted",
+            None,
+            MetricType::Gauge,
+        )?;
+        for (namespace, routes) in rejected {
+            let labels = [("namespace", namespace.as_ref())];
+            let rejected_encoder = rejected_encoder.encode_family(&labels)?;
+            ConstGauge::new(routes).encode(rejected_encoder)?;
+        }
+
         Ok(())
     }
 }
+
+fn count_default_ports(
+    counts: &mut BTreeMap<(&'static str, String, &'static str), u32>,
+    kind: &'static str,
+    name: &str,
+    ports: &PortMap<WorkloadPortServer>,
+) {
+    for server in ports.values() {
+        if let ServerRef::Default(policy) = server.watch.borrow().reference {
+            *counts.entry((kind, name.to_string(), policy)).or_default() += 1;
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Inverted label indexes that narrow the set of resources that must be
+//! considered when matching `Server` selectors to workloads.
+//!
+//! Both indexes only use a selector's `matchLabels`; they return candidates
+//! that must still be matched against the full selector.
+
+use ahash::{AHashMap as HashMap, AHashSet as HashSet};
+use linkerd_policy_controller_k8s_api::{self as k8s, labels};
+
+/// Indexes a namespace's workloads by their labels and by the servers to which
+/// their ports are bound.
+#[derive(Debug, Default)]
+pub(super) struct WorkloadLabels {
+    by_label: HashMap<String, HashMap<String, HashSet<String>>>,
+    by_server: HashMap<String, HashSet<String>>,
+}
+
+/// Indexes a namespace's servers by one of their selector's required labels.
+#[derive(Debug, Default)]
+pub(super) struct ServerLabels {
+    by_label: HashMap<String, HashMap<String, HashSet<String>>>,
+
+    /// Servers with selectors that do not require any labels.
+    unlabeled: HashSet<String>,
+}
+
+// === impl WorkloadLabels ===
+
+impl WorkloadLabels {
+    pub(super) fn insert(&mut self, name: &str, labels: &k8s::Labels) {
+        for (k, v) in labels.as_ref() {
+            self.by_label
+                .entry(k.clone())
+                .or_default()
+                .entry(v.clone())
+                .or_default()
+                .insert(name.to_string());
+        }
+    }
+
+    pub(super) fn remove(&mut self, name: &str, labels: &k8s::Labels) {
+        for (k, v) in labels.as_ref() {
+            remove_labeled(&mut self.by_label, k, v, name);
+        }
+    }
+
+    /// Updates the servers to which a workload's ports are bound.
+    pub(super) fn bind(&mut self, name: &str, prior: HashSet<String>, servers: HashSet<String>) {
+        for server in prior.difference(&servers) {
+            if let Some(names) = self.by_server.get_mut(server) {
+                names.remove(name);
+                if names.is_empty() {
+                    self.by_server.remove(server);
+                }
+            }
+        }
+        for server in servers.difference(&prior) {
+            self.by_server
+                .entry(server.clone())
+                .or_default()
+                .insert(name.to_string());
+        }
+    }
+
+    /// Returns the names of all workloads with ports bound to a server.
+    pub(super) fn bound(&self) -> Vec<String> {
+        self.by_server
+            .values()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the names of workloads that may be selected by the given
+    /// selector or that have ports bound to the named server.
+    ///
+    /// Returns `None` if the selector does not require any labels, in which
+    /// case all workloads must be considered.
+    pub(super) fn candidates(
+        &self,
+        server: &str,
+        selector: Option<&labels::Selector>,
+    ) -> Option<Vec<String>> {
+        let mut names = HashSet::<&String>::default();
+        if let Some(selector) = selector {
+            names.extend(smallest(&self.by_label, selector)?);
+        }
+        names.extend(self.by_server.get(server).into_iter().flatten());
+        Some(names.into_iter().cloned().collect())
+    }
+}
+
+// === impl ServerLabels ===
+
+impl ServerLabels {
+    pub(super) fn insert(&mut self, name: &str, selector: &labels::Selector) {
+        match selector.required_labels().next() {
+            Some((k, v)) => {
+                self.by_label
+                    .entry(k.to_string())
+                    .or_default()
+                    .entry(v.to_string())
+                    .or_default()
+                    .insert(name.to_string());
+            }
+            None => {
+                self.unlabeled.insert(name.to_string());
+            }
+        }
+    }
+
+    pub(super) fn remove(&mut self, name: &str, selector: &labels::Selector) {
+        match selector.required_labels().next() {
+            Some((k, v)) => remove_labeled(&mut self.by_label, k, v, name),
+            None => {
+                self.unlabeled.remove(name);
+            }
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.by_label.clear();
+        self.unlabeled.clear();
+    }
+
+    /// Returns the names of servers that may select a workload with the given
+    /// labels.
+    ///
+    /// Names are returned in order so that conflicting servers are always
+    /// resolved in the same way.
+    pub(super) fn candidates(&self, labels: &k8s::Labels) -> Vec<&str> {
+        let mut names = self
+            .unlabeled
+            .iter()
+            .chain(labels.as_ref().iter().flat_map(|(k, v)| {
+                self.by_label
+                    .get(k)
+                    .and_then(|values| values.get(v))
+                    .into_iter()
+                    .flatten()
+            }))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+/// Returns the smallest set of names with one of the selector's required
+/// labels, or `None` if the selector does not require any labels.
+fn smallest<'i>(
+    by_label: &'i HashMap<String, HashMap<String, HashSet<String>>>,
+    selector: &labels::Selector,
+) -> Option<impl Iterator<Item = &'i String>> {
+    let mut smallest = None::<(usize, Option<&HashSet<String>>)>;
+    for (k, v) in selector.required_labels() {
+        let names = by_label.get(k).and_then(|values| values.get(v));
+        let len = names.map_or(0, |names| names.len());
+        if smallest.map_or(true, |(min, _)| len < min) {
+            smallest = Some((len, names));
+        }
+    }
+    let (_, names) = smallest?;
+    Some(names.into_iter().flatten())
+}
+
+fn remove_labeled(
+    by_label: &mut HashMap<String, HashMap<String, HashSet<String>>>,
+    k: &str,
+    v: &str,
+    name: &str,
+) {
+    if let Some(values) = by_label.get_mut(k) {
+        if let Some(names) = values.get_mut(v) {
+            names.remove(name);
+            if names.is_empty() {
+                values.remove(v);
+            }
+        }
+        if values.is_empty() {
+            by_label.remove(k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> k8s::Labels {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<labels::Map>()
+            .into()
+    }
+
+    fn sorted(names: Option<Vec<String>>) -> Option<Vec<String>> {
+        names.map(|mut names| {
+            names.sort();
+            names
+        })
+    }
+
+    #[test]
+    fn workload_candidates() {
+        let mut index = WorkloadLabels::default();
+        index.insert("pod-0", &labels(&[("app", "a"), ("tier", "web")]));
+        index.insert("pod-1", &labels(&[("app", "b"), ("tier", "web")]));
+        index.insert("pod-2", &labels(&[("app", "a"), ("tier", "db")]));
+
+        // Candidates are drawn from the least common required label.
+        let selector = [("app", "b"), ("tier", "web")]
+            .into_iter()
+            .collect::<labels::Selector>();
+        assert_eq!(
+            sorted(index.candidates("srv", Some(&selector))),
+            Some(vec!["pod-1".to_string()])
+        );
+
+        let selector = Some(("app", "c")).into_iter().collect();
+        assert_eq!(index.candidates("srv", Some(&selector)), Some(vec![]));
+
+        assert_eq!(
+            index.candidates("srv", Some(&labels::Selector::default())),
+            None,
+            "selectors without required labels may select any workload"
+        );
+
+        // Workloads bound to the server are always candidates.
+        index.bind(
+            "pod-1",
+            HashSet::default(),
+            Some("srv".to_string()).into_iter().collect(),
+        );
+        assert_eq!(
+            sorted(index.candidates("srv", Some(&selector))),
+            Some(vec!["pod-1".to_string()])
+        );
+        assert_eq!(index.bound(), vec!["pod-1".to_string()]);
+
+        index.remove("pod-0", &labels(&[("app", "a"), ("tier", "web")]));
+        index.bind(
+            "pod-1",
+            Some("srv".to_string()).into_iter().collect(),
+            HashSet::default(),
+        );
+        let selector = Some(("tier", "web")).into_iter().collect();
+        assert_eq!(
+            sorted(index.candidates("srv", Some(&selector))),
+            Some(vec!["pod-1".to_string()])
+        );
+        assert!(index.bound().is_empty());
+    }
+
+    #[test]
+    fn server_candidates() {
+        let mut index = ServerLabels::default();
+        let web = [("app", "a"), ("tier", "web")]
+            .into_iter()
+            .collect::<labels::Selector>();
+        index.insert("srv-web", &web);
+        index.insert("srv-b", &Some(("app", "b")).into_iter().collect());
+        index.insert("srv-all", &labels::Selector::default());
+
+        assert_eq!(
+            index.candidates(&labels(&[("app", "a"), ("tier", "db")])),
+            ["srv-all", "srv-web"]
+        );
+        assert_eq!(
+            index.candidates(&labels(&[("app", "b")])),
+            ["srv-all", "srv-b"]
+        );
+
+        index.remove("srv-web", &web);
+        assert_eq!(
+            index.candidates(&labels(&[("app", "a"), ("tier", "web")])),
+            ["srv-all"]
+        );
+
+        index.clear();
+        assert!(index.candidates(&labels(&[("app", "b")])).is_empty());
+    }
+}
@@ -1,49 +1,80 @@
-use crate::ClusterInfo;
+use crate::{cluster_info::IdentityRef, ClusterInfo};
 use anyhow::Result;
 use linkerd_policy_controller_core::IdentityMatch;
 use linkerd_policy_controller_k8s_api::{
-    policy::MeshTLSAuthentication, Namespace, ResourceExt, ServiceAccount,
+    policy::{meshtls_authentication::RemoteIdentityRef, MeshTLSAuthentication},
+    Namespace, ResourceExt, ServiceAccount,
 };
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct Spec {
-    pub matches: Vec<IdentityMatch>,
+    pub identities: Vec<IdentityRef>,
 }
 
 impl Spec {
-    pub(crate) fn try_from_resource(
-        ma: MeshTLSAuthentication,
-        cluster: &ClusterInfo,
-    ) -> anyhow::Result<Self> {
+    pub(crate) fn try_from_resource(ma: MeshTLSAuthentication) -> anyhow::Result<Self> {
         let namespace = ma
             .namespace()
             .expect("MeshTLSAuthentication must have a namespace");
 
         let identities = ma.spec.identities.into_iter().flatten().map(|s| {
-            Ok(s.parse::<IdentityMatch>()
-                .expect("identity match parsing is infallible"))
+            Ok(IdentityRef::Match(
+                s.parse::<IdentityMatch>()
+                    .expect("identity match parsing is infallible"),
+            ))
         });
 
         let identity_refs = ma.spec.identity_refs.into_iter().flatten().map(|tgt| {
             if tgt.targets_kind::<ServiceAccount>() {
                 let ns = tgt.namespace.as_deref().unwrap_or(&namespace);
-                let id = cluster.service_account_identity(ns, &tgt.name);
-                Ok(IdentityMatch::Exact(id))
+                Ok(IdentityRef::ServiceAccount {
+                    namespace: ns.to_string(),
+                    name: tgt.name,
+                })
             } else if tgt.targets_kind::<Namespace>() {
-                let id = cluster.namespace_identity(tgt.name.as_str());
-                Ok(id.parse::<IdentityMatch>()?)
+                Ok(IdentityRef::Namespace(tgt.name))
             } else {
                 anyhow::bail!("unsupported target type: {:?}", tgt.canonical_kind())
             }
         });
 
-        let matches = identities
+        let remote_identity_refs = ma.spec.remote_identity_refs.into_iter().flatten().map(
+            |RemoteIdentityRef { cluster, target }| {
+                if target.targets_kind::<ServiceAccount>() {
+                    let ns = target.namespace.as_deref().unwrap_or(&namespace);
+                    Ok(IdentityRef::RemoteServiceAccount {
+                        cluster,
+                        namespace: ns.to_string(),
+                        name: target.name,
+                    })
+                } else if target.targets_kind::<Namespace>() {
+                    Ok(IdentityRef::RemoteNamespace {
+                        cluster,
+                        name: target.name,
+                    })
+                } else {
+                    anyhow::bail!("unsupported target type: {:?}", target.canonical_kind())
+                }
+            },
+        );
+
+        let identities = identities
             .chain(identity_refs)
+            .chain(remote_identity_refs)
             .collect::<Result<Vec<_>>>()?;
-        if matches.is_empty() {
+        if identities.is_empty() {
             anyhow::bail!("No identities configured");
         }
 
-        Ok(Spec { matches })
+        Ok(Spec { identities })
+    }
+
+    /// Formats the authentication's identities with the cluster's identity
+    /// configuration.
+    pub(crate) fn matches(&self, cluster: &ClusterInfo) -> Vec<IdentityMatch> {
+        self.identities
+            .iter()
+            .filter_map(|id| cluster.identity_match(id))
+            .collect()
     }
 }
@@ -1,8 +1,9 @@
-use crate::ClusterInfo;
+use crate::{cluster_info::detect_timeout_annotation, ClusterInfo, MAX_DETECT_TIMEOUT};
 use linkerd_policy_controller_core::inbound::ProxyProtocol;
 use linkerd_policy_controller_k8s_api::{
-    self as k8s, policy::server::Port, policy::server::Selector,
+    self as k8s, policy::server::Port, policy::server::Selector, ResourceExt,
 };
+use std::time;
 
 /// The parts of a `Server` resource that can change.
 #[derive(Debug, PartialEq)]
@@ -10,32 +11,48 @@ pub(crate) struct Server {
     pub labels: k8s::Labels,
     pub selector: Selector,
     pub port_ref: Port,
-    pub protocol: ProxyProtocol,
+    pub proxy_protocol: Option<k8s::policy::server::ProxyProtocol>,
+
+    /// The detection timeout configured by the server's spec or, if unset, its
+    /// annotations. When neither is set, the cluster's default is used.
+    pub detect_timeout: Option<time::Duration>,
 }
 
 impl Server {
-    pub(crate) fn from_resource(srv: k8s::policy::Server, cluster: &ClusterInfo) -> Self {
+    pub(crate) fn from_resource(srv: k8s::policy::Server) -> Self {
+        let detect_timeout = match srv.spec.detect_timeout {
+            Some(timeout) if timeout.is_negative() => {
+                tracing::warn!(%timeout, "Ignoring negative protocol detection timeout");
+                detect_timeout_annotation(srv.annotations())
+            }
+            Some(timeout) if time::Duration::from(timeout) > MAX_DETECT_TIMEOUT => {
+                tracing::warn!(%timeout, max = ?MAX_DETECT_TIMEOUT, "Ignoring protocol detection timeout that exceeds the maximum");
+                detect_timeout_annotation(srv.annotations())
+            }
+            Some(timeout) => Some(timeout.into()),
+            None => detect_timeout_annotation(srv.annotations()),
+        };
         Self {
             labels: srv.metadata.labels.into(),
             selector: srv.spec.selector,
             port_ref: srv.spec.port,
-            protocol: proxy_protocol(srv.spec.proxy_protocol, cluster),
+            proxy_protocol: srv.spec.proxy_protocol,
+            detect_timeout,
         }
     }
-}
 
-fn proxy_protocol(
-    p: Option<k8s::policy::server::ProxyProtocol>,
-    cluster: &ClusterInfo,
-) -> ProxyProtocol {
-    match p {
-        None | Some(k8s::policy::server::ProxyProtocol::Unknown) => ProxyProtocol::Detect {
-            timeout: cluster.default_detect_timeout,
-        },
-        Some(k8s::policy::server::ProxyProtocol::Http1) => ProxyProtocol::Http1,
-        Some(k8s::policy::server::ProxyProtocol::Http2) => ProxyProtocol::Http2,
-        Some(k8s::policy::server::ProxyProtocol::Grpc) => ProxyProtocol::Http2,
-        Some(k8s::policy::server::ProxyProtocol::Opaque) => ProxyProtocol::Opaque,
-        Some(k8s::policy::server::ProxyProtocol::Tls) => ProxyProtocol::Tls,
+    pub(crate) fn protocol(&self, cluster: &ClusterInfo) -> ProxyProtocol {
+        match self.proxy_protocol {
+            None | Some(k8s::policy::server::ProxyProtocol::Unknown) => ProxyProtocol::Detect {
+                timeout: self
+                    .detect_timeout
+                    .unwrap_or(cluster.default_detect_timeout),
+            },
+            Some(k8s::policy::server::ProxyProtocol::Http1) => ProxyProtocol::Http1,
+            Some(k8s::policy::server::ProxyProtocol::Http2) => ProxyProtocol::Http2,
+            Some(k8s::policy::server::ProxyProtocol::Grpc) => ProxyProtocol::Http2,
+            Some(k8s::policy::server::ProxyProtocol::Opaque) => ProxyProtocol::Opaque,
+            Some(k8s::policy::server::ProxyProtocol::Tls) => ProxyProtocol::Tls,
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::ClusterInfo;
+use crate::{cluster_info::IdentityRef, ClusterInfo};
 use anyhow::Result;
 use linkerd_policy_controller_core::{
     inbound::{ClientAuthentication, ClientAuthorization},
@@ -9,10 +9,20 @@ use linkerd_policy_controller_k8s_api::{self as k8s, policy::server_authorizatio
 /// The parts of a `ServerAuthorization` resource that can change.
 #[derive(Debug, PartialEq)]
 pub(crate) struct ServerAuthz {
-    pub authz: ClientAuthorization,
+    pub networks: Vec<NetworkMatch>,
+    pub authentication: Authentication,
     pub server_selector: ServerSelector,
 }
 
+/// A `ServerAuthorization`'s client authentication, whose identities are
+/// formatted when the authorization is computed.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Authentication {
+    Unauthenticated,
+    TlsUnauthenticated,
+    TlsAuthenticated(Vec<IdentityRef>),
+}
+
 /// Selects `Server`s for a `ServerAuthoriation`
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum ServerSelector {
@@ -21,24 +31,38 @@ pub(crate) enum ServerSelector {
 }
 
 impl ServerAuthz {
-    pub(crate) fn from_resource(
-        saz: k8s::policy::ServerAuthorization,
-        cluster: &ClusterInfo,
-    ) -> Result<Self> {
-        let authz = {
-            let namespace = saz
-                .metadata
-                .namespace
-                .as_deref()
-                .expect("resource must be namespaced");
-            client_authz(saz.spec.client, namespace, cluster)?
-        };
+    pub(crate) fn from_resource(saz: k8s::policy::ServerAuthorization) -> Result<Self> {
+        let namespace = saz
+            .metadata
+            .namespace
+            .as_deref()
+            .expect("resource must be namespaced");
+        let (networks, authentication) = client_authz(saz.spec.client, namespace)?;
         let server_selector = saz.spec.server.into();
         Ok(Self {
-            authz,
+            networks,
+            authentication,
             server_selector,
         })
     }
+
+    /// Returns the client authorization, with identities formatted with the
+    /// cluster's identity configuration.
+    pub(crate) fn client_authz(&self, cluster: &ClusterInfo) -> ClientAuthorization {
+        let authentication = match &self.authentication {
+            Authentication::Unauthenticated => ClientAuthentication::Unauthenticated,
+            Authentication::TlsUnauthenticated => ClientAuthentication::TlsUnauthenticated,
+            Authentication::TlsAuthenticated(ids) => ClientAuthentication::TlsAuthenticated(
+                ids.iter()
+                    .filter_map(|id| cluster.identity_match(id))
+                    .collect(),
+            ),
+        };
+        ClientAuthorization {
+            networks: self.networks.clone(),
+            authentication,
+        }
+    }
 }
 
 // === impl ServerSelector ===
@@ -63,8 +87,7 @@ impl ServerSelector {
 fn client_authz(
     client: k8s::policy::server_authorization::Client,
     namespace: &str,
-    cluster: &ClusterInfo,
-) -> Result<ClientAuthorization> {
+) -> Result<(Vec<NetworkMatch>, Authentication)> {
     let networks = client
         .networks
         .into_iter()
@@ -76,40 +99,36 @@ fn client_authz(
         .collect();
 
     let authentication = if client.unauthenticated {
-        ClientAuthentication::Unauthenticated
+        Authentication::Unauthenticated
     } else if let Some(mtls) = client.mesh_tls {
-        client_mtls_authn(mtls, namespace, cluster)?
+        client_mtls_authn(mtls, namespace)?
     } else {
         anyhow::bail!("no client authentication configured");
     };
 
-    Ok(ClientAuthorization {
-        networks,
-        authentication,
-    })
+    Ok((networks, authentication))
 }
 
-fn client_mtls_authn(
-    mtls: MeshTls,
-    namespace: &str,
-    cluster: &ClusterInfo,
-) -> Result<ClientAuthentication> {
+fn client_mtls_authn(mtls: MeshTls, namespace: &str) -> Result<Authentication> {
     if mtls.unauthenticated_tls {
-        return Ok(ClientAuthentication::TlsUnauthenticated);
+        return Ok(Authentication::TlsUnauthenticated);
     }
 
     let ids = mtls
         .identities
         .into_iter()
         .flatten()
-        .map(|id| match id.parse() {
-            Ok(id) => id,
+        .map(|id| match id.parse::<IdentityMatch>() {
+            Ok(id) => IdentityRef::Match(id),
             Err(e) => match e {},
         });
 
     let sas = mtls.service_accounts.into_iter().flatten().map(|sa| {
         let ns = sa.namespace.as_deref().unwrap_or(namespace);
-        IdentityMatch::Exact(cluster.service_account_identity(ns, &sa.name))
+        IdentityRef::ServiceAccount {
+            namespace: ns.to_string(),
+            name: sa.name,
+        }
     });
 
     let identities = ids.chain(sas).collect::<Vec<_>>();
@@ -117,5 +136,5 @@ fn client_mtls_authn(
         anyhow::bail!("authorization authorizes no clients");
     }
 
-    Ok(ClientAuthentication::TlsAuthenticated(identities))
+    Ok(Authentication::TlsAuthenticated(identities))
 }
@@ -1,5 +1,6 @@
 mod annotation;
 mod authorization_policy;
+mod external_workload;
 mod http_routes;
 mod server_authorization;
 
@@ -36,6 +37,372 @@ fn pod_must_exist_for_lookup() {
         .expect_err("pod-0.ns-0 must not exist");
 }
 
+#[test]
+fn lookups_do_not_hold_index_lock() {
+    let test = TestConfig::default();
+    let lookup = test.index.read().lookup();
+    test.index
+        .write()
+        .apply(mk_pod("ns-0", "pod-0", Some(("container-0", None))));
+
+    // Lookups must succeed while the index is being updated.
+    let mut index = test.index.write();
+    let rx = lookup
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow(), test.default_server());
+
+    // Changes to the index are visible to the lookup.
+    IndexNamespacedResource::<k8s::Pod>::delete(
+        &mut *index,
+        "ns-0".to_string(),
+        "pod-0".to_string(),
+    );
+    drop(index);
+    assert!(lookup
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .is_err());
+}
+
+#[test]
+fn reindexes_only_affected_pods() {
+    let test = TestConfig::default();
+    for (name, app) in [("pod-0", "app-0"), ("pod-1", "app-1")] {
+        let mut pod = mk_pod("ns-0", name, Some(("container-0", None)));
+        pod.labels_mut().insert("app".to_string(), app.to_string());
+        test.index.write().apply(pod);
+    }
+
+    let port = 8080.try_into().unwrap();
+    let mut rx0 = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", port)
+        .expect("pod-0.ns-0 should exist");
+    let mut rx1 = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-1", port)
+        .expect("pod-1.ns-0 should exist");
+    assert_eq!(*rx0.borrow_and_update(), test.default_server());
+    assert_eq!(*rx1.borrow_and_update(), test.default_server());
+
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-0",
+        Port::Number(port),
+        None,
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    assert!(rx0.has_changed().unwrap());
+    assert_eq!(
+        rx0.borrow_and_update().reference,
+        ServerRef::Server("srv-0".to_string())
+    );
+    assert!(
+        !rx1.has_changed().unwrap(),
+        "pods not selected by the server must not be updated"
+    );
+
+    // Authorizations only affect pods with ports bound to a server.
+    test.index.write().apply(k8s::policy::ServerAuthorization {
+        metadata: k8s::ObjectMeta {
+            namespace: Some("ns-0".to_string()),
+            name: Some("authz-0".to_string()),
+            ..Default::default()
+        },
+        spec: k8s::policy::ServerAuthorizationSpec {
+            server: k8s::policy::server_authorization::Server {
+                name: Some("srv-0".to_string()),
+                selector: None,
+            },
+            client: k8s::policy::server_authorization::Client {
+                unauthenticated: true,
+                ..k8s::policy::server_authorization::Client::default()
+            },
+        },
+    });
+    assert!(rx0.has_changed().unwrap());
+    assert!(rx0.borrow_and_update().authorizations.contains_key(
+        &AuthorizationRef::ServerAuthorization("authz-0".to_string())
+    ));
+    assert!(!rx1.has_changed().unwrap());
+
+    // Pods that were bound to a deleted server revert to the default policy.
+    IndexNamespacedResource::<k8s::policy::Server>::delete(
+        &mut *test.index.write(),
+        "ns-0".to_string(),
+        "srv-0".to_string(),
+    );
+    assert!(rx0.has_changed().unwrap());
+    assert_eq!(*rx0.borrow_and_update(), test.default_server());
+    assert!(!rx1.has_changed().unwrap());
+}
+
+#[test]
+fn reindexes_relabeled_pods() {
+    let test = TestConfig::default();
+    let port = 8080.try_into().unwrap();
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-0",
+        Port::Number(port),
+        None,
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+
+    let mk_pod = |app: &str| {
+        let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+        pod.labels_mut().insert("app".to_string(), app.to_string());
+        pod
+    };
+    test.index.write().apply(mk_pod("app-1"));
+    let mut rx = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", port)
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+
+    // Pods are selected by servers when their labels change.
+    test.index.write().apply(mk_pod("app-0"));
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        rx.borrow_and_update().reference,
+        ServerRef::Server("srv-0".to_string())
+    );
+
+    // Pods that are no longer selected by a server are updated when the
+    // server changes.
+    test.index.write().apply(mk_pod("app-1"));
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-0",
+        Port::Number(port),
+        None,
+        Some(("app", "app-1")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        rx.borrow_and_update().reference,
+        ServerRef::Server("srv-0".to_string())
+    );
+}
+
+#[test]
+fn completed_pods_are_removed() {
+    let test = TestConfig::default();
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    test.index.write().apply(pod.clone());
+    let mut rx = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+
+    pod.status = Some(k8s::PodStatus {
+        phase: Some("Succeeded".to_string()),
+        ..Default::default()
+    });
+    test.index.write().apply(pod);
+    assert!(
+        rx.has_changed().is_err(),
+        "watches for completed pods must complete"
+    );
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect_err("completed pods must not be found");
+}
+
+#[test]
+fn unmeshed_pods_are_not_indexed() {
+    let mut test = TestConfig::default();
+    test.cluster.meshed_pods_only = true;
+    test.index = Index::shared(test.cluster.clone());
+
+    let pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    test.index.write().apply(pod.clone());
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect_err("unmeshed pods must not be found");
+
+    let mut meshed = mk_pod(
+        "ns-0",
+        "pod-0",
+        [("container-0", None), ("linkerd-proxy", None)],
+    );
+    test.index.write().apply(meshed.clone());
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pods with a proxy container must be found");
+
+    meshed.spec.as_mut().unwrap().containers.pop();
+    meshed.metadata.annotations = Some(
+        Some(("linkerd.io/proxy-version".to_string(), "edge".to_string()))
+            .into_iter()
+            .collect(),
+    );
+    test.index.write().apply(meshed);
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pods with a proxy version annotation must be found");
+
+    test.index.write().apply(pod);
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect_err("pods that are no longer meshed must be removed");
+}
+
+#[test]
+fn proxy_control_port_requires_identity() {
+    let test = TestConfig::default();
+
+    let pod = mk_pod_with_containers("ns-0", "pod-0", Some(mk_proxy_container(None)));
+    test.index.write().apply(pod);
+
+    let rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 4190.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let policy = DefaultPolicy::Allow {
+        authenticated_only: true,
+        cluster_only: true,
+    };
+    assert_eq!(
+        *rx.borrow(),
+        InboundServer {
+            reference: ServerRef::Default(policy.as_str()),
+            authorizations: mk_default_policy(policy, test.cluster.networks.clone()),
+            protocol: ProxyProtocol::Detect {
+                timeout: test.detect_timeout,
+            },
+            http_routes: mk_default_routes(),
+        },
+    );
+
+    // Other ports retain the default policy.
+    let rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 4191.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow(), test.default_server());
+}
+
+#[test]
+fn terminating_pods_are_served_until_deleted() {
+    let test = TestConfig::default();
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.metadata.deletion_timestamp = Some(k8s::Time(
+        chrono::Utc::now() + chrono::Duration::seconds(30),
+    ));
+    test.index.write().apply(pod.clone());
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("terminating pods must be served during their grace period");
+
+    // If the pod's deletion is not observed once its grace period expires,
+    // lookups fail as though the pod had been deleted.
+    pod.metadata.deletion_timestamp =
+        Some(k8s::Time(chrono::Utc::now() - chrono::Duration::seconds(1)));
+    test.index.write().apply(pod);
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect_err("pods must not be found after their deletion deadline");
+}
+
+#[test]
+fn counts_default_policy_ports() {
+    let test = TestConfig::default();
+    let mut prom = prometheus_client::registry::Registry::default();
+    crate::inbound::metrics::register(&mut prom, test.index.clone());
+    let metrics = || {
+        let mut text = String::new();
+        prometheus_client::encoding::text::encode(&mut text, &prom).unwrap();
+        text
+    };
+
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().apply(pod);
+    for port in [8080, 9090] {
+        test.index
+            .read()
+            .pod_server_rx("ns-0", "pod-0", port.try_into().unwrap())
+            .expect("pod-0.ns-0 should exist");
+    }
+    let policy = test.default_policy.as_str();
+    let text = metrics();
+    assert!(
+        text.contains(&format!(
+            r#"default_policy_ports{{namespace="ns-0",kind="pod",name="pod-0",policy="{policy}"}} 2"#
+        )),
+        "{text}"
+    );
+
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-0",
+        Port::Number(8080.try_into().unwrap()),
+        None,
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    let text = metrics();
+    assert!(
+        text.contains(&format!(
+            r#"default_policy_ports{{namespace="ns-0",kind="pod",name="pod-0",policy="{policy}"}} 1"#
+        )),
+        "{text}"
+    );
+}
+
+#[test]
+fn estimates_published_server_bytes() {
+    let test = TestConfig::default();
+    let mut prom = prometheus_client::registry::Registry::default();
+    crate::inbound::metrics::register(&mut prom, test.index.clone());
+    let metrics = || {
+        let mut text = String::new();
+        prometheus_client::encoding::text::encode(&mut text, &prom).unwrap();
+        text
+    };
+
+    test.index
+        .write()
+        .apply(mk_pod("ns-0", "pod-0", Some(("container-0", None))));
+    let text = metrics();
+    assert!(
+        text.contains(r#"published_servers_bytes{namespace="ns-0"} 0"#),
+        "{text}"
+    );
+
+    test.index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let text = metrics();
+    assert!(
+        !text.contains(r#"published_servers_bytes{namespace="ns-0"} 0"#)
+            && text.contains(r#"published_servers_bytes{namespace="ns-0"}"#),
+        "{text}"
+    );
+}
+
 struct TestConfig {
     index: SharedIndex,
     detect_timeout: time::Duration,
@@ -83,6 +450,32 @@ pub fn mk_pod_with_containers(
     }
 }
 
+/// Builds a proxy container that serves its admin server on port 4191 and its
+/// control server on port 4190.
+fn mk_proxy_container(probe: Option<&str>) -> Container {
+    let env = |name: &str, addr: &str| k8s::api::core::v1::EnvVar {
+        name: name.to_string(),
+        value: Some(addr.to_string()),
+        ..Default::default()
+    };
+    Container {
+        name: "linkerd-proxy".to_string(),
+        env: Some(vec![
+            env("LINKERD2_PROXY_ADMIN_LISTEN_ADDR", "[::]:4191"),
+            env("LINKERD2_PROXY_CONTROL_LISTEN_ADDR", "[::]:4190"),
+        ]),
+        liveness_probe: probe.map(|path| k8s::Probe {
+            http_get: Some(k8s::HTTPGetAction {
+                path: Some(path.to_string()),
+                port: k8s::IntOrString::Int(4191),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
 fn mk_pod(
     ns: impl ToString,
     name: impl ToString,
@@ -120,6 +513,7 @@ fn mk_server(
             port,
             selector: k8s::policy::server::Selector::Pod(pod_labels.into_iter().collect()),
             proxy_protocol,
+            detect_timeout: None,
         },
     }
 }
@@ -187,6 +581,86 @@ fn mk_default_routes() -> HashMap<HttpRouteRef, HttpRoute> {
         .collect()
 }
 
+#[test]
+fn cluster_networks_update_defaults() {
+    let mut test = TestConfig::default();
+    test.index
+        .write()
+        .apply(mk_pod("ns-0", "pod-0", Some(("container-0", None))));
+    let mut rx = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+
+    let networks = vec!["198.51.100.0/24".parse().unwrap()];
+    test.index.write().set_cluster_networks(networks.clone());
+    test.cluster.networks = networks;
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+
+    // Ports that are first looked up after the change use the new networks.
+    let rx = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 9090.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow(), test.default_server());
+}
+
+#[test]
+fn cluster_policy_overrides_defaults() {
+    let test = TestConfig::default();
+    test.index
+        .write()
+        .apply(mk_pod("ns-0", "pod-0", Some(("container-0", None))));
+    let mut rx = test
+        .index
+        .read()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+
+    let mk_policy = |name: &str| k8s::policy::ClusterPolicy {
+        metadata: k8s::ObjectMeta {
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        spec: k8s::policy::ClusterPolicySpec {
+            default_inbound_policy: Some("deny".to_string()),
+            probe_networks: None,
+            detect_timeout: Some(time::Duration::from_secs(5).into()),
+        },
+    };
+
+    // Only the default policy is used.
+    kubert::index::IndexClusterResource::apply(&mut *test.index.write(), mk_policy("other"));
+    assert!(!rx.has_changed().unwrap());
+
+    kubert::index::IndexClusterResource::apply(&mut *test.index.write(), mk_policy("default"));
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        *rx.borrow_and_update(),
+        InboundServer {
+            reference: ServerRef::Default("deny"),
+            authorizations: Default::default(),
+            protocol: ProxyProtocol::Detect {
+                timeout: time::Duration::from_secs(5),
+            },
+            http_routes: mk_default_routes(),
+        }
+    );
+
+    // Deleting the policy restores the configured defaults.
+    kubert::index::IndexClusterResource::<k8s::policy::ClusterPolicy>::delete(
+        &mut *test.index.write(),
+        "default".to_string(),
+    );
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+}
+
 impl TestConfig {
     fn from_default_policy(default_policy: DefaultPolicy) -> Self {
         Self::from_default_policy_with_probes(default_policy, vec![])
@@ -208,6 +682,11 @@ impl TestConfig {
             default_detect_timeout: detect_timeout,
             default_opaque_ports: Default::default(),
             probe_networks,
+            max_authorizations_per_server: None,
+            max_routes_per_parent: None,
+            max_backends_per_route: None,
+            meshed_pods_only: false,
+            remote_identity_domains: Default::default(),
         };
         let index = Index::shared(cluster.clone());
         Self {
@@ -1,4 +1,5 @@
 use super::*;
+use linkerd_policy_controller_core::routes::{HttpRouteMatch, Method, PathMatch};
 
 /// Tests that pod servers are configured with defaults based on the
 /// workload-defined `DefaultPolicy` policy.
@@ -128,3 +129,243 @@ fn authenticated_annotated() {
         assert_eq!(*rx.borrow(), config);
     }
 }
+
+#[test]
+fn detect_timeout_annotated() {
+    let test = TestConfig::default();
+
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().reset(vec![pod], Default::default());
+
+    let mut srv = mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        None,
+        Some(("app", "app-0")),
+        None,
+    );
+    srv.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "5s".into(),
+    );
+    test.index.write().apply(srv.clone());
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(
+        rx.borrow_and_update().protocol,
+        ProxyProtocol::Detect {
+            timeout: time::Duration::from_secs(5),
+        },
+    );
+
+    // An invalid timeout is ignored in favor of the cluster default.
+    srv.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "bogus".into(),
+    );
+    test.index.write().apply(srv);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        rx.borrow().protocol,
+        ProxyProtocol::Detect {
+            timeout: test.detect_timeout,
+        },
+    );
+}
+
+#[test]
+fn detect_timeout_spec() {
+    let test = TestConfig::default();
+
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().reset(vec![pod], Default::default());
+
+    // The spec's timeout supersedes the annotation.
+    let mut srv = mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        None,
+        Some(("app", "app-0")),
+        None,
+    );
+    srv.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "5s".into(),
+    );
+    srv.spec.detect_timeout = Some("3s".parse().unwrap());
+    test.index.write().apply(srv.clone());
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(
+        rx.borrow_and_update().protocol,
+        ProxyProtocol::Detect {
+            timeout: time::Duration::from_secs(3),
+        },
+    );
+
+    // A negative timeout is ignored in favor of the annotation.
+    srv.spec.detect_timeout = Some("-3s".parse().unwrap());
+    test.index.write().apply(srv.clone());
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        rx.borrow_and_update().protocol,
+        ProxyProtocol::Detect {
+            timeout: time::Duration::from_secs(5),
+        },
+    );
+
+    // So is a timeout that exceeds the maximum.
+    srv.spec.detect_timeout = Some("2h".parse().unwrap());
+    srv.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "7s".into(),
+    );
+    test.index.write().apply(srv);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        rx.borrow().protocol,
+        ProxyProtocol::Detect {
+            timeout: time::Duration::from_secs(7),
+        },
+    );
+}
+
+#[test]
+fn gateway_probe_annotated() {
+    let test = TestConfig::default();
+
+    let mut p = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    p.labels_mut()
+        .insert("app".to_string(), "gateway".to_string());
+    p.annotations_mut()
+        .insert("mirror.linkerd.io/probe-port".into(), "4191".into());
+    p.annotations_mut()
+        .insert("mirror.linkerd.io/probe-path".into(), "/ready".into());
+    test.index.write().reset(vec![p], Default::default());
+
+    // Ports other than the probe port are not affected.
+    let rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 2222.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow(), test.default_server());
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 4191.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let probe_route = rx
+        .borrow_and_update()
+        .http_routes
+        .get(&HttpRouteRef::Default("gateway-probe"))
+        .cloned()
+        .expect("gateway probe route must be set");
+    assert_eq!(
+        probe_route.rules[0].matches,
+        vec![HttpRouteMatch {
+            path: Some(PathMatch::Exact("/ready".to_string())),
+            headers: vec![],
+            query_params: vec![],
+            method: Some(Method::GET),
+        }],
+    );
+    assert_eq!(
+        probe_route.authorizations[&AuthorizationRef::Default("gateway-probe")].authentication,
+        ClientAuthentication::Unauthenticated,
+    );
+
+    // Servers that select the probe port authorize the probe as well.
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-admin",
+        Port::Number(4191.try_into().unwrap()),
+        None,
+        Some(("app", "gateway")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    assert!(rx.has_changed().unwrap());
+    let server = rx.borrow();
+    assert_eq!(server.reference, ServerRef::Server("srv-admin".to_string()));
+    assert_eq!(
+        server.http_routes[&HttpRouteRef::Default("gateway-probe")],
+        probe_route,
+    );
+}
+
+#[test]
+fn probe_paths_annotated() {
+    let policy = DefaultPolicy::Allow {
+        authenticated_only: false,
+        cluster_only: true,
+    };
+    let probe_networks = vec!["10.0.0.1/24".parse().unwrap()];
+    let test = TestConfig::from_default_policy_with_probes(policy, probe_networks);
+
+    let container = k8s::Container {
+        liveness_probe: Some(k8s::Probe {
+            http_get: Some(k8s::HTTPGetAction {
+                path: Some("/live".to_string()),
+                port: k8s::IntOrString::Int(8080),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let mut p = mk_pod_with_containers("ns-0", "pod-0", Some(container));
+    test.index
+        .write()
+        .reset(vec![p.clone()], Default::default());
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let probe_matches = |server: &InboundServer| {
+        server.http_routes[&HttpRouteRef::Default("probe")].rules[0]
+            .matches
+            .iter()
+            .map(|m| match &m.path {
+                Some(PathMatch::Exact(path)) => path.clone(),
+                path => panic!("unexpected probe path: {path:?}"),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(probe_matches(&rx.borrow_and_update()), ["/live"]);
+
+    // Annotated paths are authorized alongside the declared probes.
+    p.annotations_mut().insert(
+        "config.linkerd.io/probe-paths".into(),
+        "8080:/healthz, 8080:/live,9090:/status".into(),
+    );
+    test.index.write().apply(p);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        probe_matches(&rx.borrow_and_update()),
+        ["/live", "/healthz"]
+    );
+
+    let rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 9090.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(probe_matches(&rx.borrow()), ["/status"]);
+}
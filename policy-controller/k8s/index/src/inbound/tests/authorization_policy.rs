@@ -266,6 +266,103 @@ fn links_authorization_policy_with_service_account() {
     );
 }
 
+#[test]
+fn links_authorization_policy_with_remote_service_account() {
+    let test = TestConfig::default();
+
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().apply(pod);
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(*rx.borrow_and_update(), test.default_server());
+
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        None,
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    test.index.write().apply(mk_authorization_policy(
+        "ns-0",
+        "authz-foo",
+        Some("srv-8080"),
+        vec![
+            NamespacedTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "NetworkAuthentication".to_string(),
+                name: "net-foo".to_string(),
+                namespace: None,
+            },
+            NamespacedTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "MeshTLSAuthentication".to_string(),
+                namespace: None,
+                name: "mtls-east".to_string(),
+            },
+        ],
+    ));
+    test.index.write().apply(mk_network_authentication(
+        "ns-0".to_string(),
+        "net-foo".to_string(),
+        vec![k8s::policy::network_authentication::Network {
+            cidr: "10.0.0.0/8".parse().unwrap(),
+            except: None,
+        }],
+    ));
+    let mut mtls = mk_meshtls_authentication("ns-0", "mtls-east", None, None);
+    mtls.spec.remote_identity_refs = Some(vec![
+        k8s::policy::meshtls_authentication::RemoteIdentityRef {
+            cluster: "east".to_string(),
+            target: NamespacedTargetRef {
+                group: None,
+                kind: "ServiceAccount".to_string(),
+                namespace: Some("ns-1".to_string()),
+                name: "foo".to_string(),
+            },
+        },
+    ]);
+    test.index.write().apply(mtls);
+
+    // Identities in clusters without a known trust domain are not authorized.
+    let authz = ClientAuthorization {
+        networks: vec!["10.0.0.0/8".parse::<IpNet>().unwrap().into()],
+        authentication: ClientAuthentication::TlsAuthenticated(vec![]),
+    };
+    assert_eq!(
+        rx.borrow_and_update().authorizations,
+        hashmap!(AuthorizationRef::AuthorizationPolicy("authz-foo".to_string()) => authz)
+            .into_iter()
+            .collect(),
+    );
+
+    test.index.write().set_remote_identity_domains(
+        Some(("east".to_string(), "east.example.com".to_string()))
+            .into_iter()
+            .collect(),
+    );
+    assert!(rx.has_changed().unwrap());
+    let authz = ClientAuthorization {
+        networks: vec!["10.0.0.0/8".parse::<IpNet>().unwrap().into()],
+        authentication: ClientAuthentication::TlsAuthenticated(vec![IdentityMatch::Exact(
+            "foo.ns-1.serviceaccount.identity.linkerd.east.example.com".to_string(),
+        )]),
+    };
+    assert_eq!(
+        rx.borrow().authorizations,
+        hashmap!(AuthorizationRef::AuthorizationPolicy("authz-foo".to_string()) => authz)
+            .into_iter()
+            .collect(),
+    );
+}
+
 #[test]
 fn authorization_policy_prevents_index_deletion() {
     let test = TestConfig::default();
@@ -294,11 +391,12 @@ fn authorization_policy_prevents_index_deletion() {
             ..Default::default()
         },
         spec: k8s::policy::AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "HTTPRoute".to_string(),
                 name: "route-foo".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
@@ -375,7 +473,7 @@ fn authorization_policy_prevents_index_deletion() {
                         method: None,
                     }],
                     filters: vec![],
-                }],
+                }].into(),
                 authorizations: hashmap!(
                     AuthorizationRef::AuthorizationPolicy("authz-foo".to_string()) => authz.clone()
                 )
@@ -389,6 +487,97 @@ fn authorization_policy_prevents_index_deletion() {
     );
 }
 
+#[test]
+fn authorization_policy_with_multiple_targets() {
+    let test = TestConfig::default();
+
+    let mut rxs = Vec::new();
+    for (pod, app) in [("pod-0", "app-0"), ("pod-1", "app-1"), ("pod-2", "app-2")] {
+        let mut pod = mk_pod("ns-0", pod, Some(("container-0", None)));
+        pod.labels_mut().insert("app".to_string(), app.to_string());
+        test.index.write().apply(pod);
+    }
+    for (pod, srv, app) in [
+        ("pod-0", "srv-0", "app-0"),
+        ("pod-1", "srv-1", "app-1"),
+        ("pod-2", "srv-2", "app-2"),
+    ] {
+        let rx = test
+            .index
+            .write()
+            .pod_server_rx("ns-0", pod, 8080.try_into().unwrap())
+            .expect("pod should exist");
+        test.index.write().apply(mk_server(
+            "ns-0",
+            srv,
+            Port::Number(8080.try_into().unwrap()),
+            None,
+            Some(("app", app)),
+            Some(k8s::policy::server::ProxyProtocol::Http1),
+        ));
+        rxs.push((srv, rx));
+    }
+
+    // A single policy targets two of the three servers.
+    let mut policy = mk_authorization_policy(
+        "ns-0",
+        "authz-foo",
+        None::<&str>,
+        Some(NamespacedTargetRef {
+            group: Some("policy.linkerd.io".to_string()),
+            kind: "NetworkAuthentication".to_string(),
+            name: "net-foo".to_string(),
+            namespace: None,
+        }),
+    );
+    policy.spec.target_ref = None;
+    policy.spec.target_refs = Some(
+        ["srv-0", "srv-1"]
+            .into_iter()
+            .map(|srv| LocalTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "Server".to_string(),
+                name: srv.to_string(),
+            })
+            .collect(),
+    );
+    test.index.write().apply(policy);
+    test.index.write().apply(mk_network_authentication(
+        "ns-0".to_string(),
+        "net-foo".to_string(),
+        vec![k8s::policy::network_authentication::Network {
+            cidr: "10.0.0.0/8".parse().unwrap(),
+            except: None,
+        }],
+    ));
+
+    let authz = ClientAuthorization {
+        networks: vec!["10.0.0.0/8".parse::<IpNet>().unwrap().into()],
+        authentication: ClientAuthentication::Unauthenticated,
+    };
+    for (srv, rx) in rxs {
+        let expected = if srv == "srv-2" {
+            Default::default()
+        } else {
+            hashmap!(
+                AuthorizationRef::AuthorizationPolicy("authz-foo".to_string()) => authz.clone()
+            )
+            .into_iter()
+            .collect()
+        };
+        assert_eq!(
+            *rx.borrow(),
+            InboundServer {
+                reference: ServerRef::Server(srv.to_string()),
+                authorizations: expected,
+                protocol: ProxyProtocol::Http1,
+                http_routes: mk_default_routes(),
+            },
+            "{srv}",
+        );
+    }
+}
+
 fn mk_authorization_policy(
     ns: impl ToString,
     name: impl ToString,
@@ -402,7 +591,7 @@ fn mk_authorization_policy(
             ..Default::default()
         },
         spec: k8s::policy::AuthorizationPolicySpec {
-            target_ref: match server {
+            target_ref: Some(match server {
                 Some(server) => LocalTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
                     kind: "Server".to_string(),
@@ -413,7 +602,8 @@ fn mk_authorization_policy(
                     kind: "Namespace".to_string(),
                     name: ns.to_string(),
                 },
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: authns.into_iter().collect(),
         },
     }
@@ -444,6 +634,7 @@ fn mk_meshtls_authentication(
             } else {
                 Some(identity_refs)
             },
+            remote_identity_refs: None,
         },
     }
 }
@@ -488,6 +679,7 @@ fn mk_http_route(
                 }]),
             },
             rules: Some(vec![k8s::policy::httproute::HttpRouteRule {
+                name: None,
                 matches: Some(vec![k8s::policy::httproute::HttpRouteMatch {
                     path: Some(k8s_gateway_api::HttpPathMatch::PathPrefix {
                         value: "/foo".to_string(),
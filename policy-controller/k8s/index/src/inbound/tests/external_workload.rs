@@ -0,0 +1,163 @@
+use super::*;
+use linkerd_policy_controller_k8s_api::external_workload::{
+    ExternalWorkload, ExternalWorkloadSpec, MeshTls, PortSpec,
+};
+
+#[test]
+fn shared_server_by_identity() {
+    let test = TestConfig::default();
+
+    // Two VMs in the same group share a Server that selects their named port.
+    for vm in ["vm-0", "vm-1"] {
+        test.index
+            .write()
+            .apply(mk_external_workload("ns-0", vm, [("app", "vms")]));
+    }
+    let mut srv = mk_server(
+        "ns-0",
+        "srv-admin",
+        Port::Name("admin-http".to_string()),
+        None,
+        None,
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    );
+    srv.spec.selector =
+        k8s::policy::server::Selector::ExternalWorkload(Some(("app", "vms")).into_iter().collect());
+    test.index.write().apply(srv);
+
+    for vm in ["vm-0", "vm-1"] {
+        let rx = test
+            .index
+            .write()
+            .external_workload_identity_server_rx(
+                "ns-0",
+                &format!("{vm}.ns-0.serviceaccount.identity.linkerd.cluster.local"),
+                9990.try_into().unwrap(),
+            )
+            .expect("workload should be found by identity");
+        assert_eq!(
+            rx.borrow().reference,
+            ServerRef::Server("srv-admin".to_string())
+        );
+    }
+
+    // Unselected ports declared by the workload are served defaults.
+    let rx = test
+        .index
+        .write()
+        .external_workload_identity_server_rx(
+            "ns-0",
+            "vm-0.ns-0.serviceaccount.identity.linkerd.cluster.local",
+            8080.try_into().unwrap(),
+        )
+        .expect("workload should be found by identity");
+    assert_eq!(*rx.borrow(), test.default_server());
+
+    test.index
+        .write()
+        .external_workload_identity_server_rx(
+            "ns-0",
+            "vm-0.ns-1.serviceaccount.identity.linkerd.cluster.local",
+            9990.try_into().unwrap(),
+        )
+        .expect_err("identities are resolved within a namespace");
+
+    IndexNamespacedResource::<ExternalWorkload>::delete(
+        &mut *test.index.write(),
+        "ns-0".to_string(),
+        "vm-0".to_string(),
+    );
+    test.index
+        .write()
+        .external_workload_identity_server_rx(
+            "ns-0",
+            "vm-0.ns-0.serviceaccount.identity.linkerd.cluster.local",
+            9990.try_into().unwrap(),
+        )
+        .expect_err("deleted workloads must not be found by identity");
+}
+
+#[test]
+fn probe_paths_annotated() {
+    let test = TestConfig::from_default_policy_with_probes(
+        DefaultPolicy::Deny,
+        vec!["10.0.0.1/24".parse().unwrap()],
+    );
+
+    let mut vm = mk_external_workload("ns-0", "vm-0", [("app", "vms")]);
+    vm.metadata.annotations = Some(
+        Some((
+            "config.linkerd.io/probe-paths".to_string(),
+            "8080:/healthz".to_string(),
+        ))
+        .into_iter()
+        .collect(),
+    );
+    test.index.write().apply(vm);
+
+    let rx = test
+        .index
+        .write()
+        .external_workload_server_rx("ns-0", "vm-0", 8080.try_into().unwrap())
+        .expect("workload should exist");
+    let probe = &rx.borrow().http_routes[&HttpRouteRef::Default("probe")];
+    assert_eq!(
+        probe.rules[0].matches[0].path,
+        Some(linkerd_policy_controller_core::routes::PathMatch::Exact(
+            "/healthz".to_string()
+        )),
+    );
+
+    // Ports without annotated paths have no probe routes.
+    let rx = test
+        .index
+        .write()
+        .external_workload_server_rx("ns-0", "vm-0", 9990.try_into().unwrap())
+        .expect("workload should exist");
+    assert!(!rx
+        .borrow()
+        .http_routes
+        .contains_key(&HttpRouteRef::Default("probe")));
+}
+
+fn mk_external_workload(
+    ns: impl ToString,
+    name: impl ToString,
+    labels: impl IntoIterator<Item = (&'static str, &'static str)>,
+) -> ExternalWorkload {
+    let name = name.to_string();
+    let ns = ns.to_string();
+    ExternalWorkload {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.clone()),
+            name: Some(name.clone()),
+            labels: Some(
+                labels
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+            ..Default::default()
+        },
+        spec: ExternalWorkloadSpec {
+            mesh_tls: MeshTls {
+                identity: format!("{name}.{ns}.serviceaccount.identity.linkerd.cluster.local"),
+                server_name: format!("{name}.{ns}.cluster.local"),
+            },
+            ports: Some(vec![
+                PortSpec {
+                    name: Some("admin-http".to_string()),
+                    port: 9990.try_into().unwrap(),
+                    protocol: None,
+                },
+                PortSpec {
+                    name: None,
+                    port: 8080.try_into().unwrap(),
+                    protocol: Some("TCP".to_string()),
+                },
+            ]),
+            workload_ips: None,
+        },
+        status: None,
+    }
+}
@@ -81,6 +81,100 @@ fn route_attaches_to_server() {
         )));
 }
 
+#[test]
+fn route_attaches_to_gateway_pods() {
+    let test = TestConfig::default();
+    let port = ContainerPort {
+        name: Some("http".to_string()),
+        container_port: 8080,
+        ..Default::default()
+    };
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", Some(port))));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    pod.labels_mut().insert(
+        "gateway.networking.k8s.io/gateway-name".to_string(),
+        "ingress".to_string(),
+    );
+    test.index.write().apply(pod);
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    assert_eq!(rx.borrow_and_update().http_routes, mk_default_routes());
+
+    // Route the Gateway's `http` listener, which the pod serves on port 8080.
+    let gateway = k8s::gateway::ParentReference {
+        group: Some("gateway.networking.k8s.io".to_string()),
+        kind: Some("Gateway".to_string()),
+        namespace: None,
+        name: "ingress".to_string(),
+        section_name: Some("http".to_string()),
+        port: Some(8080),
+    };
+    let mut route = mk_route("ns-0", "route-foo", "srv-8080");
+    route.spec.inner.parent_refs = Some(vec![gateway.clone()]);
+    let status = route.status.take().unwrap();
+    test.index.write().apply(route.clone());
+    assert!(!rx.has_changed().unwrap());
+
+    // The Gateway's own controller accepting the route does not attach it.
+    let mut parent_status = status.inner.parents[0].clone();
+    parent_status.parent_ref = gateway.clone();
+    parent_status.controller_name = "example.com/gateway-controller".to_string();
+    route.status = Some(k8s::policy::httproute::HttpRouteStatus {
+        inner: k8s::gateway::RouteStatus {
+            parents: vec![parent_status.clone()],
+        },
+    });
+    test.index.write().apply(route.clone());
+    assert!(!rx.has_changed().unwrap());
+
+    // The route is attached once the policy controller accepts it.
+    parent_status.controller_name = POLICY_CONTROLLER_NAME.to_string();
+    route.status = Some(k8s::policy::httproute::HttpRouteStatus {
+        inner: k8s::gateway::RouteStatus {
+            parents: vec![parent_status.clone()],
+        },
+    });
+    test.index.write().apply(route);
+    assert!(rx.has_changed().unwrap());
+    let routes = rx.borrow_and_update().http_routes.clone();
+    assert_eq!(
+        routes.keys().collect::<Vec<_>>(),
+        [&HttpRouteRef::Linkerd(gkn_for_linkerd_http_route(
+            "route-foo".to_string()
+        ))],
+        "the Gateway's routes replace the default routes"
+    );
+
+    // Routes for the Gateway's other listeners are not attached to the port.
+    let listener = k8s::gateway::ParentReference {
+        port: Some(9090),
+        section_name: None,
+        ..gateway
+    };
+    let mut route = mk_route("ns-0", "route-bar", "srv-8080");
+    route.spec.inner.parent_refs = Some(vec![listener.clone()]);
+    parent_status.parent_ref = listener;
+    route.status = Some(k8s::policy::httproute::HttpRouteStatus {
+        inner: k8s::gateway::RouteStatus {
+            parents: vec![parent_status],
+        },
+    });
+    test.index.write().apply(route);
+    assert!(!rx.has_changed().unwrap());
+}
+
 #[test]
 fn routes_created_for_probes() {
     let policy = DefaultPolicy::Allow {
@@ -209,6 +303,143 @@ fn routes_created_for_probes() {
         .contains_key(&HttpRouteRef::Default("probes")));
 }
 
+#[test]
+fn proxy_admin_probes_authorized_with_routes() {
+    let policy = DefaultPolicy::Allow {
+        authenticated_only: false,
+        cluster_only: true,
+    };
+    let probe_networks = vec!["10.0.0.1/24".parse().unwrap()];
+    let test = TestConfig::from_default_policy_with_probes(policy, probe_networks);
+
+    let mut pod = mk_pod_with_containers("ns-0", "pod-0", Some(mk_proxy_container(Some("/live"))));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().apply(pod);
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 4191.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let probe_match = |path: &str| HttpRouteMatch {
+        path: Some(PathMatch::Exact(path.to_string())),
+        headers: vec![],
+        query_params: vec![],
+        method: Some(Method::GET),
+    };
+
+    // Without a Server, only the declared probe paths are authorized.
+    assert_eq!(
+        rx.borrow_and_update().http_routes[&HttpRouteRef::Default("probe")].rules[0].matches,
+        vec![probe_match("/live")],
+    );
+
+    // A Server with its own routes restricts access to the admin server, but
+    // the proxy's probe paths remain authorized.
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-admin",
+        Port::Number(4191.try_into().unwrap()),
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    test.index
+        .write()
+        .apply(mk_route("ns-0", "route-metrics", "srv-admin"));
+    assert!(rx.has_changed().unwrap());
+    let update = rx.borrow_and_update();
+    assert!(update
+        .http_routes
+        .contains_key(&HttpRouteRef::Linkerd(gkn_for_linkerd_http_route(
+            "route-metrics".to_string()
+        ))));
+    assert_eq!(
+        update.http_routes[&HttpRouteRef::Default("probe")].rules[0].matches,
+        vec![probe_match("/live"), probe_match("/ready")],
+    );
+}
+
+#[test]
+fn routes_limited_per_server() {
+    let mut test = TestConfig::default();
+    test.cluster.max_routes_per_parent = Some(1);
+    test.cluster.max_backends_per_route = Some(1);
+    test.index = Index::shared(test.cluster.clone());
+
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().apply(pod);
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let route_names = |server: &InboundServer| {
+        let mut names = server
+            .http_routes
+            .keys()
+            .filter_map(|r| match r {
+                HttpRouteRef::Linkerd(gkn) => Some(gkn.name.to_string()),
+                HttpRouteRef::Default(_) => None,
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    // Only the oldest route is attached to the server, even though the status
+    // of each route indicates that it has been accepted.
+    let created = chrono::Utc::now();
+    for (name, age) in [("route-b", 1), ("route-a", 2)] {
+        let mut route = mk_route("ns-0", name, "srv-8080");
+        route.metadata.creation_timestamp =
+            Some(k8s::Time(created - chrono::Duration::seconds(age)));
+        test.index.write().apply(route);
+    }
+    assert_eq!(route_names(&rx.borrow_and_update()), vec!["route-a"]);
+
+    // When the oldest route is removed, the next route is attached.
+    <Index as kubert::index::IndexNamespacedResource<k8s::policy::HttpRoute>>::delete(
+        &mut test.index.write(),
+        "ns-0".to_string(),
+        "route-a".to_string(),
+    );
+    assert_eq!(route_names(&rx.borrow_and_update()), vec!["route-b"]);
+
+    // Routes that reference too many backends are not attached.
+    let mut route = mk_route("ns-0", "route-b", "srv-8080");
+    route.metadata.creation_timestamp = Some(k8s::Time(created));
+    let backend = |name: &str| k8s::gateway::HttpBackendRef {
+        backend_ref: Some(k8s::gateway::BackendRef {
+            weight: None,
+            inner: k8s::gateway::BackendObjectReference {
+                group: None,
+                kind: None,
+                name: name.to_string(),
+                namespace: None,
+                port: Some(8080),
+            },
+        }),
+        filters: None,
+    };
+    route.spec.rules.as_mut().unwrap()[0].backend_refs =
+        Some(vec![backend("backend-0"), backend("backend-1")]);
+    test.index.write().apply(route);
+    assert!(route_names(&rx.borrow_and_update()).is_empty());
+}
+
 fn mk_route(
     ns: impl ToString,
     name: impl ToString,
@@ -237,6 +468,7 @@ fn mk_route(
             },
             hostnames: None,
             rules: Some(vec![HttpRouteRule {
+                name: None,
                 matches: Some(vec![HttpRouteMatch {
                     path: Some(HttpPathMatch::PathPrefix {
                         value: "/foo/bar".to_string(),
@@ -288,11 +520,12 @@ fn mk_authorization_policy(
             ..Default::default()
         },
         spec: k8s::policy::AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some(POLICY_API_GROUP.to_string()),
                 kind: "HttpRoute".to_string(),
                 name: route.to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: authns.into_iter().collect(),
         },
     }
@@ -74,6 +74,140 @@ fn link_server_authz(selector: ServerSelector) {
         .contains_key(&AuthorizationRef::ServerAuthorization(
             "authz-foo".to_string()
         )));
+
+    let server = test
+        .index
+        .read()
+        .server("ns-0", "srv-8080")
+        .expect("srv-8080.ns-0 should exist");
+    assert_eq!(server.authorizations, rx.borrow().authorizations);
+    assert!(test.index.read().server("ns-0", "srv-8081").is_none());
+}
+
+#[test]
+fn limits_server_authzs() {
+    let mut test = TestConfig::default();
+    test.cluster.max_authorizations_per_server = Some(2);
+    test.index = Index::shared(test.cluster.clone());
+
+    let mut pod = mk_pod("ns-0", "pod-0", Some(("container-0", None)));
+    pod.labels_mut()
+        .insert("app".to_string(), "app-0".to_string());
+    test.index.write().apply(pod);
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+
+    for name in ["authz-c", "authz-a", "authz-b"] {
+        test.index.write().apply(mk_server_authz(
+            "ns-0",
+            name,
+            ServerSelector::Name("srv-8080".to_string()),
+            k8s::policy::server_authorization::Client {
+                networks: None,
+                unauthenticated: true,
+                mesh_tls: None,
+            },
+        ));
+    }
+
+    // Authorizations in excess of the limit are ignored in a stable order.
+    let rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let mut authzs = rx
+        .borrow()
+        .authorizations
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    authzs.sort_by_key(|r| format!("{r:?}"));
+    assert_eq!(
+        authzs,
+        vec![
+            AuthorizationRef::ServerAuthorization("authz-a".to_string()),
+            AuthorizationRef::ServerAuthorization("authz-b".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn server_authz_identities_follow_trust_domain() {
+    let test = TestConfig::default();
+    test.index
+        .write()
+        .apply(mk_pod("ns-0", "pod-0", Some(("container-0", None))));
+    test.index.write().apply(mk_server(
+        "ns-0",
+        "srv-8080",
+        Port::Number(8080.try_into().unwrap()),
+        None,
+        None,
+        Some(k8s::policy::server::ProxyProtocol::Http1),
+    ));
+    test.index.write().apply(mk_server_authz(
+        "ns-0",
+        "authz-foo",
+        ServerSelector::Name("srv-8080".to_string()),
+        k8s::policy::server_authorization::Client {
+            networks: None,
+            unauthenticated: false,
+            mesh_tls: Some(k8s::policy::server_authorization::MeshTls {
+                identities: Some(vec!["foo.bar".to_string()]),
+                service_accounts: Some(vec![
+                    k8s::policy::server_authorization::ServiceAccountRef {
+                        namespace: None,
+                        name: "sa-0".to_string(),
+                    },
+                ]),
+                ..Default::default()
+            }),
+        },
+    ));
+
+    let mut rx = test
+        .index
+        .write()
+        .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+        .expect("pod-0.ns-0 should exist");
+    let authn = |rx: &tokio::sync::watch::Receiver<InboundServer>| {
+        rx.borrow().authorizations[&AuthorizationRef::ServerAuthorization("authz-foo".to_string())]
+            .authentication
+            .clone()
+    };
+    assert_eq!(
+        authn(&rx),
+        ClientAuthentication::TlsAuthenticated(vec![
+            IdentityMatch::Exact("foo.bar".to_string()),
+            IdentityMatch::Exact(
+                "sa-0.ns-0.serviceaccount.identity.linkerd.cluster.example.com".to_string()
+            ),
+        ])
+    );
+    rx.borrow_and_update();
+
+    // Identities derived from service accounts are updated when the trust
+    // domain changes, but explicit identities are not.
+    test.index
+        .write()
+        .set_identity_domain("new.example.com".to_string());
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        authn(&rx),
+        ClientAuthentication::TlsAuthenticated(vec![
+            IdentityMatch::Exact("foo.bar".to_string()),
+            IdentityMatch::Exact(
+                "sa-0.ns-0.serviceaccount.identity.linkerd.new.example.com".to_string()
+            ),
+        ])
+    );
 }
 
 fn mk_server_authz(
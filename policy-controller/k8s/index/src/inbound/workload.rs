@@ -1,10 +1,20 @@
 use crate::defaults::DefaultPolicy;
 use crate::ports::{parse_portset, PortMap, PortSet};
 use ahash::AHashMap as HashMap;
-use anyhow::Result;
+use anyhow::{bail, ensure, Result};
 use linkerd_policy_controller_k8s_api as k8s;
 use std::{collections::BTreeSet, num::NonZeroU16};
 
+const PROXY_CONTAINER_NAME: &str = "linkerd-proxy";
+const PROXY_VERSION_ANNOTATION: &str = "linkerd.io/proxy-version";
+const PROXY_ADMIN_LISTEN_ADDR_ENV: &str = "LINKERD2_PROXY_ADMIN_LISTEN_ADDR";
+const PROXY_CONTROL_LISTEN_ADDR_ENV: &str = "LINKERD2_PROXY_CONTROL_LISTEN_ADDR";
+
+const PROBE_PATHS_ANNOTATION: &str = "config.linkerd.io/probe-paths";
+
+/// The paths on which the kubelet probes the proxy's admin server.
+pub(crate) const PROXY_ADMIN_PROBE_PATHS: [&str; 2] = ["/live", "/ready"];
+
 /// Holds workload metadata/config that can change.
 #[derive(Debug, PartialEq)]
 pub(crate) struct Meta {
@@ -21,6 +31,31 @@ pub(crate) struct Settings {
     pub require_id_ports: PortSet,
     pub opaque_ports: PortSet,
     pub default_policy: Option<DefaultPolicy>,
+
+    /// The port and path on which linked clusters probe the workload, if it is
+    /// a multicluster gateway.
+    pub gateway_probe: Option<(NonZeroU16, String)>,
+
+    /// Paths on which the workload is probed in addition to those declared by
+    /// its containers' probes, e.g. by sidecar health checkers or external
+    /// load balancers.
+    pub probe_paths: PortMap<BTreeSet<String>>,
+
+    /// The ports on which the workload's proxy serves its own admin and
+    /// control (tap) servers. Unlike other settings, these are read from the
+    /// pod spec.
+    pub proxy_ports: ProxyPorts,
+}
+
+/// The ports of a proxy's own servers, as configured in its container's
+/// environment.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct ProxyPorts {
+    /// The admin server, which serves probes, metrics, and shutdown requests.
+    pub admin: Option<NonZeroU16>,
+
+    /// The control server, which serves tap requests.
+    pub control: Option<NonZeroU16>,
 }
 
 /// Gets the set of named ports with `protocol: TCP` from a pod spec.
@@ -57,6 +92,71 @@ pub(crate) fn external_tcp_ports_by_name(
     ports
 }
 
+/// Gets the set of ports with `protocol: TCP` from an external workload spec,
+/// whether or not they are named.
+pub(crate) fn external_tcp_ports(spec: &k8s::external_workload::ExternalWorkloadSpec) -> PortSet {
+    spec.ports
+        .iter()
+        .flatten()
+        .filter(|p| {
+            p.protocol
+                .as_deref()
+                .map_or(true, |proto| proto.eq_ignore_ascii_case("TCP"))
+        })
+        .map(|p| p.port)
+        .collect()
+}
+
+/// Returns true if all of a pod's containers have terminated and will not be
+/// restarted, i.e. if the pod's phase is `Succeeded` or `Failed`.
+pub(crate) fn pod_completed(pod: &k8s::Pod) -> bool {
+    matches!(
+        pod.status.as_ref().and_then(|s| s.phase.as_deref()),
+        Some("Succeeded" | "Failed")
+    )
+}
+
+/// Returns true if a pod has been injected with a proxy, i.e. if it has a
+/// proxy container (or a native sidecar proxy init container) or the proxy
+/// injector's version annotation.
+pub(crate) fn pod_meshed(pod: &k8s::Pod) -> bool {
+    let annotated = pod
+        .metadata
+        .annotations
+        .as_ref()
+        .map_or(false, |a| a.contains_key(PROXY_VERSION_ANNOTATION));
+    annotated
+        || pod.spec.as_ref().map_or(false, |spec| {
+            spec.containers
+                .iter()
+                .chain(spec.init_containers.iter().flatten())
+                .any(|c| c.name == PROXY_CONTAINER_NAME)
+        })
+}
+
+/// Gets the ports of a pod's proxy admin and control servers from its proxy
+/// container's environment.
+pub(crate) fn pod_proxy_ports(spec: &k8s::PodSpec) -> ProxyPorts {
+    let Some(proxy) = spec
+        .containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+        .find(|c| c.name == PROXY_CONTAINER_NAME)
+    else {
+        return ProxyPorts::default();
+    };
+
+    let listen_port = |name: &str| {
+        let env = proxy.env.iter().flatten().find(|e| e.name == name)?;
+        let (_, port) = env.value.as_deref()?.rsplit_once(':')?;
+        port.parse::<NonZeroU16>().ok()
+    };
+    ProxyPorts {
+        admin: listen_port(PROXY_ADMIN_LISTEN_ADDR_ENV),
+        control: listen_port(PROXY_CONTROL_LISTEN_ADDR_ENV),
+    }
+}
+
 /// Gets the container probe ports for a Pod.
 ///
 /// The result is a mapping for each probe port exposed by a container in the
@@ -154,6 +254,8 @@ impl Settings {
     /// - Opaque ports
     /// - Ports that require identity
     /// - The pod's default policy
+    /// - The multicluster gateway probe
+    /// - Additional probe paths
     pub(crate) fn from_metadata(meta: &k8s::ObjectMeta) -> Self {
         let anns = match meta.annotations.as_ref() {
             None => return Self::default(),
@@ -173,12 +275,34 @@ impl Settings {
         )
         .unwrap_or_default();
 
+        let gateway_probe = gateway_probe(anns).unwrap_or_else(|error| {
+            tracing::warn!(%error, "invalid gateway probe annotation value");
+            None
+        });
+
+        let probe_paths = probe_paths(anns).unwrap_or_else(|error| {
+            tracing::warn!(%error, "invalid probe paths annotation value");
+            Default::default()
+        });
+
         Self {
             default_policy,
             opaque_ports,
             require_id_ports,
+            gateway_probe,
+            probe_paths,
+            proxy_ports: ProxyPorts::default(),
         }
     }
+
+    /// Returns the additional paths on which the given port is probed.
+    pub(crate) fn probe_paths(&self, port: NonZeroU16) -> impl Iterator<Item = &str> {
+        self.probe_paths
+            .get(&port)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
 }
 
 /// Attempts to read a default policy override from an annotation map.
@@ -193,6 +317,48 @@ fn default_policy(
     Ok(None)
 }
 
+/// Reads a multicluster gateway's probe port and path from an annotation map.
+/// Both must be set for the probe to be authorized.
+fn gateway_probe(
+    ann: &std::collections::BTreeMap<String, String>,
+) -> Result<Option<(NonZeroU16, String)>> {
+    let (Some(port), Some(path)) = (
+        ann.get("mirror.linkerd.io/probe-port"),
+        ann.get("mirror.linkerd.io/probe-path"),
+    ) else {
+        return Ok(None);
+    };
+    let port = port.parse::<NonZeroU16>()?;
+    let path = http::Uri::try_from(path.as_str())?.path().to_string();
+    Ok(Some((port, path)))
+}
+
+/// Reads additional probe paths from an annotation map. The annotation's value
+/// is a comma-separated list of `<port>:<path>` pairs, e.g.
+/// `8080:/healthz,9090:/status`.
+fn probe_paths(
+    ann: &std::collections::BTreeMap<String, String>,
+) -> Result<PortMap<BTreeSet<String>>> {
+    let mut paths = PortMap::<BTreeSet<String>>::default();
+    let Some(spec) = ann.get(PROBE_PATHS_ANNOTATION) else {
+        return Ok(paths);
+    };
+    for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((port, path)) = pair.split_once(':') else {
+            bail!("expected <port>:<path>, got {pair:?}");
+        };
+        let port = port.trim().parse::<NonZeroU16>()?;
+        let path = path.trim();
+        ensure!(
+            path.starts_with('/'),
+            "probe path must be absolute: {path:?}"
+        );
+        let path = http::Uri::try_from(path)?.path().to_string();
+        paths.entry(port).or_default().insert(path);
+    }
+    Ok(paths)
+}
+
 /// Reads `annotation` from the provided set of annotations, parsing it as a port set.  If the
 /// annotation is not set or is invalid, the empty set is returned.
 pub(crate) fn ports_annotation(
@@ -212,6 +378,74 @@ mod tests {
     use super::*;
     use linkerd_policy_controller_k8s_api as k8s;
 
+    #[test]
+    fn proxy_ports_from_env() {
+        let env = |name: &str, value: &str| k8s::api::core::v1::EnvVar {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+            ..Default::default()
+        };
+        let spec = k8s::PodSpec {
+            containers: vec![
+                k8s::Container {
+                    name: "app".to_string(),
+                    env: Some(vec![env(PROXY_ADMIN_LISTEN_ADDR_ENV, "0.0.0.0:9999")]),
+                    ..Default::default()
+                },
+                k8s::Container {
+                    name: PROXY_CONTAINER_NAME.to_string(),
+                    env: Some(vec![
+                        env(PROXY_ADMIN_LISTEN_ADDR_ENV, "[::]:4191"),
+                        env(PROXY_CONTROL_LISTEN_ADDR_ENV, "0.0.0.0:4190"),
+                    ]),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            pod_proxy_ports(&spec),
+            ProxyPorts {
+                admin: NonZeroU16::new(4191),
+                control: NonZeroU16::new(4190),
+            }
+        );
+
+        let spec = k8s::PodSpec {
+            containers: vec![spec.containers[0].clone()],
+            ..Default::default()
+        };
+        assert_eq!(pod_proxy_ports(&spec), ProxyPorts::default());
+    }
+
+    #[test]
+    fn probe_paths_annotation() {
+        let ann = |v: &str| {
+            Some((PROBE_PATHS_ANNOTATION.to_string(), v.to_string()))
+                .into_iter()
+                .collect()
+        };
+
+        let paths = probe_paths(&ann("8080:/healthz, 8080:/readyz?full=1,9090:/status,"))
+            .expect("annotation must parse");
+        assert_eq!(
+            paths.get(&NonZeroU16::new(8080).unwrap()),
+            Some(&BTreeSet::from([
+                "/healthz".to_string(),
+                "/readyz".to_string()
+            ]))
+        );
+        assert_eq!(
+            paths.get(&NonZeroU16::new(9090).unwrap()),
+            Some(&BTreeSet::from(["/status".to_string()]))
+        );
+
+        for invalid in ["/healthz", "0:/healthz", "http:/healthz", "8080:healthz"] {
+            assert!(probe_paths(&ann(invalid)).is_err(), "{invalid}");
+        }
+        assert!(probe_paths(&Default::default()).unwrap().is_empty());
+    }
+
     #[test]
     fn probe_multiple_paths() {
         let probes = pod_http_probes(&k8s::PodSpec {
@@ -29,7 +29,8 @@ pub mod http_route;
 pub mod inbound;
 pub mod outbound;
 pub mod ports;
+mod size;
 
-pub use cluster_info::ClusterInfo;
+pub use cluster_info::{ClusterInfo, MAX_DETECT_TIMEOUT};
 pub use defaults::DefaultPolicy;
 pub use inbound::authorization_policy;
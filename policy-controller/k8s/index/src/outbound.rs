@@ -1,6 +1,6 @@
 pub mod index;
 
-pub use index::{metrics, Index, ServiceRef, SharedIndex};
+pub use index::{dump, metrics, Index, ServiceRef, SharedIndex};
 
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,8 @@
 use crate::{
-    http_route::{self, gkn_for_gateway_http_route, gkn_for_linkerd_http_route, HttpRouteResource},
-    ports::{ports_annotation, PortSet},
+    http_route::{
+        self, gkn_for_gateway_http_route, gkn_for_linkerd_http_route, HttpRouteResource, ParseCache,
+    },
+    ports::{parse_portset, PortMap, PortSet},
     ClusterInfo,
 };
 use ahash::AHashMap as HashMap;
@@ -8,25 +10,86 @@ use anyhow::{bail, ensure, Result};
 use k8s_gateway_api::{BackendObjectReference, HttpBackendRef, ParentReference};
 use linkerd_policy_controller_core::{
     outbound::{
-        Backend, Backoff, FailureAccrual, Filter, HttpRoute, HttpRouteRule, OutboundPolicy,
-        WeightedService,
+        AppProtocol, Backend, Backoff, FailureAccrual, Filter, HttpRoute, HttpRouteRule,
+        OutboundPolicy, RetryBudget, WeightedService,
     },
-    routes::GroupKindNamespaceName,
+    routes::{GroupKindNamespaceName, HostMatch, HttpRouteMatch, Quarantine},
+};
+use linkerd_policy_controller_k8s_api::{
+    api::discovery::v1::EndpointSlice, policy as api, ResourceExt, Service, ServiceSpec, Time,
 };
-use linkerd_policy_controller_k8s_api::{policy as api, ResourceExt, Service, Time};
 use parking_lot::RwLock;
-use std::{hash::Hash, net::IpAddr, num::NonZeroU16, sync::Arc, time};
+use std::{
+    collections::{hash_map::Entry, BTreeSet},
+    hash::Hash,
+    net::IpAddr,
+    num::NonZeroU16,
+    sync::Arc,
+    time,
+};
 use tokio::sync::watch;
+use tracing::info_span;
 
 #[derive(Debug)]
 pub struct Index {
     namespaces: NamespaceIndex,
     services_by_ip: HashMap<IpAddr, ServiceRef>,
     service_info: HashMap<ServiceRef, ServiceInfo>,
+    parsed_routes: ParseCache<ParsedRoute>,
+
+    /// The endpoints of each EndpointSlice, by namespace and EndpointSlice
+    /// name.
+    endpoint_slices: HashMap<String, HashMap<String, EndpointSliceInfo>>,
+
+    /// The EndpointSlices that include each endpoint address, by namespace and
+    /// EndpointSlice name.
+    endpoint_slices_by_ip: HashMap<IpAddr, BTreeSet<(String, String)>>,
+
+    /// The UID of the pod that currently owns each endpoint address. Pod IPs
+    /// may be reused by a new pod before the old pod's endpoints have been
+    /// removed, so lookups are resolved against the current owner and watches
+    /// that were resolved for a previous owner are reset.
+    endpoint_owners: HashMap<IpAddr, watch::Sender<String>>,
+
+    /// The original Service of each Service that the multicluster extension
+    /// has mirrored from a linked cluster.
+    mirrors: HashMap<ServiceRef, ServiceRef>,
+
+    /// Routes that opt in to being applied to the mirrors of their parent
+    /// Services, so that they may be bound as mirrors come and go.
+    mirrored_routes: HashMap<GroupKindNamespaceName, HttpRouteResource>,
 }
 
+pub mod dump;
 pub mod metrics;
 
+/// Identifies the Service that an EndpointSlice belongs to.
+const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+const OPAQUE_PORTS_ANNOTATION: &str = "config.linkerd.io/opaque-ports";
+
+/// Routes annotated with `policy.linkerd.io/apply-to-mirrors: "true"` also
+/// apply to the mirrors of their parent Services.
+const APPLY_TO_MIRRORS_ANNOTATION: &str = "policy.linkerd.io/apply-to-mirrors";
+
+/// Identifies Services that the multicluster extension mirrors from a linked
+/// cluster, and the name of that cluster. A mirror is named after its original
+/// Service, suffixed with the cluster name.
+const MIRRORED_SERVICE_LABEL: &str = "mirror.linkerd.io/mirrored-service";
+const MIRROR_CLUSTER_NAME_LABEL: &str = "mirror.linkerd.io/cluster-name";
+
+/// Identifies Services that are mirrored in remote-discovery mode, whose
+/// endpoints are resolved from the linked cluster rather than locally, and the
+/// name of the Service in that cluster.
+const REMOTE_DISCOVERY_LABEL: &str = "multicluster.linkerd.io/remote-discovery";
+const REMOTE_SERVICE_LABEL: &str = "multicluster.linkerd.io/remote-service";
+
+/// Distributes a Service's traffic across clusters, e.g. `local=1,east=3`.
+/// `local` refers to the Service itself; other entries name linked clusters
+/// from which the Service is mirrored in remote-discovery mode.
+const CLUSTER_WEIGHTS_ANNOTATION: &str = "multicluster.linkerd.io/cluster-weights";
+const LOCAL_CLUSTER: &str = "local";
+
 pub type SharedIndex = Arc<RwLock<Index>>;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -57,7 +120,50 @@ struct Namespace {
 #[derive(Debug, Default)]
 struct ServiceInfo {
     opaque_ports: PortSet,
+    app_protocols: PortMap<AppProtocol>,
     accrual: Option<FailureAccrual>,
+    retry_budget: Option<RetryBudget>,
+    detect_timeout: time::Duration,
+    ready: bool,
+
+    /// The zones of the Service's ready endpoints. See
+    /// [`EndpointSliceInfo::zones`].
+    zones: Vec<String>,
+
+    /// The Service's ports, by port name. Unnamed ports have an empty name.
+    ports_by_name: HashMap<String, NonZeroU16>,
+
+    /// The linked cluster and remote Service name, if this Service is mirrored
+    /// in remote-discovery mode.
+    remote_discovery: Option<(String, String)>,
+
+    /// The weights with which traffic is distributed across clusters.
+    cluster_weights: Vec<(String, u32)>,
+
+    /// Errors in the Service's balancer and retry annotations. Invalid
+    /// configuration is ignored.
+    errors: Vec<String>,
+}
+
+/// The parts of an EndpointSlice that describe a Service's endpoints.
+#[derive(Debug)]
+struct EndpointSliceInfo {
+    service: String,
+    ready: usize,
+    addrs: Vec<IpAddr>,
+
+    /// The zones for which the slice's ready endpoints are hinted. Endpoints
+    /// without hints contribute the zone in which they are located.
+    zones: BTreeSet<String>,
+
+    /// The UID of the pod that each address refers to, for endpoints that
+    /// reference a pod.
+    owners: HashMap<IpAddr, String>,
+
+    /// The name of each Service port and the target port to which it is
+    /// mapped on the endpoints. Named target ports are resolved by the
+    /// EndpointSlice controller, so these ports are always numeric.
+    target_ports: Vec<(String, NonZeroU16)>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -74,13 +180,35 @@ struct ServiceRoutes {
     authority: String,
     watches_by_ns: HashMap<String, RoutesWatch>,
     opaque: bool,
+    app_protocol: Option<AppProtocol>,
     accrual: Option<FailureAccrual>,
+    retry_budget: Option<RetryBudget>,
+    detect_timeout: time::Duration,
+    cluster_backends: Vec<WeightedService>,
+}
+
+/// The parts of an HTTPRoute that do not depend on the namespace or services to
+/// which it is bound.
+#[derive(Debug)]
+struct ParsedRoute {
+    hostnames: Vec<HostMatch>,
+    rules: Vec<ParsedRule>,
+}
+
+#[derive(Debug)]
+struct ParsedRule {
+    matches: Vec<HttpRouteMatch>,
+    filters: Vec<Filter>,
 }
 
 #[derive(Debug)]
 struct RoutesWatch {
     opaque: bool,
+    app_protocol: Option<AppProtocol>,
     accrual: Option<FailureAccrual>,
+    retry_budget: Option<RetryBudget>,
+    detect_timeout: time::Duration,
+    cluster_backends: Vec<WeightedService>,
     routes: HashMap<GroupKindNamespaceName, HttpRoute>,
     watch: watch::Sender<OutboundPolicy>,
 }
@@ -91,11 +219,15 @@ impl kubert::index::IndexNamespacedResource<api::HttpRoute> for Index {
     }
 
     fn delete(&mut self, namespace: String, name: String) {
+        let _span = info_span!("delete", ns = %namespace, %name).entered();
         let gknn = gkn_for_linkerd_http_route(name).namespaced(namespace);
         tracing::debug!(?gknn, "deleting route");
-        for ns_index in self.namespaces.by_ns.values_mut() {
+        self.parsed_routes.remove(&gknn);
+        self.mirrored_routes.remove(&gknn);
+        self.namespaces.by_ns.retain(|_, ns_index| {
             ns_index.delete(&gknn);
-        }
+            !ns_index.gc()
+        });
     }
 }
 
@@ -105,11 +237,15 @@ impl kubert::index::IndexNamespacedResource<k8s_gateway_api::HttpRoute> for Inde
     }
 
     fn delete(&mut self, namespace: String, name: String) {
+        let _span = info_span!("delete", ns = %namespace, %name).entered();
         let gknn = gkn_for_gateway_http_route(name).namespaced(namespace);
         tracing::debug!(?gknn, "deleting route");
-        for ns_index in self.namespaces.by_ns.values_mut() {
+        self.parsed_routes.remove(&gknn);
+        self.mirrored_routes.remove(&gknn);
+        self.namespaces.by_ns.retain(|_, ns_index| {
             ns_index.delete(&gknn);
-        }
+            !ns_index.gc()
+        });
     }
 }
 
@@ -117,13 +253,40 @@ impl kubert::index::IndexNamespacedResource<Service> for Index {
     fn apply(&mut self, service: Service) {
         let name = service.name_unchecked();
         let ns = service.namespace().expect("Service must have a namespace");
+        let _span = info_span!("apply", %ns, %name).entered();
         tracing::debug!(name, ns, "indexing service");
+        let mut errors = Vec::new();
         let accrual = parse_accrual_config(service.annotations())
-            .map_err(|error| tracing::error!(%error, service=name, namespace=ns, "failed to parse accrual config"))
+            .map_err(|error| {
+                tracing::error!(%error, service=name, namespace=ns, "failed to parse accrual config");
+                errors.push(format!("invalid failure accrual: {error}"));
+            })
+            .unwrap_or_default();
+        let retry_budget = parse_retry_budget(service.annotations())
+            .map_err(|error| {
+                tracing::error!(%error, service=name, namespace=ns, "failed to parse retry budget");
+                errors.push(format!("invalid retry budget: {error}"));
+            })
+            .unwrap_or_default();
+        let (tcp_ports, app_protocols) = service
+            .spec
+            .as_ref()
+            .map(service_app_protocols)
             .unwrap_or_default();
-        let opaque_ports =
-            ports_annotation(service.annotations(), "config.linkerd.io/opaque-ports")
-                .unwrap_or_else(|| self.namespaces.cluster_info.default_opaque_ports.clone());
+        // Ports are opaque if the annotation marks them opaque or if their
+        // `appProtocol` is TCP. Ports that declare an HTTP `appProtocol` are
+        // not made opaque by the cluster's defaults, though the annotation
+        // takes precedence over an `appProtocol`.
+        let mut opaque_ports = service_opaque_ports(&service).unwrap_or_else(|| {
+            let mut ports = self.namespaces.cluster_info.default_opaque_ports.clone();
+            ports.retain(|port| !app_protocols.contains_key(port));
+            ports
+        });
+        opaque_ports.extend(tcp_ports);
+        let detect_timeout = self
+            .namespaces
+            .cluster_info
+            .detect_timeout(service.annotations());
 
         if let Some(cluster_ips) = service
             .spec
@@ -149,9 +312,43 @@ impl kubert::index::IndexNamespacedResource<Service> for Index {
             }
         }
 
+        // Services in remote-discovery mode have no local endpoints; their
+        // endpoints are discovered in the linked cluster.
+        let remote_discovery = remote_discovery(&service);
+        let ready = remote_discovery.is_some() || self.has_ready_endpoints(&ns, &name);
+        let zones = self.endpoint_zones(&ns, &name);
+        let cluster_weights = parse_cluster_weights(service.annotations());
+        let ports_by_name = service
+            .spec
+            .iter()
+            .flat_map(|spec| spec.ports.iter().flatten())
+            .filter_map(|svc_port| {
+                let port = u16::try_from(svc_port.port)
+                    .ok()
+                    .and_then(|p| NonZeroU16::try_from(p).ok())?;
+                Some((svc_port.name.clone().unwrap_or_default(), port))
+            })
+            .collect();
         let service_info = ServiceInfo {
             opaque_ports,
+            app_protocols,
             accrual,
+            retry_budget,
+            detect_timeout,
+            ready,
+            zones,
+            ports_by_name,
+            remote_discovery,
+            cluster_weights,
+            errors,
+        };
+        let original = mirrored_service_name(&service).map(|original| ServiceRef {
+            name: original,
+            namespace: ns.clone(),
+        });
+        let service_ref = ServiceRef {
+            name: name.clone(),
+            namespace: ns.clone(),
         };
 
         self.namespaces
@@ -164,24 +361,115 @@ impl kubert::index::IndexNamespacedResource<Service> for Index {
             })
             .update_service(service.name_unchecked(), &service_info);
 
-        self.service_info.insert(
-            ServiceRef {
-                name: service.name_unchecked(),
-                namespace: service.namespace().expect("Service must have Namespace"),
-            },
-            service_info,
-        );
+        self.service_info.insert(service_ref.clone(), service_info);
+        self.update_mirror(service_ref, original);
 
         self.reindex_services()
     }
 
     fn delete(&mut self, namespace: String, name: String) {
+        let _span = info_span!("delete", ns = %namespace, %name).entered();
         tracing::debug!(name, namespace, "deleting service");
         let service_ref = ServiceRef { name, namespace };
         self.service_info.remove(&service_ref);
         self.services_by_ip.retain(|_, v| *v != service_ref);
+        self.update_mirror(service_ref.clone(), None);
 
-        self.reindex_services()
+        self.reindex_services();
+
+        if let Entry::Occupied(mut ns) = self.namespaces.by_ns.entry(service_ref.namespace) {
+            if ns.get_mut().gc() {
+                ns.remove();
+            }
+        }
+    }
+}
+
+// EndpointSlices are indexed to determine whether each Service has any ready
+// endpoints, so that backends that exist but cannot currently serve traffic
+// (e.g. because they have been scaled to zero) may be distinguished from
+// backends that do not exist. They also map the endpoints' target ports back
+// to the Service ports to which routes are bound.
+impl kubert::index::IndexNamespacedResource<EndpointSlice> for Index {
+    fn apply(&mut self, slice: EndpointSlice) {
+        let ns = slice
+            .namespace()
+            .expect("EndpointSlice must have a namespace");
+        let name = slice.name_unchecked();
+        let _span = info_span!("apply", %ns, %name).entered();
+
+        let Some(service) = slice.labels().get(SERVICE_NAME_LABEL).cloned() else {
+            // Slices that are not managed for a Service are ignored.
+            self.update_endpoint_slice(ns, name, None);
+            return;
+        };
+        // Endpoints whose readiness is unknown are considered ready.
+        let ready_endpoints = slice
+            .endpoints
+            .iter()
+            .filter(|ep| ep.conditions.as_ref().and_then(|c| c.ready).unwrap_or(true));
+        let ready = ready_endpoints.clone().count();
+        let zones = ready_endpoints
+            .flat_map(|ep| {
+                let hinted = ep
+                    .hints
+                    .as_ref()
+                    .and_then(|h| h.for_zones.as_ref())
+                    .filter(|zones| !zones.is_empty());
+                match hinted {
+                    Some(zones) => zones.iter().map(|z| z.name.clone()).collect(),
+                    None => ep.zone.clone().into_iter().collect::<Vec<_>>(),
+                }
+            })
+            .collect();
+        let addrs = slice
+            .endpoints
+            .iter()
+            .flat_map(|ep| ep.addresses.iter())
+            .filter_map(|addr| addr.parse().ok())
+            .collect();
+        let owners = slice
+            .endpoints
+            .iter()
+            .filter_map(|ep| {
+                let target = ep.target_ref.as_ref()?;
+                if target.kind.as_deref() != Some("Pod") {
+                    return None;
+                }
+                let uid = target.uid.clone()?;
+                Some(ep.addresses.iter().filter_map(move |addr| {
+                    let addr = addr.parse().ok()?;
+                    Some((addr, uid.clone()))
+                }))
+            })
+            .flatten()
+            .collect();
+        let target_ports = slice
+            .ports
+            .iter()
+            .flatten()
+            .filter_map(|ep_port| {
+                let port = ep_port
+                    .port
+                    .and_then(|p| u16::try_from(p).ok())
+                    .and_then(|p| NonZeroU16::try_from(p).ok())?;
+                Some((ep_port.name.clone().unwrap_or_default(), port))
+            })
+            .collect();
+        let info = EndpointSliceInfo {
+            service,
+            ready,
+            addrs,
+            zones,
+            owners,
+            target_ports,
+        };
+        self.update_endpoint_slice(ns, name, Some(info));
+    }
+
+    fn delete(&mut self, namespace: String, name: String) {
+        let _span = info_span!("delete", ns = %namespace, %name).entered();
+        self.update_endpoint_slice(namespace, name, None);
     }
 }
 
@@ -194,6 +482,12 @@ impl Index {
             },
             services_by_ip: HashMap::default(),
             service_info: HashMap::default(),
+            parsed_routes: ParseCache::default(),
+            endpoint_slices: HashMap::default(),
+            endpoint_slices_by_ip: HashMap::default(),
+            endpoint_owners: HashMap::default(),
+            mirrors: HashMap::default(),
+            mirrored_routes: HashMap::default(),
         }))
     }
 
@@ -224,44 +518,518 @@ impl Index {
         Ok(watch.watch.subscribe())
     }
 
+    /// Returns the policy that would be served for a Service port to clients
+    /// in the source namespace, without creating index state for it.
+    pub fn outbound_policy(
+        &self,
+        service_name: String,
+        service_namespace: String,
+        service_port: NonZeroU16,
+        source_namespace: &str,
+    ) -> OutboundPolicy {
+        let empty;
+        let ns = match self.namespaces.by_ns.get(&service_namespace) {
+            Some(ns) => ns,
+            None => {
+                empty = Namespace {
+                    service_routes: Default::default(),
+                    service_port_routes: Default::default(),
+                    namespace: Arc::new(service_namespace),
+                };
+                &empty
+            }
+        };
+        let key = ServicePort {
+            service: service_name,
+            port: service_port,
+        };
+        match ns.service_port_routes.get(&key) {
+            Some(routes) => routes.policy_for_ns(source_namespace),
+            None => Namespace::new_service_routes(
+                &ns.namespace,
+                &ns.service_routes,
+                key,
+                &self.namespaces.cluster_info,
+                &self.service_info,
+            )
+            .policy_for_ns(source_namespace),
+        }
+    }
+
+    /// Records the routes that this index fails to parse in the given
+    /// quarantine.
+    pub fn set_quarantine(&mut self, quarantine: Quarantine) {
+        self.parsed_routes.set_quarantine("outbound", quarantine);
+    }
+
+    /// Removes route watches that have neither routes nor subscribers and
+    /// namespaces that no longer hold any state, returning the number of
+    /// namespaces that were removed.
+    pub fn gc(&mut self) -> usize {
+        let before = self.namespaces.by_ns.len();
+        self.namespaces.by_ns.retain(|_, ns| !ns.gc());
+        before - self.namespaces.by_ns.len()
+    }
+
     pub fn lookup_service(&self, addr: IpAddr) -> Option<ServiceRef> {
         self.services_by_ip.get(&addr).cloned()
     }
 
+    /// Finds a Service that has an endpoint with the given address and target
+    /// port, returning the Service port that targets it.
+    ///
+    /// Routes are bound to Service ports, so traffic addressed directly to an
+    /// endpoint must be mapped through the Service's `targetPort`. If the
+    /// endpoint belongs to multiple Services, the first matching Service (by
+    /// namespace and EndpointSlice name) is used. Endpoints that refer to a
+    /// pod other than the address's current owner are ignored.
+    pub fn lookup_endpoint(
+        &self,
+        addr: IpAddr,
+        target_port: NonZeroU16,
+    ) -> Option<(ServiceRef, NonZeroU16)> {
+        let owner = self.endpoint_owner(addr);
+        self.endpoint_slices_by_ip
+            .get(&addr)?
+            .iter()
+            .find_map(|(namespace, name)| {
+                let slice = self.endpoint_slices.get(namespace)?.get(name)?;
+                if let (Some(owner), Some(uid)) = (&owner, slice.owners.get(&addr)) {
+                    if owner != uid {
+                        return None;
+                    }
+                }
+                let service_ref = ServiceRef {
+                    name: slice.service.clone(),
+                    namespace: namespace.clone(),
+                };
+                let info = self.service_info.get(&service_ref)?;
+                let port = slice
+                    .target_ports
+                    .iter()
+                    .filter(|(_, port)| *port == target_port)
+                    .find_map(|(name, _)| info.ports_by_name.get(name).copied())?;
+                Some((service_ref, port))
+            })
+    }
+
+    /// Finds a Service with an HTTPRoute whose hostnames match the given
+    /// hostname, so that policy may be discovered for destinations (e.g.
+    /// outside of the cluster) that are not addressed by a Service's name.
+    ///
+    /// Only routes that apply to clients in the source namespace are
+    /// considered. Exact hostnames are preferred over wildcards and routes in
+    /// the source namespace over routes in their Service's namespace; any
+    /// remaining ties are broken by the Service's namespace and name.
+    pub fn lookup_hostname(
+        &self,
+        host: &str,
+        port: NonZeroU16,
+        source_namespace: &str,
+    ) -> Option<ServiceRef> {
+        self.namespaces
+            .by_ns
+            .iter()
+            .flat_map(|(namespace, ns)| {
+                let port_routes = ns
+                    .service_port_routes
+                    .iter()
+                    .filter(|(sp, _)| sp.port == port)
+                    .flat_map(|(sp, routes)| {
+                        routes
+                            .watches_by_ns
+                            .values()
+                            .flat_map(|watch| watch.routes.iter())
+                            .map(move |route| (&sp.service, route))
+                    });
+                let portless_routes = ns
+                    .service_routes
+                    .iter()
+                    .flat_map(|(service, routes)| routes.iter().map(move |route| (service, route)));
+                port_routes
+                    .chain(portless_routes)
+                    .filter_map(move |(service, (gknn, route))| {
+                        let consumer = *gknn.namespace == *source_namespace;
+                        if !consumer && *gknn.namespace != **namespace {
+                            return None;
+                        }
+                        let exact = route
+                            .hostnames
+                            .iter()
+                            .filter(|h| h.matches(host))
+                            .map(|h| matches!(h, HostMatch::Exact(_)))
+                            .max()?;
+                        Some((!exact, !consumer, namespace, service))
+                    })
+            })
+            .min()
+            .map(|(_, _, namespace, name)| ServiceRef {
+                name: name.clone(),
+                namespace: namespace.clone(),
+            })
+    }
+
+    /// Returns the UID of the pod that currently owns the given endpoint
+    /// address, if it is known.
+    pub fn endpoint_owner(&self, addr: IpAddr) -> Option<String> {
+        self.endpoint_owners
+            .get(&addr)
+            .map(|owner| owner.borrow().clone())
+    }
+
+    /// Watches the owner of the given endpoint address. The watch is updated
+    /// when the address is reassigned to another pod and is closed when the
+    /// address is no longer an endpoint of any Service.
+    pub fn endpoint_owner_rx(&self, addr: IpAddr) -> Option<watch::Receiver<String>> {
+        self.endpoint_owners
+            .get(&addr)
+            .map(|owner| owner.subscribe())
+    }
+
     fn apply(&mut self, route: HttpRouteResource) {
+        let _span = info_span!("apply", ns = %route.namespace(), name = %route.name()).entered();
         tracing::debug!(name = route.name(), "indexing route");
 
-        for parent_ref in route.inner().parent_refs.iter().flatten() {
-            if !is_parent_service(parent_ref) {
-                continue;
+        let parent_refs = route
+            .inner()
+            .parent_refs
+            .iter()
+            .flatten()
+            .filter(|parent_ref| {
+                is_parent_service(parent_ref)
+                    && route_accepted_by_service(route.status(), &parent_ref.name)
+            })
+            .collect::<Vec<_>>();
+        if parent_refs.is_empty() {
+            return;
+        }
+
+        let gknn = route.gknn();
+        let apply_to_mirrors = route
+            .annotations()
+            .get(APPLY_TO_MIRRORS_ANNOTATION)
+            .map_or(false, |v| v == "true");
+        if apply_to_mirrors {
+            self.mirrored_routes.insert(gknn.clone(), route.clone());
+        } else if let Some(prior) = self.mirrored_routes.remove(&gknn) {
+            self.unbind_mirrors(&prior);
+        }
+        let parsed = match self
+            .parsed_routes
+            .get_or_parse(gknn.clone(), route.metadata(), || parse_route(&route))
+        {
+            Ok(parsed) => parsed,
+            Err(error) if error.quarantined => {
+                tracing::debug!(%error, "Ignoring quarantined HttpRoute");
+                return;
             }
-            if !route_accepted_by_service(route.status(), &parent_ref.name) {
-                continue;
+            Err(error) => {
+                tracing::error!(%error, "failed to convert HttpRoute");
+                return;
             }
+        };
+
+        for parent_ref in parent_refs {
             let ns = parent_ref
                 .namespace
                 .clone()
                 .unwrap_or_else(|| route.namespace());
-            self.namespaces
+            let original = ServiceRef {
+                name: parent_ref.name.clone(),
+                namespace: ns.clone(),
+            };
+            let mirrors = if apply_to_mirrors {
+                self.mirrors_of(&original)
+            } else {
+                vec![]
+            };
+            let ns_index = self
+                .namespaces
                 .by_ns
                 .entry(ns.clone())
                 .or_insert_with(|| Namespace {
                     service_routes: Default::default(),
                     service_port_routes: Default::default(),
                     namespace: Arc::new(ns),
-                })
-                .apply(
-                    route.clone(),
-                    parent_ref,
+                });
+            ns_index.apply(
+                &route,
+                &gknn,
+                &parsed,
+                parent_ref,
+                &self.namespaces.cluster_info,
+                &self.service_info,
+            );
+
+            // Mirrors are bound to a copy of the route whose backends refer to
+            // the mirror rather than the original Service.
+            for mirror in mirrors {
+                tracing::debug!(service = %original.name, %mirror, "applying route to mirror");
+                let mirrored = mirror_route(&route, &original, &mirror);
+                let mut mirror_ref = parent_ref.clone();
+                mirror_ref.name = mirror;
+                ns_index.apply(
+                    &mirrored,
+                    &gknn,
+                    &parsed,
+                    &mirror_ref,
                     &self.namespaces.cluster_info,
                     &self.service_info,
                 );
+            }
+        }
+    }
+
+    /// Returns the names of the Services that mirror the given Service.
+    fn mirrors_of(&self, original: &ServiceRef) -> Vec<String> {
+        self.mirrors
+            .iter()
+            .filter(|(_, o)| *o == original)
+            .map(|(mirror, _)| mirror.name.clone())
+            .collect()
+    }
+
+    /// Returns the routes that opt in to being applied to the mirrors of the
+    /// given Service.
+    fn mirrored_routes_for(&self, original: &ServiceRef) -> Vec<HttpRouteResource> {
+        self.mirrored_routes
+            .values()
+            .filter(|route| {
+                route
+                    .inner()
+                    .parent_refs
+                    .iter()
+                    .flatten()
+                    .any(|parent_ref| {
+                        is_parent_service(parent_ref)
+                            && parent_ref.name == original.name
+                            && parent_ref
+                                .namespace
+                                .clone()
+                                .unwrap_or_else(|| route.namespace())
+                                == original.namespace
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Unbinds a route from the mirrors of its parent Services.
+    fn unbind_mirrors(&mut self, route: &HttpRouteResource) {
+        let gknn = route.gknn();
+        for (mirror, original) in self.mirrors.iter() {
+            let is_parent = route
+                .inner()
+                .parent_refs
+                .iter()
+                .flatten()
+                .any(|parent_ref| {
+                    parent_ref.name == original.name
+                        && parent_ref
+                            .namespace
+                            .clone()
+                            .unwrap_or_else(|| route.namespace())
+                            == original.namespace
+                });
+            if !is_parent {
+                continue;
+            }
+            if let Some(ns) = self.namespaces.by_ns.get_mut(&mirror.namespace) {
+                ns.delete_service_route(&mirror.name, &gknn);
+            }
+        }
+    }
+
+    /// Records the original Service of a (possibly) mirrored Service, binding
+    /// or unbinding the original Service's routes as needed.
+    fn update_mirror(&mut self, mirror: ServiceRef, original: Option<ServiceRef>) {
+        let prior = match original.clone() {
+            Some(original) => self.mirrors.insert(mirror.clone(), original),
+            None => self.mirrors.remove(&mirror),
+        };
+        if prior == original {
+            return;
+        }
+
+        if let Some(prior) = prior {
+            let routes = self.mirrored_routes_for(&prior);
+            if let Some(ns) = self.namespaces.by_ns.get_mut(&mirror.namespace) {
+                for route in routes {
+                    ns.delete_service_route(&mirror.name, &route.gknn());
+                }
+            }
+        }
+        if let Some(original) = original {
+            tracing::debug!(service = %original.name, mirror = %mirror.name, "indexing mirror");
+            for route in self.mirrored_routes_for(&original) {
+                self.apply(route);
+            }
+        }
+    }
+
+    fn update_endpoint_slice(
+        &mut self,
+        namespace: String,
+        name: String,
+        slice: Option<EndpointSliceInfo>,
+    ) {
+        let key = (namespace.clone(), name.clone());
+        for addr in slice.iter().flat_map(|s| s.addrs.iter()) {
+            self.endpoint_slices_by_ip
+                .entry(*addr)
+                .or_default()
+                .insert(key.clone());
+        }
+
+        // A pod that was not previously listed for an address takes ownership
+        // of it. Stale endpoints for a previous owner may still be listed (and
+        // even re-applied) until their EndpointSlices are updated, so they do
+        // not reclaim the address.
+        let mut touched = slice
+            .iter()
+            .flat_map(|s| s.addrs.iter().copied())
+            .collect::<Vec<_>>();
+        for (addr, uid) in slice.iter().flat_map(|s| s.owners.iter()) {
+            if !self.endpoint_owners_listed(*addr).contains(uid) {
+                self.set_endpoint_owner(*addr, uid);
+            }
+        }
+
+        let slices = self.endpoint_slices.entry(namespace.clone()).or_default();
+        let new_service = slice.as_ref().map(|s| s.service.clone());
+        let old = match slice {
+            Some(slice) => slices.insert(name, slice),
+            None => slices.remove(&name),
+        };
+        if slices.is_empty() {
+            self.endpoint_slices.remove(&namespace);
+        }
+
+        let old_service = old.map(|old| {
+            let current = self
+                .endpoint_slices
+                .get(&namespace)
+                .and_then(|slices| slices.get(&key.1));
+            touched.extend(old.addrs.iter().copied());
+            for addr in old.addrs {
+                if current.map_or(false, |s| s.addrs.contains(&addr)) {
+                    continue;
+                }
+                if let Entry::Occupied(mut slices) = self.endpoint_slices_by_ip.entry(addr) {
+                    slices.get_mut().remove(&key);
+                    if slices.get().is_empty() {
+                        slices.remove();
+                    }
+                }
+            }
+            old.service
+        });
+
+        for addr in touched {
+            self.refresh_endpoint_owner(addr);
+        }
+
+        let mut changed = false;
+        for name in old_service.into_iter().chain(new_service) {
+            let ready = self.has_ready_endpoints(&namespace, &name);
+            let zones = self.endpoint_zones(&namespace, &name);
+            let service_ref = ServiceRef {
+                name,
+                namespace: namespace.clone(),
+            };
+            if let Some(info) = self.service_info.get_mut(&service_ref) {
+                if info.remote_discovery.is_some() {
+                    continue;
+                }
+                if info.ready != ready {
+                    tracing::debug!(service = %service_ref.name, ready, "Service readiness changed");
+                    info.ready = ready;
+                    changed = true;
+                }
+                if info.zones != zones {
+                    tracing::debug!(service = %service_ref.name, ?zones, "Service zones changed");
+                    info.zones = zones;
+                    changed = true;
+                }
+            }
         }
+        if changed {
+            self.reindex_services();
+        }
+    }
+
+    /// Returns the UIDs of the pods that are listed for an endpoint address.
+    fn endpoint_owners_listed(&self, addr: IpAddr) -> Vec<String> {
+        self.endpoint_slices_by_ip
+            .get(&addr)
+            .into_iter()
+            .flatten()
+            .filter_map(|(namespace, name)| {
+                let slice = self.endpoint_slices.get(namespace)?.get(name)?;
+                slice.owners.get(&addr).cloned()
+            })
+            .collect()
+    }
+
+    fn set_endpoint_owner(&mut self, addr: IpAddr, uid: &str) {
+        match self.endpoint_owners.entry(addr) {
+            Entry::Occupied(owner) => {
+                owner.get().send_if_modified(|current| {
+                    if current == uid {
+                        return false;
+                    }
+                    tracing::debug!(%addr, %uid, "Endpoint address reassigned");
+                    *current = uid.to_string();
+                    true
+                });
+            }
+            Entry::Vacant(owner) => {
+                owner.insert(watch::channel(uid.to_string()).0);
+            }
+        }
+    }
+
+    /// Ensures that an endpoint address's owner is still listed for it,
+    /// falling back to another listed pod or clearing the owner if the address
+    /// is no longer listed.
+    fn refresh_endpoint_owner(&mut self, addr: IpAddr) {
+        let listed = self.endpoint_owners_listed(addr);
+        if let Some(owner) = self.endpoint_owners.get(&addr) {
+            if listed.contains(&*owner.borrow()) {
+                return;
+            }
+        }
+        match listed.first() {
+            Some(uid) => self.set_endpoint_owner(addr, uid),
+            None => {
+                self.endpoint_owners.remove(&addr);
+            }
+        }
+    }
+
+    fn has_ready_endpoints(&self, namespace: &str, service: &str) -> bool {
+        self.endpoint_slices
+            .get(namespace)
+            .into_iter()
+            .flat_map(|slices| slices.values())
+            .any(|slice| slice.service == service && slice.ready > 0)
+    }
+
+    fn endpoint_zones(&self, namespace: &str, service: &str) -> Vec<String> {
+        self.endpoint_slices
+            .get(namespace)
+            .into_iter()
+            .flat_map(|slices| slices.values())
+            .filter(|slice| slice.service == service)
+            .flat_map(|slice| slice.zones.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
     }
 
     fn reindex_services(&mut self) {
         for ns in self.namespaces.by_ns.values_mut() {
-            ns.reindex_services(&self.service_info);
+            ns.reindex_services(&self.namespaces.cluster_info, &self.service_info);
         }
     }
 }
@@ -269,19 +1037,15 @@ impl Index {
 impl Namespace {
     fn apply(
         &mut self,
-        route: HttpRouteResource,
+        route: &HttpRouteResource,
+        gknn: &GroupKindNamespaceName,
+        parsed: &ParsedRoute,
         parent_ref: &ParentReference,
         cluster_info: &ClusterInfo,
         service_info: &HashMap<ServiceRef, ServiceInfo>,
     ) {
         tracing::debug!(?route);
-        let outbound_route = match self.convert_route(route.clone(), cluster_info, service_info) {
-            Ok(route) => route,
-            Err(error) => {
-                tracing::error!(%error, "failed to convert HttpRoute");
-                return;
-            }
-        };
+        let outbound_route = self.convert_route(route, parsed, cluster_info, service_info);
         tracing::debug!(?outbound_route);
 
         let port = parent_ref.port.and_then(NonZeroU16::new);
@@ -297,14 +1061,14 @@ impl Namespace {
             );
             let service_routes =
                 self.service_routes_or_default(service_port, cluster_info, service_info);
-            service_routes.apply(route.gknn(), outbound_route);
+            service_routes.apply(gknn.clone(), outbound_route);
         } else {
             // If the parent_ref doesn't include a port, apply this route
             // to all ServiceRoutes which match the Service name.
             self.service_port_routes.iter_mut().for_each(
                 |(ServicePort { service, port: _ }, routes)| {
                     if service == &parent_ref.name {
-                        routes.apply(route.gknn(), outbound_route.clone());
+                        routes.apply(gknn.clone(), outbound_route.clone());
                     }
                 },
             );
@@ -313,12 +1077,21 @@ impl Namespace {
             self.service_routes
                 .entry(parent_ref.name.clone())
                 .or_default()
-                .insert(route.gknn(), outbound_route);
+                .insert(gknn.clone(), outbound_route);
         }
     }
 
-    fn reindex_services(&mut self, service_info: &HashMap<ServiceRef, ServiceInfo>) {
-        for routes in self.service_port_routes.values_mut() {
+    fn reindex_services(
+        &mut self,
+        cluster: &ClusterInfo,
+        service_info: &HashMap<ServiceRef, ServiceInfo>,
+    ) {
+        let _span = info_span!("reindex", ns = %self.namespace).entered();
+        for (sp, routes) in self.service_port_routes.iter_mut() {
+            routes.cluster_backends = cluster_backends(&self.namespace, sp, cluster, service_info);
+            for watch in routes.watches_by_ns.values_mut() {
+                watch.cluster_backends = routes.cluster_backends.clone();
+            }
             for routes in routes.watches_by_ns.values_mut() {
                 for route in routes.routes.values_mut() {
                     for rule in route.rules.iter_mut() {
@@ -328,7 +1101,10 @@ impl Namespace {
                                     name: svc.name.clone(),
                                     namespace: svc.namespace.clone(),
                                 };
-                                svc.exists = service_info.contains_key(&service_ref);
+                                let info = service_info.get(&service_ref);
+                                svc.exists = info.is_some();
+                                svc.ready = info.map_or(false, |info| info.ready);
+                                svc.zones = info.map(|info| info.zones.clone()).unwrap_or_default();
                             }
                         }
                     }
@@ -344,9 +1120,14 @@ impl Namespace {
             if svc_port.service != name {
                 continue;
             }
-            let opaque = service.opaque_ports.contains(&svc_port.port);
-
-            svc_routes.update_service(opaque, service.accrual);
+            let (opaque, app_protocol) = service.protocol(svc_port.port);
+            svc_routes.update_service(
+                opaque,
+                app_protocol,
+                service.accrual,
+                service.retry_budget,
+                service.detect_timeout,
+            );
         }
     }
 
@@ -359,6 +1140,38 @@ impl Namespace {
         }
     }
 
+    /// Unbinds a route from a single Service.
+    fn delete_service_route(&mut self, service: &str, gknn: &GroupKindNamespaceName) {
+        for (sp, routes) in self.service_port_routes.iter_mut() {
+            if sp.service == service {
+                routes.delete(gknn);
+            }
+        }
+        if let Some(routes) = self.service_routes.get_mut(service) {
+            routes.remove(gknn);
+        }
+    }
+
+    /// Removes route watches that have neither routes nor subscribers, returning
+    /// true if the namespace no longer holds any state.
+    fn gc(&mut self) -> bool {
+        for routes in self.service_port_routes.values_mut() {
+            routes.watches_by_ns.retain(|_, watch| !watch.is_unused());
+        }
+        self.service_port_routes
+            .retain(|_, routes| !routes.watches_by_ns.is_empty());
+        self.service_routes.retain(|_, routes| !routes.is_empty());
+        self.is_empty()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.service_routes.values().all(|routes| routes.is_empty())
+            && self
+                .service_port_routes
+                .values()
+                .all(|routes| routes.watches_by_ns.values().all(RoutesWatch::is_unused))
+    }
+
     fn service_routes_or_default(
         &mut self,
         sp: ServicePort,
@@ -368,151 +1181,144 @@ impl Namespace {
         self.service_port_routes
             .entry(sp.clone())
             .or_insert_with(|| {
-                let authority =
-                    cluster.service_dns_authority(&self.namespace, &sp.service, sp.port);
-                let service_ref = ServiceRef {
-                    name: sp.service.clone(),
-                    namespace: self.namespace.to_string(),
-                };
-                let (opaque, accrual) = match service_info.get(&service_ref) {
-                    Some(svc) => (svc.opaque_ports.contains(&sp.port), svc.accrual),
-                    None => (false, None),
-                };
+                Self::new_service_routes(
+                    &self.namespace,
+                    &self.service_routes,
+                    sp,
+                    cluster,
+                    service_info,
+                )
+            })
+    }
 
-                // The HttpRoutes which target this Service but don't specify
-                // a port apply to all ports. Therefore we include them.
-                let routes = self
-                    .service_routes
-                    .get(&sp.service)
-                    .cloned()
-                    .unwrap_or_default();
-
-                let mut service_routes = ServiceRoutes {
-                    opaque,
-                    accrual,
-                    authority,
-                    namespace: self.namespace.clone(),
-                    name: sp.service,
-                    port: sp.port,
-                    watches_by_ns: Default::default(),
-                };
+    /// Builds the routes for a Service port that is not yet indexed.
+    fn new_service_routes(
+        namespace: &Arc<String>,
+        service_routes: &HashMap<String, HashMap<GroupKindNamespaceName, HttpRoute>>,
+        sp: ServicePort,
+        cluster: &ClusterInfo,
+        service_info: &HashMap<ServiceRef, ServiceInfo>,
+    ) -> ServiceRoutes {
+        let authority = cluster.service_dns_authority(namespace, &sp.service, sp.port);
+        let service_ref = ServiceRef {
+            name: sp.service.clone(),
+            namespace: namespace.to_string(),
+        };
+        let ((opaque, app_protocol), accrual, retry_budget, detect_timeout) =
+            match service_info.get(&service_ref) {
+                Some(svc) => (
+                    svc.protocol(sp.port),
+                    svc.accrual,
+                    svc.retry_budget,
+                    svc.detect_timeout,
+                ),
+                None => ((false, None), None, None, cluster.default_detect_timeout),
+            };
 
-                // Producer routes are routes in the same namespace as their
-                // parent service. Consumer routes are routes in other
-                // namespaces.
-                let (producer_routes, consumer_routes): (Vec<_>, Vec<_>) = routes
-                    .into_iter()
-                    .partition(|(gknn, _route)| *gknn.namespace == *self.namespace);
-                for (gknn, route) in consumer_routes {
-                    // Consumer routes should only apply to watches from the
-                    // consumer namespace.
-                    let watch = service_routes.watch_for_ns_or_default(gknn.namespace.to_string());
-                    watch.routes.insert(gknn, route);
-                }
-                for (gknn, route) in producer_routes {
-                    // Insert the route into the producer namespace.
-                    let watch = service_routes.watch_for_ns_or_default(gknn.namespace.to_string());
+        // The HttpRoutes which target this Service but don't specify
+        // a port apply to all ports. Therefore we include them.
+        let routes = service_routes.get(&sp.service).cloned().unwrap_or_default();
+
+        let cluster_backends = cluster_backends(namespace, &sp, cluster, service_info);
+        let mut service_routes = ServiceRoutes {
+            opaque,
+            app_protocol,
+            accrual,
+            retry_budget,
+            detect_timeout,
+            cluster_backends,
+            authority,
+            namespace: namespace.clone(),
+            name: sp.service,
+            port: sp.port,
+            watches_by_ns: Default::default(),
+        };
+
+        // Producer routes are routes in the same namespace as their
+        // parent service. Consumer routes are routes in other
+        // namespaces.
+        let (producer_routes, consumer_routes): (Vec<_>, Vec<_>) = routes
+            .into_iter()
+            .partition(|(gknn, _route)| *gknn.namespace == **namespace);
+        for (gknn, route) in consumer_routes {
+            // Consumer routes should only apply to watches from the
+            // consumer namespace.
+            let watch = service_routes.watch_for_ns_or_default(gknn.namespace.to_string());
+            watch.routes.insert(gknn, route);
+        }
+        for (gknn, route) in producer_routes {
+            // Insert the route into the producer namespace.
+            let watch = service_routes.watch_for_ns_or_default(gknn.namespace.to_string());
+            watch.routes.insert(gknn.clone(), route.clone());
+            // Producer routes apply to clients in all namespaces, so
+            // apply it to watches for all other namespaces too.
+            for (ns, watch) in service_routes.watches_by_ns.iter_mut() {
+                if **ns != *gknn.namespace {
                     watch.routes.insert(gknn.clone(), route.clone());
-                    // Producer routes apply to clients in all namespaces, so
-                    // apply it to watches for all other namespaces too.
-                    for (ns, watch) in service_routes.watches_by_ns.iter_mut() {
-                        if ns != &gknn.namespace {
-                            watch.routes.insert(gknn.clone(), route.clone());
-                        }
-                    }
                 }
+            }
+        }
 
-                service_routes
-            })
+        service_routes
     }
 
     fn convert_route(
         &self,
-        route: HttpRouteResource,
+        route: &HttpRouteResource,
+        parsed: &ParsedRoute,
         cluster: &ClusterInfo,
         service_info: &HashMap<ServiceRef, ServiceInfo>,
-    ) -> Result<HttpRoute> {
-        match route {
+    ) -> HttpRoute {
+        let (rules, creation_timestamp) = match route {
             HttpRouteResource::Linkerd(route) => {
-                let hostnames = route
-                    .spec
-                    .hostnames
-                    .into_iter()
-                    .flatten()
-                    .map(http_route::host_match)
-                    .collect();
-
                 let rules = route
                     .spec
                     .rules
-                    .into_iter()
+                    .iter()
                     .flatten()
-                    .map(|r| self.convert_linkerd_rule(r, cluster, service_info))
-                    .collect::<Result<_>>()?;
-
-                let creation_timestamp = route.metadata.creation_timestamp.map(|Time(t)| t);
-
-                Ok(HttpRoute {
-                    hostnames,
-                    rules,
-                    creation_timestamp,
-                })
+                    .zip(parsed.rules.iter())
+                    .map(|(rule, parsed)| {
+                        self.convert_linkerd_rule(rule, parsed, cluster, service_info)
+                    })
+                    .collect();
+                (rules, route.metadata.creation_timestamp.as_ref())
             }
             HttpRouteResource::Gateway(route) => {
-                let hostnames = route
-                    .spec
-                    .hostnames
-                    .into_iter()
-                    .flatten()
-                    .map(http_route::host_match)
-                    .collect();
-
                 let rules = route
                     .spec
                     .rules
-                    .into_iter()
+                    .iter()
                     .flatten()
-                    .map(|r| self.convert_gateway_rule(r, cluster, service_info))
-                    .collect::<Result<_>>()?;
-
-                let creation_timestamp = route.metadata.creation_timestamp.map(|Time(t)| t);
-
-                Ok(HttpRoute {
-                    hostnames,
-                    rules,
-                    creation_timestamp,
-                })
+                    .zip(parsed.rules.iter())
+                    .map(|(rule, parsed)| {
+                        self.convert_gateway_rule(rule, parsed, cluster, service_info)
+                    })
+                    .collect();
+                (rules, route.metadata.creation_timestamp.as_ref())
             }
+        };
+
+        HttpRoute {
+            hostnames: parsed.hostnames.clone(),
+            rules,
+            creation_timestamp: creation_timestamp.map(|Time(t)| *t),
         }
     }
 
     fn convert_linkerd_rule(
         &self,
-        rule: api::httproute::HttpRouteRule,
+        rule: &api::httproute::HttpRouteRule,
+        parsed: &ParsedRule,
         cluster: &ClusterInfo,
         service_info: &HashMap<ServiceRef, ServiceInfo>,
-    ) -> Result<HttpRouteRule> {
-        let matches = rule
-            .matches
-            .into_iter()
-            .flatten()
-            .map(http_route::try_match)
-            .collect::<Result<_>>()?;
-
+    ) -> HttpRouteRule {
         let backends = rule
             .backend_refs
-            .into_iter()
+            .iter()
             .flatten()
             .filter_map(|b| convert_backend(&self.namespace, b, cluster, service_info))
             .collect();
 
-        let filters = rule
-            .filters
-            .into_iter()
-            .flatten()
-            .map(convert_linkerd_filter)
-            .collect::<Result<_>>()?;
-
         let request_timeout = rule.timeouts.as_ref().and_then(|timeouts| {
             let timeout = time::Duration::from(timeouts.request?);
 
@@ -538,60 +1344,120 @@ impl Namespace {
                     Some(timeout)
                 });
 
-        Ok(HttpRouteRule {
-            matches,
+        HttpRouteRule {
+            matches: parsed.matches.clone(),
             backends,
             request_timeout,
             backend_request_timeout,
-            filters,
-        })
+            filters: parsed.filters.clone(),
+        }
     }
 
     fn convert_gateway_rule(
         &self,
-        rule: k8s_gateway_api::HttpRouteRule,
+        rule: &k8s_gateway_api::HttpRouteRule,
+        parsed: &ParsedRule,
         cluster: &ClusterInfo,
         service_info: &HashMap<ServiceRef, ServiceInfo>,
-    ) -> Result<HttpRouteRule> {
-        let matches = rule
-            .matches
-            .into_iter()
-            .flatten()
-            .map(http_route::try_match)
-            .collect::<Result<_>>()?;
-
+    ) -> HttpRouteRule {
         let backends = rule
             .backend_refs
-            .into_iter()
+            .iter()
             .flatten()
             .filter_map(|b| convert_backend(&self.namespace, b, cluster, service_info))
             .collect();
 
-        let filters = rule
-            .filters
+        HttpRouteRule {
+            matches: parsed.matches.clone(),
+            backends,
+            request_timeout: None,
+            backend_request_timeout: None,
+            filters: parsed.filters.clone(),
+        }
+    }
+}
+
+/// Parses the matches, filters, and hostnames of a route, which do not depend
+/// on the namespace or services to which the route is bound.
+fn parse_route(route: &HttpRouteResource) -> Result<ParsedRoute> {
+    fn parse_rule<F>(
+        matches: Option<&Vec<k8s_gateway_api::HttpRouteMatch>>,
+        filters: Option<&Vec<F>>,
+        convert_filter: impl Fn(F) -> Result<Filter>,
+    ) -> Result<ParsedRule>
+    where
+        F: Clone,
+    {
+        let matches = matches
             .into_iter()
             .flatten()
-            .map(convert_gateway_filter)
+            .cloned()
+            .map(http_route::try_match)
             .collect::<Result<_>>()?;
 
-        Ok(HttpRouteRule {
-            matches,
-            backends,
-            request_timeout: None,
-            backend_request_timeout: None,
-            filters,
-        })
+        let filters = filters
+            .into_iter()
+            .flatten()
+            .cloned()
+            .map(convert_filter)
+            .collect::<Result<_>>()?;
+
+        Ok(ParsedRule { matches, filters })
     }
+
+    let (hostnames, rules) = match route {
+        HttpRouteResource::Linkerd(route) => {
+            let rules = route
+                .spec
+                .rules
+                .iter()
+                .flatten()
+                .map(|r| {
+                    parse_rule(
+                        r.matches.as_ref(),
+                        r.filters.as_ref(),
+                        convert_linkerd_filter,
+                    )
+                })
+                .collect::<Result<_>>()?;
+            (route.spec.hostnames.as_ref(), rules)
+        }
+        HttpRouteResource::Gateway(route) => {
+            let rules = route
+                .spec
+                .rules
+                .iter()
+                .flatten()
+                .map(|r| {
+                    parse_rule(
+                        r.matches.as_ref(),
+                        r.filters.as_ref(),
+                        convert_gateway_filter,
+                    )
+                })
+                .collect::<Result<_>>()?;
+            (route.spec.hostnames.as_ref(), rules)
+        }
+    };
+
+    let hostnames = hostnames
+        .into_iter()
+        .flatten()
+        .cloned()
+        .map(http_route::host_match)
+        .collect();
+
+    Ok(ParsedRoute { hostnames, rules })
 }
 
 fn convert_backend(
     ns: &str,
-    backend: HttpBackendRef,
+    backend: &HttpBackendRef,
     cluster: &ClusterInfo,
     services: &HashMap<ServiceRef, ServiceInfo>,
 ) -> Option<Backend> {
-    let filters = backend.filters;
-    let backend = backend.backend_ref?;
+    let filters = backend.filters.as_ref();
+    let backend = backend.backend_ref.as_ref()?;
     if !is_backend_service(&backend.inner) {
         return Some(Backend::Invalid {
             weight: backend.weight.unwrap_or(1).into(),
@@ -603,7 +1469,7 @@ fn convert_backend(
         });
     }
 
-    let name = backend.inner.name;
+    let name = backend.inner.name.clone();
     let weight = backend.weight.unwrap_or(1);
 
     // The gateway API dictates:
@@ -624,12 +1490,17 @@ fn convert_backend(
     };
     let service_ref = ServiceRef {
         name: name.clone(),
-        namespace: backend.inner.namespace.unwrap_or_else(|| ns.to_string()),
+        namespace: backend
+            .inner
+            .namespace
+            .clone()
+            .unwrap_or_else(|| ns.to_string()),
     };
 
     let filters = match filters
         .into_iter()
         .flatten()
+        .cloned()
         .map(convert_gateway_filter)
         .collect::<Result<_>>()
     {
@@ -650,6 +1521,11 @@ fn convert_backend(
         port,
         filters,
         exists: services.contains_key(&service_ref),
+        ready: services.get(&service_ref).map_or(false, |info| info.ready),
+        zones: services
+            .get(&service_ref)
+            .map(|info| info.zones.clone())
+            .unwrap_or_default(),
     }))
 }
 
@@ -710,6 +1586,148 @@ fn convert_gateway_filter(filter: k8s_gateway_api::HttpRouteFilter) -> Result<Fi
     Ok(filter)
 }
 
+/// Returns the linked cluster and remote Service name of a Service that is
+/// mirrored in remote-discovery mode.
+fn remote_discovery(service: &Service) -> Option<(String, String)> {
+    let labels = service.labels();
+    let cluster = labels.get(REMOTE_DISCOVERY_LABEL)?;
+    let remote = labels.get(REMOTE_SERVICE_LABEL)?;
+    Some((cluster.clone(), remote.clone()))
+}
+
+/// Parses a Service's `multicluster.linkerd.io/cluster-weights` annotation.
+/// Invalid entries are skipped.
+fn parse_cluster_weights(
+    annotations: &std::collections::BTreeMap<String, String>,
+) -> Vec<(String, u32)> {
+    let Some(weights) = annotations.get(CLUSTER_WEIGHTS_ANNOTATION) else {
+        return vec![];
+    };
+    weights
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .and_then(|(cluster, weight)| Some((cluster.trim(), weight.trim().parse().ok()?)));
+            match parsed {
+                Some((cluster, weight)) if !cluster.is_empty() => {
+                    Some((cluster.to_string(), weight))
+                }
+                _ => {
+                    tracing::warn!(%entry, "Invalid cluster weight");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Determines the backends across which a Service port's traffic is
+/// distributed, per its cluster weights. Clusters from which the Service is
+/// not mirrored in remote-discovery mode are ignored.
+fn cluster_backends(
+    namespace: &str,
+    sp: &ServicePort,
+    cluster: &ClusterInfo,
+    service_info: &HashMap<ServiceRef, ServiceInfo>,
+) -> Vec<WeightedService> {
+    let service_ref = ServiceRef {
+        name: sp.service.clone(),
+        namespace: namespace.to_string(),
+    };
+    let Some(info) = service_info.get(&service_ref) else {
+        return vec![];
+    };
+    info.cluster_weights
+        .iter()
+        .filter_map(|(cluster_name, weight)| {
+            let (name, ready, zones) = if cluster_name == LOCAL_CLUSTER {
+                (sp.service.clone(), info.ready, info.zones.clone())
+            } else {
+                service_info.iter().find_map(|(svc, info)| {
+                    let (remote_cluster, remote) = info.remote_discovery.as_ref()?;
+                    if svc.namespace != namespace
+                        || remote_cluster != cluster_name
+                        || *remote != sp.service
+                    {
+                        return None;
+                    }
+                    Some((svc.name.clone(), info.ready, info.zones.clone()))
+                })?
+            };
+            Some(WeightedService {
+                weight: *weight,
+                authority: cluster.service_dns_authority(namespace, &name, sp.port),
+                name,
+                namespace: namespace.to_string(),
+                port: sp.port,
+                filters: vec![],
+                exists: true,
+                ready,
+                zones,
+            })
+        })
+        .collect()
+}
+
+/// Returns the name of the original Service that a Service mirrors from a
+/// linked cluster, if it is a mirror.
+fn mirrored_service_name(service: &Service) -> Option<String> {
+    let labels = service.labels();
+    if labels.get(MIRRORED_SERVICE_LABEL).map(String::as_str) != Some("true") {
+        return None;
+    }
+    let cluster = labels.get(MIRROR_CLUSTER_NAME_LABEL)?;
+    let name = service.name_unchecked();
+    let original = name.strip_suffix(cluster.as_str())?.strip_suffix('-')?;
+    if original.is_empty() {
+        return None;
+    }
+    Some(original.to_string())
+}
+
+/// Returns a copy of a route in which backends that refer to the original
+/// Service instead refer to its mirror.
+fn mirror_route(
+    route: &HttpRouteResource,
+    original: &ServiceRef,
+    mirror: &str,
+) -> HttpRouteResource {
+    let route_ns = route.namespace();
+    let mut route = route.clone();
+    let backends: Vec<&mut HttpBackendRef> = match &mut route {
+        HttpRouteResource::Linkerd(route) => route
+            .spec
+            .rules
+            .iter_mut()
+            .flatten()
+            .flat_map(|rule| rule.backend_refs.iter_mut().flatten())
+            .collect(),
+        HttpRouteResource::Gateway(route) => route
+            .spec
+            .rules
+            .iter_mut()
+            .flatten()
+            .flat_map(|rule| rule.backend_refs.iter_mut().flatten())
+            .collect(),
+    };
+    for backend in backends {
+        if let Some(backend) = backend.backend_ref.as_mut() {
+            let backend = &mut backend.inner;
+            let namespace = backend.namespace.as_deref().unwrap_or(&route_ns);
+            if is_backend_service(backend)
+                && backend.name == original.name
+                && namespace == original.namespace
+            {
+                backend.name = mirror.to_string();
+            }
+        }
+    }
+    route
+}
+
 #[inline]
 fn is_parent_service(parent: &ParentReference) -> bool {
     parent
@@ -757,32 +1775,71 @@ fn is_service(group: Option<&str>, kind: &str) -> bool {
         && kind.eq_ignore_ascii_case("Service")
 }
 
+impl ServiceInfo {
+    /// Returns whether the given port is opaque and, if not, the HTTP version
+    /// declared by its `appProtocol`.
+    fn protocol(&self, port: NonZeroU16) -> (bool, Option<AppProtocol>) {
+        if self.opaque_ports.contains(&port) {
+            return (true, None);
+        }
+        (false, self.app_protocols.get(&port).copied())
+    }
+}
+
 impl ServiceRoutes {
     fn watch_for_ns_or_default(&mut self, namespace: String) -> &mut RoutesWatch {
-        // The routes from the producer namespace apply to watches in all
-        // namespaces so we copy them.
-        let routes = self
-            .watches_by_ns
-            .get(self.namespace.as_ref())
-            .map(|watch| watch.routes.clone())
-            .unwrap_or_default();
-        self.watches_by_ns.entry(namespace).or_insert_with(|| {
-            let (sender, _) = watch::channel(OutboundPolicy {
-                http_routes: routes.clone(),
-                authority: self.authority.clone(),
-                name: self.name.to_string(),
-                namespace: self.namespace.to_string(),
-                port: self.port,
-                opaque: self.opaque,
-                accrual: self.accrual,
-            });
-            RoutesWatch {
+        if !self.watches_by_ns.contains_key(&namespace) {
+            let routes = self.producer_routes();
+            let (sender, _) = watch::channel(self.policy(routes.clone()));
+            let watch = RoutesWatch {
                 opaque: self.opaque,
+                app_protocol: self.app_protocol,
                 accrual: self.accrual,
+                retry_budget: self.retry_budget,
+                detect_timeout: self.detect_timeout,
+                cluster_backends: self.cluster_backends.clone(),
                 routes,
                 watch: sender,
-            }
-        })
+            };
+            self.watches_by_ns.insert(namespace.clone(), watch);
+        }
+        self.watches_by_ns
+            .get_mut(&namespace)
+            .expect("watch must exist")
+    }
+
+    /// Returns the policy served to clients in the given namespace without
+    /// creating a watch for it.
+    fn policy_for_ns(&self, namespace: &str) -> OutboundPolicy {
+        match self.watches_by_ns.get(namespace) {
+            Some(watch) => watch.watch.borrow().clone(),
+            None => self.policy(self.producer_routes()),
+        }
+    }
+
+    /// The routes from the producer namespace apply to watches in all
+    /// namespaces so we copy them.
+    fn producer_routes(&self) -> HashMap<GroupKindNamespaceName, HttpRoute> {
+        self.watches_by_ns
+            .get(self.namespace.as_ref())
+            .map(|watch| watch.routes.clone())
+            .unwrap_or_default()
+    }
+
+    fn policy(&self, http_routes: HashMap<GroupKindNamespaceName, HttpRoute>) -> OutboundPolicy {
+        OutboundPolicy {
+            http_routes,
+            authority: self.authority.clone(),
+            name: self.name.to_string(),
+            namespace: self.namespace.to_string(),
+            port: self.port,
+            opaque: self.opaque,
+            app_protocol: self.app_protocol,
+            accrual: self.accrual,
+            retry_budget: self.retry_budget,
+            detect_timeout: self.detect_timeout,
+            cluster_backends: self.cluster_backends.clone(),
+        }
     }
 
     fn apply(&mut self, gknn: GroupKindNamespaceName, route: HttpRoute) {
@@ -794,7 +1851,7 @@ impl ServiceRoutes {
             // Producer routes apply to clients in all namespaces, so
             // apply it to watches for all other namespaces too.
             for (ns, watch) in self.watches_by_ns.iter_mut() {
-                if ns != &gknn.namespace {
+                if **ns != *gknn.namespace {
                     watch.routes.insert(gknn.clone(), route.clone());
                     watch.send_if_modified();
                 }
@@ -808,12 +1865,25 @@ impl ServiceRoutes {
         }
     }
 
-    fn update_service(&mut self, opaque: bool, accrual: Option<FailureAccrual>) {
+    fn update_service(
+        &mut self,
+        opaque: bool,
+        app_protocol: Option<AppProtocol>,
+        accrual: Option<FailureAccrual>,
+        retry_budget: Option<RetryBudget>,
+        detect_timeout: time::Duration,
+    ) {
         self.opaque = opaque;
+        self.app_protocol = app_protocol;
         self.accrual = accrual;
+        self.retry_budget = retry_budget;
+        self.detect_timeout = detect_timeout;
         for watch in self.watches_by_ns.values_mut() {
             watch.opaque = opaque;
+            watch.app_protocol = app_protocol;
             watch.accrual = accrual;
+            watch.retry_budget = retry_budget;
+            watch.detect_timeout = detect_timeout;
             watch.send_if_modified();
         }
     }
@@ -827,6 +1897,10 @@ impl ServiceRoutes {
 }
 
 impl RoutesWatch {
+    fn is_unused(&self) -> bool {
+        self.routes.is_empty() && self.watch.receiver_count() == 0
+    }
+
     fn send_if_modified(&mut self) {
         self.watch.send_if_modified(|policy| {
             let mut modified = false;
@@ -838,15 +1912,99 @@ impl RoutesWatch {
                 policy.opaque = self.opaque;
                 modified = true;
             }
+            if self.app_protocol != policy.app_protocol {
+                policy.app_protocol = self.app_protocol;
+                modified = true;
+            }
             if self.accrual != policy.accrual {
                 policy.accrual = self.accrual;
                 modified = true;
             }
+            if self.retry_budget != policy.retry_budget {
+                policy.retry_budget = self.retry_budget;
+                modified = true;
+            }
+            if self.detect_timeout != policy.detect_timeout {
+                policy.detect_timeout = self.detect_timeout;
+                modified = true;
+            }
+            if self.cluster_backends != policy.cluster_backends {
+                policy.cluster_backends = self.cluster_backends.clone();
+                modified = true;
+            }
             modified
         });
     }
 }
 
+/// Reads a Service's `config.linkerd.io/opaque-ports` annotation, if it is set.
+///
+/// The annotation is interpreted as the destination controller interprets it:
+/// entries may name one of the Service's ports, and entries that are invalid
+/// are skipped without invalidating the rest of the annotation.
+fn service_opaque_ports(service: &Service) -> Option<PortSet> {
+    let spec = service.annotations().get(OPAQUE_PORTS_ANNOTATION)?;
+    let svc_ports = service
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.ports.as_deref())
+        .unwrap_or_default();
+
+    let mut ports = PortSet::default();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        if let Some(port) = svc_ports
+            .iter()
+            .find(|p| p.name.as_deref() == Some(entry))
+            .and_then(|p| u16::try_from(p.port).ok())
+            .and_then(|p| NonZeroU16::try_from(p).ok())
+        {
+            ports.insert(port);
+            continue;
+        }
+        match parse_portset(entry) {
+            Ok(range) => ports.extend(range),
+            Err(error) => {
+                tracing::info!(%entry, %error, "Ignoring invalid opaque port");
+            }
+        }
+    }
+    Some(ports)
+}
+
+/// Returns the ports of a Service whose `appProtocol` is TCP and the HTTP
+/// versions declared by the `appProtocol`s of its other ports.
+///
+/// Unrecognized protocols (e.g. `https`) are ignored, so that the protocols of
+/// those ports are detected.
+fn service_app_protocols(spec: &ServiceSpec) -> (PortSet, PortMap<AppProtocol>) {
+    let mut tcp = PortSet::default();
+    let mut http = PortMap::default();
+    for svc_port in spec.ports.iter().flatten() {
+        let Some(port) = u16::try_from(svc_port.port)
+            .ok()
+            .and_then(|p| NonZeroU16::try_from(p).ok())
+        else {
+            continue;
+        };
+        let Some(app_protocol) = svc_port.app_protocol.as_deref() else {
+            continue;
+        };
+        match app_protocol.to_ascii_lowercase().as_str() {
+            "tcp" => {
+                tcp.insert(port);
+            }
+            "http" | "kubernetes.io/ws" => {
+                http.insert(port, AppProtocol::Http1);
+            }
+            "kubernetes.io/h2c" | "h2c" | "http2" | "grpc" => {
+                http.insert(port, AppProtocol::Http2);
+            }
+            _ => {}
+        }
+    }
+    (tcp, http)
+}
+
 fn parse_accrual_config(
     annotations: &std::collections::BTreeMap<String, String>,
 ) -> Result<Option<FailureAccrual>> {
@@ -876,6 +2034,7 @@ fn parse_accrual_config(
                     .map(|s| s.parse::<f32>())
                     .transpose()?
                     .unwrap_or(0.5);
+                ensure!(max_failures > 0, "max_failures must be positive");
                 ensure!(
                     min_penalty <= max_penalty,
                     "min_penalty ({min_penalty:?}) cannot exceed max_penalty ({max_penalty:?})"
@@ -902,6 +2061,41 @@ fn parse_accrual_config(
         .transpose()
 }
 
+fn parse_retry_budget(
+    annotations: &std::collections::BTreeMap<String, String>,
+) -> Result<Option<RetryBudget>> {
+    annotations
+        .get("retry.linkerd.io/budget-ratio")
+        .map(|ratio| {
+            let retry_ratio = ratio.parse::<f32>()?;
+            let min_retries_per_second = annotations
+                .get("retry.linkerd.io/budget-min-retries-per-second")
+                .map(|s| s.parse::<u32>())
+                .transpose()?
+                .unwrap_or(10);
+            let ttl = annotations
+                .get("retry.linkerd.io/budget-ttl")
+                .map(|s| parse_duration(s))
+                .transpose()?
+                .unwrap_or_else(|| time::Duration::from_secs(10));
+            ensure!(
+                retry_ratio.is_finite() && retry_ratio >= 0.0,
+                "retry ratio must be a non-negative number"
+            );
+            ensure!(
+                ttl >= time::Duration::from_secs(1) && ttl <= time::Duration::from_secs(60),
+                "ttl must be between 1s and 60s"
+            );
+
+            Ok(RetryBudget {
+                retry_ratio,
+                min_retries_per_second,
+                ttl,
+            })
+        })
+        .transpose()
+}
+
 fn parse_duration(s: &str) -> Result<time::Duration> {
     let s = s.trim();
     let offset = s
@@ -0,0 +1,106 @@
+use super::{Index, ServicePort};
+use linkerd_policy_controller_core::outbound::{HttpRoute, OutboundPolicy};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::IpAddr,
+    num::NonZeroU16,
+};
+
+/// The contents of a single namespace's outbound index.
+#[derive(Debug, Default, Serialize)]
+pub struct Namespace {
+    pub services: BTreeMap<String, Service>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    /// Whether the Service exists. Routes may reference a Service before it is
+    /// created.
+    pub exists: bool,
+
+    /// Whether the Service has any ready endpoints.
+    pub ready: bool,
+
+    pub cluster_ips: BTreeSet<IpAddr>,
+
+    /// The policy published for each port of the Service that has been looked
+    /// up or that is targeted by a route, by the namespace of its clients.
+    pub ports: BTreeMap<NonZeroU16, BTreeMap<String, OutboundPolicy>>,
+
+    /// The routes that target the Service without specifying a port.
+    pub http_routes: BTreeMap<String, HttpRoute>,
+
+    /// Errors in the Service's annotations, whose configuration is ignored.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Returns the contents of every namespace or, if one is given, of a single
+/// namespace.
+pub fn dump(index: &Index, namespace: Option<&str>) -> BTreeMap<String, Namespace> {
+    let included = |ns: &str| namespace.map_or(true, |n| n == ns);
+    let mut namespaces = BTreeMap::<String, Namespace>::new();
+
+    for (svc, info) in index.service_info.iter() {
+        if included(&svc.namespace) {
+            let service = service(&mut namespaces, &svc.namespace, &svc.name);
+            service.exists = true;
+            service.ready = info.ready;
+            service.errors = info.errors.clone();
+        }
+    }
+    for (addr, svc) in index.services_by_ip.iter() {
+        if included(&svc.namespace) {
+            service(&mut namespaces, &svc.namespace, &svc.name)
+                .cluster_ips
+                .insert(*addr);
+        }
+    }
+    for (ns, index) in index.namespaces.by_ns.iter() {
+        if !included(ns) {
+            continue;
+        }
+        for (
+            ServicePort {
+                service: name,
+                port,
+            },
+            routes,
+        ) in index.service_port_routes.iter()
+        {
+            let policies = routes
+                .watches_by_ns
+                .iter()
+                .map(|(source, watch)| (source.clone(), watch.watch.borrow().clone()))
+                .collect();
+            service(&mut namespaces, ns, name)
+                .ports
+                .insert(*port, policies);
+        }
+        for (name, routes) in index.service_routes.iter() {
+            let routes = routes
+                .iter()
+                .map(|(gknn, route)| (gknn.to_string(), route.clone()));
+            service(&mut namespaces, ns, name)
+                .http_routes
+                .extend(routes);
+        }
+    }
+
+    namespaces
+}
+
+fn service<'n>(
+    namespaces: &'n mut BTreeMap<String, Namespace>,
+    ns: &str,
+    name: &str,
+) -> &'n mut Service {
+    namespaces
+        .entry(ns.to_string())
+        .or_default()
+        .services
+        .entry(name.to_string())
+        .or_default()
+}
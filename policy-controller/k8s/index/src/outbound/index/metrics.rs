@@ -2,10 +2,12 @@ use prometheus_client::{
     collector::Collector,
     encoding::{DescriptorEncoder, EncodeMetric},
     metrics::{gauge::ConstGauge, MetricType},
-    registry::Registry,
+    registry::{Registry, Unit},
 };
 
 use super::SharedIndex;
+use ahash::AHashSet as HashSet;
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 struct Instrumented(SharedIndex);
@@ -62,6 +64,109 @@ impl Collector for Instrumented {
             service_port_routes.encode(service_port_route_encoder)?;
         }
 
+        let mut published_encoder = encoder.encode_descriptor(
+            "published_policies",
+            "An estimate of the memory held by the policies published for service ports, from \
+             their encoded size",
+            Some(&Unit::Bytes),
+            MetricType::Gauge,
+        )?;
+        for (ns, index) in &this.namespaces.by_ns {
+            let labels = [("namespace", ns.as_str())];
+            let bytes = index
+                .service_port_routes
+                .values()
+                .flat_map(|routes| routes.watches_by_ns.values())
+                .map(|watch| crate::size::estimate_bytes(&*watch.watch.borrow()))
+                .sum::<usize>();
+            let published_encoder = published_encoder.encode_family(&labels)?;
+            ConstGauge::new(bytes as i64).encode(published_encoder)?;
+        }
+
+        // Routes may be bound to many services and ports, so each route is
+        // counted once in the namespace in which it is defined.
+        let mut routes = HashSet::new();
+        for index in this.namespaces.by_ns.values() {
+            routes.extend(
+                index
+                    .service_routes
+                    .values()
+                    .flat_map(|routes| routes.keys()),
+            );
+            routes.extend(
+                index
+                    .service_port_routes
+                    .values()
+                    .flat_map(|svc| svc.watches_by_ns.values())
+                    .flat_map(|watch| watch.routes.keys()),
+            );
+        }
+        let mut routes_by_kind = BTreeMap::<_, u32>::new();
+        for gknn in routes {
+            *routes_by_kind
+                .entry((&*gknn.namespace, &*gknn.group, &*gknn.kind))
+                .or_default() += 1;
+        }
+        let mut routes_encoder = encoder.encode_descriptor(
+            "route_index_size",
+            "The number of routes bound to services in index by group and kind",
+            None,
+            MetricType::Gauge,
+        )?;
+        for ((namespace, group, kind), routes) in routes_by_kind {
+            let labels = [("namespace", namespace), ("group", group), ("kind", kind)];
+            let routes_encoder = routes_encoder.encode_family(&labels)?;
+            ConstGauge::new(routes).encode(routes_encoder)?;
+        }
+
+        let empty_encoder = encoder.encode_descriptor(
+            "empty_namespaces",
+            "The number of namespaces retained in index without any routes or watches",
+            None,
+            MetricType::Gauge,
+        )?;
+        let empty = this
+            .namespaces
+            .by_ns
+            .values()
+            .filter(|index| index.is_empty())
+            .count();
+        ConstGauge::new(empty as u32).encode(empty_encoder)?;
+
+        let mut rejected = BTreeMap::<_, u32>::new();
+        for gknn in this.parsed_routes.rejected() {
+            *rejected.entry(&gknn.namespace).or_default() += 1;
+        }
+        let mut rejected_encoder = encoder.encode_descriptor(
+            "rejected_routes",
+            "The number of routes that are quarantined because they could not be converted",
+            None,
+            MetricType::Gauge,
+        )?;
+        for (namespace, routes) in rejected {
+            let labels = [("namespace", namespace.as_ref())];
+            let rejected_encoder = rejected_encoder.encode_family(&labels)?;
+            ConstGauge::new(routes).encode(rejected_encoder)?;
+        }
+
+        let mut invalid = BTreeMap::<_, u32>::new();
+        for (svc, info) in this.service_info.iter() {
+            if !info.errors.is_empty() {
+                *invalid.entry(svc.namespace.as_str()).or_default() += 1;
+            }
+        }
+        let mut invalid_encoder = encoder.encode_descriptor(
+            "invalid_services",
+            "The number of services whose balancer or retry annotations are invalid",
+            None,
+            MetricType::Gauge,
+        )?;
+        for (namespace, services) in invalid {
+            let labels = [("namespace", namespace)];
+            let invalid_encoder = invalid_encoder.encode_family(&labels)?;
+            ConstGauge::new(services).encode(invalid_encoder)?;
+        }
+
         Ok(())
     }
 }
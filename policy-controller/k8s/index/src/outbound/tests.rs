@@ -2,12 +2,15 @@ use std::sync::Arc;
 
 use crate::{
     defaults::DefaultPolicy,
-    outbound::index::{Index, SharedIndex},
+    outbound::index::{dump, Index, SharedIndex},
     ClusterInfo,
 };
 use kubert::index::IndexNamespacedResource;
-use linkerd_policy_controller_core::IpNet;
-use linkerd_policy_controller_k8s_api::{self as k8s};
+use linkerd_policy_controller_core::{
+    outbound::{AppProtocol, OutboundPolicy, RetryBudget},
+    IpNet,
+};
+use linkerd_policy_controller_k8s_api::{self as k8s, ResourceExt};
 use tokio::time;
 
 mod http_routes;
@@ -34,6 +37,479 @@ pub fn mk_service(ns: impl ToString, name: impl ToString, port: i32) -> k8s::Ser
     }
 }
 
+#[test]
+fn detect_timeout_annotated() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    svc.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "5s".into(),
+    );
+    test.index.write().apply(svc.clone());
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "svc".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("svc.ns should exist");
+    assert_eq!(
+        rx.borrow_and_update().detect_timeout,
+        time::Duration::from_secs(5)
+    );
+
+    // Removing the annotation restores the cluster default.
+    svc.annotations_mut()
+        .remove("config.linkerd.io/protocol-detect-timeout");
+    test.index.write().apply(svc);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(rx.borrow().detect_timeout, time::Duration::from_secs(1));
+}
+
+#[test]
+fn detect_timeout_oversized() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    svc.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "3000000000000000h".into(),
+    );
+    test.index.write().apply(svc.clone());
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "svc".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("svc.ns should exist");
+    assert_eq!(
+        rx.borrow_and_update().detect_timeout,
+        time::Duration::from_secs(1),
+        "timeouts that cannot be represented fall back to the default"
+    );
+
+    // Timeouts beyond the maximum also fall back to the default.
+    svc.annotations_mut().insert(
+        "config.linkerd.io/protocol-detect-timeout".into(),
+        "2h".into(),
+    );
+    test.index.write().apply(svc);
+    assert_eq!(rx.borrow().detect_timeout, time::Duration::from_secs(1));
+}
+
+#[test]
+fn retry_budget_annotated() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    svc.annotations_mut()
+        .insert("retry.linkerd.io/budget-ratio".into(), "0.5".into());
+    svc.annotations_mut()
+        .insert("retry.linkerd.io/budget-ttl".into(), "30s".into());
+    test.index.write().apply(svc.clone());
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "svc".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("svc.ns should exist");
+    assert_eq!(
+        rx.borrow_and_update().retry_budget,
+        Some(RetryBudget {
+            retry_ratio: 0.5,
+            min_retries_per_second: 10,
+            ttl: time::Duration::from_secs(30),
+        })
+    );
+
+    // An invalid budget is ignored.
+    svc.annotations_mut()
+        .insert("retry.linkerd.io/budget-ttl".into(), "5m".into());
+    test.index.write().apply(svc);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(rx.borrow().retry_budget, None);
+}
+
+#[test]
+fn invalid_accrual_reported() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    svc.annotations_mut().insert(
+        "balancer.linkerd.io/failure-accrual".into(),
+        "consecutive".into(),
+    );
+    svc.annotations_mut().insert(
+        "balancer.linkerd.io/failure-accrual-consecutive-max-failures".into(),
+        "0".into(),
+    );
+    test.index.write().apply(svc.clone());
+
+    let rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "svc".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("svc.ns should exist");
+    assert_eq!(rx.borrow().accrual, None);
+    let errors = |index: &SharedIndex| {
+        dump::dump(&index.read(), Some("ns"))["ns"].services["svc"]
+            .errors
+            .clone()
+    };
+    assert_eq!(
+        errors(&test.index),
+        vec!["invalid failure accrual: max_failures must be positive".to_string()]
+    );
+
+    // Errors are cleared once the annotations are fixed.
+    svc.annotations_mut().insert(
+        "balancer.linkerd.io/failure-accrual-consecutive-max-failures".into(),
+        "3".into(),
+    );
+    test.index.write().apply(svc);
+    assert!(rx.borrow().accrual.is_some());
+    assert!(errors(&test.index).is_empty());
+}
+
+#[test]
+fn app_protocol_hints() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    let ports = svc.spec.as_mut().unwrap().ports.as_mut().unwrap();
+    for (port, app_protocol) in [
+        (8080, "kubernetes.io/h2c"),
+        (8081, "http"),
+        (8082, "tcp"),
+        (8083, "https"),
+    ] {
+        ports.push(k8s::api::core::v1::ServicePort {
+            port,
+            app_protocol: Some(app_protocol.to_string()),
+            ..Default::default()
+        });
+    }
+    svc.annotations_mut()
+        .insert("config.linkerd.io/opaque-ports".into(), "8081".into());
+    test.index.write().apply(svc);
+
+    let policy = |port: u16| {
+        let rx = test
+            .index
+            .write()
+            .outbound_policy_rx(
+                "svc".to_string(),
+                "ns".to_string(),
+                port.try_into().unwrap(),
+                "ns".to_string(),
+            )
+            .expect("svc.ns should exist");
+        let policy = rx.borrow();
+        (policy.opaque, policy.app_protocol)
+    };
+    assert_eq!(policy(8080), (false, Some(AppProtocol::Http2)));
+    // The annotation takes precedence over the port's appProtocol.
+    assert_eq!(policy(8081), (true, None));
+    assert_eq!(policy(8082), (true, None));
+    assert_eq!(policy(8083), (false, None));
+}
+
+#[test]
+fn endpoint_target_ports() {
+    use k8s::api::{
+        core::v1::ServicePort,
+        discovery::v1::{Endpoint, EndpointPort, EndpointSlice},
+    };
+
+    let test = TestConfig::default();
+
+    // The Service maps port 80 to a named target port and port 9090 to a
+    // numeric target port.
+    let mut svc = mk_service("ns", "svc", 80);
+    let ports = svc.spec.as_mut().unwrap().ports.as_mut().unwrap();
+    ports[0].name = Some("http".to_string());
+    ports[0].target_port = Some(k8s::IntOrString::String("web".to_string()));
+    ports.push(ServicePort {
+        name: Some("admin".to_string()),
+        port: 9090,
+        target_port: Some(k8s::IntOrString::Int(9990)),
+        ..Default::default()
+    });
+    test.index.write().apply(svc);
+
+    // The EndpointSlice controller resolves target ports to numbers.
+    let slice = EndpointSlice {
+        metadata: k8s::ObjectMeta {
+            namespace: Some("ns".to_string()),
+            name: Some("svc-abcde".to_string()),
+            labels: Some([("kubernetes.io/service-name".to_string(), "svc".to_string())].into()),
+            ..Default::default()
+        },
+        address_type: "IPv4".to_string(),
+        endpoints: vec![Endpoint {
+            addresses: vec!["192.0.2.1".to_string()],
+            ..Default::default()
+        }],
+        ports: Some(
+            [("http", 8080), ("admin", 9990)]
+                .into_iter()
+                .map(|(name, port)| EndpointPort {
+                    name: Some(name.to_string()),
+                    port: Some(port),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+    };
+    test.index.write().apply(slice);
+
+    let lookup = |port: u16| {
+        test.index
+            .read()
+            .lookup_endpoint("192.0.2.1".parse().unwrap(), port.try_into().unwrap())
+            .map(|(svc, port)| (svc.name, port.get()))
+    };
+    assert_eq!(lookup(8080), Some(("svc".to_string(), 80)));
+    assert_eq!(lookup(9990), Some(("svc".to_string(), 9090)));
+    assert_eq!(lookup(80), None, "Service ports are not endpoint ports");
+
+    <Index as IndexNamespacedResource<EndpointSlice>>::delete(
+        &mut test.index.write(),
+        "ns".to_string(),
+        "svc-abcde".to_string(),
+    );
+    assert_eq!(lookup(8080), None);
+}
+
+#[test]
+fn opaque_ports_annotated() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    let ports = svc.spec.as_mut().unwrap().ports.as_mut().unwrap();
+    for (port, name) in [(3306, "mysql"), (9000, "9000-9001")] {
+        ports.push(k8s::api::core::v1::ServicePort {
+            name: Some(name.to_string()),
+            port,
+            ..Default::default()
+        });
+    }
+    // Ports may be referenced by name, and invalid entries are ignored. Names
+    // take precedence over ranges.
+    svc.annotations_mut().insert(
+        "config.linkerd.io/opaque-ports".into(),
+        "mysql, bogus, 9000-9001, 0".into(),
+    );
+    test.index.write().apply(svc);
+
+    let opaque = |port: u16| {
+        test.index
+            .write()
+            .outbound_policy_rx(
+                "svc".to_string(),
+                "ns".to_string(),
+                port.try_into().unwrap(),
+                "ns".to_string(),
+            )
+            .expect("svc.ns should exist")
+            .borrow()
+            .opaque
+    };
+    assert!(opaque(3306));
+    assert!(opaque(9000));
+    assert!(!opaque(9001));
+    assert!(!opaque(8080));
+}
+
+#[test]
+fn endpoint_address_reassigned() {
+    use k8s::api::{
+        core::v1::ObjectReference,
+        discovery::v1::{Endpoint, EndpointPort, EndpointSlice},
+    };
+
+    let test = TestConfig::default();
+    test.index.write().apply(mk_service("ns", "old", 8080));
+    test.index.write().apply(mk_service("ns", "new", 8080));
+
+    let mk_slice = |service: &str, pod_uid: &str| EndpointSlice {
+        metadata: k8s::ObjectMeta {
+            namespace: Some("ns".to_string()),
+            name: Some(format!("{service}-abcde")),
+            labels: Some(
+                [(
+                    "kubernetes.io/service-name".to_string(),
+                    service.to_string(),
+                )]
+                .into(),
+            ),
+            ..Default::default()
+        },
+        address_type: "IPv4".to_string(),
+        endpoints: vec![Endpoint {
+            addresses: vec!["192.0.2.1".to_string()],
+            target_ref: Some(ObjectReference {
+                kind: Some("Pod".to_string()),
+                uid: Some(pod_uid.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }],
+        ports: Some(vec![EndpointPort {
+            port: Some(8080),
+            ..Default::default()
+        }]),
+    };
+    let addr = "192.0.2.1".parse().unwrap();
+    let lookup = || {
+        test.index
+            .read()
+            .lookup_endpoint(addr, 8080.try_into().unwrap())
+            .map(|(svc, _)| svc.name)
+    };
+
+    test.index.write().apply(mk_slice("old", "uid-1"));
+    assert_eq!(lookup(), Some("old".to_string()));
+    let mut owner_rx = test.index.read().endpoint_owner_rx(addr).unwrap();
+    assert_eq!(*owner_rx.borrow_and_update(), "uid-1");
+
+    // A new pod is assigned the address before the old pod's endpoint has
+    // been removed.
+    test.index.write().apply(mk_slice("new", "uid-2"));
+    assert!(owner_rx.has_changed().unwrap());
+    assert_eq!(*owner_rx.borrow_and_update(), "uid-2");
+    assert_eq!(lookup(), Some("new".to_string()));
+
+    // Re-applying the stale endpoint does not reclaim the address.
+    test.index.write().apply(mk_slice("old", "uid-1"));
+    assert!(!owner_rx.has_changed().unwrap());
+    assert_eq!(lookup(), Some("new".to_string()));
+
+    // When the new pod's endpoint is removed, the stale endpoint is all that
+    // remains.
+    <Index as IndexNamespacedResource<EndpointSlice>>::delete(
+        &mut test.index.write(),
+        "ns".to_string(),
+        "new-abcde".to_string(),
+    );
+    assert_eq!(*owner_rx.borrow_and_update(), "uid-1");
+    assert_eq!(lookup(), Some("old".to_string()));
+
+    <Index as IndexNamespacedResource<EndpointSlice>>::delete(
+        &mut test.index.write(),
+        "ns".to_string(),
+        "old-abcde".to_string(),
+    );
+    assert!(
+        owner_rx.has_changed().is_err(),
+        "owner watch must close when the address is removed"
+    );
+    assert_eq!(lookup(), None);
+}
+
+#[test]
+fn cluster_weights_distribute_to_remote_discovery() {
+    let test = TestConfig::default();
+
+    let mut svc = mk_service("ns", "svc", 8080);
+    svc.annotations_mut().insert(
+        "multicluster.linkerd.io/cluster-weights".to_string(),
+        "local=1, east=3, west=2, bogus".to_string(),
+    );
+    test.index.write().apply(svc);
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "svc".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("svc.ns should exist");
+    let backends = |rx: &mut tokio::sync::watch::Receiver<OutboundPolicy>| {
+        rx.borrow_and_update()
+            .cluster_backends
+            .iter()
+            .map(|b| (b.name.clone(), b.weight, b.ready))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        backends(&mut rx),
+        vec![("svc".to_string(), 1, false)],
+        "clusters without remote-discovery mirrors are ignored"
+    );
+
+    // Remote-discovery mirrors have no local endpoints but are ready.
+    let mut mirror = mk_service("ns", "svc-east", 8080);
+    mirror.labels_mut().extend([
+        (
+            "multicluster.linkerd.io/remote-discovery".to_string(),
+            "east".to_string(),
+        ),
+        (
+            "multicluster.linkerd.io/remote-service".to_string(),
+            "svc".to_string(),
+        ),
+    ]);
+    test.index.write().apply(mirror);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(
+        backends(&mut rx),
+        vec![
+            ("svc".to_string(), 1, false),
+            ("svc-east".to_string(), 3, true)
+        ]
+    );
+}
+
+#[test]
+fn gc_unused_namespaces() {
+    let test = TestConfig::default();
+    test.index.write().apply(mk_service("ns", "svc", 8080));
+
+    let rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "svc".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("svc.ns should exist");
+    assert_eq!(
+        test.index.write().gc(),
+        0,
+        "namespaces with watches must be retained"
+    );
+
+    drop(rx);
+    assert_eq!(test.index.write().gc(), 1);
+    assert_eq!(test.index.write().gc(), 0);
+}
+
 impl TestConfig {
     fn from_default_policy(default_policy: DefaultPolicy) -> Self {
         Self::from_default_policy_with_probes(default_policy, vec![])
@@ -54,6 +530,11 @@ impl TestConfig {
             default_detect_timeout: detect_timeout,
             default_opaque_ports: Default::default(),
             probe_networks,
+            max_authorizations_per_server: None,
+            max_routes_per_parent: None,
+            max_backends_per_route: None,
+            meshed_pods_only: false,
+            remote_identity_domains: Default::default(),
         };
         let index = Index::shared(Arc::new(cluster));
         Self { index }
@@ -91,6 +91,296 @@ fn backend_service() {
     }
 }
 
+#[test]
+fn backend_service_ready() {
+    let test = TestConfig::default();
+    test.index.write().apply(mk_service("ns", "apex", 8080));
+    test.index.write().apply(mk_service("ns", "backend", 8080));
+    test.index
+        .write()
+        .apply(mk_route("ns", "route", 8080, "apex", "backend"));
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "apex".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("apex.ns should exist");
+    let mut backend_ready = || {
+        let policy = rx.borrow_and_update();
+        let backend = policy
+            .http_routes
+            .get(&GroupKindNamespaceName {
+                group: k8s::policy::HttpRoute::group(&()),
+                kind: k8s::policy::HttpRoute::kind(&()),
+                namespace: "ns".into(),
+                name: "route".into(),
+            })
+            .expect("route should exist")
+            .rules
+            .first()
+            .expect("rule should exist")
+            .backends
+            .first()
+            .expect("backend should exist")
+            .clone();
+        match backend {
+            Backend::Service(WeightedService { exists, ready, .. }) => {
+                assert!(exists);
+                ready
+            }
+            _ => panic!("backend should be a service"),
+        }
+    };
+    assert!(!backend_ready(), "backends without endpoints are not ready");
+
+    test.index.write().apply(mk_endpoint_slice(
+        "ns",
+        "backend-abc",
+        "backend",
+        [true, false],
+    ));
+    assert!(backend_ready());
+
+    test.index
+        .write()
+        .apply(mk_endpoint_slice("ns", "backend-abc", "backend", [false]));
+    assert!(
+        !backend_ready(),
+        "backends without ready endpoints are not ready"
+    );
+
+    test.index
+        .write()
+        .apply(mk_endpoint_slice("ns", "backend-def", "backend", [true]));
+    assert!(backend_ready());
+
+    kubert::index::IndexNamespacedResource::<k8s::api::discovery::v1::EndpointSlice>::delete(
+        &mut *test.index.write(),
+        "ns".to_string(),
+        "backend-def".to_string(),
+    );
+    assert!(!backend_ready());
+}
+
+#[test]
+fn backend_service_zones() {
+    use k8s::api::discovery::v1::{EndpointHints, ForZone};
+
+    let test = TestConfig::default();
+    test.index.write().apply(mk_service("ns", "apex", 8080));
+    test.index.write().apply(mk_service("ns", "backend", 8080));
+    test.index
+        .write()
+        .apply(mk_route("ns", "route", 8080, "apex", "backend"));
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "apex".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("apex.ns should exist");
+    let mut backend_zones = || {
+        let policy = rx.borrow_and_update();
+        let backend = policy
+            .http_routes
+            .values()
+            .next()
+            .expect("route should exist")
+            .rules
+            .first()
+            .expect("rule should exist")
+            .backends
+            .first()
+            .expect("backend should exist")
+            .clone();
+        match backend {
+            Backend::Service(WeightedService { zones, .. }) => zones,
+            _ => panic!("backend should be a service"),
+        }
+    };
+    assert!(backend_zones().is_empty());
+
+    // Endpoints without hints are preferred in their own zones, and endpoints
+    // that are not ready are ignored.
+    let mut slice = mk_endpoint_slice("ns", "backend-abc", "backend", [true, true, false]);
+    for (ep, zone) in slice
+        .endpoints
+        .iter_mut()
+        .zip(["zone-b", "zone-a", "zone-c"])
+    {
+        ep.zone = Some(zone.to_string());
+    }
+    test.index.write().apply(slice.clone());
+    assert_eq!(backend_zones(), ["zone-a", "zone-b"]);
+
+    // Hints take precedence over the endpoints' own zones.
+    for ep in slice.endpoints.iter_mut() {
+        ep.hints = Some(EndpointHints {
+            for_zones: Some(vec![ForZone {
+                name: "zone-d".to_string(),
+            }]),
+        });
+    }
+    test.index.write().apply(slice);
+    assert_eq!(backend_zones(), ["zone-d"]);
+
+    kubert::index::IndexNamespacedResource::<k8s::api::discovery::v1::EndpointSlice>::delete(
+        &mut *test.index.write(),
+        "ns".to_string(),
+        "backend-abc".to_string(),
+    );
+    assert!(backend_zones().is_empty());
+}
+
+#[test]
+fn routes_applied_to_mirrors() {
+    let test = TestConfig::default();
+    test.index.write().apply(mk_service("ns", "apex", 8080));
+    let mut route = mk_route("ns", "route", 8080, "apex", "apex");
+    route.annotations_mut().insert(
+        "policy.linkerd.io/apply-to-mirrors".to_string(),
+        "true".to_string(),
+    );
+    test.index.write().apply(route.clone());
+
+    // The mirror is created after the route.
+    let mut mirror = mk_service("ns", "apex-east", 8080);
+    mirror.labels_mut().extend([
+        (
+            "mirror.linkerd.io/mirrored-service".to_string(),
+            "true".to_string(),
+        ),
+        (
+            "mirror.linkerd.io/cluster-name".to_string(),
+            "east".to_string(),
+        ),
+    ]);
+    test.index.write().apply(mirror);
+
+    let mut rx = test
+        .index
+        .write()
+        .outbound_policy_rx(
+            "apex-east".to_string(),
+            "ns".to_string(),
+            8080.try_into().unwrap(),
+            "ns".to_string(),
+        )
+        .expect("apex-east.ns should exist");
+    let gknn = GroupKindNamespaceName {
+        group: k8s::policy::HttpRoute::group(&()),
+        kind: k8s::policy::HttpRoute::kind(&()),
+        namespace: "ns".into(),
+        name: "route".into(),
+    };
+    {
+        let policy = rx.borrow_and_update();
+        let backend = policy
+            .http_routes
+            .get(&gknn)
+            .expect("route should apply to the mirror")
+            .rules
+            .first()
+            .expect("rule should exist")
+            .backends
+            .first()
+            .expect("backend should exist");
+        match backend {
+            Backend::Service(WeightedService { name, .. }) => assert_eq!(
+                name, "apex-east",
+                "backends must refer to the mirror rather than the original"
+            ),
+            _ => panic!("backend should be a service"),
+        }
+    }
+
+    // Routes that no longer opt in are removed from the mirror.
+    route.annotations_mut().clear();
+    test.index.write().apply(route);
+    assert!(rx.has_changed().unwrap());
+    assert!(!rx.borrow_and_update().http_routes.contains_key(&gknn));
+}
+
+#[test]
+fn lookup_hostname() {
+    let test = TestConfig::default();
+    test.index.write().apply(mk_service("ns", "egress", 8080));
+    test.index
+        .write()
+        .apply(mk_service("ns", "egress-wild", 8080));
+    let mut route = mk_route("ns", "exact", 8080, "egress", "egress");
+    route.spec.hostnames = Some(vec!["api.example.com".to_string()]);
+    test.index.write().apply(route);
+    let mut route = mk_route("ns", "wild", 8080, "egress-wild", "egress-wild");
+    route.spec.hostnames = Some(vec!["*.example.com".to_string()]);
+    test.index.write().apply(route);
+
+    let lookup = |host: &str, port: u16, source: &str| {
+        test.index
+            .read()
+            .lookup_hostname(host, port.try_into().unwrap(), source)
+            .map(|svc| svc.name)
+    };
+    assert_eq!(
+        lookup("api.example.com", 8080, "ns").as_deref(),
+        Some("egress"),
+        "exact hostnames are preferred"
+    );
+    assert_eq!(
+        lookup("www.example.com", 8080, "other").as_deref(),
+        Some("egress-wild"),
+        "producer routes apply to all namespaces"
+    );
+    assert_eq!(lookup("api.example.org", 8080, "ns"), None);
+    assert_eq!(lookup("api.example.com", 9090, "ns"), None);
+}
+
+fn mk_endpoint_slice(
+    ns: impl ToString,
+    name: impl ToString,
+    service: impl ToString,
+    ready: impl IntoIterator<Item = bool>,
+) -> k8s::api::discovery::v1::EndpointSlice {
+    use k8s::api::discovery::v1::{Endpoint, EndpointConditions, EndpointSlice};
+
+    EndpointSlice {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            labels: Some(
+                [(
+                    "kubernetes.io/service-name".to_string(),
+                    service.to_string(),
+                )]
+                .into(),
+            ),
+            ..Default::default()
+        },
+        address_type: "IPv4".to_string(),
+        endpoints: ready
+            .into_iter()
+            .map(|ready| Endpoint {
+                addresses: vec!["192.0.2.1".to_string()],
+                conditions: Some(EndpointConditions {
+                    ready: Some(ready),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .collect(),
+        ports: None,
+    }
+}
+
 fn mk_route(
     ns: impl ToString,
     name: impl ToString,
@@ -121,6 +411,7 @@ fn mk_route(
             },
             hostnames: None,
             rules: Some(vec![HttpRouteRule {
+                name: None,
                 matches: Some(vec![HttpRouteMatch {
                     path: Some(HttpPathMatch::PathPrefix {
                         value: "/foo/bar".to_string(),
@@ -41,20 +41,6 @@ impl std::hash::Hasher for PortHasher {
     }
 }
 
-/// Reads `annotation` from the provided set of annotations, parsing it as a port set.  If the
-/// annotation is not set or is invalid, the empty set is returned.
-pub(crate) fn ports_annotation(
-    annotations: &std::collections::BTreeMap<String, String>,
-    annotation: &str,
-) -> Option<PortSet> {
-    annotations.get(annotation).map(|spec| {
-        parse_portset(spec).unwrap_or_else(|error| {
-            tracing::info!(%spec, %error, %annotation, "Invalid ports list");
-            Default::default()
-        })
-    })
-}
-
 /// Read a comma-separated of ports or port ranges from the given string.
 pub fn parse_portset(s: &str) -> Result<PortSet> {
     let mut ports = PortSet::default();
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use std::io;
+
+/// Estimates the memory held by a value from the size of its JSON encoding.
+///
+/// Encoded sizes do not account for the overhead of allocations and
+/// collections, so this is useful to track how the state held by the indexes
+/// grows rather than as an exact measure. The value is encoded without being
+/// buffered.
+pub(crate) fn estimate_bytes(value: &impl Serialize) -> usize {
+    let mut counter = Counter(0);
+    match serde_json::to_writer(&mut counter, value) {
+        Ok(()) => counter.0,
+        Err(error) => {
+            tracing::debug!(%error, "Failed to estimate size");
+            0
+        }
+    }
+}
+
+struct Counter(usize);
+
+impl io::Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -5,19 +5,28 @@ use crate::{
 };
 use ahash::{AHashMap as HashMap, AHashSet as HashSet};
 use chrono::{offset::Utc, DateTime};
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
 use kubert::lease::Claim;
-use linkerd_policy_controller_core::{routes::GroupKindName, POLICY_CONTROLLER_NAME};
+use linkerd_policy_controller_core::{
+    routes::{GroupKindName, Quarantine},
+    POLICY_CONTROLLER_NAME,
+};
 use linkerd_policy_controller_k8s_api::{
     self as k8s_core_api, gateway as k8s_gateway_api, policy as linkerd_k8s_api,
     NamespaceResourceScope, Resource, ResourceExt,
 };
 use parking_lot::RwLock;
 use prometheus_client::{
-    metrics::{counter::Counter, histogram::Histogram},
+    collector::Collector,
+    encoding::{DescriptorEncoder, EncodeMetric},
+    metrics::{counter::Counter, gauge::ConstGauge, histogram::Histogram, MetricType},
     registry::{Registry, Unit},
 };
 use serde::de::DeserializeOwned;
-use std::{collections::hash_map::Entry, sync::Arc};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, BTreeSet},
+    sync::{Arc, OnceLock, Weak},
+};
 use tokio::{
     sync::{mpsc, watch::Receiver},
     time::{self, Duration},
@@ -36,6 +45,10 @@ mod reasons {
     pub const BACKEND_NOT_FOUND: &str = "BackendNotFound";
     pub const INVALID_KIND: &str = "InvalidKind";
     pub const NO_MATCHING_PARENT: &str = "NoMatchingParent";
+    pub const ROUTE_LIMIT_EXCEEDED: &str = "RouteLimitExceeded";
+    pub const BACKEND_LIMIT_EXCEEDED: &str = "BackendLimitExceeded";
+    pub const CIRCULAR_REFERENCE: &str = "CircularReference";
+    pub const UNSUPPORTED_VALUE: &str = "UnsupportedValue";
 }
 
 mod cond_statuses {
@@ -52,12 +65,16 @@ pub struct Controller {
     updates: mpsc::Receiver<Update>,
     patch_timeout: Duration,
 
+    /// The maximum number of patches that may be applied concurrently.
+    patch_concurrency: usize,
+
     /// True if this policy controller is the leader — false otherwise.
     leader: bool,
 
     metrics: ControllerMetrics,
 }
 
+#[derive(Clone)]
 pub struct ControllerMetrics {
     patch_succeeded: Counter,
     patch_failed: Counter,
@@ -83,27 +100,84 @@ pub struct Index {
     servers: HashSet<ResourceId>,
     services: HashMap<ResourceId, Service>,
 
+    /// Orders the routes attached to each parent so that, when a parent has
+    /// more routes than permitted, the oldest routes are accepted. Only
+    /// maintained when a route limit is configured.
+    parent_routes: HashMap<routes::ParentReference, BTreeSet<RouteRank>>,
+    limits: Limits,
+
+    /// The routes attached to each Service, regardless of port, so that the
+    /// backends of a Service's routes may be followed to detect cycles.
+    service_routes: HashMap<ResourceId, HashSet<NamespaceGroupKindName>>,
+
+    /// The names of controllers that no longer run (e.g. those of prior
+    /// Linkerd versions), whose statuses are removed from routes.
+    retired_controllers: HashSet<String>,
+
+    /// The routes that the policy indexes could not parse, which are not
+    /// accepted by any parent.
+    quarantine: Quarantine,
+
     metrics: IndexMetrics,
 }
 
+/// Bounds the fanout of routes so that a pathological namespace cannot
+/// degrade the policies served for every workload in it.
+///
+/// Routes that exceed a limit are not accepted by their parents.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    /// The maximum number of routes that may be attached to a single parent.
+    pub routes_per_parent: Option<usize>,
+
+    /// The maximum number of backends that a single route may reference.
+    pub backends_per_route: Option<usize>,
+}
+
 pub struct IndexMetrics {
     patch_enqueues: Counter,
     patch_channel_full: Counter,
+    bindings: Arc<OnceLock<Weak<RwLock<Index>>>>,
 }
 
+/// Exposes the depth of the channel on which the index sends patches to the
+/// controller.
+///
+/// The channel is only weakly referenced, so that it closes when the index is
+/// dropped.
+#[derive(Debug)]
+pub struct UpdatesQueue(mpsc::WeakSender<Update>);
+
+/// Exposes the outcome of binding each route to its parents and resolving its
+/// backends, as reflected in the statuses that the index computes.
+///
+/// The metrics are registered before the index is built, so the index is set
+/// once it has been.
+struct RouteBindings(Arc<OnceLock<Weak<RwLock<Index>>>>);
+
 #[derive(Clone, PartialEq)]
 struct RouteRef {
     parents: Vec<routes::ParentReference>,
     backends: Vec<routes::BackendReference>,
     statuses: Vec<k8s_gateway_api::RouteParentStatus>,
+    created: Option<DateTime<Utc>>,
+
+    /// The error with which the policy indexes rejected the route's current
+    /// generation, if any.
+    rejection: Option<Arc<str>>,
 }
 
+/// Routes are ranked by their creation time and then by their identity.
+type RouteRank = (Option<DateTime<Utc>>, NamespaceGroupKindName);
+
 #[derive(Debug, PartialEq)]
 pub struct Update {
     pub id: NamespaceGroupKindName,
-    pub patch: k8s_core_api::Patch<serde_json::Value>,
+    pub patch: Patch,
 }
 
+type Patch = k8s_core_api::Patch<serde_json::Value>;
+
 impl ControllerMetrics {
     pub fn register(prom: &mut Registry) -> Self {
         let patch_succeeded = Counter::default();
@@ -177,10 +251,106 @@ impl IndexMetrics {
             patch_channel_full.clone(),
         );
 
+        let bindings = Arc::<OnceLock<_>>::default();
+        prom.register_collector(Box::new(RouteBindings(bindings.clone())));
+
         Self {
             patch_enqueues,
             patch_channel_full,
+            bindings,
+        }
+    }
+}
+
+// === impl UpdatesQueue ===
+
+impl UpdatesQueue {
+    pub fn register(prom: &mut Registry, updates: &mpsc::Sender<Update>) {
+        prom.register_collector(Box::new(Self(updates.downgrade())));
+    }
+}
+
+impl Collector for UpdatesQueue {
+    fn encode(&self, mut encoder: DescriptorEncoder<'_>) -> Result<(), std::fmt::Error> {
+        let Some(updates) = self.0.upgrade() else {
+            return Ok(());
+        };
+
+        let depth = ConstGauge::new((updates.max_capacity() - updates.capacity()) as i64);
+        depth.encode(encoder.encode_descriptor(
+            "patch_queue_depth",
+            "The number of patches waiting in the updates channel",
+            None,
+            MetricType::Gauge,
+        )?)?;
+
+        let capacity = ConstGauge::new(updates.max_capacity() as i64);
+        capacity.encode(encoder.encode_descriptor(
+            "patch_queue_capacity",
+            "The maximum number of patches that may wait in the updates channel",
+            None,
+            MetricType::Gauge,
+        )?)?;
+
+        Ok(())
+    }
+}
+
+// === impl RouteBindings ===
+
+impl std::fmt::Debug for RouteBindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RouteBindings").finish()
+    }
+}
+
+impl Collector for RouteBindings {
+    fn encode(&self, mut encoder: DescriptorEncoder<'_>) -> Result<(), std::fmt::Error> {
+        let mut parents = BTreeMap::<(&'static str, String), u32>::new();
+        let mut backends = BTreeMap::<String, u32>::new();
+        if let Some(index) = self.0.get().and_then(Weak::upgrade) {
+            let index = index.read();
+            for (id, route) in index.route_refs.iter() {
+                for parent_ref in &route.parents {
+                    let kind = match parent_ref {
+                        routes::ParentReference::Server(_) => "Server",
+                        routes::ParentReference::Service(..) => "Service",
+                        routes::ParentReference::Gateway(..) => "Gateway",
+                        routes::ParentReference::UnknownKind => "Unknown",
+                    };
+                    let reason = index
+                        .parent_condition(id, route, parent_ref)
+                        .map_or_else(|| reasons::INVALID_KIND.to_string(), |c| c.reason);
+                    *parents.entry((kind, reason)).or_default() += 1;
+                }
+                let reason = index.backend_condition(&route.backends).reason;
+                *backends.entry(reason).or_default() += 1;
+            }
+        }
+
+        let mut parents_encoder = encoder.encode_descriptor(
+            "route_parent_bindings",
+            "The number of route parent references by the parent's kind and the reason given in the route's Accepted condition",
+            None,
+            MetricType::Gauge,
+        )?;
+        for ((kind, reason), n) in &parents {
+            let labels = [("parent_kind", *kind), ("reason", reason.as_str())];
+            ConstGauge::new(*n).encode(parents_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut backends_encoder = encoder.encode_descriptor(
+            "route_backend_resolutions",
+            "The number of routes by the reason given in their ResolvedRefs condition",
+            None,
+            MetricType::Gauge,
+        )?;
+        for (reason, n) in &backends {
+            let labels = [("reason", reason.as_str())];
+            ConstGauge::new(*n).encode(backends_encoder.encode_family(&labels)?)?;
         }
+
+        Ok(())
     }
 }
 
@@ -191,6 +361,7 @@ impl Controller {
         name: String,
         updates: mpsc::Receiver<Update>,
         patch_timeout: Duration,
+        patch_concurrency: usize,
         metrics: ControllerMetrics,
     ) -> Self {
         Self {
@@ -199,6 +370,7 @@ impl Controller {
             name,
             updates,
             patch_timeout,
+            patch_concurrency: patch_concurrency.max(1),
             leader: false,
             metrics,
         }
@@ -207,7 +379,15 @@ impl Controller {
     /// Process updates received from the index; each update is a patch that
     /// should be applied to update the status of a route. A patch should
     /// only be applied if we are the holder of the write lease.
+    ///
+    /// Patches for different routes may be applied concurrently. Patches for
+    /// the same route are applied in order: while a patch is in flight, only
+    /// the most recent subsequent patch for the route is retained.
     pub async fn run(mut self) {
+        let mut in_flight = FuturesUnordered::new();
+        let mut patching = HashSet::<NamespaceGroupKindName>::default();
+        let mut pending = HashMap::<NamespaceGroupKindName, Patch>::default();
+
         // Select between the write lease claim changing and receiving updates
         // from the index. If the lease claim changes, then check if we are
         // now the leader. If so, we should apply the patches received;
@@ -226,60 +406,113 @@ impl Controller {
                     }
                 }
 
-                Some(Update { id, patch}) = self.updates.recv() => {
+                Some(id) = in_flight.next() => {
+                    patching.remove(&id);
+                    if let Some(patch) = pending.remove(&id) {
+                        if self.leader {
+                            patching.insert(id.clone());
+                            in_flight.push(self.patch(id, patch));
+                        } else {
+                            self.metrics.patch_drops.inc();
+                        }
+                    }
+                }
+
+                Some(Update { id, patch}) = self.updates.recv(), if in_flight.len() < self.patch_concurrency => {
                     self.metrics.patch_dequeues.inc();
                     // If this policy controller is not the leader, it should
                     // process through the updates queue but not actually patch
                     // any resources.
-                    if self.leader {
-                        if id.gkn.group == linkerd_k8s_api::HttpRoute::group(&()) && id.gkn.kind == linkerd_k8s_api::HttpRoute::kind(&()){
-                            self.patch_status::<linkerd_k8s_api::HttpRoute>(&id.gkn.name, &id.namespace, patch).await;
-                        } else if id.gkn.group == k8s_gateway_api::HttpRoute::group(&()) && id.gkn.kind == k8s_gateway_api::HttpRoute::kind(&()) {
-                            self.patch_status::<k8s_gateway_api::HttpRoute>(&id.gkn.name, &id.namespace, patch).await;
+                    if !self.leader {
+                        self.metrics.patch_drops.inc();
+                    } else if patching.contains(&id) {
+                        // The patch is applied when the route's prior patch
+                        // completes, superseding any patch already waiting.
+                        if pending.insert(id, patch).is_some() {
+                            self.metrics.patch_drops.inc();
                         }
                     } else {
-                        self.metrics.patch_drops.inc();
+                        patching.insert(id.clone());
+                        in_flight.push(self.patch(id, patch));
                     }
                 }
             }
         }
     }
 
-    async fn patch_status<K>(
+    /// Returns a future that applies a status patch, completing with the ID of
+    /// the patched route.
+    fn patch(
         &self,
+        id: NamespaceGroupKindName,
+        patch: Patch,
+    ) -> BoxFuture<'static, NamespaceGroupKindName> {
+        let client = self.client.clone();
+        let timeout = self.patch_timeout;
+        let metrics = self.metrics.clone();
+        Box::pin(async move {
+            if id.gkn.group == linkerd_k8s_api::HttpRoute::group(&())
+                && id.gkn.kind == linkerd_k8s_api::HttpRoute::kind(&())
+            {
+                Self::patch_status::<linkerd_k8s_api::HttpRoute>(
+                    client,
+                    timeout,
+                    &metrics,
+                    &id.gkn.name,
+                    &id.namespace,
+                    patch,
+                )
+                .await;
+            } else if id.gkn.group == k8s_gateway_api::HttpRoute::group(&())
+                && id.gkn.kind == k8s_gateway_api::HttpRoute::kind(&())
+            {
+                Self::patch_status::<k8s_gateway_api::HttpRoute>(
+                    client,
+                    timeout,
+                    &metrics,
+                    &id.gkn.name,
+                    &id.namespace,
+                    patch,
+                )
+                .await;
+            }
+            id
+        })
+    }
+
+    #[tracing::instrument(name = "patch", skip_all, fields(%namespace, %name))]
+    async fn patch_status<K>(
+        client: k8s_core_api::Client,
+        patch_timeout: Duration,
+        metrics: &ControllerMetrics,
         name: &str,
         namespace: &str,
-        patch: k8s_core_api::Patch<serde_json::Value>,
+        patch: Patch,
     ) where
         K: Resource<Scope = NamespaceResourceScope>,
         <K as Resource>::DynamicType: Default,
         K: DeserializeOwned,
     {
         let patch_params = k8s_core_api::PatchParams::apply(K::group(&Default::default()).as_ref());
-        let api = k8s_core_api::Api::<K>::namespaced(self.client.clone(), namespace);
+        let api = k8s_core_api::Api::<K>::namespaced(client, namespace);
         let start = time::Instant::now();
 
-        match time::timeout(
-            self.patch_timeout,
-            api.patch_status(name, &patch_params, &patch),
-        )
-        .await
-        {
+        match time::timeout(patch_timeout, api.patch_status(name, &patch_params, &patch)).await {
             Ok(Ok(_)) => {
-                self.metrics.patch_succeeded.inc();
-                self.metrics
+                metrics.patch_succeeded.inc();
+                metrics
                     .patch_duration
                     .observe(start.elapsed().as_secs_f64());
             }
             Ok(Err(error)) => {
-                self.metrics.patch_failed.inc();
-                self.metrics
+                metrics.patch_failed.inc();
+                metrics
                     .patch_duration
                     .observe(start.elapsed().as_secs_f64());
                 tracing::error!(%namespace, %name, kind = %K::kind(&Default::default()), %error, "Patch failed");
             }
             Err(_) => {
-                self.metrics.patch_timeout.inc();
+                metrics.patch_timeout.inc();
                 tracing::error!(%namespace, %name, kind = %K::kind(&Default::default()), "Patch timed out");
             }
         }
@@ -291,17 +524,38 @@ impl Index {
         name: impl ToString,
         claims: Receiver<Arc<Claim>>,
         updates: mpsc::Sender<Update>,
+        limits: Limits,
         metrics: IndexMetrics,
     ) -> SharedIndex {
-        Arc::new(RwLock::new(Self {
+        let bindings = metrics.bindings.clone();
+        let index = Arc::new(RwLock::new(Self {
             name: name.to_string(),
             claims,
             updates,
             route_refs: HashMap::new(),
             servers: HashSet::new(),
             services: HashMap::new(),
+            parent_routes: HashMap::new(),
+            limits,
+            service_routes: HashMap::new(),
+            retired_controllers: HashSet::new(),
+            quarantine: Quarantine::default(),
             metrics,
-        }))
+        }));
+        let _ = bindings.set(Arc::downgrade(&index));
+        index
+    }
+
+    /// Sets the names of retired controllers. Route statuses written by these
+    /// controllers are removed when the routes' statuses are next reconciled.
+    pub fn set_retired_controllers(&mut self, names: impl IntoIterator<Item = String>) {
+        self.retired_controllers = names.into_iter().collect();
+    }
+
+    /// Sets the quarantine in which the policy indexes record the routes that
+    /// they could not parse. Quarantined routes are not accepted.
+    pub fn set_quarantine(&mut self, quarantine: Quarantine) {
+        self.quarantine = quarantine;
     }
 
     /// When the write leaseholder changes or a time duration has elapsed,
@@ -356,44 +610,167 @@ impl Index {
         true
     }
 
+    /// Updates the ranks of a route on its previous and current parents.
+    ///
+    /// Returns true if any of the parents has more routes than permitted, in
+    /// which case the acceptance of the parent's other routes may have
+    /// changed.
+    fn rank_route(
+        &mut self,
+        id: &NamespaceGroupKindName,
+        created: Option<DateTime<Utc>>,
+        previous: &[routes::ParentReference],
+        parents: &[routes::ParentReference],
+    ) -> bool {
+        let Some(limit) = self.limits.routes_per_parent else {
+            return false;
+        };
+        let rank = (created, id.clone());
+
+        let mut reranked = false;
+        for parent in previous {
+            if let Entry::Occupied(mut entry) = self.parent_routes.entry(parent.clone()) {
+                reranked |= entry.get().len() > limit;
+                entry.get_mut().remove(&rank);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+            }
+        }
+        for parent in parents {
+            if *parent == routes::ParentReference::UnknownKind {
+                continue;
+            }
+            let routes = self.parent_routes.entry(parent.clone()).or_default();
+            routes.insert(rank.clone());
+            reranked |= routes.len() > limit;
+        }
+        reranked
+    }
+
+    /// Updates the Services to which a route is attached.
+    ///
+    /// Returns true if the route links any Service to a backend Service, in
+    /// which case routes on other Services may have become (or ceased to be)
+    /// circular.
+    fn link_route(
+        &mut self,
+        id: &NamespaceGroupKindName,
+        previous: Option<&RouteRef>,
+        route: Option<&RouteRef>,
+    ) -> bool {
+        let mut relinked = false;
+        if let Some(previous) = previous {
+            relinked |= has_service_backends(previous);
+            for service in service_parents(&previous.parents) {
+                if let Entry::Occupied(mut entry) = self.service_routes.entry(service.clone()) {
+                    entry.get_mut().remove(id);
+                    if entry.get().is_empty() {
+                        entry.remove();
+                    }
+                }
+            }
+        }
+        if let Some(route) = route {
+            relinked |= has_service_backends(route);
+            for service in service_parents(&route.parents) {
+                self.service_routes
+                    .entry(service.clone())
+                    .or_default()
+                    .insert(id.clone());
+            }
+        }
+        relinked
+    }
+
+    /// Returns a condition rejecting the route on a Service parent if any of
+    /// its backends leads back to that Service, either directly or through
+    /// the routes of other Services.
+    ///
+    /// Every route that participates in a cycle is rejected, so the cycle is
+    /// not served to proxies.
+    fn cycle_condition(
+        &self,
+        route: &RouteRef,
+        parent_ref: &routes::ParentReference,
+    ) -> Option<k8s_core_api::Condition> {
+        let routes::ParentReference::Service(parent, _) = parent_ref else {
+            return None;
+        };
+
+        for backend in service_backends(&route.backends) {
+            let mut visited = HashSet::new();
+            let mut pending = vec![backend];
+            while let Some(service) = pending.pop() {
+                if service == parent {
+                    return Some(circular_reference(backend));
+                }
+                if !visited.insert(service) {
+                    continue;
+                }
+                let next = self
+                    .service_routes
+                    .get(service)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| self.route_refs.get(id))
+                    .flat_map(|route| service_backends(&route.backends));
+                pending.extend(next);
+            }
+        }
+        None
+    }
+
+    /// Returns a condition rejecting the route if it exceeds a limit on the
+    /// given parent.
+    fn limit_condition(
+        &self,
+        id: &NamespaceGroupKindName,
+        route: &RouteRef,
+        parent_ref: &routes::ParentReference,
+    ) -> Option<k8s_core_api::Condition> {
+        if let Some(limit) = self.limits.routes_per_parent {
+            let rank = (route.created, id.clone());
+            let preceding = self
+                .parent_routes
+                .get(parent_ref)
+                .map_or(0, |routes| routes.range(..rank).count());
+            if preceding >= limit {
+                return Some(route_limit_exceeded(limit));
+            }
+        }
+
+        if let Some(limit) = self.limits.backends_per_route {
+            if route.backends.len() > limit {
+                return Some(backend_limit_exceeded(limit));
+            }
+        }
+
+        None
+    }
+
     fn parent_status(
         &self,
+        id: &NamespaceGroupKindName,
+        route: &RouteRef,
         parent_ref: &routes::ParentReference,
         backend_condition: k8s_core_api::Condition,
     ) -> Option<k8s_gateway_api::RouteParentStatus> {
+        let condition = self.parent_condition(id, route, parent_ref)?;
         match parent_ref {
-            routes::ParentReference::Server(server) => {
-                let condition = if self.servers.contains(server) {
-                    accepted()
-                } else {
-                    no_matching_parent()
-                };
-
-                Some(k8s_gateway_api::RouteParentStatus {
-                    parent_ref: k8s_gateway_api::ParentReference {
-                        group: Some(POLICY_API_GROUP.to_string()),
-                        kind: Some("Server".to_string()),
-                        namespace: Some(server.namespace.clone()),
-                        name: server.name.clone(),
-                        section_name: None,
-                        port: None,
-                    },
-                    controller_name: POLICY_CONTROLLER_NAME.to_string(),
-                    conditions: vec![condition],
-                })
-            }
+            routes::ParentReference::Server(server) => Some(k8s_gateway_api::RouteParentStatus {
+                parent_ref: k8s_gateway_api::ParentReference {
+                    group: Some(POLICY_API_GROUP.to_string()),
+                    kind: Some("Server".to_string()),
+                    namespace: Some(server.namespace.clone()),
+                    name: server.name.clone(),
+                    section_name: None,
+                    port: None,
+                },
+                controller_name: POLICY_CONTROLLER_NAME.to_string(),
+                conditions: vec![condition],
+            }),
             routes::ParentReference::Service(service, port) => {
-                // service is a valid parent if it exists and it has a cluster_ip.
-                let condition = if self
-                    .services
-                    .get(service)
-                    .map_or(false, |svc| svc.valid_parent_service())
-                {
-                    accepted()
-                } else {
-                    no_matching_parent()
-                };
-
                 Some(k8s_gateway_api::RouteParentStatus {
                     parent_ref: k8s_gateway_api::ParentReference {
                         group: Some("core".to_string()),
@@ -407,10 +784,58 @@ impl Index {
                     conditions: vec![condition, backend_condition],
                 })
             }
+            routes::ParentReference::Gateway(gateway, section_name, port) => {
+                Some(k8s_gateway_api::RouteParentStatus {
+                    parent_ref: k8s_gateway_api::ParentReference {
+                        group: Some("gateway.networking.k8s.io".to_string()),
+                        kind: Some("Gateway".to_string()),
+                        namespace: Some(gateway.namespace.clone()),
+                        name: gateway.name.clone(),
+                        section_name: section_name.clone(),
+                        port: *port,
+                    },
+                    controller_name: POLICY_CONTROLLER_NAME.to_string(),
+                    conditions: vec![condition],
+                })
+            }
             routes::ParentReference::UnknownKind => None,
         }
     }
 
+    /// Returns the `Accepted` condition of a route for one of its parents, if
+    /// the parent has a kind we support.
+    fn parent_condition(
+        &self,
+        id: &NamespaceGroupKindName,
+        route: &RouteRef,
+        parent_ref: &routes::ParentReference,
+    ) -> Option<k8s_core_api::Condition> {
+        let exists = match parent_ref {
+            routes::ParentReference::Server(server) => self.servers.contains(server),
+            // service is a valid parent if it exists and is not an ExternalName service.
+            routes::ParentReference::Service(service, _) => self
+                .services
+                .get(service)
+                .map_or(false, |svc| svc.valid_parent_service()),
+            // Gateways are not indexed: the route applies to whichever pods
+            // are labeled with the Gateway's name.
+            routes::ParentReference::Gateway(..) => true,
+            routes::ParentReference::UnknownKind => return None,
+        };
+        let condition = if !exists {
+            no_matching_parent()
+        } else if let Some(error) = &route.rejection {
+            unsupported_value(error)
+        } else if let Some(condition) = self.limit_condition(id, route, parent_ref) {
+            condition
+        } else if let Some(condition) = self.cycle_condition(route, parent_ref) {
+            condition
+        } else {
+            accepted()
+        };
+        Some(condition)
+    }
+
     fn backend_condition(
         &self,
         backend_refs: &[routes::BackendReference],
@@ -442,19 +867,19 @@ impl Index {
         route: &RouteRef,
     ) -> Option<k8s_core_api::Patch<serde_json::Value>> {
         // To preserve any statuses from other controllers, we copy those
-        // statuses.
+        // statuses, except for those written by retired controllers.
         let unowned_statuses = route
             .statuses
             .iter()
             .filter(|status| status.controller_name != POLICY_CONTROLLER_NAME)
+            .filter(|status| !self.retired_controllers.contains(&status.controller_name))
             .cloned();
 
         // Compute a status for each parent_ref which has a kind we support.
         let backend_condition = self.backend_condition(&route.backends);
-        let parent_statuses = route
-            .parents
-            .iter()
-            .filter_map(|parent_ref| self.parent_status(parent_ref, backend_condition.clone()));
+        let parent_statuses = route.parents.iter().filter_map(|parent_ref| {
+            self.parent_status(id, route, parent_ref, backend_condition.clone())
+        });
 
         let all_statuses = unowned_statuses.chain(parent_statuses).collect::<Vec<_>>();
 
@@ -488,6 +913,7 @@ impl Index {
     }
 
     fn reconcile(&self) {
+        let _span = tracing::info_span!("reconcile").entered();
         for (id, route) in self.route_refs.iter() {
             if let Some(patch) = self.make_route_patch(id, route) {
                 match self.updates.try_send(Update {
@@ -543,12 +969,25 @@ impl kubert::index::IndexNamespacedResource<linkerd_k8s_api::HttpRoute> for Inde
             .flat_map(|status| status.inner.parents)
             .collect();
 
+        let created = resource
+            .metadata
+            .creation_timestamp
+            .map(|k8s_core_api::Time(t)| t);
+
+        let rejection = self.quarantine.error(
+            &id.gknn(),
+            resource.metadata.uid.as_deref(),
+            resource.metadata.generation,
+        );
+
         // Construct route and insert into the index; if the HTTPRoute is
         // already in the index, and it hasn't changed, skip creating a patch.
         let route = RouteRef {
             parents,
             backends,
             statuses,
+            created,
+            rejection,
         };
         self.index_route(id, route);
     }
@@ -562,7 +1001,7 @@ impl kubert::index::IndexNamespacedResource<linkerd_k8s_api::HttpRoute> for Inde
                 name: name.into(),
             },
         };
-        self.route_refs.remove(&id);
+        self.delete_route(id);
     }
 
     // Since apply only reindexes a single HTTPRoute at a time, there's no need
@@ -605,12 +1044,25 @@ impl kubert::index::IndexNamespacedResource<k8s_gateway_api::HttpRoute> for Inde
             .flat_map(|status| status.inner.parents)
             .collect();
 
+        let created = resource
+            .metadata
+            .creation_timestamp
+            .map(|k8s_core_api::Time(t)| t);
+
+        let rejection = self.quarantine.error(
+            &id.gknn(),
+            resource.metadata.uid.as_deref(),
+            resource.metadata.generation,
+        );
+
         // Construct route and insert into the index; if the HTTPRoute is
         // already in the index, and it hasn't changed, skip creating a patch.
         let route = RouteRef {
             parents,
             backends,
             statuses,
+            created,
+            rejection,
         };
         self.index_route(id, route);
     }
@@ -624,7 +1076,7 @@ impl kubert::index::IndexNamespacedResource<k8s_gateway_api::HttpRoute> for Inde
                 name: name.into(),
             },
         };
-        self.route_refs.remove(&id);
+        self.delete_route(id);
     }
 
     // Since apply only reindexes a single HTTPRoute at a time, there's no need
@@ -699,11 +1151,19 @@ impl kubert::index::IndexNamespacedResource<k8s_core_api::Service> for Index {
 
 impl Index {
     fn index_route(&mut self, id: NamespaceGroupKindName, route: RouteRef) {
+        let previous = self.route_refs.get(&id).cloned();
+
         // Insert into the index; if the route is already in the index, and it hasn't
         // changed, skip creating a patch.
         if !self.update_route(id.clone(), &route) {
             return;
         }
+        let previous_parents = previous
+            .as_ref()
+            .map(|route| route.parents.as_slice())
+            .unwrap_or_default();
+        let reranked = self.rank_route(&id, route.created, previous_parents, &route.parents);
+        let relinked = self.link_route(&id, previous.as_ref(), Some(&route));
 
         // If we're not the leader, skip creating a patch and sending an
         // update to the Controller.
@@ -712,6 +1172,14 @@ impl Index {
             return;
         }
 
+        // If the route shares a parent that has exceeded its route limit, or
+        // if it may have formed or broken a cycle of backend references, other
+        // routes may have been accepted or rejected as a result.
+        if reranked || relinked {
+            self.reconcile();
+            return;
+        }
+
         // Create a patch for the route and send it to the Controller so
         // that it is applied.
         if let Some(patch) = self.make_route_patch(&id, &route) {
@@ -729,6 +1197,21 @@ impl Index {
             }
         }
     }
+
+    fn delete_route(&mut self, id: NamespaceGroupKindName) {
+        let Some(route) = self.route_refs.remove(&id) else {
+            return;
+        };
+
+        // If the route was attached to a parent that exceeded its route limit,
+        // another route may now be accepted in its place. Likewise, removing
+        // the route may break a cycle of backend references.
+        let reranked = self.rank_route(&id, route.created, &route.parents, &[]);
+        let relinked = self.link_route(&id, Some(&route), None);
+        if (reranked || relinked) && self.claims.borrow().is_current_for(&self.name) {
+            self.reconcile();
+        }
+    }
 }
 
 pub(crate) fn make_patch<RouteStatus>(
@@ -747,7 +1230,7 @@ where
             let patch = serde_json::json!({
                 "apiVersion": api_version,
                     "kind": &route_id.gkn.kind,
-                    "name": &route_id.gkn.name,
+                    "name": &*route_id.gkn.name,
                     "status": status,
             });
 
@@ -819,6 +1302,74 @@ fn invalid_backend_kind() -> k8s_core_api::Condition {
     }
 }
 
+fn unsupported_value(error: &str) -> k8s_core_api::Condition {
+    k8s_core_api::Condition {
+        last_transition_time: k8s_core_api::Time(now()),
+        message: error.to_string(),
+        observed_generation: None,
+        reason: reasons::UNSUPPORTED_VALUE.to_string(),
+        status: cond_statuses::STATUS_FALSE.to_string(),
+        type_: conditions::ACCEPTED.to_string(),
+    }
+}
+
+fn route_limit_exceeded(limit: usize) -> k8s_core_api::Condition {
+    k8s_core_api::Condition {
+        last_transition_time: k8s_core_api::Time(now()),
+        message: format!("parent has more than {limit} routes"),
+        observed_generation: None,
+        reason: reasons::ROUTE_LIMIT_EXCEEDED.to_string(),
+        status: cond_statuses::STATUS_FALSE.to_string(),
+        type_: conditions::ACCEPTED.to_string(),
+    }
+}
+
+fn backend_limit_exceeded(limit: usize) -> k8s_core_api::Condition {
+    k8s_core_api::Condition {
+        last_transition_time: k8s_core_api::Time(now()),
+        message: format!("route has more than {limit} backends"),
+        observed_generation: None,
+        reason: reasons::BACKEND_LIMIT_EXCEEDED.to_string(),
+        status: cond_statuses::STATUS_FALSE.to_string(),
+        type_: conditions::ACCEPTED.to_string(),
+    }
+}
+
+fn circular_reference(backend: &ResourceId) -> k8s_core_api::Condition {
+    k8s_core_api::Condition {
+        last_transition_time: k8s_core_api::Time(now()),
+        message: format!(
+            "backend Service {}.{} routes back to the parent Service",
+            backend.name, backend.namespace
+        ),
+        observed_generation: None,
+        reason: reasons::CIRCULAR_REFERENCE.to_string(),
+        status: cond_statuses::STATUS_FALSE.to_string(),
+        type_: conditions::ACCEPTED.to_string(),
+    }
+}
+
+fn service_parents(parents: &[routes::ParentReference]) -> impl Iterator<Item = &ResourceId> + '_ {
+    parents.iter().filter_map(|parent| match parent {
+        routes::ParentReference::Service(service, _) => Some(service),
+        _ => None,
+    })
+}
+
+fn service_backends(
+    backends: &[routes::BackendReference],
+) -> impl Iterator<Item = &ResourceId> + '_ {
+    backends.iter().filter_map(|backend| match backend {
+        routes::BackendReference::Service(service) => Some(service),
+        routes::BackendReference::Unknown => None,
+    })
+}
+
+fn has_service_backends(route: &RouteRef) -> bool {
+    service_parents(&route.parents).next().is_some()
+        && service_backends(&route.backends).next().is_some()
+}
+
 fn eq_time_insensitive(
     left: &[k8s_gateway_api::RouteParentStatus],
     right: &[k8s_gateway_api::RouteParentStatus],
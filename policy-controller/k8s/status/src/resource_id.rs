@@ -1,5 +1,5 @@
 use crate::index::{GATEWAY_API_GROUP, POLICY_API_GROUP};
-use linkerd_policy_controller_core::routes::GroupKindName;
+use linkerd_policy_controller_core::routes::{GroupKindName, GroupKindNamespaceName};
 use linkerd_policy_controller_k8s_api::{
     gateway as k8s_gateway_api, policy as linkerd_k8s_api, Resource,
 };
@@ -17,13 +17,17 @@ impl ResourceId {
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct NamespaceGroupKindName {
     pub namespace: String,
     pub gkn: GroupKindName,
 }
 
 impl NamespaceGroupKindName {
+    pub fn gknn(&self) -> GroupKindNamespaceName {
+        self.gkn.clone().namespaced(self.namespace.clone())
+    }
+
     pub fn api_version(&self) -> anyhow::Result<Cow<'static, str>> {
         match (self.gkn.group.as_ref(), self.gkn.kind.as_ref()) {
             (POLICY_API_GROUP, "HTTPRoute") => Ok(linkerd_k8s_api::HttpRoute::api_version(&())),
@@ -12,10 +12,16 @@ pub(crate) mod http;
 /// namespace. This is something that should be relaxed in the future in the
 /// policy controller's index, and we could then consider consolidating these
 /// types into a single shared lib.
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, Hash, PartialEq)]
 pub enum ParentReference {
     Server(ResourceId),
     Service(ResourceId, Option<u16>),
+
+    /// A Gateway in the route's namespace, with the section name and port of
+    /// the referenced listener. The route applies to the Gateway's meshed
+    /// ingress pods.
+    Gateway(ResourceId, Option<String>, Option<u16>),
+
     UnknownKind,
 }
 
@@ -50,6 +56,18 @@ impl ParentReference {
                 ResourceId::new(namespace.to_string(), parent_ref.name.clone()),
                 parent_ref.port,
             )
+        } else if linkerd_k8s_api::httproute::parent_ref_targets_gateway(parent_ref) {
+            // The index only attaches routes to Gateways in the route's own
+            // namespace.
+            let namespace = parent_ref.namespace.as_deref().unwrap_or(default_namespace);
+            if namespace != default_namespace {
+                return Self::UnknownKind;
+            }
+            Self::Gateway(
+                ResourceId::new(namespace.to_string(), parent_ref.name.clone()),
+                parent_ref.section_name.clone(),
+                parent_ref.port,
+            )
         } else {
             Self::UnknownKind
         }
@@ -59,6 +59,7 @@ mod test {
                 hostnames: None,
                 rules: Some(vec![
                     policy::httproute::HttpRouteRule {
+                        name: None,
                         matches: None,
                         filters: None,
                         backend_refs: mk_default_http_backends(vec![
@@ -80,6 +81,7 @@ mod test {
                         timeouts: None,
                     },
                     policy::httproute::HttpRouteRule {
+                        name: None,
                         matches: None,
                         filters: None,
                         backend_refs: mk_default_http_backends(vec![
@@ -94,6 +96,7 @@ mod test {
                         timeouts: None,
                     },
                     policy::httproute::HttpRouteRule {
+                        name: None,
                         matches: None,
                         filters: None,
                         backend_refs: None,
@@ -140,6 +143,7 @@ mod test {
                 inner: k8s_gateway_api::CommonRouteSpec { parent_refs: None },
                 hostnames: None,
                 rules: Some(vec![policy::httproute::HttpRouteRule {
+                    name: None,
                     matches: None,
                     filters: None,
                     backend_refs: mk_default_http_backends(vec![
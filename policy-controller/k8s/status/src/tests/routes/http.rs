@@ -1,7 +1,12 @@
-use crate::{index::POLICY_API_GROUP, resource_id::NamespaceGroupKindName, Index, IndexMetrics};
+use crate::{
+    index::POLICY_API_GROUP, resource_id::NamespaceGroupKindName, Index, IndexMetrics, Limits,
+};
 use chrono::{DateTime, Utc};
 use kubert::index::IndexNamespacedResource;
-use linkerd_policy_controller_core::{routes::GroupKindName, POLICY_CONTROLLER_NAME};
+use linkerd_policy_controller_core::{
+    routes::{GroupKindName, Quarantine, Rejection},
+    POLICY_CONTROLLER_NAME,
+};
 use linkerd_policy_controller_k8s_api::{
     self as k8s_core_api, gateway as k8s_gateway_api, policy as linkerd_k8s_api, Resource,
     ResourceExt,
@@ -22,6 +27,7 @@ fn linkerd_route_with_no_backends() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -95,6 +101,7 @@ fn gateway_route_with_no_backends() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -167,6 +174,7 @@ fn linkerd_route_with_valid_backends() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -278,6 +286,7 @@ fn gateway_route_with_valid_backends() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -389,6 +398,7 @@ fn linkerd_route_with_invalid_backend() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -496,6 +506,7 @@ fn gateway_route_with_invalid_backend() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -603,6 +614,7 @@ fn linkerd_route_accepted_after_server_create() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -683,6 +695,7 @@ fn gateway_route_accepted_after_server_create() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -763,6 +776,7 @@ fn linkerd_route_rejected_after_server_delete() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -849,6 +863,7 @@ fn gateway_route_rejected_after_server_delete() {
         hostname,
         claims_rx,
         updates_tx,
+        Limits::default(),
         IndexMetrics::register(&mut Default::default()),
     );
 
@@ -922,6 +937,630 @@ fn gateway_route_rejected_after_server_delete() {
     assert!(updates_rx.try_recv().is_err());
 }
 
+#[test]
+fn linkerd_routes_rejected_over_route_limit() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, mut updates_rx) = mpsc::channel(10000);
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits {
+            routes_per_parent: Some(1),
+            backends_per_route: None,
+        },
+        IndexMetrics::register(&mut Default::default()),
+    );
+
+    // Apply the server
+    let server = super::make_server(
+        "ns-0",
+        "srv-8080",
+        8080,
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(linkerd_k8s_api::server::ProxyProtocol::Http1),
+    );
+    index.write().apply(server);
+
+    let parent = linkerd_k8s_api::httproute::ParentReference {
+        group: Some(POLICY_API_GROUP.to_string()),
+        kind: Some("Server".to_string()),
+        namespace: None,
+        name: "srv-8080".to_string(),
+        section_name: None,
+        port: None,
+    };
+    let mk_route = |name: &str, created: DateTime<Utc>| {
+        let id = NamespaceGroupKindName {
+            namespace: "ns-0".to_string(),
+            gkn: GroupKindName {
+                group: linkerd_k8s_api::HttpRoute::group(&()),
+                kind: linkerd_k8s_api::HttpRoute::kind(&()),
+                name: name.into(),
+            },
+        };
+        let mut route = make_linkerd_route(&id, parent.clone(), None);
+        route.metadata.creation_timestamp = Some(k8s_core_api::Time(created));
+        (id, route)
+    };
+    let (older_id, older) = mk_route("route-b", DateTime::<Utc>::MIN_UTC);
+    let (newer_id, newer) = mk_route("route-a", Utc::now());
+
+    let accepted = make_status(vec![make_parent_status(
+        "ns-0", "srv-8080", "Accepted", "True", "Accepted",
+    )]);
+    let mut rejected = make_parent_status(
+        "ns-0",
+        "srv-8080",
+        "Accepted",
+        "False",
+        "RouteLimitExceeded",
+    );
+    rejected.conditions[0].message = "parent has more than 1 routes".to_string();
+    let rejected = make_status(vec![rejected]);
+
+    // The first route is accepted.
+    index.write().apply(newer);
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(newer_id, update.id);
+    assert_eq!(
+        crate::index::make_patch(&newer_id, accepted.clone()).unwrap(),
+        update.patch
+    );
+    assert!(updates_rx.try_recv().is_err());
+
+    // An older route displaces the newer route from the parent.
+    index.write().apply(older);
+    let mut updates = std::iter::from_fn(|| updates_rx.try_recv().ok())
+        .map(|update| (update.id, update.patch))
+        .collect::<std::collections::HashMap<_, _>>();
+    assert_eq!(
+        updates.remove(&older_id),
+        crate::index::make_patch(&older_id, accepted.clone()),
+    );
+    assert_eq!(
+        updates.remove(&newer_id),
+        crate::index::make_patch(&newer_id, rejected),
+    );
+    assert!(updates.is_empty());
+
+    // When the older route is deleted, the newer route is accepted again.
+    IndexNamespacedResource::<linkerd_k8s_api::HttpRoute>::delete(
+        &mut *index.write(),
+        "ns-0".to_string(),
+        "route-b".to_string(),
+    );
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(newer_id, update.id);
+    assert_eq!(
+        crate::index::make_patch(&newer_id, accepted).unwrap(),
+        update.patch
+    );
+    assert!(updates_rx.try_recv().is_err());
+}
+
+#[test]
+fn linkerd_route_rejected_over_backend_limit() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, mut updates_rx) = mpsc::channel(10000);
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits {
+            routes_per_parent: None,
+            backends_per_route: Some(1),
+        },
+        IndexMetrics::register(&mut Default::default()),
+    );
+
+    // Apply the server
+    let server = super::make_server(
+        "ns-0",
+        "srv-8080",
+        8080,
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(linkerd_k8s_api::server::ProxyProtocol::Http1),
+    );
+    index.write().apply(server);
+
+    // Apply a route with more backends than permitted.
+    let id = NamespaceGroupKindName {
+        namespace: "ns-0".to_string(),
+        gkn: GroupKindName {
+            group: linkerd_k8s_api::HttpRoute::group(&()),
+            kind: linkerd_k8s_api::HttpRoute::kind(&()),
+            name: "route-foo".into(),
+        },
+    };
+    let parent = linkerd_k8s_api::httproute::ParentReference {
+        group: Some(POLICY_API_GROUP.to_string()),
+        kind: Some("Server".to_string()),
+        namespace: None,
+        name: "srv-8080".to_string(),
+        section_name: None,
+        port: None,
+    };
+    let backends = ["backend-1", "backend-2"]
+        .into_iter()
+        .map(|name| linkerd_k8s_api::httproute::HttpBackendRef {
+            backend_ref: Some(k8s_gateway_api::BackendRef {
+                weight: None,
+                inner: k8s_gateway_api::BackendObjectReference {
+                    group: None,
+                    kind: None,
+                    namespace: Some("ns-0".to_string()),
+                    name: name.to_string(),
+                    port: Some(8080),
+                },
+            }),
+            filters: None,
+        })
+        .collect();
+    let route = make_linkerd_route(&id, parent, Some(backends));
+    index.write().apply(route);
+
+    // Create the expected update.
+    let mut parent_status = make_parent_status(
+        &id.namespace,
+        "srv-8080",
+        "Accepted",
+        "False",
+        "BackendLimitExceeded",
+    );
+    parent_status.conditions[0].message = "route has more than 1 backends".to_string();
+    let status = make_status(vec![parent_status]);
+    let patch = crate::index::make_patch(&id, status).unwrap();
+
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(id, update.id);
+    assert_eq!(patch, update.patch);
+    assert!(updates_rx.try_recv().is_err());
+}
+
+#[test]
+fn linkerd_routes_rejected_with_circular_backends() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, _updates_rx) = mpsc::channel(10000);
+    let mut prom = prometheus_client::registry::Registry::default();
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits::default(),
+        IndexMetrics::register(&mut prom),
+    );
+    let bindings = |reason: &str| {
+        let mut buf = String::new();
+        prometheus_client::encoding::text::encode(&mut buf, &prom).unwrap();
+        let metric =
+            format!(r#"route_parent_bindings{{parent_kind="Service",reason="{reason}"}} "#);
+        buf.lines()
+            .find_map(|line| line.strip_prefix(&metric)?.parse::<u32>().ok())
+            .unwrap_or(0)
+    };
+
+    for name in ["svc-a", "svc-b", "svc-c"] {
+        index.write().apply(super::make_service("ns-0", name));
+    }
+    let mk_route = |name: &str, parent: &str, backend: &str| {
+        let id = NamespaceGroupKindName {
+            namespace: "ns-0".to_string(),
+            gkn: GroupKindName {
+                group: linkerd_k8s_api::HttpRoute::group(&()),
+                kind: linkerd_k8s_api::HttpRoute::kind(&()),
+                name: name.to_string().into(),
+            },
+        };
+        let parent = linkerd_k8s_api::httproute::ParentReference {
+            group: Some("core".to_string()),
+            kind: Some("Service".to_string()),
+            namespace: Some("ns-0".to_string()),
+            name: parent.to_string(),
+            section_name: None,
+            port: Some(8080),
+        };
+        let backend = linkerd_k8s_api::httproute::HttpBackendRef {
+            backend_ref: Some(k8s_gateway_api::BackendRef {
+                weight: None,
+                inner: k8s_gateway_api::BackendObjectReference {
+                    group: None,
+                    kind: None,
+                    namespace: Some("ns-0".to_string()),
+                    name: backend.to_string(),
+                    port: Some(8080),
+                },
+            }),
+            filters: None,
+        };
+        make_linkerd_route(&id, parent, Some(vec![backend]))
+    };
+
+    // A chain of routes is accepted.
+    index.write().apply(mk_route("a-to-b", "svc-a", "svc-b"));
+    index.write().apply(mk_route("b-to-c", "svc-b", "svc-c"));
+    assert_eq!(bindings("Accepted"), 2);
+
+    // Closing the chain into a loop rejects every route in the loop.
+    index.write().apply(mk_route("c-to-a", "svc-c", "svc-a"));
+    assert_eq!(bindings("Accepted"), 0);
+    assert_eq!(bindings("CircularReference"), 3);
+
+    // Breaking the loop accepts the remaining routes again.
+    IndexNamespacedResource::<linkerd_k8s_api::HttpRoute>::delete(
+        &mut *index.write(),
+        "ns-0".to_string(),
+        "b-to-c".to_string(),
+    );
+    assert_eq!(bindings("Accepted"), 2);
+    assert_eq!(bindings("CircularReference"), 0);
+
+    // A route whose backend is its own parent is rejected.
+    index.write().apply(mk_route("b-to-b", "svc-b", "svc-b"));
+    assert_eq!(bindings("CircularReference"), 1);
+}
+
+#[test]
+fn route_binding_metrics() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, _updates_rx) = mpsc::channel(10000);
+    let mut prom = prometheus_client::registry::Registry::default();
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits::default(),
+        IndexMetrics::register(&mut prom),
+    );
+    let metrics = || {
+        let mut buf = String::new();
+        prometheus_client::encoding::text::encode(&mut buf, &prom).unwrap();
+        buf
+    };
+
+    let id = NamespaceGroupKindName {
+        namespace: "ns-0".to_string(),
+        gkn: GroupKindName {
+            group: linkerd_k8s_api::HttpRoute::group(&()),
+            kind: linkerd_k8s_api::HttpRoute::kind(&()),
+            name: "route-foo".into(),
+        },
+    };
+    let parent = linkerd_k8s_api::httproute::ParentReference {
+        group: Some(POLICY_API_GROUP.to_string()),
+        kind: Some("Server".to_string()),
+        namespace: None,
+        name: "srv-8080".to_string(),
+        section_name: None,
+        port: None,
+    };
+    index.write().apply(make_linkerd_route(&id, parent, None));
+    let text = metrics();
+    assert!(
+        text.contains(r#"route_parent_bindings{parent_kind="Server",reason="NoMatchingParent"} 1"#),
+        "{text}"
+    );
+    assert!(
+        text.contains(r#"route_backend_resolutions{reason="ResolvedRefs"} 1"#),
+        "{text}"
+    );
+
+    index.write().apply(super::make_server(
+        "ns-0",
+        "srv-8080",
+        8080,
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(linkerd_k8s_api::server::ProxyProtocol::Http1),
+    ));
+    let text = metrics();
+    assert!(
+        text.contains(r#"route_parent_bindings{parent_kind="Server",reason="Accepted"} 1"#),
+        "{text}"
+    );
+    assert!(!text.contains("NoMatchingParent"), "{text}");
+}
+
+#[test]
+fn linkerd_route_accepted_by_headless_service() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, mut updates_rx) = mpsc::channel(10000);
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits::default(),
+        IndexMetrics::register(&mut Default::default()),
+    );
+
+    // Apply a headless parent service.
+    let mut parent = super::make_service("ns-0", "svc");
+    let spec = parent.spec.as_mut().unwrap();
+    spec.cluster_ip = Some("None".to_string());
+    spec.cluster_ips = Some(vec!["None".to_string()]);
+    index.write().apply(parent.clone());
+
+    let id = NamespaceGroupKindName {
+        namespace: "ns-0".to_string(),
+        gkn: GroupKindName {
+            group: linkerd_k8s_api::HttpRoute::group(&()),
+            kind: linkerd_k8s_api::HttpRoute::kind(&()),
+            name: "route-foo".into(),
+        },
+    };
+    let parent = linkerd_k8s_api::httproute::ParentReference {
+        group: Some("core".to_string()),
+        kind: Some("Service".to_string()),
+        namespace: parent.namespace(),
+        name: parent.name_unchecked(),
+        section_name: None,
+        port: Some(8080),
+    };
+    index
+        .write()
+        .apply(make_linkerd_route(&id, parent.clone(), None));
+
+    let accepted_condition = k8s_core_api::Condition {
+        last_transition_time: k8s_core_api::Time(DateTime::<Utc>::MIN_UTC),
+        message: "".to_string(),
+        observed_generation: None,
+        reason: "Accepted".to_string(),
+        status: "True".to_string(),
+        type_: "Accepted".to_string(),
+    };
+    let backend_condition = k8s_core_api::Condition {
+        last_transition_time: k8s_core_api::Time(DateTime::<Utc>::MIN_UTC),
+        message: "".to_string(),
+        observed_generation: None,
+        reason: "ResolvedRefs".to_string(),
+        status: "True".to_string(),
+        type_: "ResolvedRefs".to_string(),
+    };
+    let parent_status = k8s_gateway_api::RouteParentStatus {
+        parent_ref: parent,
+        controller_name: POLICY_CONTROLLER_NAME.to_string(),
+        conditions: vec![accepted_condition, backend_condition],
+    };
+    let patch = crate::index::make_patch(&id, make_status(vec![parent_status])).unwrap();
+
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(id, update.id);
+    assert_eq!(patch, update.patch);
+    assert!(updates_rx.try_recv().is_err())
+}
+
+#[test]
+fn gateway_route_accepted_by_gateway_listener() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, mut updates_rx) = mpsc::channel(10000);
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits::default(),
+        IndexMetrics::register(&mut Default::default()),
+    );
+
+    let id = NamespaceGroupKindName {
+        namespace: "ns-0".to_string(),
+        gkn: GroupKindName {
+            group: k8s_gateway_api::HttpRoute::group(&()),
+            kind: k8s_gateway_api::HttpRoute::kind(&()),
+            name: "route-foo".into(),
+        },
+    };
+    let parent = k8s_gateway_api::ParentReference {
+        group: None,
+        kind: Some("Gateway".to_string()),
+        namespace: None,
+        name: "ingress".to_string(),
+        section_name: Some("http".to_string()),
+        port: Some(8080),
+    };
+    index
+        .write()
+        .apply(make_gateway_route(&id, parent.clone(), None));
+
+    // The policy controller accepts the route on the Gateway's listener, so
+    // that the inbound index attaches it to the Gateway's ingress pods.
+    let mut parent_status =
+        make_parent_status(&id.namespace, "ingress", "Accepted", "True", "Accepted");
+    parent_status.parent_ref = k8s_gateway_api::ParentReference {
+        group: Some("gateway.networking.k8s.io".to_string()),
+        namespace: Some(id.namespace.clone()),
+        ..parent
+    };
+    let patch = crate::index::make_patch(&id, make_status(vec![parent_status])).unwrap();
+
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(id, update.id);
+    assert_eq!(patch, update.patch);
+    assert!(updates_rx.try_recv().is_err())
+}
+
+#[test]
+fn linkerd_route_statuses_from_retired_controllers_removed() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, mut updates_rx) = mpsc::channel(10000);
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits::default(),
+        IndexMetrics::register(&mut Default::default()),
+    );
+    index
+        .write()
+        .set_retired_controllers(["linkerd.io/retired-controller".to_string()]);
+
+    let server = super::make_server(
+        "ns-0",
+        "srv-8080",
+        8080,
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(linkerd_k8s_api::server::ProxyProtocol::Http1),
+    );
+    index.write().apply(server);
+
+    let id = NamespaceGroupKindName {
+        namespace: "ns-0".to_string(),
+        gkn: GroupKindName {
+            group: linkerd_k8s_api::HttpRoute::group(&()),
+            kind: linkerd_k8s_api::HttpRoute::kind(&()),
+            name: "route-foo".into(),
+        },
+    };
+    let parent = linkerd_k8s_api::httproute::ParentReference {
+        group: Some(POLICY_API_GROUP.to_string()),
+        kind: Some("Server".to_string()),
+        namespace: None,
+        name: "srv-8080".to_string(),
+        section_name: None,
+        port: None,
+    };
+    let retired = k8s_gateway_api::RouteParentStatus {
+        controller_name: "linkerd.io/retired-controller".to_string(),
+        ..make_parent_status("ns-0", "srv-8080", "Accepted", "True", "Accepted")
+    };
+    let other = k8s_gateway_api::RouteParentStatus {
+        controller_name: "example.com/other-controller".to_string(),
+        ..make_parent_status("ns-0", "srv-8080", "Accepted", "True", "Accepted")
+    };
+    let mut route = make_linkerd_route(&id, parent, None);
+    route.status = Some(linkerd_k8s_api::httproute::HttpRouteStatus {
+        inner: linkerd_k8s_api::httproute::RouteStatus {
+            parents: vec![retired, other.clone()],
+        },
+    });
+    index.write().apply(route);
+
+    // The retired controller's status is removed, and the statuses of other
+    // controllers are preserved.
+    let accepted = make_parent_status("ns-0", "srv-8080", "Accepted", "True", "Accepted");
+    let patch = crate::index::make_patch(&id, make_status(vec![other, accepted])).unwrap();
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(id, update.id);
+    assert_eq!(patch, update.patch);
+    assert!(updates_rx.try_recv().is_err());
+}
+
+#[test]
+fn linkerd_route_rejected_by_indexes_not_accepted() {
+    let hostname = "test";
+    let claim = kubert::lease::Claim {
+        holder: "test".to_string(),
+        expiry: DateTime::<Utc>::MAX_UTC,
+    };
+    let (_claims_tx, claims_rx) = watch::channel(Arc::new(claim));
+    let (updates_tx, mut updates_rx) = mpsc::channel(10000);
+    let index = Index::shared(
+        hostname,
+        claims_rx,
+        updates_tx,
+        Limits::default(),
+        IndexMetrics::register(&mut Default::default()),
+    );
+    let quarantine = Quarantine::default();
+    index.write().set_quarantine(quarantine.clone());
+
+    let server = super::make_server(
+        "ns-0",
+        "srv-8080",
+        8080,
+        Some(("app", "app-0")),
+        Some(("app", "app-0")),
+        Some(linkerd_k8s_api::server::ProxyProtocol::Http1),
+    );
+    index.write().apply(server);
+
+    let id = NamespaceGroupKindName {
+        namespace: "ns-0".to_string(),
+        gkn: GroupKindName {
+            group: linkerd_k8s_api::HttpRoute::group(&()),
+            kind: linkerd_k8s_api::HttpRoute::kind(&()),
+            name: "route-foo".into(),
+        },
+    };
+    let parent = linkerd_k8s_api::httproute::ParentReference {
+        group: Some(POLICY_API_GROUP.to_string()),
+        kind: Some("Server".to_string()),
+        namespace: None,
+        name: "srv-8080".to_string(),
+        section_name: None,
+        port: None,
+    };
+    let mut route = make_linkerd_route(&id, parent, None);
+    route.metadata.generation = Some(1);
+    quarantine.insert(
+        "inbound",
+        id.gknn(),
+        Rejection {
+            uid: None,
+            generation: Some(1),
+            error: "invalid path regex".into(),
+        },
+    );
+    index.write().apply(route.clone());
+
+    // The route is rejected with the error from the index.
+    let mut rejected =
+        make_parent_status("ns-0", "srv-8080", "Accepted", "False", "UnsupportedValue");
+    rejected.conditions[0].message = "invalid path regex".to_string();
+    let patch = crate::index::make_patch(&id, make_status(vec![rejected])).unwrap();
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(id, update.id);
+    assert_eq!(patch, update.patch);
+    assert!(updates_rx.try_recv().is_err());
+
+    // Once the route is changed, the rejection no longer applies.
+    route.metadata.generation = Some(2);
+    index.write().apply(route);
+    let accepted = make_parent_status("ns-0", "srv-8080", "Accepted", "True", "Accepted");
+    let patch = crate::index::make_patch(&id, make_status(vec![accepted])).unwrap();
+    let update = updates_rx.try_recv().unwrap();
+    assert_eq!(id, update.id);
+    assert_eq!(patch, update.patch);
+    assert!(updates_rx.try_recv().is_err());
+}
+
 fn make_status(
     parents: Vec<k8s_gateway_api::RouteParentStatus>,
 ) -> k8s_gateway_api::HttpRouteStatus {
@@ -948,6 +1587,7 @@ fn make_linkerd_route(
             },
             hostnames: None,
             rules: Some(vec![linkerd_k8s_api::httproute::HttpRouteRule {
+                name: None,
                 matches: Some(vec![linkerd_k8s_api::httproute::HttpRouteMatch {
                     path: Some(linkerd_k8s_api::httproute::HttpPathMatch::PathPrefix {
                         value: "/foo/bar".to_string(),
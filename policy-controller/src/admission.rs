@@ -1,9 +1,10 @@
-use super::validation;
+use super::{audit::AuditLog, validation};
 use crate::k8s::policy::{
-    httproute, server::Selector, AuthorizationPolicy, AuthorizationPolicySpec, HttpRoute,
-    HttpRouteSpec, LocalTargetRef, MeshTLSAuthentication, MeshTLSAuthenticationSpec,
-    NamespacedTargetRef, NetworkAuthentication, NetworkAuthenticationSpec, Server,
-    ServerAuthorization, ServerAuthorizationSpec, ServerSpec,
+    httproute,
+    server::{ProxyProtocol, Selector},
+    AuthorizationPolicy, AuthorizationPolicySpec, HttpRoute, HttpRouteSpec, LocalTargetRef,
+    MeshTLSAuthentication, MeshTLSAuthenticationSpec, NamespacedTargetRef, NetworkAuthentication,
+    NetworkAuthenticationSpec, Server, ServerAuthorization, ServerAuthorizationSpec, ServerSpec,
 };
 use anyhow::{anyhow, bail, ensure, Result};
 use futures::future;
@@ -13,6 +14,7 @@ use kube::{core::DynamicObject, Resource, ResourceExt};
 use linkerd_policy_controller_core as core;
 use linkerd_policy_controller_k8s_index as index;
 use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
 use std::task;
 use thiserror::Error;
 use tracing::{debug, info, trace, warn};
@@ -20,6 +22,7 @@ use tracing::{debug, info, trace, warn};
 #[derive(Clone)]
 pub struct Admission {
     client: kube::Client,
+    audit: Option<AuditLog>,
 }
 
 #[derive(Debug, Error)]
@@ -54,14 +57,19 @@ impl hyper::service::Service<Request<Body>> for Admission {
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
         trace!(?req);
-        if req.method() != http::Method::POST || req.uri().path() != "/" {
-            return Box::pin(future::ok(
-                Response::builder()
-                    .status(http::StatusCode::NOT_FOUND)
-                    .body(Body::empty())
-                    .expect("not found response must be valid"),
-            ));
-        }
+        // Resources are validated at `/` and defaulted at `/mutate`.
+        let mutate = match (req.method(), req.uri().path()) {
+            (&http::Method::POST, "/") => false,
+            (&http::Method::POST, "/mutate") => true,
+            _ => {
+                return Box::pin(future::ok(
+                    Response::builder()
+                        .status(http::StatusCode::NOT_FOUND)
+                        .body(Body::empty())
+                        .expect("not found response must be valid"),
+                ));
+            }
+        };
 
         let admission = self.clone();
         Box::pin(async move {
@@ -75,10 +83,22 @@ impl hyper::service::Service<Request<Body>> for Admission {
             };
             trace!(?review);
 
-            let rsp = match review.try_into() {
+            let req: Result<AdmissionRequest, _> = review.try_into();
+            let rsp = match req {
+                Ok(req) if mutate => {
+                    debug!(?req);
+                    mutate_spec(req)
+                }
                 Ok(req) => {
                     debug!(?req);
-                    admission.admit(req).await
+                    match admission.audit.clone() {
+                        Some(audit) => {
+                            let rsp = admission.admit(req.clone()).await;
+                            audit.record(&req, &rsp);
+                            rsp
+                        }
+                        None => admission.admit(req).await,
+                    }
                 }
                 Err(error) => {
                     warn!(%error, "invalid admission request");
@@ -93,7 +113,18 @@ impl hyper::service::Service<Request<Body>> for Admission {
 
 impl Admission {
     pub fn new(client: kube::Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            audit: None,
+        }
+    }
+
+    /// Records each admission decision in the given audit log.
+    pub fn with_audit_log(self, audit: AuditLog) -> Self {
+        Self {
+            audit: Some(audit),
+            ..self
+        }
     }
 
     async fn admit(self, req: AdmissionRequest) -> AdmissionResponse {
@@ -156,6 +187,77 @@ impl Admission {
     }
 }
 
+/// Fills in the fields of a resource that have well-defined defaults, so that
+/// resources are validated and indexed in a normalized form.
+fn mutate_spec(req: AdmissionRequest) -> AdmissionResponse {
+    let rsp = AdmissionResponse::from(&req);
+    let Some(obj) = req.object.as_ref() else {
+        return rsp;
+    };
+    let Some(spec) = obj.data.get("spec") else {
+        return rsp;
+    };
+    let Some(ns) = obj.namespace().or_else(|| req.namespace.clone()) else {
+        return rsp;
+    };
+
+    let mut defaulted = spec.clone();
+    if is_kind::<Server>(&req) {
+        default_server(&mut defaulted);
+    } else if is_kind::<HttpRoute>(&req) || is_kind::<k8s_gateway_api::HttpRoute>(&req) {
+        default_http_route(&ns, &mut defaulted);
+    }
+
+    let patch = json_patch::diff(&json!({ "spec": spec }), &json!({ "spec": defaulted }));
+    if patch.0.is_empty() {
+        return rsp;
+    }
+    debug!(%patch, "Defaulted");
+    match rsp.with_patch(patch) {
+        Ok(rsp) => rsp,
+        Err(error) => {
+            warn!(%error, "Failed to encode patch");
+            AdmissionResponse::from(&req).deny(error)
+        }
+    }
+}
+
+/// Sets a field if it is unset or null.
+fn set_default(obj: &mut Map<String, Value>, field: &str, value: impl Into<Value>) {
+    if obj.get(field).map_or(true, Value::is_null) {
+        obj.insert(field.to_string(), value.into());
+    }
+}
+
+/// A Server's `proxyProtocol` defaults to `unknown`, i.e. protocol detection.
+fn default_server(spec: &mut Value) {
+    if let Some(spec) = spec.as_object_mut() {
+        set_default(spec, "proxyProtocol", "unknown");
+    }
+}
+
+/// A route's parent references default to the route's namespace, its rules are
+/// named by their index and its backend references have a weight of 1.
+fn default_http_route(ns: &str, spec: &mut Value) {
+    fn objects(value: Option<&mut Value>) -> impl Iterator<Item = &mut Map<String, Value>> {
+        value
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object_mut)
+    }
+
+    for parent_ref in objects(spec.get_mut("parentRefs")) {
+        set_default(parent_ref, "namespace", ns);
+    }
+    for (i, rule) in objects(spec.get_mut("rules")).enumerate() {
+        set_default(rule, "name", format!("rule-{i}"));
+        for backend_ref in objects(rule.get_mut("backendRefs")) {
+            set_default(backend_ref, "weight", 1);
+        }
+    }
+}
+
 fn is_kind<T>(req: &AdmissionRequest) -> bool
 where
     T: Resource,
@@ -220,7 +322,12 @@ fn validate_policy_target(ns: &str, tgt: &LocalTargetRef) -> Result<()> {
 #[async_trait::async_trait]
 impl Validate<AuthorizationPolicySpec> for Admission {
     async fn validate(self, ns: &str, _name: &str, spec: AuthorizationPolicySpec) -> Result<()> {
-        validate_policy_target(ns, &spec.target_ref)?;
+        if spec.targets().next().is_none() {
+            bail!("AuthorizationPolicy must specify a targetRef or targetRefs");
+        }
+        for target in spec.targets() {
+            validate_policy_target(ns, target)?;
+        }
 
         let mtls_authns_count = spec
             .required_authentication_refs
@@ -304,6 +411,13 @@ impl Validate<MeshTLSAuthenticationSpec> for Admission {
             validate_identity_ref(id)?;
         }
 
+        for id in spec.remote_identity_refs.iter().flatten() {
+            if id.cluster.is_empty() {
+                bail!("remote identity references must name a cluster");
+            }
+            validate_identity_ref(&id.target)?;
+        }
+
         Ok(())
     }
 }
@@ -315,6 +429,19 @@ impl Validate<ServerSpec> for Admission {
     // TODO(ver) this isn't rigorous about detecting servers that select the same port if one port
     // specifies a numeric port and the other specifies the port's name.
     async fn validate(self, ns: &str, name: &str, spec: ServerSpec) -> Result<()> {
+        if let Some(timeout) = spec.detect_timeout {
+            ensure!(!timeout.is_negative(), "detectTimeout must not be negative");
+            ensure!(
+                std::time::Duration::from(timeout) <= index::MAX_DETECT_TIMEOUT,
+                "detectTimeout must not exceed {:?}",
+                index::MAX_DETECT_TIMEOUT
+            );
+            ensure!(
+                matches!(spec.proxy_protocol, None | Some(ProxyProtocol::Unknown)),
+                "detectTimeout may only be set when the proxyProtocol is detected"
+            );
+        }
+
         // Since we can't ensure that the local index is up-to-date with the API server (i.e.
         // updates may be delayed), we issue an API request to get the latest state of servers in
         // the namespace.
@@ -578,3 +705,92 @@ impl Validate<k8s_gateway_api::HttpRouteSpec> for Admission {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_server() {
+        let mut spec = json!({ "port": 8080 });
+        default_server(&mut spec);
+        assert_eq!(spec, json!({ "port": 8080, "proxyProtocol": "unknown" }));
+
+        let mut spec = json!({ "port": 8080, "proxyProtocol": "HTTP/2" });
+        default_server(&mut spec);
+        assert_eq!(spec, json!({ "port": 8080, "proxyProtocol": "HTTP/2" }));
+    }
+
+    #[test]
+    fn defaults_http_route() {
+        let mut spec = json!({
+            "parentRefs": [
+                { "kind": "Service", "name": "svc" },
+                { "kind": "Service", "name": "svc", "namespace": "other" },
+            ],
+            "rules": [{
+                "backendRefs": [
+                    { "name": "a", "port": 8080 },
+                    { "name": "b", "port": 8080, "weight": 0 },
+                ],
+            }, {}],
+        });
+        default_http_route("ns", &mut spec);
+        assert_eq!(
+            spec,
+            json!({
+                "parentRefs": [
+                    { "kind": "Service", "name": "svc", "namespace": "ns" },
+                    { "kind": "Service", "name": "svc", "namespace": "other" },
+                ],
+                "rules": [{
+                    "name": "rule-0",
+                    "backendRefs": [
+                        { "name": "a", "port": 8080, "weight": 1 },
+                        { "name": "b", "port": 8080, "weight": 0 },
+                    ],
+                }, {
+                    "name": "rule-1",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn defaults_http_route_rule_names() {
+        let mut spec = json!({
+            "rules": [{}, { "name": "admin" }, { "name": null }],
+        });
+        default_http_route("ns", &mut spec);
+        assert_eq!(
+            spec,
+            json!({
+                "rules": [{ "name": "rule-0" }, { "name": "admin" }, { "name": "rule-2" }],
+            })
+        );
+    }
+}
+
+#[cfg(fuzzing)]
+pub mod fuzz_logic {
+    use super::*;
+    use hyper::service::Service;
+
+    /// A list response from an API server on which no resources exist.
+    const EMPTY_LIST: &str = r#"{"metadata":{},"items":[]}"#;
+
+    /// Feeds an arbitrary request body through the admission webhook.
+    ///
+    /// Resources that are validated against the API server (i.e. `Server`s)
+    /// are checked against a server on which no other resources exist.
+    pub async fn fuzz_entry(body: &[u8]) {
+        let api = hyper::service::service_fn(|_: Request<Body>| {
+            future::ok::<_, std::convert::Infallible>(Response::new(Body::from(EMPTY_LIST)))
+        });
+        let mut admission = Admission::new(kube::Client::new(api, "default"));
+        let req = Request::post("/")
+            .body(Body::from(body.to_vec()))
+            .expect("admission request must be valid");
+        let _ = admission.call(req).await;
+    }
+}
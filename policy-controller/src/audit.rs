@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use kube::core::{admission::Operation, DynamicObject};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+type AdmissionRequest = kube::core::admission::AdmissionRequest<DynamicObject>;
+type AdmissionResponse = kube::core::admission::AdmissionResponse;
+
+/// An append-only log of the admission controller's decisions.
+///
+/// Each decision is written as a JSON object on its own line, recording the
+/// user that submitted the resource, the resource itself, and whether it was
+/// admitted (and, if not, why). When the log exceeds its maximum size, it is
+/// rotated: `audit.log` is renamed to `audit.log.1`, `audit.log.1` to
+/// `audit.log.2`, and so on, discarding the oldest file.
+#[derive(Clone, Debug)]
+pub struct AuditLog(Arc<Mutex<Writer>>);
+
+#[derive(Debug)]
+struct Writer {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry<'a> {
+    time: String,
+    uid: &'a str,
+    user: Option<&'a str>,
+    groups: &'a [String],
+    operation: &'a Operation,
+    group: &'a str,
+    version: &'a str,
+    kind: &'a str,
+    namespace: Option<&'a str>,
+    name: &'a str,
+    allowed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    object: Option<&'a DynamicObject>,
+}
+
+// === impl AuditLog ===
+
+impl AuditLog {
+    /// Opens the log at `path`, appending to it if it exists.
+    ///
+    /// The log is rotated once it exceeds `max_bytes`, retaining at most
+    /// `max_files` rotated files.
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> Result<Self> {
+        let file = open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self(Arc::new(Mutex::new(Writer {
+            path,
+            file,
+            size,
+            max_bytes,
+            max_files,
+        }))))
+    }
+
+    /// Records the decision made for an admission request.
+    ///
+    /// Failures to write the log are logged but otherwise ignored, so that
+    /// admission does not depend on the log's availability.
+    pub(crate) fn record(&self, req: &AdmissionRequest, rsp: &AdmissionResponse) {
+        let entry = Entry {
+            time: k8s_openapi::chrono::Utc::now().to_rfc3339(),
+            uid: &req.uid,
+            user: req.user_info.username.as_deref(),
+            groups: req.user_info.groups.as_deref().unwrap_or_default(),
+            operation: &req.operation,
+            group: &req.kind.group,
+            version: &req.kind.version,
+            kind: &req.kind.kind,
+            namespace: req.namespace.as_deref(),
+            name: &req.name,
+            allowed: rsp.allowed,
+            reason: (!rsp.allowed).then_some(rsp.result.message.as_str()),
+            object: req.object.as_ref().or(req.old_object.as_ref()),
+        };
+        let mut line = match serde_json::to_vec(&entry) {
+            Ok(line) => line,
+            Err(error) => {
+                tracing::warn!(%error, "Failed to encode audit log entry");
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        let mut writer = self.0.lock();
+        if let Err(error) = writer.write(&line) {
+            tracing::warn!(%error, path = %writer.path.display(), "Failed to write audit log");
+        }
+    }
+}
+
+// === impl Writer ===
+
+impl Writer {
+    fn write(&mut self, line: &[u8]) -> Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.max_files).rev() {
+                let from = rotated(&self.path, n);
+                if from.exists() {
+                    fs::rename(from, rotated(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+        }
+        self.file = open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open audit log {}", path.display()))
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{n}"));
+    path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str) -> AdmissionRequest {
+        serde_json::from_value(serde_json::json!({
+            "uid": "9f4c2b1e",
+            "kind": { "group": "policy.linkerd.io", "version": "v1beta1", "kind": "Server" },
+            "resource": { "group": "policy.linkerd.io", "version": "v1beta1", "resource": "servers" },
+            "name": name,
+            "namespace": "ns-0",
+            "operation": "CREATE",
+            "userInfo": { "username": "alice", "groups": ["system:authenticated"] },
+            "object": {
+                "apiVersion": "policy.linkerd.io/v1beta1",
+                "kind": "Server",
+                "metadata": { "name": name, "namespace": "ns-0" },
+                "spec": { "port": 8080 },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn records_and_rotates() {
+        let dir = std::env::temp_dir().join(format!("audit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+
+        let log = AuditLog::open(path.clone(), 1, 2).unwrap();
+        let req = request("srv-0");
+        log.record(&req, &AdmissionResponse::from(&req));
+        let req = request("srv-1");
+        log.record(&req, &AdmissionResponse::from(&req).deny("invalid port"));
+        let req = request("srv-2");
+        log.record(&req, &AdmissionResponse::from(&req));
+        let req = request("srv-3");
+        log.record(&req, &AdmissionResponse::from(&req));
+
+        let read = |path: &Path| -> serde_json::Value {
+            serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+        };
+        let entry = read(&path);
+        assert_eq!(entry["name"], "srv-3");
+        assert_eq!(entry["user"], "alice");
+        assert_eq!(entry["operation"], "CREATE");
+        assert_eq!(entry["allowed"], true);
+        assert_eq!(entry["object"]["spec"]["port"], 8080);
+
+        assert_eq!(read(&rotated(&path, 1))["name"], "srv-2");
+        let denied = read(&rotated(&path, 2));
+        assert_eq!(denied["name"], "srv-1");
+        assert_eq!(denied["allowed"], false);
+        assert_eq!(denied["reason"], "invalid port");
+        assert!(
+            !rotated(&path, 3).exists(),
+            "only the configured number of files may be retained"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
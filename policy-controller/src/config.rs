@@ -0,0 +1,144 @@
+//! Settings that may be changed without restarting the controller.
+//!
+//! Settings are read from a directory in which each setting is a file named by
+//! its key, as when a ConfigMap is mounted as a volume. The directory is polled
+//! so that changes to the ConfigMap take effect once the kubelet has updated
+//! the volume. When a setting's file does not exist, the setting's default
+//! (i.e. the value configured on the command line) is used. Invalid values are
+//! logged and ignored, so that the last valid value remains in effect.
+
+use futures::prelude::*;
+use std::{fmt, path::PathBuf, str::FromStr};
+use tokio::{sync::watch, time};
+
+#[derive(Clone, Debug)]
+pub struct ConfigDir {
+    path: Option<PathBuf>,
+    interval: time::Duration,
+}
+
+// === impl ConfigDir ===
+
+impl ConfigDir {
+    /// Reads settings from `path`, if one is configured, at the given
+    /// interval.
+    pub fn new(path: Option<PathBuf>, interval: time::Duration) -> Self {
+        Self { path, interval }
+    }
+
+    /// Reads the setting named `key`, returning a receiver that is updated as
+    /// the setting changes and a task that polls the setting.
+    pub fn watch<T>(
+        &self,
+        key: &'static str,
+        default: T,
+    ) -> (
+        watch::Receiver<T>,
+        impl Future<Output = ()> + Send + 'static,
+    )
+    where
+        T: FromStr + Clone + PartialEq + fmt::Debug + Send + Sync + 'static,
+        T::Err: fmt::Display,
+    {
+        let path = self.path.as_ref().map(|dir| dir.join(key));
+        let mut raw = path.as_ref().and_then(|path| read(key, path));
+        let value = raw
+            .as_deref()
+            .and_then(|raw| parse(key, raw))
+            .unwrap_or_else(|| default.clone());
+        if raw.is_some() {
+            tracing::info!(key, ?value, "Configured");
+        }
+        let (tx, rx) = watch::channel(value);
+
+        let interval = self.interval;
+        let task = async move {
+            let Some(path) = path else {
+                // Nothing can change, but the sender is held so that the
+                // setting is not considered closed.
+                return future::pending().await;
+            };
+            let mut interval = time::interval(interval);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let update = read(key, &path);
+                if update == raw {
+                    continue;
+                }
+                let value = match update.as_deref() {
+                    Some(update) => match parse(key, update) {
+                        Some(value) => value,
+                        None => continue,
+                    },
+                    None => default.clone(),
+                };
+                raw = update;
+                if tx.send_if_modified(|v| std::mem::replace(v, value.clone()) != value) {
+                    tracing::info!(key, ?value, "Setting changed");
+                }
+            }
+        };
+        (rx, task)
+    }
+}
+
+fn read(key: &str, path: &std::path::Path) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => Some(raw.trim().to_string()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => {
+            tracing::warn!(key, %error, path = %path.display(), "Failed to read setting");
+            None
+        }
+    }
+}
+
+fn parse<T>(key: &str, raw: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match raw.parse() {
+        Ok(value) => Some(value),
+        Err(error) => {
+            tracing::warn!(key, %error, value = raw, "Ignoring invalid setting");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn reloads_settings() {
+        let dir = std::env::temp_dir().join(format!("config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("setting");
+        std::fs::write(&path, "2\n").unwrap();
+
+        let config = ConfigDir::new(Some(dir.clone()), time::Duration::from_secs(1));
+        let (mut rx, task) = config.watch::<u32>("setting", 1);
+        tokio::spawn(task);
+        assert_eq!(*rx.borrow_and_update(), 2);
+
+        // Invalid values are ignored.
+        std::fs::write(&path, "bogus").unwrap();
+        time::sleep(time::Duration::from_secs(2)).await;
+        assert!(!rx.has_changed().unwrap());
+
+        std::fs::write(&path, "3").unwrap();
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow_and_update(), 3);
+
+        // Removing the setting restores the default.
+        std::fs::remove_file(&path).unwrap();
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow_and_update(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
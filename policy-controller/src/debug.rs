@@ -0,0 +1,891 @@
+//! Admin endpoints that expose the controller's indexes and the policies they
+//! resolve, that adjust the controller's logging, and that resync its resource
+//! watches, for debugging.
+
+use crate::{inbound, outbound, trace, watches::Resync};
+use hyper::{http, Body, Request, Response};
+use linkerd_policy_controller_core::{
+    evaluate::{self, Decision},
+    inbound::{
+        AuthorizationRef, ClientAuthorization, HttpRoute, HttpRouteRef, InboundServer,
+        ProxyProtocol, ServerRef,
+    },
+};
+use serde::Serialize;
+use std::{borrow::Cow, collections::BTreeMap, net::SocketAddr, num::NonZeroU16};
+
+/// Serves the inbound server resolved for a workload's port.
+///
+/// The workload is specified by the `namespace` query parameter and either a
+/// `pod` or an `external_workload` parameter; the port is specified by the
+/// `port` parameter. Alternatively, the policy configured by a `Server`
+/// resource may be requested with the `namespace` and `server` parameters. For
+/// example:
+///
+/// ```text
+/// GET /debug/inbound?namespace=emojivoto&pod=web-7d5c5b8d9-x2x7k&port=8080
+/// GET /debug/inbound?namespace=emojivoto&server=web-http
+/// ```
+pub fn inbound(
+    index: inbound::Lookup,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        if req.method() != http::Method::GET {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+        match inbound_server(&index, &query_params(&req)) {
+            Ok(server) => json(&server),
+            Err(rsp) => rsp,
+        }
+    }
+}
+
+/// Serves every authorization that applies to an inbound server, along with
+/// the resource that grants it.
+///
+/// Authorizations that only apply to a single route are listed with that
+/// route. The server is specified with the same parameters as the
+/// `/debug/inbound` endpoint.
+pub fn authorizations(
+    index: inbound::Lookup,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        if req.method() != http::Method::GET {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+        let server = match inbound_server(&index, &query_params(&req)) {
+            Ok(server) => server,
+            Err(rsp) => return rsp,
+        };
+
+        let server_authzs =
+            server
+                .authorizations
+                .iter()
+                .map(|(source, authorization)| EffectiveAuthorization {
+                    source,
+                    route: None,
+                    authorization,
+                });
+        let route_authzs = server.http_routes.iter().flat_map(|(route, r)| {
+            r.authorizations
+                .iter()
+                .map(move |(source, authorization)| EffectiveAuthorization {
+                    source,
+                    route: Some(route),
+                    authorization,
+                })
+        });
+        let mut authorizations = server_authzs.chain(route_authzs).collect::<Vec<_>>();
+        authorizations.sort_by_cached_key(|a| (a.route, a.source.to_string()));
+
+        json(&EffectiveAuthorizations {
+            server: &server.reference,
+            authorizations,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct EffectiveAuthorizations<'a> {
+    server: &'a ServerRef,
+    authorizations: Vec<EffectiveAuthorization<'a>>,
+}
+
+#[derive(Serialize)]
+struct EffectiveAuthorization<'a> {
+    source: &'a AuthorizationRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route: Option<&'a HttpRouteRef>,
+    #[serde(flatten)]
+    authorization: &'a ClientAuthorization,
+}
+
+/// Explains how an inbound server handles a client: the decision, along with
+/// the server, route, and authorization that produced it, and whether each of
+/// the server's authorizations permits the client.
+///
+/// The server is specified with the same parameters as the `/debug/inbound`
+/// endpoint. The client is described by the `client_ip` parameter and, if it
+/// uses mutual TLS, the `client_identity` parameter; `client_tls=true`
+/// describes a client that uses TLS without an identity. If a `path` parameter
+/// is set, an HTTP request is evaluated, with the optional `method` (`GET` by
+/// default) and `host` parameters; otherwise a connection is evaluated. For
+/// example:
+///
+/// ```text
+/// GET /debug/explain?namespace=emojivoto&pod=web-7d5c5b8d9-x2x7k&port=8080&client_ip=10.42.0.7&client_identity=default.emojivoto.serviceaccount.identity.linkerd.cluster.local&path=/api/vote
+/// ```
+pub fn explain(
+    index: inbound::Lookup,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        if req.method() != http::Method::GET {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+        let params = query_params(&req);
+        let server = match inbound_server(&index, &params) {
+            Ok(server) => server,
+            Err(rsp) => return rsp,
+        };
+        let param = |name| param(&params, name);
+
+        let Some(addr) = param("client_ip").and_then(|ip| ip.parse().ok()) else {
+            return error(
+                http::StatusCode::BAD_REQUEST,
+                "missing or invalid client_ip parameter",
+            );
+        };
+        let tls = match (param("client_identity"), param("client_tls")) {
+            (Some(id), _) => evaluate::ClientTls::Authenticated(id.to_string()),
+            (None, Some("true")) => evaluate::ClientTls::Unauthenticated,
+            (None, _) => evaluate::ClientTls::None,
+        };
+        let client = evaluate::Client { addr, tls };
+
+        let decision = match param("path") {
+            Some(path) => {
+                let mut request = Request::builder()
+                    .method(param("method").unwrap_or("GET"))
+                    .uri(path);
+                if let Some(host) = param("host") {
+                    request = request.header(http::header::HOST, host);
+                }
+                match request.body(()) {
+                    Ok(request) => evaluate::request(&server, &client, &request),
+                    Err(e) => return error(http::StatusCode::BAD_REQUEST, &e.to_string()),
+                }
+            }
+            None => evaluate::connection(&server, &client),
+        };
+
+        let default_route = HttpRoute::default();
+        let (outcome, route_ref, authorization_ref) = match &decision {
+            Decision::Allow {
+                route,
+                authorization,
+            } => ("allow", route.as_ref(), Some(authorization)),
+            Decision::Deny { route } => ("deny", route.as_ref(), None),
+            Decision::NotFound => ("notfound", None, None),
+        };
+        let route = match route_ref {
+            Some(r) => server.http_routes.get(r),
+            None if param("path").is_some() && decision != Decision::NotFound => {
+                Some(&default_route)
+            }
+            None => None,
+        };
+        let authorization = authorization_ref.and_then(|a| {
+            route
+                .and_then(|r| r.authorizations.get(a))
+                .or_else(|| server.authorizations.get(a))
+        });
+
+        let route_authzs = route.into_iter().flat_map(|r| r.authorizations.iter());
+        let mut evaluated = route_authzs
+            .chain(server.authorizations.iter())
+            .map(|(source, authz)| EvaluatedAuthorization {
+                source,
+                permits: evaluate::permits(authz, &client),
+            })
+            .collect::<Vec<_>>();
+        evaluated.sort_by_cached_key(|a| a.source.to_string());
+
+        json(&Explanation {
+            decision: outcome,
+            server: &server.reference,
+            protocol: &server.protocol,
+            route: route.map(|route| ExplainedRoute {
+                reference: route_ref,
+                route,
+            }),
+            authorization: authorization_ref
+                .zip(authorization)
+                .map(|(source, authorization)| EffectiveAuthorization {
+                    source,
+                    route: route_ref,
+                    authorization,
+                }),
+            evaluated,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Explanation<'a> {
+    decision: &'static str,
+    server: &'a ServerRef,
+    protocol: &'a ProxyProtocol,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route: Option<ExplainedRoute<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authorization: Option<EffectiveAuthorization<'a>>,
+    evaluated: Vec<EvaluatedAuthorization<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExplainedRoute<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference: Option<&'a HttpRouteRef>,
+    #[serde(flatten)]
+    route: &'a HttpRoute,
+}
+
+#[derive(Serialize)]
+struct EvaluatedAuthorization<'a> {
+    source: &'a AuthorizationRef,
+    permits: bool,
+}
+
+fn inbound_server(
+    index: &inbound::Lookup,
+    params: &[(Cow<'_, str>, Cow<'_, str>)],
+) -> Result<InboundServer, Response<Body>> {
+    let param = |name| param(params, name);
+
+    let Some(namespace) = param("namespace") else {
+        return Err(error(
+            http::StatusCode::BAD_REQUEST,
+            "missing namespace parameter",
+        ));
+    };
+
+    if let Some(name) = param("server") {
+        return index.server(namespace, name).ok_or_else(|| {
+            error(
+                http::StatusCode::NOT_FOUND,
+                &format!("server {name}.{namespace} not found"),
+            )
+        });
+    }
+
+    let Some(port) = param("port").and_then(|p| p.parse::<NonZeroU16>().ok()) else {
+        return Err(error(
+            http::StatusCode::BAD_REQUEST,
+            "missing or invalid port parameter",
+        ));
+    };
+    let server = match (param("pod"), param("external_workload")) {
+        (Some(pod), None) => index.pod_server(namespace, pod, port),
+        (None, Some(name)) => index.external_workload_server(namespace, name, port),
+        _ => {
+            return Err(error(
+                http::StatusCode::BAD_REQUEST,
+                "exactly one of the pod or external_workload parameters must be set",
+            ))
+        }
+    };
+
+    server.map_err(|e| error(http::StatusCode::NOT_FOUND, &e.to_string()))
+}
+
+/// Serves the outbound policy resolved for a Service's port.
+///
+/// The Service is specified either by its cluster IP and port, with the `addr`
+/// query parameter, or by the `namespace`, `service`, and `port` parameters.
+/// Policies are resolved for clients in the Service's namespace unless the
+/// `source_namespace` parameter is set. For example:
+///
+/// ```text
+/// GET /debug/outbound?namespace=emojivoto&service=web-svc&port=80
+/// GET /debug/outbound?addr=10.43.12.7:80&source_namespace=linkerd-viz
+/// ```
+pub fn outbound(
+    index: outbound::SharedIndex,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        if req.method() != http::Method::GET {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+
+        let params = query_params(&req);
+        let param = |name| param(&params, name);
+
+        let (service, namespace, port) = if let Some(addr) = param("addr") {
+            let Some(addr) = addr.parse::<SocketAddr>().ok() else {
+                return error(http::StatusCode::BAD_REQUEST, "invalid addr parameter");
+            };
+            let Some(port) = NonZeroU16::new(addr.port()) else {
+                return error(http::StatusCode::BAD_REQUEST, "invalid addr parameter");
+            };
+            let Some(outbound::ServiceRef { name, namespace }) =
+                index.read().lookup_service(addr.ip())
+            else {
+                return error(
+                    http::StatusCode::NOT_FOUND,
+                    &format!("no service found for {addr}"),
+                );
+            };
+            (name, namespace, port)
+        } else {
+            let (Some(namespace), Some(service)) = (param("namespace"), param("service")) else {
+                return error(
+                    http::StatusCode::BAD_REQUEST,
+                    "either the addr or the namespace and service parameters must be set",
+                );
+            };
+            let Some(port) = param("port").and_then(|p| p.parse::<NonZeroU16>().ok()) else {
+                return error(
+                    http::StatusCode::BAD_REQUEST,
+                    "missing or invalid port parameter",
+                );
+            };
+            (service.to_string(), namespace.to_string(), port)
+        };
+        let source_namespace = param("source_namespace").unwrap_or(&namespace).to_string();
+
+        json(
+            &index
+                .read()
+                .outbound_policy(service, namespace, port, &source_namespace),
+        )
+    }
+}
+
+/// Serves the entire contents of the inbound and outbound indexes: the policy
+/// configured by each server, the routes attached to each server and Service,
+/// the server bound to each workload port, and the policy published for each
+/// Service port. The `namespace` query parameter limits the output to a single
+/// namespace. For example:
+///
+/// ```text
+/// GET /debug/index
+/// GET /debug/index?namespace=emojivoto
+/// ```
+///
+/// Maps are sorted by key, so that the outputs of two controllers may be
+/// compared with `diff`.
+pub fn index(
+    inbound: inbound::Lookup,
+    outbound: outbound::SharedIndex,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        if req.method() != http::Method::GET {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+        let params = query_params(&req);
+        let namespace = param(&params, "namespace");
+        json(&IndexDump {
+            inbound: inbound::dump::dump(&inbound, namespace),
+            outbound: outbound::dump::dump(&outbound.read(), namespace),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct IndexDump {
+    inbound: BTreeMap<String, inbound::dump::Namespace>,
+    outbound: BTreeMap<String, outbound::dump::Namespace>,
+}
+
+/// Serves the log filter directives and, on `PUT`, replaces them, so that a
+/// running controller's log level may be changed without restarting it (and
+/// dropping its watches and streams). For example:
+///
+/// ```text
+/// GET /log-level
+/// PUT /log-level?filter=linkerd=debug,warn
+/// ```
+///
+/// `PUT` requests must carry the configured admin token as a bearer token. If
+/// no token is configured, the log filter may not be changed.
+pub fn log_level(
+    level: trace::LogLevel,
+    token: Option<String>,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        if req.method() == http::Method::PUT {
+            let Some(token) = token.as_deref() else {
+                return error(http::StatusCode::FORBIDDEN, "admin token not configured");
+            };
+            if !authorized(&req, token) {
+                return error(http::StatusCode::UNAUTHORIZED, "unauthorized");
+            }
+            let params = query_params(&req);
+            let Some(filter) = param(&params, "filter") else {
+                return error(http::StatusCode::BAD_REQUEST, "missing filter parameter");
+            };
+            let filter = match filter.parse::<kubert::LogFilter>() {
+                Ok(filter) => filter,
+                Err(e) => return error(http::StatusCode::BAD_REQUEST, &e.to_string()),
+            };
+            if let Err(e) = level.set(filter) {
+                return error(http::StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+            }
+        } else if req.method() != http::Method::GET {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+        match level.get() {
+            Ok(filter) => text(http::StatusCode::OK, &filter),
+            Err(e) => error(http::StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        }
+    }
+}
+
+/// Forces resource watches to relist their resources, so that the indexes may
+/// recover from missed watch events without restarting the controller (and
+/// dropping its streams). A single resource may be named by the `resource`
+/// query parameter; otherwise, all watches are resynced. For example:
+///
+/// ```text
+/// POST /resync?resource=httproutes.policy.linkerd.io
+/// POST /resync
+/// ```
+///
+/// Requests must carry the configured admin token as a bearer token. If no
+/// token is configured, the endpoint is disabled.
+pub fn resync(
+    resync: Resync,
+    token: Option<String>,
+) -> impl Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static {
+    move |req| {
+        let Some(token) = token.as_deref() else {
+            return error(http::StatusCode::NOT_FOUND, "admin token not configured");
+        };
+        if !authorized(&req, token) {
+            return error(http::StatusCode::UNAUTHORIZED, "unauthorized");
+        }
+        if req.method() != http::Method::POST {
+            return error(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+
+        let params = query_params(&req);
+        let resource = param(&params, "resource").filter(|r| *r != "all");
+        let resynced = resync.trigger(resource);
+        if resynced.is_empty() {
+            return error(http::StatusCode::NOT_FOUND, "unknown resource");
+        }
+        json(&resynced)
+    }
+}
+
+/// Returns true if the request carries the given token as a bearer token.
+fn authorized(req: &Request<Body>, token: &str) -> bool {
+    req.headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map_or(false, |t| constant_time_eq(t.as_bytes(), token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Parses the request's percent-encoded query parameters.
+fn query_params(req: &Request<Body>) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+    form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes()).collect()
+}
+
+fn param<'a>(params: &'a [(Cow<'_, str>, Cow<'_, str>)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find_map(|(k, v)| (k == name).then_some(v.as_ref()))
+        .filter(|v| !v.is_empty())
+}
+
+fn json(value: &impl Serialize) -> Response<Body> {
+    match serde_json::to_vec_pretty(value) {
+        Ok(body) => Response::builder()
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body.into())
+            .unwrap(),
+        Err(e) => error(http::StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+fn error(status: http::StatusCode, msg: &str) -> Response<Body> {
+    text(status, msg)
+}
+
+fn text(status: http::StatusCode, msg: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "text/plain")
+        .body(format!("{msg}\n").into())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{k8s, ClusterInfo, DefaultPolicy};
+    use kubert::index::IndexNamespacedResource;
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    fn cluster_info() -> ClusterInfo {
+        ClusterInfo {
+            networks: vec!["10.0.0.0/8".parse().unwrap()],
+            control_plane_ns: "linkerd".to_string(),
+            dns_domain: "cluster.example.com".to_string(),
+            identity_domain: "cluster.example.com".to_string(),
+            default_policy: DefaultPolicy::Allow {
+                authenticated_only: false,
+                cluster_only: true,
+            },
+            default_detect_timeout: std::time::Duration::from_secs(10),
+            default_opaque_ports: Default::default(),
+            probe_networks: vec![],
+            max_authorizations_per_server: None,
+            max_routes_per_parent: None,
+            max_backends_per_route: None,
+            meshed_pods_only: false,
+            remote_identity_domains: Default::default(),
+        }
+    }
+
+    async fn json_body(rsp: Response<Body>) -> serde_json::Value {
+        assert_eq!(rsp.status(), http::StatusCode::OK);
+        assert_eq!(
+            rsp.headers()[http::header::CONTENT_TYPE],
+            "application/json"
+        );
+        let body = hyper::body::to_bytes(rsp.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_inbound_server() {
+        let index = inbound::Index::shared(cluster_info());
+        index.write().apply(k8s::Pod {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some("pod-0".to_string()),
+                ..Default::default()
+            },
+            spec: Some(Default::default()),
+            ..Default::default()
+        });
+        let handler = inbound(index.read().lookup());
+
+        let server = json_body(handler(get(
+            "/debug/inbound?namespace=ns-0&pod=pod-0&port=8080",
+        )))
+        .await;
+        assert_eq!(
+            server["reference"],
+            serde_json::json!({ "Default": "cluster-unauthenticated" })
+        );
+
+        let rsp = handler(get("/debug/inbound?namespace=ns-0&pod=pod-1&port=8080"));
+        assert_eq!(rsp.status(), http::StatusCode::NOT_FOUND);
+
+        let rsp = handler(get("/debug/inbound?namespace=ns-0&pod=pod-0&port=0"));
+        assert_eq!(rsp.status(), http::StatusCode::BAD_REQUEST);
+
+        let rsp = handler(get("/debug/inbound?namespace=ns-0&port=8080"));
+        assert_eq!(rsp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn inbound_lookups_do_not_index_ports() {
+        let index = inbound::Index::shared(cluster_info());
+        index.write().apply(k8s::Pod {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some("pod-0".to_string()),
+                ..Default::default()
+            },
+            spec: Some(Default::default()),
+            ..Default::default()
+        });
+        let lookup = index.read().lookup();
+        let handler = inbound(lookup.clone());
+
+        let rsp = handler(get("/debug/inbound?namespace=ns%2D0&pod=pod%2D0&port=8080"));
+        assert_eq!(rsp.status(), http::StatusCode::OK);
+
+        let dump = inbound::dump::dump(&lookup, Some("ns-0"));
+        assert!(dump["ns-0"].pods["pod-0"].is_empty());
+    }
+
+    #[tokio::test]
+    async fn serves_authorizations() {
+        let index = inbound::Index::shared(cluster_info());
+        index.write().apply(k8s::Pod {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some("pod-0".to_string()),
+                ..Default::default()
+            },
+            spec: Some(Default::default()),
+            ..Default::default()
+        });
+        let handler = authorizations(index.read().lookup());
+
+        let authzs = json_body(handler(get(
+            "/debug/authorizations?namespace=ns-0&pod=pod-0&port=8080",
+        )))
+        .await;
+        assert_eq!(
+            authzs["server"],
+            serde_json::json!({ "Default": "cluster-unauthenticated" })
+        );
+        assert_eq!(
+            authzs["authorizations"],
+            serde_json::json!([{
+                "source": "default:cluster-unauthenticated",
+                "networks": [{ "net": "10.0.0.0/8", "except": [] }],
+                "authentication": "Unauthenticated",
+            }])
+        );
+
+        let rsp = handler(get("/debug/authorizations?namespace=ns-0&server=srv-0"));
+        assert_eq!(rsp.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn explains_decisions() {
+        let index = inbound::Index::shared(cluster_info());
+        index.write().apply(k8s::Pod {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some("pod-0".to_string()),
+                ..Default::default()
+            },
+            spec: Some(Default::default()),
+            ..Default::default()
+        });
+        let handler = explain(index.read().lookup());
+
+        let explanation = json_body(handler(get(
+            "/debug/explain?namespace=ns-0&pod=pod-0&port=8080&client_ip=10.1.2.3&path=/",
+        )))
+        .await;
+        assert_eq!(explanation["decision"], "allow");
+        assert_eq!(
+            explanation["server"],
+            serde_json::json!({ "Default": "cluster-unauthenticated" })
+        );
+        assert_eq!(
+            explanation["authorization"]["source"],
+            "default:cluster-unauthenticated"
+        );
+        assert_eq!(explanation["route"]["reference"], "default:default");
+
+        let explanation = json_body(handler(get(
+            "/debug/explain?namespace=ns-0&pod=pod-0&port=8080&client_ip=192.168.0.1",
+        )))
+        .await;
+        assert_eq!(explanation["decision"], "deny");
+        assert_eq!(explanation.get("route"), None);
+        assert_eq!(
+            explanation["evaluated"],
+            serde_json::json!([{
+                "source": "default:cluster-unauthenticated",
+                "permits": false,
+            }])
+        );
+
+        let rsp = handler(get("/debug/explain?namespace=ns-0&pod=pod-0&port=8080"));
+        assert_eq!(rsp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn serves_outbound_policy() {
+        let index = outbound::Index::shared(std::sync::Arc::new(cluster_info()));
+        index.write().apply(k8s::Service {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some("svc-0".to_string()),
+                ..Default::default()
+            },
+            spec: Some(k8s::ServiceSpec {
+                cluster_ips: Some(vec!["10.1.2.3".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let handler = outbound(index.clone());
+
+        let policy = json_body(handler(get("/debug/outbound?addr=10.1.2.3:80"))).await;
+        assert_eq!(policy["name"], "svc-0");
+        assert_eq!(policy["namespace"], "ns-0");
+        assert_eq!(policy["port"], 80);
+
+        let policy = json_body(handler(get(
+            "/debug/outbound?namespace=ns-0&service=svc-0&port=80&source_namespace=ns-1",
+        )))
+        .await;
+        assert_eq!(policy["name"], "svc-0");
+
+        let rsp = handler(get("/debug/outbound?addr=10.1.2.4:80"));
+        assert_eq!(rsp.status(), http::StatusCode::NOT_FOUND);
+
+        let rsp = handler(get("/debug/outbound?namespace=ns-0&service=svc-0"));
+        assert_eq!(rsp.status(), http::StatusCode::BAD_REQUEST);
+
+        // Lookups must not leave watches behind in the index.
+        let policy = json_body(handler(get(
+            "/debug/outbound?namespace=ns-2&service=svc-2&port=80",
+        )))
+        .await;
+        assert_eq!(policy["name"], "svc-2");
+        let dump = outbound::dump::dump(&index.read(), None);
+        assert!(dump["ns-0"].services["svc-0"].ports.is_empty());
+        assert!(!dump.contains_key("ns-2"));
+    }
+
+    #[tokio::test]
+    async fn serves_index() {
+        let inbound_index = inbound::Index::shared(cluster_info());
+        for ns in ["ns-0", "ns-1"] {
+            inbound_index.write().apply(k8s::Pod {
+                metadata: k8s::ObjectMeta {
+                    namespace: Some(ns.to_string()),
+                    name: Some("pod-0".to_string()),
+                    ..Default::default()
+                },
+                spec: Some(Default::default()),
+                ..Default::default()
+            });
+        }
+        let outbound_index = outbound::Index::shared(std::sync::Arc::new(cluster_info()));
+        outbound_index.write().apply(k8s::Service {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some("svc-0".to_string()),
+                ..Default::default()
+            },
+            spec: Some(k8s::ServiceSpec {
+                cluster_ips: Some(vec!["10.1.2.3".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let lookup = inbound_index.read().lookup();
+        lookup
+            .pod_server_rx("ns-0", "pod-0", 8080.try_into().unwrap())
+            .unwrap();
+        outbound_index
+            .write()
+            .outbound_policy_rx(
+                "svc-0".to_string(),
+                "ns-0".to_string(),
+                80.try_into().unwrap(),
+                "ns-1".to_string(),
+            )
+            .unwrap();
+        let handler = index(lookup, outbound_index);
+
+        let dump = json_body(handler(get("/debug/index"))).await;
+        assert_eq!(
+            dump["inbound"]["ns-0"]["pods"],
+            serde_json::json!({ "pod-0": { "8080": null } })
+        );
+        assert_eq!(
+            dump["inbound"]["ns-1"]["pods"],
+            serde_json::json!({ "pod-0": {} })
+        );
+        let svc = &dump["outbound"]["ns-0"]["services"]["svc-0"];
+        assert_eq!(svc["exists"], true);
+        assert_eq!(svc["clusterIps"], serde_json::json!(["10.1.2.3"]));
+        assert_eq!(svc["ports"]["80"]["ns-1"]["name"], "svc-0");
+
+        let dump = json_body(handler(get("/debug/index?namespace=ns-1"))).await;
+        assert_eq!(
+            dump["inbound"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["ns-1"]
+        );
+        assert_eq!(dump["outbound"], serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn sets_log_level() {
+        let (_dispatch, level) = trace::dispatch(
+            "linkerd=info,warn".parse().unwrap(),
+            kubert::LogFormat::Plain,
+            None,
+        )
+        .unwrap();
+        let handler = log_level(level.clone(), Some("secret".to_string()));
+
+        let put = |uri, token: Option<&str>| {
+            let mut req = Request::put(uri);
+            if let Some(token) = token {
+                req = req.header(http::header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            req.body(Body::empty()).unwrap()
+        };
+        let text_body = |rsp: Response<Body>| async move {
+            assert_eq!(rsp.status(), http::StatusCode::OK);
+            hyper::body::to_bytes(rsp.into_body()).await.unwrap()
+        };
+
+        assert_eq!(
+            text_body(handler(get("/log-level"))).await,
+            "linkerd=info,warn\n"
+        );
+        assert_eq!(
+            text_body(handler(put("/log-level?filter=linkerd=debug", Some("secret")))).await,
+            "linkerd=debug\n"
+        );
+        assert_eq!(
+            text_body(handler(get("/log-level"))).await,
+            "linkerd=debug\n"
+        );
+
+        let rsp = handler(put("/log-level?filter=linkerd=loud", Some("secret")));
+        assert_eq!(rsp.status(), http::StatusCode::BAD_REQUEST);
+        let rsp = handler(put("/log-level", Some("secret")));
+        assert_eq!(rsp.status(), http::StatusCode::BAD_REQUEST);
+
+        // Changing the filter requires the admin token.
+        let rsp = handler(put("/log-level?filter=trace", None));
+        assert_eq!(rsp.status(), http::StatusCode::UNAUTHORIZED);
+        let rsp = handler(put("/log-level?filter=trace", Some("wrong")));
+        assert_eq!(rsp.status(), http::StatusCode::UNAUTHORIZED);
+        let disabled = log_level(level, None);
+        let rsp = disabled(put("/log-level?filter=trace", None));
+        assert_eq!(rsp.status(), http::StatusCode::FORBIDDEN);
+        assert_eq!(
+            text_body(handler(get("/log-level"))).await,
+            "linkerd=debug\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn resyncs_watches() {
+        let watches = Resync::default();
+        let _pods = watches.watch::<k8s::Pod, _>("pods", futures::stream::pending);
+        let _servers = watches.watch::<k8s::policy::Server, _>("servers", futures::stream::pending);
+
+        let post = |uri, token: Option<&str>| {
+            let mut req = Request::post(uri);
+            if let Some(token) = token {
+                req = req.header(http::header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            req.body(Body::empty()).unwrap()
+        };
+
+        let disabled = resync(watches.clone(), None);
+        let rsp = disabled(post("/resync", Some("secret")));
+        assert_eq!(rsp.status(), http::StatusCode::NOT_FOUND);
+
+        let handler = resync(watches, Some("secret".to_string()));
+        let rsp = handler(post("/resync", None));
+        assert_eq!(rsp.status(), http::StatusCode::UNAUTHORIZED);
+        let rsp = handler(post("/resync", Some("wrong")));
+        assert_eq!(rsp.status(), http::StatusCode::UNAUTHORIZED);
+        let rsp = handler(get("/resync"));
+        assert_eq!(rsp.status(), http::StatusCode::UNAUTHORIZED);
+
+        assert_eq!(
+            json_body(handler(post("/resync?resource=pods", Some("secret")))).await,
+            serde_json::json!(["pods"])
+        );
+        assert_eq!(
+            json_body(handler(post("/resync", Some("secret")))).await,
+            serde_json::json!(["pods", "servers"])
+        );
+        let rsp = handler(post("/resync?resource=nodes", Some("secret")));
+        assert_eq!(rsp.status(), http::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,269 @@
+//! Feature gates that control which route kinds and filters are indexed, so
+//! that new policy features may be staged per cluster.
+//!
+//! Gates are configured as comma-separated `Name=true|false` pairs, as
+//! Kubernetes feature gates are, e.g. `GatewayHTTPRoute=false`. Features are
+//! enabled unless they are disabled explicitly. Routes of a disabled kind are
+//! not watched, and routes that use a disabled filter are not indexed.
+
+use crate::k8s::{gateway as k8s_gateway_api, policy};
+use anyhow::{anyhow, bail, Error, Result};
+use futures::prelude::*;
+use kube::runtime::watcher;
+use std::{collections::BTreeSet, fmt, str::FromStr};
+use tokio::sync::watch;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    PolicyHttpRoute,
+    GatewayHttpRoute,
+    RequestHeaderModifier,
+    ResponseHeaderModifier,
+    RequestRedirect,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeatureGates {
+    disabled: BTreeSet<Feature>,
+}
+
+/// A route resource whose kind and filters are subject to feature gates.
+pub trait GatedRoute {
+    const KIND: Feature;
+
+    /// Returns the gated features used by the route's filters.
+    fn filter_features(&self) -> BTreeSet<Feature>;
+}
+
+/// Drops routes that use disabled filters from a watch, as if they had been
+/// deleted.
+///
+/// The gates are read as each event is processed, so the watch must be
+/// restarted when the gates change for the change to apply to routes that
+/// have already been indexed.
+pub fn filter_routes<R, S>(
+    gates: watch::Receiver<FeatureGates>,
+    watch: S,
+) -> impl Stream<Item = Result<watcher::Event<R>, watcher::Error>>
+where
+    R: GatedRoute + kube::ResourceExt,
+    S: Stream<Item = Result<watcher::Event<R>, watcher::Error>>,
+{
+    watch.map_ok(move |event| {
+        let gates = gates.borrow();
+        match event {
+            watcher::Event::Applied(route) if !gates.admits(&route) => {
+                tracing::info!(ns = ?route.namespace(), name = %route.name_any(), "Ignoring route that uses a disabled feature");
+                watcher::Event::Deleted(route)
+            }
+            watcher::Event::Restarted(routes) => watcher::Event::Restarted(
+                routes
+                    .into_iter()
+                    .filter(|route| {
+                        let admitted = gates.admits(route);
+                        if !admitted {
+                            tracing::info!(ns = ?route.namespace(), name = %route.name_any(), "Ignoring route that uses a disabled feature");
+                        }
+                        admitted
+                    })
+                    .collect(),
+            ),
+            event => event,
+        }
+    })
+}
+
+// === impl Feature ===
+
+impl Feature {
+    const ALL: [Self; 5] = [
+        Self::PolicyHttpRoute,
+        Self::GatewayHttpRoute,
+        Self::RequestHeaderModifier,
+        Self::ResponseHeaderModifier,
+        Self::RequestRedirect,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::PolicyHttpRoute => "PolicyHTTPRoute",
+            Self::GatewayHttpRoute => "GatewayHTTPRoute",
+            Self::RequestHeaderModifier => "RequestHeaderModifier",
+            Self::ResponseHeaderModifier => "ResponseHeaderModifier",
+            Self::RequestRedirect => "RequestRedirect",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.name() == s)
+            .ok_or_else(|| anyhow!("unknown feature {s:?}"))
+    }
+}
+
+// === impl FeatureGates ===
+
+impl FeatureGates {
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        !self.disabled.contains(&feature)
+    }
+
+    /// Returns true if the route's kind and all of its filters are enabled.
+    pub fn admits<R: GatedRoute>(&self, route: &R) -> bool {
+        self.is_enabled(R::KIND)
+            && route
+                .filter_features()
+                .into_iter()
+                .all(|feature| self.is_enabled(feature))
+    }
+}
+
+impl FromStr for FeatureGates {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut disabled = BTreeSet::new();
+        for gate in s.split(',').map(str::trim).filter(|g| !g.is_empty()) {
+            let Some((feature, enabled)) = gate.split_once('=') else {
+                bail!("feature gate {gate:?} must be of the form Name=true|false");
+            };
+            let feature = feature.trim().parse::<Feature>()?;
+            match enabled.trim().parse::<bool>()? {
+                true => disabled.remove(&feature),
+                false => disabled.insert(feature),
+            };
+        }
+        Ok(Self { disabled })
+    }
+}
+
+impl fmt::Display for FeatureGates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, feature) in self.disabled.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{feature}=false")?;
+        }
+        Ok(())
+    }
+}
+
+// === impl GatedRoute ===
+
+impl GatedRoute for policy::HttpRoute {
+    const KIND: Feature = Feature::PolicyHttpRoute;
+
+    fn filter_features(&self) -> BTreeSet<Feature> {
+        let rules = self.spec.rules.iter().flatten();
+        let route_filters = rules.clone().flat_map(|rule| {
+            rule.filters.iter().flatten().map(|filter| match filter {
+                policy::httproute::HttpRouteFilter::RequestHeaderModifier { .. } => {
+                    Feature::RequestHeaderModifier
+                }
+                policy::httproute::HttpRouteFilter::ResponseHeaderModifier { .. } => {
+                    Feature::ResponseHeaderModifier
+                }
+                policy::httproute::HttpRouteFilter::RequestRedirect { .. } => {
+                    Feature::RequestRedirect
+                }
+            })
+        });
+        let backend_filters = rules
+            .flat_map(|rule| rule.backend_refs.iter().flatten())
+            .flat_map(|backend| backend.filters.iter().flatten())
+            .filter_map(gateway_filter_feature);
+        route_filters.chain(backend_filters).collect()
+    }
+}
+
+impl GatedRoute for k8s_gateway_api::HttpRoute {
+    const KIND: Feature = Feature::GatewayHttpRoute;
+
+    fn filter_features(&self) -> BTreeSet<Feature> {
+        let rules = self.spec.rules.iter().flatten();
+        let route_filters = rules.clone().flat_map(|rule| rule.filters.iter().flatten());
+        let backend_filters = rules
+            .flat_map(|rule| rule.backend_refs.iter().flatten())
+            .flat_map(|backend| backend.filters.iter().flatten());
+        route_filters
+            .chain(backend_filters)
+            .filter_map(gateway_filter_feature)
+            .collect()
+    }
+}
+
+/// Filters that are not supported by the indexes are not gated, since routes
+/// that use them are never served.
+fn gateway_filter_feature(filter: &k8s_gateway_api::HttpRouteFilter) -> Option<Feature> {
+    match filter {
+        k8s_gateway_api::HttpRouteFilter::RequestHeaderModifier { .. } => {
+            Some(Feature::RequestHeaderModifier)
+        }
+        k8s_gateway_api::HttpRouteFilter::ResponseHeaderModifier { .. } => {
+            Some(Feature::ResponseHeaderModifier)
+        }
+        k8s_gateway_api::HttpRouteFilter::RequestRedirect { .. } => Some(Feature::RequestRedirect),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gates() {
+        let gates = "GatewayHTTPRoute=false, RequestRedirect=false,RequestRedirect=true"
+            .parse::<FeatureGates>()
+            .unwrap();
+        assert!(!gates.is_enabled(Feature::GatewayHttpRoute));
+        assert!(gates.is_enabled(Feature::RequestRedirect));
+        assert!(gates.is_enabled(Feature::PolicyHttpRoute));
+        assert_eq!(gates.to_string(), "GatewayHTTPRoute=false");
+
+        assert_eq!("".parse::<FeatureGates>().unwrap(), FeatureGates::default());
+        assert!("GatewayHTTPRoute".parse::<FeatureGates>().is_err());
+        assert!("Bogus=false".parse::<FeatureGates>().is_err());
+        assert!("RequestRedirect=no".parse::<FeatureGates>().is_err());
+    }
+
+    #[test]
+    fn admits_routes_with_enabled_filters() {
+        let route = serde_json::from_value::<k8s_gateway_api::HttpRoute>(serde_json::json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "namespace": "ns", "name": "route" },
+            "spec": {
+                "rules": [{
+                    "filters": [{
+                        "type": "RequestRedirect",
+                        "requestRedirect": { "statusCode": 302 },
+                    }],
+                }],
+            },
+        }))
+        .unwrap();
+
+        assert!(FeatureGates::default().admits(&route));
+        let gates = "RequestHeaderModifier=false"
+            .parse::<FeatureGates>()
+            .unwrap();
+        assert!(gates.admits(&route));
+        let gates = "RequestRedirect=false".parse::<FeatureGates>().unwrap();
+        assert!(!gates.admits(&route));
+        let gates = "GatewayHTTPRoute=false".parse::<FeatureGates>().unwrap();
+        assert!(!gates.admits(&route));
+    }
+}
@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
-use kubert::index::IndexNamespacedResource;
+use kubert::index::{IndexNamespacedResource, NamespacedRemoved};
 use parking_lot::RwLock;
 
 /// A list of indexes for a specific resource type.
 ///
 /// An `IndexList` itself can then act as an index for that resource, and fans updates
-/// out to each index in the list by cloning the update.
+/// out to each index in the list by cloning the update. This allows a single watch
+/// to be shared by all of the indexes that consume a resource.
 pub struct IndexList<A, T = A> {
     index: Arc<RwLock<A>>,
     tail: Option<T>,
@@ -31,6 +32,15 @@ where
         }
         self.index.write().delete(namespace, name);
     }
+
+    // Resets are forwarded as a whole so that each index may handle them in
+    // bulk, rather than as individual updates.
+    fn reset(&mut self, resources: Vec<R>, removed: NamespacedRemoved) {
+        if let Some(tail) = &mut self.tail {
+            tail.reset(resources.clone(), removed.clone());
+        }
+        self.index.write().reset(resources, removed);
+    }
 }
 
 impl<A, T> IndexList<A, T> {
@@ -57,3 +67,32 @@ impl<A> IndexList<A> {
         IndexList { index, tail: None }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Resets(usize);
+
+    impl IndexNamespacedResource<String> for Resets {
+        fn apply(&mut self, _: String) {}
+
+        fn delete(&mut self, _: String, _: String) {}
+
+        fn reset(&mut self, _: Vec<String>, _: NamespacedRemoved) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn forwards_resets() {
+        let a = Arc::new(RwLock::new(Resets::default()));
+        let b = Arc::new(RwLock::new(Resets::default()));
+        let mut list = IndexList::new(a.clone()).push(b.clone());
+
+        list.reset(vec!["res-0".to_string()], Default::default());
+        assert_eq!(a.read().0, 1);
+        assert_eq!(b.read().0, 1);
+    }
+}
@@ -1,10 +1,22 @@
 #![deny(warnings, rust_2018_idioms)]
 #![forbid(unsafe_code)]
 mod admission;
+pub mod audit;
+pub mod config;
+pub mod debug;
+pub mod features;
 pub mod index_list;
+pub mod manifests;
+pub mod memory;
+pub mod snapshot;
+pub mod trace;
 mod validation;
+pub mod watches;
+#[cfg(fuzzing)]
+pub use self::admission::fuzz_logic as admission_fuzz_logic;
 pub use self::admission::Admission;
 use anyhow::Result;
+use futures::StreamExt;
 use linkerd_policy_controller_core::inbound::{
     DiscoverInboundServer, InboundServer, InboundServerStream,
 };
@@ -18,20 +30,54 @@ pub use linkerd_policy_controller_k8s_index::{inbound, outbound, ClusterInfo, De
 use std::{net::IpAddr, num::NonZeroU16};
 
 #[derive(Clone, Debug)]
-pub struct InboundDiscover(inbound::SharedIndex);
+pub struct InboundDiscover {
+    index: inbound::Lookup,
+    sync: Option<watches::InitialSync>,
+}
 
 #[derive(Clone, Debug)]
-pub struct OutboundDiscover(outbound::SharedIndex);
+pub struct OutboundDiscover {
+    index: outbound::SharedIndex,
+    sync: Option<watches::InitialSync>,
+}
 
 impl InboundDiscover {
-    pub fn new(index: inbound::SharedIndex) -> Self {
-        Self(index)
+    pub fn new(index: inbound::Lookup) -> Self {
+        Self { index, sync: None }
+    }
+
+    /// Holds lookups until the initial sync of all resource watches completes.
+    pub fn with_initial_sync(self, sync: watches::InitialSync) -> Self {
+        Self {
+            sync: Some(sync),
+            ..self
+        }
+    }
+
+    async fn synced(&self) {
+        if let Some(sync) = &self.sync {
+            sync.wait().await;
+        }
     }
 }
 
 impl OutboundDiscover {
     pub fn new(index: outbound::SharedIndex) -> Self {
-        Self(index)
+        Self { index, sync: None }
+    }
+
+    /// Holds lookups until the initial sync of all resource watches completes.
+    pub fn with_initial_sync(self, sync: watches::InitialSync) -> Self {
+        Self {
+            sync: Some(sync),
+            ..self
+        }
+    }
+
+    async fn synced(&self) {
+        if let Some(sync) = &self.sync {
+            sync.wait().await;
+        }
     }
 }
 
@@ -41,15 +87,16 @@ impl DiscoverInboundServer<(grpc::workload::Workload, NonZeroU16)> for InboundDi
         &self,
         (workload, port): (grpc::workload::Workload, NonZeroU16),
     ) -> Result<Option<InboundServer>> {
+        self.synced().await;
         let grpc::workload::Workload { namespace, kind } = workload;
         let rx = match kind {
             grpc::workload::Kind::External(name) => self
-                .0
-                .write()
+                .index
                 .external_workload_server_rx(&namespace, &name, port),
-            grpc::workload::Kind::Pod(name) => {
-                self.0.write().pod_server_rx(&namespace, &name, port)
-            }
+            grpc::workload::Kind::ExternalIdentity(identity) => self
+                .index
+                .external_workload_identity_server_rx(&namespace, &identity, port),
+            grpc::workload::Kind::Pod(name) => self.index.pod_server_rx(&namespace, &name, port),
         };
 
         if let Ok(rx) = rx {
@@ -64,15 +111,16 @@ impl DiscoverInboundServer<(grpc::workload::Workload, NonZeroU16)> for InboundDi
         &self,
         (workload, port): (grpc::workload::Workload, NonZeroU16),
     ) -> Result<Option<InboundServerStream>> {
+        self.synced().await;
         let grpc::workload::Workload { namespace, kind } = workload;
         let rx = match kind {
             grpc::workload::Kind::External(name) => self
-                .0
-                .write()
+                .index
                 .external_workload_server_rx(&namespace, &name, port),
-            grpc::workload::Kind::Pod(name) => {
-                self.0.write().pod_server_rx(&namespace, &name, port)
-            }
+            grpc::workload::Kind::ExternalIdentity(identity) => self
+                .index
+                .external_workload_identity_server_rx(&namespace, &identity, port),
+            grpc::workload::Kind::Pod(name) => self.index.pod_server_rx(&namespace, &name, port),
         };
 
         if let Ok(rx) = rx {
@@ -92,9 +140,11 @@ impl DiscoverOutboundPolicy<OutboundDiscoverTarget> for OutboundDiscover {
             service_namespace,
             service_port,
             source_namespace,
+            ..
         }: OutboundDiscoverTarget,
     ) -> Result<Option<OutboundPolicy>> {
-        let rx = match self.0.write().outbound_policy_rx(
+        self.synced().await;
+        let rx = match self.index.write().outbound_policy_rx(
             service_name,
             service_namespace,
             service_port,
@@ -117,17 +167,36 @@ impl DiscoverOutboundPolicy<OutboundDiscoverTarget> for OutboundDiscover {
             service_namespace,
             service_port,
             source_namespace,
+            endpoint_owner,
         }: OutboundDiscoverTarget,
     ) -> Result<Option<OutboundPolicyStream>> {
-        match self.0.write().outbound_policy_rx(
+        self.synced().await;
+        let mut index = self.index.write();
+        let rx = match index.outbound_policy_rx(
             service_name,
             service_namespace,
             service_port,
             source_namespace,
         ) {
-            Ok(rx) => Ok(Some(Box::pin(tokio_stream::wrappers::WatchStream::new(rx)))),
-            Err(_) => Ok(None),
-        }
+            Ok(rx) => rx,
+            Err(_) => return Ok(None),
+        };
+        let stream = tokio_stream::wrappers::WatchStream::new(rx);
+        let Some((addr, uid)) = endpoint_owner else {
+            return Ok(Some(Box::pin(stream)));
+        };
+
+        // If the endpoint address has been reassigned to another pod since it
+        // was resolved, the stream ends so that the client resolves it again
+        // rather than being served the previous owner's policy.
+        let owner_rx = index.endpoint_owner_rx(addr);
+        drop(index);
+        let reassigned = async move {
+            if let Some(mut owner_rx) = owner_rx {
+                let _ = owner_rx.wait_for(|owner| *owner != uid).await;
+            }
+        };
+        Ok(Some(Box::pin(stream.take_until(reassigned))))
     }
 
     fn lookup_ip(
@@ -136,16 +205,43 @@ impl DiscoverOutboundPolicy<OutboundDiscoverTarget> for OutboundDiscover {
         port: NonZeroU16,
         source_namespace: String,
     ) -> Option<OutboundDiscoverTarget> {
-        self.0
-            .read()
-            .lookup_service(addr)
-            .map(
-                |outbound::ServiceRef { name, namespace }| OutboundDiscoverTarget {
-                    service_name: name,
-                    service_namespace: namespace,
-                    service_port: port,
-                    source_namespace,
-                },
-            )
+        let index = self.index.read();
+        // Cluster IPs are looked up by Service port, while endpoint addresses
+        // are looked up by the target port to which the Service port maps.
+        let ((outbound::ServiceRef { name, namespace }, port), endpoint_owner) =
+            match index.lookup_service(addr) {
+                Some(svc) => ((svc, port), None),
+                None => {
+                    let target = index.lookup_endpoint(addr, port)?;
+                    let owner = index.endpoint_owner(addr).map(|uid| (addr, uid));
+                    (target, owner)
+                }
+            };
+        Some(OutboundDiscoverTarget {
+            service_name: name,
+            service_namespace: namespace,
+            service_port: port,
+            source_namespace,
+            endpoint_owner,
+        })
+    }
+
+    fn lookup_hostname(
+        &self,
+        host: &str,
+        port: NonZeroU16,
+        source_namespace: String,
+    ) -> Option<OutboundDiscoverTarget> {
+        let outbound::ServiceRef { name, namespace } =
+            self.index
+                .read()
+                .lookup_hostname(host, port, &source_namespace)?;
+        Some(OutboundDiscoverTarget {
+            service_name: name,
+            service_namespace: namespace,
+            service_port: port,
+            source_namespace,
+            endpoint_owner: None,
+        })
     }
 }
@@ -9,22 +9,39 @@ use k8s_openapi::api::coordination::v1 as coordv1;
 use kube::{api::PatchParams, runtime::watcher};
 use kubert::LeaseManager;
 use linkerd_policy_controller::{
-    grpc, inbound, index_list::IndexList, k8s, outbound, Admission, ClusterInfo, DefaultPolicy,
-    InboundDiscover, IpNet, OutboundDiscover,
+    audit::AuditLog,
+    config::ConfigDir,
+    debug,
+    features::{self, FeatureGates, GatedRoute},
+    grpc, inbound,
+    index_list::IndexList,
+    k8s,
+    manifests::Manifests,
+    memory, outbound,
+    snapshot::Snapshot,
+    trace,
+    watches::{self, Backoff, ExcludedNamespaces, InitialSync, Resync, Served, WatchHealth},
+    Admission, ClusterInfo, DefaultPolicy, InboundDiscover, IpNet, OutboundDiscover,
 };
+use linkerd_policy_controller_core::routes::Quarantine;
 use linkerd_policy_controller_k8s_index::ports::parse_portset;
 use linkerd_policy_controller_k8s_status::{self as status};
 use prometheus_client::registry::Registry;
-use std::{net::SocketAddr, sync::Arc};
-use tokio::{sync::mpsc, time::Duration};
-use tonic::transport::Server;
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf, sync::Arc};
+use tokio::{
+    sync::{mpsc, watch},
+    time::{self, Duration},
+};
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{server::TcpIncoming, Server},
+};
 use tracing::{info, info_span, instrument, Instrument};
 
 #[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu"))]
 #[global_allocator]
 static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
-const DETECT_TIMEOUT: Duration = Duration::from_secs(10);
 const LEASE_DURATION: Duration = Duration::from_secs(30);
 const LEASE_NAME: &str = "policy-controller-write";
 const RENEW_GRACE_PERIOD: Duration = Duration::from_secs(1);
@@ -56,25 +73,59 @@ struct Args {
     #[clap(flatten)]
     admin: kubert::AdminArgs,
 
+    /// A bearer token that authorizes requests to the admin server's
+    /// `/resync` endpoint and changes to its `/log-level` filter. If unset,
+    /// these requests are refused.
+    #[clap(
+        long,
+        env = "LINKERD_POLICY_CONTROLLER_ADMIN_TOKEN",
+        hide_env_values = true
+    )]
+    admin_token: Option<String>,
+
     /// Disables the admission controller server.
     #[clap(long)]
     admission_controller_disabled: bool,
 
+    /// The path of an append-only log to which the admission controller's
+    /// decisions are written, as JSON lines.
+    #[clap(long)]
+    admission_audit_log: Option<PathBuf>,
+
+    /// The size, in bytes, beyond which the admission audit log is rotated.
+    #[clap(long, default_value = "10485760")]
+    admission_audit_log_max_bytes: u64,
+
+    /// The number of rotated admission audit logs to retain.
+    #[clap(long, default_value = "5")]
+    admission_audit_log_max_files: usize,
+
     #[clap(long, default_value = "0.0.0.0:8090")]
     grpc_addr: SocketAddr,
 
     /// Network CIDRs of pod IPs.
     ///
-    /// The default includes all private networks.
+    /// The default includes all private networks. May be overridden at runtime
+    /// by the `cluster-networks` setting in the config directory.
     #[clap(
         long,
         default_value = "10.0.0.0/8,100.64.0.0/10,172.16.0.0/12,192.168.0.0/16"
     )]
     cluster_networks: IpNets,
 
+    /// The mesh identity trust domain. May be overridden at runtime by the
+    /// `identity-domain` setting in the config directory.
     #[clap(long, default_value = "cluster.local")]
     identity_domain: String,
 
+    /// The mesh identity trust domains of linked clusters, e.g.
+    /// `east=east.example.com,west=west.example.com`, so that
+    /// MeshTLSAuthentications may refer to their ServiceAccounts. May be
+    /// overridden at runtime by the `remote-identity-domains` setting in the
+    /// config directory.
+    #[clap(long, default_value = "")]
+    remote_identity_domains: TrustDomains,
+
     #[clap(long, default_value = "cluster.local")]
     cluster_domain: String,
 
@@ -94,21 +145,215 @@ struct Args {
     #[clap(long)]
     default_opaque_ports: String,
 
+    /// The default protocol detection timeout served to proxies. This may be
+    /// overridden for individual Servers and Services with the
+    /// `config.linkerd.io/protocol-detect-timeout` annotation.
+    #[clap(long, default_value = "10000")]
+    default_detect_timeout_ms: u64,
+
     #[clap(long, default_value = "5000")]
     patch_timeout_ms: u64,
+
+    /// The maximum number of resource status patches that may be applied
+    /// concurrently. Patches to the same resource are always applied in order.
+    #[clap(long, default_value = "1")]
+    patch_concurrency: usize,
+
+    /// The maximum number of routes that may be attached to a single parent.
+    /// The newest routes in excess of the limit are not accepted, and are not
+    /// served to proxies.
+    #[clap(long)]
+    max_routes_per_parent: Option<usize>,
+
+    /// The maximum number of backends that a single route may reference.
+    /// Routes in excess of the limit are not accepted, and are not served to
+    /// proxies.
+    #[clap(long)]
+    max_backends_per_route: Option<usize>,
+
+    /// A comma-separated list of the controller names of retired controllers,
+    /// e.g. those of prior Linkerd versions. Route statuses written by these
+    /// controllers are removed.
+    #[clap(long, value_delimiter = ',')]
+    retired_status_controllers: Vec<String>,
+
+    /// A label selector, e.g. `ci.example.com/ephemeral=true`, of namespaces
+    /// whose resources are ignored by the indexes and the status controller.
+    /// Requires that the controller may list and watch namespaces.
+    #[clap(long)]
+    excluded_namespace_labels: Option<LabelSelector>,
+
+    /// The maximum number of authorizations that may apply to a single
+    /// Server. Authorizations in excess of the limit are ignored.
+    #[clap(long)]
+    max_authorizations_per_server: Option<usize>,
+
+    /// Indexes only pods that have been injected with a proxy, i.e. those with
+    /// a `linkerd-proxy` container or the `linkerd.io/proxy-version`
+    /// annotation, rather than all pods that carry the control plane label.
+    #[clap(long)]
+    index_meshed_pods_only: bool,
+
+    /// The amount of time a resource watch may fail before the controller is
+    /// marked unready. The last known state continues to be served in the
+    /// meantime.
+    #[clap(long, default_value = "300000")]
+    watch_stale_threshold_ms: u64,
+
+    /// The delay before a failed resource watch is first restarted. The delay
+    /// doubles with each consecutive failure.
+    #[clap(long, default_value = "1000")]
+    watch_backoff_min_ms: u64,
+
+    /// The maximum delay before a failed resource watch is restarted.
+    #[clap(long, default_value = "60000")]
+    watch_backoff_max_ms: u64,
+
+    /// The number of failures a resource watch may incur, either in a row or
+    /// within the error window, before it is only retried at the maximum
+    /// delay.
+    #[clap(long, default_value = "5")]
+    watch_error_budget: u32,
+
+    /// The window within which a resource watch's failures are counted against
+    /// its error budget, even if the watch recovers between failures.
+    #[clap(long, default_value = "60000")]
+    watch_error_window_ms: u64,
+
+    /// The maximum number of concurrent policy watches that a single client
+    /// connection may hold open.
+    #[clap(long, default_value = "1000")]
+    grpc_max_watches_per_connection: usize,
+
+    /// The amount of time a watch may wait for a client to consume an update
+    /// before the watch is reset.
+    #[clap(long, default_value = "30000")]
+    grpc_watch_lag_timeout_ms: u64,
+
+    /// The interval at which HTTP/2 PING frames are sent to gRPC clients to
+    /// keep connections alive.
+    #[clap(long)]
+    grpc_keepalive_interval_ms: Option<u64>,
+
+    /// The amount of time to wait for an HTTP/2 keepalive PING to be
+    /// acknowledged before closing the connection.
+    #[clap(long)]
+    grpc_keepalive_timeout_ms: Option<u64>,
+
+    /// The maximum number of concurrent HTTP/2 streams per gRPC connection.
+    #[clap(long)]
+    grpc_max_concurrent_streams: Option<u32>,
+
+    /// The maximum number of concurrent gRPC connections. When the limit is
+    /// reached, new connections are not accepted until existing ones close.
+    #[clap(long)]
+    grpc_max_connections: Option<usize>,
+
+    /// Enables compression of policy API responses for clients that accept
+    /// the given encoding. Only `gzip` is supported: `zstd` is deferred until
+    /// the controller's tonic 0.10 dependency is upgraded to a release that
+    /// supports it.
+    #[clap(long)]
+    grpc_compression: Option<Compression>,
+
+    /// The amount of time that open policy watches are kept alive after
+    /// shutdown begins, giving clients an opportunity to reconnect to another
+    /// replica before their streams are closed.
+    #[clap(long, default_value = "5000")]
+    grpc_drain_timeout_ms: u64,
+
+    /// A file in which to persist the resources observed by the controller.
+    /// When set, the indexes are populated from the snapshot at startup, so
+    /// that policies may be served before the resource watches have synced.
+    #[clap(long)]
+    index_snapshot_path: Option<PathBuf>,
+
+    /// The interval at which the index snapshot is written.
+    #[clap(long, default_value = "60000")]
+    index_snapshot_interval_ms: u64,
+
+    /// The interval at which the indexes are swept for namespaces that no
+    /// longer hold any resources.
+    #[clap(long, default_value = "300000")]
+    index_gc_interval_ms: u64,
+
+    /// The interval at which API discovery is polled to determine whether the
+    /// Gateway API CRDs are installed. Gateway API resources are only watched
+    /// while they are installed.
+    #[clap(long, default_value = "30000")]
+    api_discovery_interval_ms: u64,
+
+    /// Disables policy features, as comma-separated `Name=false` pairs, e.g.
+    /// `GatewayHTTPRoute=false,RequestRedirect=false`. May be overridden at
+    /// runtime by the `feature-gates` setting in the config directory.
+    #[clap(long, default_value = "")]
+    feature_gates: FeatureGates,
+
+    /// A directory, typically a mounted ConfigMap, from which settings that
+    /// may be changed at runtime are read. Each setting is a file named by its
+    /// key.
+    #[clap(long)]
+    config_dir: Option<PathBuf>,
+
+    /// The interval at which the config directory is polled for changes.
+    #[clap(long, default_value = "10000")]
+    config_poll_interval_ms: u64,
+
+    /// Holds policy lookups until all resource watches have completed their
+    /// initial sync, so that policies are never served from a partially
+    /// populated index.
+    #[clap(long)]
+    grpc_hold_until_synced: bool,
+
+    /// Serves policies from a directory of resource manifests rather than the
+    /// Kubernetes API. The directory is polled for changes at the config poll
+    /// interval.
+    #[clap(long)]
+    manifests_dir: Option<PathBuf>,
+
+    /// The endpoint of an OpenTelemetry collector to which traces are exported
+    /// over OTLP/gRPC, e.g. `http://otel-collector.linkerd-jaeger:4317`.
+    #[clap(long)]
+    trace_collector: Option<String>,
+
+    /// The ratio of traces that are sampled when exporting traces.
+    #[clap(long, default_value = "1.0")]
+    trace_sample_ratio: f64,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let exporter = args
+        .trace_collector
+        .clone()
+        .map(|endpoint| trace::Exporter {
+            endpoint,
+            sample_ratio: args.trace_sample_ratio,
+        });
+    let (dispatch, log_level) =
+        trace::dispatch(args.log_level.clone(), args.log_format.clone(), exporter)?;
+    let (rt, _guard) = trace::runtime(dispatch)?;
+    let res = rt.block_on(run(args, log_level));
+    trace::shutdown();
+    res
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+async fn run(args: Args, log_level_handle: trace::LogLevel) -> Result<()> {
     let Args {
         admin,
+        admin_token,
         client,
         log_level,
         log_format,
         server,
         grpc_addr,
         admission_controller_disabled,
+        admission_audit_log,
+        admission_audit_log_max_bytes,
+        admission_audit_log_max_files,
         identity_domain,
+        remote_identity_domains,
         cluster_domain,
         cluster_networks: IpNets(cluster_networks),
         default_policy,
@@ -116,38 +361,96 @@ async fn main() -> Result<()> {
         control_plane_namespace,
         probe_networks,
         default_opaque_ports,
+        default_detect_timeout_ms,
         patch_timeout_ms,
-    } = Args::parse();
+        patch_concurrency,
+        max_routes_per_parent,
+        max_backends_per_route,
+        retired_status_controllers,
+        excluded_namespace_labels,
+        max_authorizations_per_server,
+        index_meshed_pods_only,
+        watch_stale_threshold_ms,
+        watch_backoff_min_ms,
+        watch_backoff_max_ms,
+        watch_error_budget,
+        watch_error_window_ms,
+        grpc_max_watches_per_connection,
+        grpc_watch_lag_timeout_ms,
+        grpc_keepalive_interval_ms,
+        grpc_keepalive_timeout_ms,
+        grpc_max_concurrent_streams,
+        grpc_max_connections,
+        grpc_compression,
+        grpc_drain_timeout_ms,
+        index_snapshot_path,
+        index_snapshot_interval_ms,
+        index_gc_interval_ms,
+        api_discovery_interval_ms,
+        feature_gates,
+        config_dir,
+        config_poll_interval_ms,
+        grpc_hold_until_synced,
+        manifests_dir,
+        trace_collector: _,
+        trace_sample_ratio: _,
+    } = args;
 
     let server = if admission_controller_disabled {
         None
     } else {
         Some(server)
     };
+    let audit_log = admission_audit_log
+        .map(|path| {
+            AuditLog::open(
+                path,
+                admission_audit_log_max_bytes,
+                admission_audit_log_max_files,
+            )
+        })
+        .transpose()?;
 
     let probe_networks = probe_networks.map(|IpNets(nets)| nets).unwrap_or_default();
 
+    // Settings that may be updated at runtime are read from the config
+    // directory, defaulting to the values configured on the command line.
+    let config = ConfigDir::new(config_dir, Duration::from_millis(config_poll_interval_ms));
+    let (cluster_networks, cluster_networks_task) =
+        config.watch("cluster-networks", IpNets(cluster_networks));
+    let (identity_domain, identity_domain_task) = config.watch("identity-domain", identity_domain);
+    let (remote_identity_domains, remote_identity_domains_task) =
+        config.watch("remote-identity-domains", remote_identity_domains);
+
     let default_opaque_ports = parse_portset(&default_opaque_ports)?;
     let cluster_info = Arc::new(ClusterInfo {
-        networks: cluster_networks.clone(),
-        identity_domain,
+        networks: cluster_networks.borrow().0.clone(),
+        identity_domain: identity_domain.borrow().clone(),
         control_plane_ns: control_plane_namespace.clone(),
         dns_domain: cluster_domain.clone(),
         default_policy,
-        default_detect_timeout: DETECT_TIMEOUT,
+        default_detect_timeout: Duration::from_millis(default_detect_timeout_ms),
         default_opaque_ports,
         probe_networks,
+        max_authorizations_per_server,
+        max_routes_per_parent,
+        max_backends_per_route,
+        meshed_pods_only: index_meshed_pods_only,
+        remote_identity_domains: remote_identity_domains.borrow().0.clone(),
     });
 
     // Build the API index data structures which will maintain information
     // necessary for serving the inbound policy and outbound policy gRPC APIs.
     let inbound_index = inbound::Index::shared(cluster_info.clone());
+    let inbound_lookup = inbound_index.read().lookup();
     let outbound_index = outbound::Index::shared(cluster_info);
 
     let mut prom = <Registry>::default();
     let resource_status = prom.sub_registry_with_prefix("resource_status");
     let status_metrics = status::ControllerMetrics::register(resource_status);
     let status_index_metrcs = status::IndexMetrics::register(resource_status);
+    let (updates_tx, updates_rx) = mpsc::channel(STATUS_UPDATE_QUEUE_SIZE);
+    status::UpdatesQueue::register(resource_status, &updates_tx);
 
     outbound::metrics::register(
         prom.sub_registry_with_prefix("outbound_index"),
@@ -158,9 +461,93 @@ async fn main() -> Result<()> {
         inbound_index.clone(),
     );
 
+    memory::register(prom.sub_registry_with_prefix("memory"));
+
+    let watch_health = WatchHealth::new(Backoff::new(
+        Duration::from_millis(watch_backoff_min_ms),
+        Duration::from_millis(watch_backoff_max_ms),
+        watch_error_budget,
+        Duration::from_millis(watch_error_window_ms),
+    )?);
+    watch_health.register(prom.sub_registry_with_prefix("index"));
+    let resync = Resync::default();
+
+    let grpc_server_metrics = prom.sub_registry_with_prefix("grpc_server");
+    let watch_metrics = grpc::limits::WatchMetrics::register(grpc_server_metrics);
+    let stream_metrics = grpc::metrics::StreamMetrics::register(grpc_server_metrics);
+    let capabilities = grpc::capabilities::Capabilities::register(grpc_server_metrics);
+    let watch_limits = grpc::limits::WatchLimits::new(
+        grpc_max_watches_per_connection,
+        Duration::from_millis(grpc_watch_lag_timeout_ms),
+        watch_metrics,
+    );
+    let grpc_server = Server::builder()
+        .http2_keepalive_interval(grpc_keepalive_interval_ms.map(Duration::from_millis))
+        .http2_keepalive_timeout(grpc_keepalive_timeout_ms.map(Duration::from_millis))
+        .max_concurrent_streams(grpc_max_concurrent_streams);
+
+    // Without the Kubernetes API, the indexes are populated from manifests and
+    // only the gRPC server is run.
+    if let Some(dir) = manifests_dir {
+        let (manifests, task) =
+            Manifests::load(dir, Duration::from_millis(config_poll_interval_ms))?;
+        tokio::spawn(task.instrument(info_span!("manifests")));
+        index_manifests(&manifests, &inbound_index, &outbound_index);
+        tokio::spawn(
+            cluster_networks_task.instrument(info_span!("config", key = "cluster-networks")),
+        );
+        let cluster_networks = update_cluster_networks(cluster_networks, inbound_index);
+
+        let (shutdown, drain) = drain::channel();
+        let mut grpc = tokio::spawn(grpc(
+            grpc_addr,
+            grpc_server,
+            grpc_max_connections,
+            grpc_compression,
+            Duration::from_millis(grpc_drain_timeout_ms),
+            cluster_domain,
+            cluster_networks,
+            inbound_lookup,
+            outbound_index,
+            watch_limits,
+            stream_metrics,
+            capabilities,
+            None,
+            watch_health,
+            drain,
+        ));
+        tokio::select! {
+            res = &mut grpc => return res?,
+            res = tokio::signal::ctrl_c() => res?,
+        }
+        info!("Shutting down");
+        shutdown.drain().await;
+        return grpc.await?;
+    }
+
     let mut runtime = kubert::Runtime::builder()
         .with_log(log_level, log_format)
-        .with_admin(admin.into_builder().with_prometheus(prom))
+        .with_admin(
+            admin
+                .into_builder()
+                .with_handler(
+                    "/log-level",
+                    debug::log_level(log_level_handle, admin_token.clone()),
+                )
+                .with_handler("/debug/inbound", debug::inbound(inbound_lookup.clone()))
+                .with_handler("/debug/outbound", debug::outbound(outbound_index.clone()))
+                .with_handler(
+                    "/debug/index",
+                    debug::index(inbound_lookup.clone(), outbound_index.clone()),
+                )
+                .with_handler(
+                    "/debug/authorizations",
+                    debug::authorizations(inbound_lookup.clone()),
+                )
+                .with_handler("/debug/explain", debug::explain(inbound_lookup.clone()))
+                .with_handler("/resync", debug::resync(resync.clone(), admin_token))
+                .with_prometheus(prom),
+        )
         .with_client(client)
         .with_optional_server(server)
         .build()
@@ -183,30 +570,144 @@ async fn main() -> Result<()> {
 
     // Build the status index which will maintain information necessary for
     // updating the status field of policy resources.
-    let (updates_tx, updates_rx) = mpsc::channel(STATUS_UPDATE_QUEUE_SIZE);
     let status_index = status::Index::shared(
         hostname.clone(),
         claims.clone(),
         updates_tx,
+        status::Limits {
+            routes_per_parent: max_routes_per_parent,
+            backends_per_route: max_backends_per_route,
+        },
         status_index_metrcs,
     );
+    status_index
+        .write()
+        .set_retired_controllers(retired_status_controllers);
+
+    // Routes that the indexes cannot parse are quarantined until they change,
+    // and their statuses report the error.
+    let quarantine = Quarantine::default();
+    inbound_index.write().set_quarantine(quarantine.clone());
+    outbound_index.write().set_quarantine(quarantine.clone());
+    status_index.write().set_quarantine(quarantine);
+
+    let (feature_gates, config_task) = config.watch("feature-gates", feature_gates);
+    tokio::spawn(config_task.instrument(info_span!("config", key = "feature-gates")));
+    tokio::spawn(cluster_networks_task.instrument(info_span!("config", key = "cluster-networks")));
+    let cluster_networks = update_cluster_networks(cluster_networks, inbound_index.clone());
+    tokio::spawn(identity_domain_task.instrument(info_span!("config", key = "identity-domain")));
+    tokio::spawn(
+        update_identity_domain(identity_domain, inbound_index.clone())
+            .instrument(info_span!("identity_domain")),
+    );
+    tokio::spawn(
+        remote_identity_domains_task
+            .instrument(info_span!("config", key = "remote-identity-domains")),
+    );
+    tokio::spawn(
+        update_remote_identity_domains(remote_identity_domains, inbound_index.clone())
+            .instrument(info_span!("remote_identity_domains")),
+    );
 
     // Spawn resource watches.
 
-    let pods = runtime
-        .watch_all::<k8s::Pod>(watcher::Config::default().labels("linkerd.io/control-plane-ns"));
+    let mut snapshot = index_snapshot_path.map(Snapshot::load);
+    let initial_sync = InitialSync::default();
+
+    // Namespaces must be known before other resources are watched, so that
+    // resources in excluded namespaces are never indexed.
+    let excluded_namespaces = match excluded_namespace_labels {
+        Some(LabelSelector(selector)) => {
+            let excluded = ExcludedNamespaces::new(selector);
+            watch_excluded_namespaces(&mut runtime, &watch_health, &resync, &excluded).await;
+            excluded
+        }
+        None => ExcludedNamespaces::default(),
+    };
+
+    tokio::spawn(
+        watch_health
+            .clone()
+            .fail_readiness(
+                runtime.readiness(),
+                Duration::from_millis(watch_stale_threshold_ms),
+            )
+            .instrument(info_span!("watch_health")),
+    );
+
+    let pods = watch_all::<k8s::Pod>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "pods",
+        watcher::Config::default().labels("linkerd.io/control-plane-ns"),
+    );
     tokio::spawn(
         kubert::index::namespaced(inbound_index.clone(), pods).instrument(info_span!("pods")),
     );
 
-    let external_workloads =
-        runtime.watch_all::<k8s::external_workload::ExternalWorkload>(watcher::Config::default());
+    let external_workloads = watch_all::<k8s::external_workload::ExternalWorkload>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "external_workloads",
+        watcher::Config::default(),
+    );
     tokio::spawn(
         kubert::index::namespaced(inbound_index.clone(), external_workloads)
             .instrument(info_span!("external_workloads")),
     );
 
-    let servers = runtime.watch_all::<k8s::policy::Server>(watcher::Config::default());
+    // The cluster policy is optional, so it is only watched while its CRD is
+    // installed.
+    let (cluster_policy_served, discovery) = Served::discover::<k8s::policy::ClusterPolicy>(
+        runtime.client(),
+        Duration::from_millis(api_discovery_interval_ms),
+    )
+    .await;
+    tokio::spawn(discovery.instrument(info_span!("discovery")));
+    let client = runtime.client();
+    let mk_watch = move || {
+        watcher::watcher(
+            k8s::Api::<k8s::policy::ClusterPolicy>::all(client.clone()),
+            watcher::Config::default().fields(&format!(
+                "metadata.name={}",
+                k8s::policy::ClusterPolicy::NAME
+            )),
+        )
+        .boxed()
+    };
+    let cluster_policies = instrument_watch(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "clusterpolicies",
+        move || cluster_policy_served.while_served(mk_watch.clone()).boxed(),
+    );
+    tokio::spawn(
+        kubert::index::cluster(inbound_index.clone(), cluster_policies)
+            .instrument(info_span!("clusterpolicies")),
+    );
+
+    let servers = watch_all::<k8s::policy::Server>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "servers",
+        watcher::Config::default(),
+    );
     let servers_indexes = IndexList::new(inbound_index.clone())
         .push(status_index.clone())
         .shared();
@@ -214,35 +715,77 @@ async fn main() -> Result<()> {
         kubert::index::namespaced(servers_indexes, servers).instrument(info_span!("servers")),
     );
 
-    let server_authzs =
-        runtime.watch_all::<k8s::policy::ServerAuthorization>(watcher::Config::default());
+    let server_authzs = watch_all::<k8s::policy::ServerAuthorization>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "serverauthorizations",
+        watcher::Config::default(),
+    );
     tokio::spawn(
         kubert::index::namespaced(inbound_index.clone(), server_authzs)
             .instrument(info_span!("serverauthorizations")),
     );
 
-    let authz_policies =
-        runtime.watch_all::<k8s::policy::AuthorizationPolicy>(watcher::Config::default());
+    let authz_policies = watch_all::<k8s::policy::AuthorizationPolicy>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "authorizationpolicies",
+        watcher::Config::default(),
+    );
     tokio::spawn(
         kubert::index::namespaced(inbound_index.clone(), authz_policies)
             .instrument(info_span!("authorizationpolicies")),
     );
 
-    let mtls_authns =
-        runtime.watch_all::<k8s::policy::MeshTLSAuthentication>(watcher::Config::default());
+    let mtls_authns = watch_all::<k8s::policy::MeshTLSAuthentication>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "meshtlsauthentications",
+        watcher::Config::default(),
+    );
     tokio::spawn(
         kubert::index::namespaced(inbound_index.clone(), mtls_authns)
             .instrument(info_span!("meshtlsauthentications")),
     );
 
-    let network_authns =
-        runtime.watch_all::<k8s::policy::NetworkAuthentication>(watcher::Config::default());
+    let network_authns = watch_all::<k8s::policy::NetworkAuthentication>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "networkauthentications",
+        watcher::Config::default(),
+    );
     tokio::spawn(
         kubert::index::namespaced(inbound_index.clone(), network_authns)
             .instrument(info_span!("networkauthentications")),
     );
 
-    let http_routes = runtime.watch_all::<k8s::policy::HttpRoute>(watcher::Config::default());
+    let http_routes = watch_routes::<k8s::policy::HttpRoute>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "httproutes.policy.linkerd.io",
+        &feature_gates,
+        None,
+    );
     let http_routes_indexes = IndexList::new(inbound_index.clone())
         .push(outbound_index.clone())
         .push(status_index.clone())
@@ -252,14 +795,40 @@ async fn main() -> Result<()> {
             .instrument(info_span!("httproutes.policy.linkerd.io")),
     );
 
-    let gateway_http_routes =
-        runtime.watch_all::<k8s_gateway_api::HttpRoute>(watcher::Config::default());
+    // The Gateway API CRDs are optional, so their resources are only watched
+    // while they are installed.
+    let (gateway_api_served, discovery) = Served::discover::<k8s_gateway_api::HttpRoute>(
+        runtime.client(),
+        Duration::from_millis(api_discovery_interval_ms),
+    )
+    .await;
+    tokio::spawn(discovery.instrument(info_span!("discovery")));
+    let gateway_http_routes = watch_routes::<k8s_gateway_api::HttpRoute>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "httproutes.gateway.networking.k8s.io",
+        &feature_gates,
+        Some(&gateway_api_served),
+    );
     tokio::spawn(
         kubert::index::namespaced(http_routes_indexes, gateway_http_routes)
             .instrument(info_span!("httproutes.gateway.networking.k8s.io")),
     );
 
-    let services = runtime.watch_all::<k8s::Service>(watcher::Config::default());
+    let services = watch_all::<k8s::Service>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "services",
+        watcher::Config::default(),
+    );
     let services_indexes = IndexList::new(outbound_index.clone())
         .push(status_index.clone())
         .shared();
@@ -267,6 +836,38 @@ async fn main() -> Result<()> {
         kubert::index::namespaced(services_indexes, services).instrument(info_span!("services")),
     );
 
+    let endpoint_slices = watch_all::<k8s::api::discovery::v1::EndpointSlice>(
+        &mut runtime,
+        &watch_health,
+        &initial_sync,
+        &resync,
+        &excluded_namespaces,
+        &mut snapshot,
+        "endpointslices",
+        watcher::Config::default(),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(outbound_index.clone(), endpoint_slices)
+            .instrument(info_span!("endpointslices")),
+    );
+
+    if let Some(snapshot) = snapshot {
+        tokio::spawn(
+            snapshot
+                .run(Duration::from_millis(index_snapshot_interval_ms))
+                .instrument(info_span!("snapshot")),
+        );
+    }
+
+    tokio::spawn(
+        gc_indexes(
+            inbound_index.clone(),
+            outbound_index.clone(),
+            Duration::from_millis(index_gc_interval_ms),
+        )
+        .instrument(info_span!("gc")),
+    );
+
     // Spawn the status Controller reconciliation.
     tokio::spawn(
         status::Index::run(status_index.clone(), RECONCILIATION_PERIOD)
@@ -276,10 +877,19 @@ async fn main() -> Result<()> {
     // Run the gRPC server, serving results by looking up against the index handle.
     tokio::spawn(grpc(
         grpc_addr,
+        grpc_server,
+        grpc_max_connections,
+        grpc_compression,
+        Duration::from_millis(grpc_drain_timeout_ms),
         cluster_domain,
         cluster_networks,
-        inbound_index,
+        inbound_lookup,
         outbound_index,
+        watch_limits,
+        stream_metrics,
+        capabilities,
+        grpc_hold_until_synced.then_some(initial_sync),
+        watch_health.clone(),
         runtime.shutdown_handle(),
     ));
 
@@ -290,6 +900,7 @@ async fn main() -> Result<()> {
         hostname,
         updates_rx,
         Duration::from_millis(patch_timeout_ms),
+        patch_concurrency,
         status_metrics,
     );
     tokio::spawn(
@@ -299,7 +910,13 @@ async fn main() -> Result<()> {
     );
 
     let client = runtime.client();
-    let runtime = runtime.spawn_server(|| Admission::new(client));
+    let runtime = runtime.spawn_server(|| {
+        let admission = Admission::new(client);
+        match audit_log {
+            Some(audit) => admission.with_audit_log(audit),
+            None => admission,
+        }
+    });
 
     // Block the main thread on the shutdown signal. Once it fires, wait for the background tasks to
     // complete before exiting.
@@ -310,7 +927,304 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-#[derive(Clone, Debug)]
+/// Periodically removes namespaces that no longer hold any resources from the
+/// indexes.
+async fn gc_indexes(
+    inbound: inbound::SharedIndex,
+    outbound: outbound::SharedIndex,
+    interval: Duration,
+) {
+    let mut interval = time::interval(interval);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let inbound = inbound.read().gc();
+        let outbound = outbound.write().gc();
+        if inbound + outbound > 0 {
+            tracing::debug!(inbound, outbound, "Removed empty namespaces");
+        }
+    }
+}
+
+/// Populates the indexes from manifests, as the resource watches would.
+fn index_manifests(
+    manifests: &Manifests,
+    inbound: &inbound::SharedIndex,
+    outbound: &outbound::SharedIndex,
+) {
+    tokio::spawn(
+        kubert::index::namespaced(inbound.clone(), manifests.watch::<k8s::Pod>())
+            .instrument(info_span!("pods")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            inbound.clone(),
+            manifests.watch::<k8s::external_workload::ExternalWorkload>(),
+        )
+        .instrument(info_span!("external_workloads")),
+    );
+    tokio::spawn(
+        kubert::index::cluster(
+            inbound.clone(),
+            manifests.watch::<k8s::policy::ClusterPolicy>(),
+        )
+        .instrument(info_span!("clusterpolicies")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(inbound.clone(), manifests.watch::<k8s::policy::Server>())
+            .instrument(info_span!("servers")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            inbound.clone(),
+            manifests.watch::<k8s::policy::ServerAuthorization>(),
+        )
+        .instrument(info_span!("serverauthorizations")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            inbound.clone(),
+            manifests.watch::<k8s::policy::AuthorizationPolicy>(),
+        )
+        .instrument(info_span!("authorizationpolicies")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            inbound.clone(),
+            manifests.watch::<k8s::policy::MeshTLSAuthentication>(),
+        )
+        .instrument(info_span!("meshtlsauthentications")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            inbound.clone(),
+            manifests.watch::<k8s::policy::NetworkAuthentication>(),
+        )
+        .instrument(info_span!("networkauthentications")),
+    );
+    let http_routes_indexes = IndexList::new(inbound.clone())
+        .push(outbound.clone())
+        .shared();
+    tokio::spawn(
+        kubert::index::namespaced(
+            http_routes_indexes.clone(),
+            manifests.watch::<k8s::policy::HttpRoute>(),
+        )
+        .instrument(info_span!("httproutes.policy.linkerd.io")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            http_routes_indexes,
+            manifests.watch::<k8s_gateway_api::HttpRoute>(),
+        )
+        .instrument(info_span!("httproutes.gateway.networking.k8s.io")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(outbound.clone(), manifests.watch::<k8s::Service>())
+            .instrument(info_span!("services")),
+    );
+    tokio::spawn(
+        kubert::index::namespaced(
+            outbound.clone(),
+            manifests.watch::<k8s::api::discovery::v1::EndpointSlice>(),
+        )
+        .instrument(info_span!("endpointslices")),
+    );
+}
+
+/// Watches all resources of type `T`, recording the health and initial sync of
+/// the watch and, if a snapshot is configured, priming the watch from the
+/// snapshot.
+///
+/// This mirrors [`kubert::Runtime::watch_all`]: errors are logged and retried
+/// with a backoff, the runtime is not ready until the watch yields its first
+/// event (i.e. the full set of resources), and the watch terminates when the
+/// runtime is shut down.
+#[allow(clippy::too_many_arguments)]
+fn watch_all<T>(
+    runtime: &mut kubert::Runtime<Option<kubert::server::Bound>>,
+    health: &WatchHealth,
+    sync: &InitialSync,
+    resync: &Resync,
+    excluded: &ExcludedNamespaces,
+    snapshot: &mut Option<Snapshot>,
+    resource: &'static str,
+    config: watcher::Config,
+) -> impl Stream<Item = watcher::Event<T>>
+where
+    T: kube::Resource + serde::de::DeserializeOwned + serde::Serialize + Clone,
+    T: std::fmt::Debug + Send + Sync + 'static,
+    T::DynamicType: Default + Eq + std::hash::Hash + Clone,
+{
+    let api = k8s::Api::all(runtime.client());
+    let mk_watch = move || watcher::watcher(api.clone(), config.clone());
+    instrument_watch(
+        runtime, health, sync, resync, excluded, snapshot, resource, mk_watch,
+    )
+}
+
+/// Watches all routes of type `R`, as [`watch_all`] does, subject to feature
+/// gates and, if the route type is optional, only while it is served.
+///
+/// The watch is restarted whenever the feature gates change, so that routes
+/// are indexed (or removed from the indexes) as features are enabled and
+/// disabled.
+#[allow(clippy::too_many_arguments)]
+fn watch_routes<R>(
+    runtime: &mut kubert::Runtime<Option<kubert::server::Bound>>,
+    health: &WatchHealth,
+    sync: &InitialSync,
+    resync: &Resync,
+    excluded: &ExcludedNamespaces,
+    snapshot: &mut Option<Snapshot>,
+    resource: &'static str,
+    gates: &watch::Receiver<FeatureGates>,
+    served: Option<&Served>,
+) -> impl Stream<Item = watcher::Event<R>>
+where
+    R: kube::Resource<DynamicType = ()> + GatedRoute,
+    R: serde::de::DeserializeOwned + serde::Serialize + Clone,
+    R: std::fmt::Debug + Send + Sync + 'static,
+{
+    let client = runtime.client();
+    let filter_gates = gates.clone();
+    let mk_watch = move || {
+        let watch = watcher::watcher(
+            k8s::Api::<R>::all(client.clone()),
+            watcher::Config::default(),
+        );
+        features::filter_routes(filter_gates.clone(), watch).boxed()
+    };
+    let served = served.cloned();
+    let gates = gates.clone();
+    let mk_watch = move || {
+        let served = served.clone();
+        let mk_watch = mk_watch.clone();
+        watches::gated(
+            gates.clone(),
+            |gates| gates.is_enabled(R::KIND),
+            move || match &served {
+                Some(served) => served.while_served(mk_watch.clone()).boxed(),
+                None => mk_watch(),
+            },
+        )
+        .boxed()
+    };
+    instrument_watch(
+        runtime, health, sync, resync, excluded, snapshot, resource, mk_watch,
+    )
+}
+
+/// Instruments the watch built by `mk_watch`. The watch is rebuilt whenever a
+/// resync of the resource is triggered.
+#[allow(clippy::too_many_arguments)]
+fn instrument_watch<T, S>(
+    runtime: &mut kubert::Runtime<Option<kubert::server::Bound>>,
+    health: &WatchHealth,
+    sync: &InitialSync,
+    resync: &Resync,
+    excluded: &ExcludedNamespaces,
+    snapshot: &mut Option<Snapshot>,
+    resource: &'static str,
+    mk_watch: impl FnMut() -> S + Send + 'static,
+) -> impl Stream<Item = watcher::Event<T>>
+where
+    S: Stream<Item = Result<watcher::Event<T>, watcher::Error>> + Send + 'static,
+    T: kube::Resource + serde::de::DeserializeOwned + serde::Serialize + Clone,
+    T: std::fmt::Debug + Send + Sync + 'static,
+    T::DynamicType: Default + Eq + std::hash::Hash + Clone,
+{
+    let watch = excluded.filter(resync.watch(resource, mk_watch)).boxed();
+    // The initial sync is tracked before the watch is primed from the
+    // snapshot, so that only a list from the API server completes it.
+    let watch = sync.track(resource, watch);
+    let watch = match snapshot {
+        Some(snapshot) => snapshot.watch(resource, watch).left_stream(),
+        None => watch.right_stream(),
+    };
+    let watch = health.instrument(resource, watch);
+    let watch = health.retry(resource, watch);
+    let watch = runtime.initialized_handle().release_on_ready(watch);
+    health.measure(resource, runtime.cancel_on_shutdown(watch))
+}
+
+/// Watches the metadata of all namespaces to track which are excluded,
+/// returning once the namespaces have been listed. Watches are resynced
+/// whenever a namespace is included or excluded.
+async fn watch_excluded_namespaces(
+    runtime: &mut kubert::Runtime<Option<kubert::server::Bound>>,
+    health: &WatchHealth,
+    resync: &Resync,
+    excluded: &ExcludedNamespaces,
+) {
+    let api = k8s::Api::<k8s::Namespace>::all(runtime.client());
+    let watch = watcher::metadata_watcher(api, watcher::Config::default());
+    let watch = health.retry("namespaces", health.instrument("namespaces", watch));
+    let mut watch = runtime.cancel_on_shutdown(watch).boxed();
+
+    if let Some(event) = watch.next().await {
+        excluded.apply(event);
+    }
+    let (excluded, resync) = (excluded.clone(), resync.clone());
+    tokio::spawn(
+        async move {
+            while let Some(event) = watch.next().await {
+                if excluded.apply(event) {
+                    resync.trigger(None);
+                }
+            }
+        }
+        .instrument(info_span!("namespaces")),
+    );
+}
+
+/// Applies changes to the cluster's networks to the inbound index, returning a
+/// receiver of the networks used to serve inbound policies.
+fn update_cluster_networks(
+    mut networks: watch::Receiver<IpNets>,
+    index: inbound::SharedIndex,
+) -> watch::Receiver<Arc<[IpNet]>> {
+    let (tx, rx) = watch::channel(networks.borrow_and_update().0.clone().into());
+    tokio::spawn(
+        async move {
+            while networks.changed().await.is_ok() {
+                let IpNets(nets) = networks.borrow_and_update().clone();
+                index.write().set_cluster_networks(nets.clone());
+                if tx.send(nets.into()).is_err() {
+                    return;
+                }
+            }
+        }
+        .instrument(info_span!("cluster_networks")),
+    );
+    rx
+}
+
+/// Applies changes to the identity trust domain to the inbound index.
+async fn update_identity_domain(mut domain: watch::Receiver<String>, index: inbound::SharedIndex) {
+    while domain.changed().await.is_ok() {
+        let domain = domain.borrow_and_update().clone();
+        if domain.is_empty() {
+            tracing::warn!("Ignoring empty identity trust domain");
+            continue;
+        }
+        index.write().set_identity_domain(domain);
+    }
+}
+
+/// Applies changes to the trust domains of linked clusters to the inbound
+/// index.
+async fn update_remote_identity_domains(
+    mut domains: watch::Receiver<TrustDomains>,
+    index: inbound::SharedIndex,
+) {
+    while domains.changed().await.is_ok() {
+        let TrustDomains(domains) = domains.borrow_and_update().clone();
+        index.write().set_remote_identity_domains(domains);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 struct IpNets(Vec<IpNet>);
 
 impl std::str::FromStr for IpNets {
@@ -323,36 +1237,158 @@ impl std::str::FromStr for IpNets {
     }
 }
 
+/// Trust domains by linked cluster name.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct TrustDomains(BTreeMap<String, String>);
+
+impl std::str::FromStr for TrustDomains {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((cluster, domain)) if !cluster.is_empty() && !domain.is_empty() => {
+                    Ok((cluster.to_string(), domain.to_string()))
+                }
+                _ => bail!("invalid trust domain {entry:?}: expected <cluster>=<domain>"),
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+/// An equality-based label selector, e.g. `a=b,c=d`.
+#[derive(Clone, Debug)]
+struct LabelSelector(k8s::labels::Selector);
+
+impl std::str::FromStr for LabelSelector {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let labels = s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+                _ => bail!("invalid label selector {entry:?}: expected <key>=<value>"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if labels.is_empty() {
+            bail!("label selector must not be empty");
+        }
+        Ok(Self(labels.into_iter().collect()))
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Compression(CompressionEncoding);
+
+impl std::str::FromStr for Compression {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "gzip" => Ok(Self(CompressionEncoding::Gzip)),
+            "zstd" => bail!("zstd compression is not supported by this version of tonic"),
+            s => bail!("unsupported compression encoding: {s}"),
+        }
+    }
+}
+
 #[instrument(skip_all, fields(port = %addr.port()))]
+#[allow(clippy::too_many_arguments)]
 async fn grpc(
     addr: SocketAddr,
+    mut server: Server,
+    max_connections: Option<usize>,
+    compression: Option<Compression>,
+    drain_timeout: Duration,
     cluster_domain: String,
-    cluster_networks: Vec<IpNet>,
-    inbound_index: inbound::SharedIndex,
+    cluster_networks: watch::Receiver<Arc<[IpNet]>>,
+    inbound_lookup: inbound::Lookup,
     outbound_index: outbound::SharedIndex,
+    watch_limits: grpc::limits::WatchLimits,
+    stream_metrics: grpc::metrics::StreamMetrics,
+    capabilities: grpc::capabilities::Capabilities,
+    initial_sync: Option<InitialSync>,
+    watch_health: WatchHealth,
     drain: drain::Watch,
 ) -> Result<()> {
-    let inbound_discover = InboundDiscover::new(inbound_index);
-    let inbound_svc =
-        grpc::inbound::InboundPolicyServer::new(inbound_discover, cluster_networks, drain.clone())
-            .svc();
+    // Response streams are closed independently of the server so that they
+    // may outlive the shutdown signal by the drain timeout.
+    let (streams_tx, streams_rx) = drain::channel();
+
+    let mut inbound_discover = InboundDiscover::new(inbound_lookup);
+    let mut outbound_discover = OutboundDiscover::new(outbound_index);
+    if let Some(sync) = initial_sync {
+        inbound_discover = inbound_discover.with_initial_sync(sync.clone());
+        outbound_discover = outbound_discover.with_initial_sync(sync);
+    }
+    let networks = cluster_networks.borrow().to_vec();
+    let staleness = grpc::staleness::Staleness::new(move || watch_health.staleness());
+    let mut inbound_svc = grpc::inbound::InboundPolicyServer::new(
+        inbound_discover,
+        networks,
+        watch_limits.clone(),
+        stream_metrics.clone(),
+        capabilities.clone(),
+        streams_rx.clone(),
+    )
+    .with_cluster_networks(cluster_networks)
+    .with_staleness(staleness.clone())
+    .svc();
+
+    let mut outbound_svc = grpc::outbound::OutboundPolicyServer::new(
+        outbound_discover,
+        cluster_domain,
+        watch_limits,
+        stream_metrics,
+        capabilities,
+        streams_rx,
+    )
+    .with_staleness(staleness)
+    .svc();
+
+    // Compression is only applied when the client advertises support for the
+    // encoding.
+    if let Some(Compression(encoding)) = compression {
+        inbound_svc = inbound_svc
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+        outbound_svc = outbound_svc
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+    }
 
-    let outbound_discover = OutboundDiscover::new(outbound_index);
-    let outbound_svc =
-        grpc::outbound::OutboundPolicyServer::new(outbound_discover, cluster_domain, drain.clone())
-            .svc();
+    let incoming = TcpIncoming::new(addr, true, None).map_err(|e| anyhow::anyhow!(e))?;
+    let incoming = grpc::limits::limit_connections(incoming, max_connections.unwrap_or(usize::MAX));
 
     let (close_tx, close_rx) = tokio::sync::oneshot::channel();
     tokio::pin! {
-        let srv = Server::builder().add_service(inbound_svc).add_service(outbound_svc).serve_with_shutdown(addr, close_rx.map(|_| {}));
+        let srv = server
+            .add_service(inbound_svc)
+            .add_service(outbound_svc)
+            .serve_with_incoming_shutdown(incoming, close_rx.map(|_| {}));
     }
 
     info!(%addr, "policy gRPC server listening");
     tokio::select! {
         res = (&mut srv) => res?,
         handle = drain.signaled() => {
+            // Stop accepting connections and send a GOAWAY to connected
+            // clients so that no new watches are started. Existing streams
+            // continue to receive updates until the drain timeout elapses.
             let _ = close_tx.send(());
-            handle.release_after(srv).await?
+            handle.release_after(async move {
+                tokio::select! {
+                    res = (&mut srv) => res,
+                    _ = time::sleep(drain_timeout) => {
+                        info!("Closing policy streams");
+                        let (res, ()) = tokio::join!(srv, streams_tx.drain());
+                        res
+                    }
+                }
+            }).await?
         }
     }
     Ok(())
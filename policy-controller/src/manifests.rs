@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use futures::prelude::*;
+use kube::runtime::watcher;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{sync::watch, time};
+use tokio_stream::wrappers::WatchStream;
+
+/// Serves resources from a directory of YAML (or JSON) manifests in place of
+/// the Kubernetes API, so that policies may be served without a cluster, e.g.
+/// for local proxy development or to reproduce the state captured by a support
+/// bundle.
+///
+/// Each file may hold multiple documents, and `List` documents are expanded
+/// into their items. The directory is polled for changes, and each watch is
+/// restarted with the full set of its resources whenever the directory's
+/// contents change.
+///
+/// There is no status controller to accept routes, so routes without a status
+/// are treated as having been accepted by each of their parents.
+#[derive(Clone, Debug)]
+pub struct Manifests(watch::Receiver<Arc<[Value]>>);
+
+// === impl Manifests ===
+
+impl Manifests {
+    /// Reads the manifests in `dir`, returning them along with a task that
+    /// polls the directory for changes at the given interval.
+    ///
+    /// The directory must be readable when it is first loaded. Later failures
+    /// are logged, and the last resources that were read continue to be
+    /// served.
+    pub fn load(
+        dir: PathBuf,
+        interval: time::Duration,
+    ) -> Result<(Self, impl Future<Output = ()>)> {
+        let resources = read_dir(&dir)?;
+        tracing::info!(dir = %dir.display(), resources = resources.len(), "Loaded manifests");
+        let (tx, rx) = watch::channel(resources);
+
+        let task = async move {
+            let mut interval = time::interval(interval);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match read_dir(&dir) {
+                    Ok(resources) => {
+                        if tx.send_if_modified(|r| {
+                            std::mem::replace(r, resources.clone()) != resources
+                        }) {
+                            tracing::info!(resources = resources.len(), "Manifests changed");
+                        }
+                    }
+                    Err(error) => {
+                        tracing::warn!(error = %format_args!("{error:#}"), "Failed to read manifests");
+                    }
+                }
+            }
+        };
+        Ok((Self(rx), task))
+    }
+
+    /// Watches the resources of type `T`.
+    ///
+    /// Resources are matched by their API group and kind, so that manifests
+    /// written against another version of a resource's API are still served.
+    /// Resources that cannot be read as a `T` are logged and skipped.
+    pub fn watch<T>(&self) -> impl Stream<Item = watcher::Event<T>>
+    where
+        T: kube::Resource<DynamicType = ()> + DeserializeOwned,
+    {
+        WatchStream::new(self.0.clone()).map(|resources| {
+            let resources = resources
+                .iter()
+                .filter(|r| is_kind::<T>(r))
+                .filter_map(|r| match serde_json::from_value::<T>(r.clone()) {
+                    Ok(resource) => Some(resource),
+                    Err(error) => {
+                        tracing::warn!(%error, kind = %T::kind(&()), name = ?r["metadata"]["name"], "Skipping invalid resource");
+                        None
+                    }
+                })
+                .collect();
+            watcher::Event::Restarted(resources)
+        })
+    }
+}
+
+fn is_kind<T: kube::Resource<DynamicType = ()>>(resource: &Value) -> bool {
+    let api_version = resource["apiVersion"].as_str().unwrap_or_default();
+    let group = api_version
+        .rsplit_once('/')
+        .map_or("", |(group, _version)| group);
+    resource["kind"] == *T::kind(&()) && group == T::group(&())
+}
+
+/// Reads every manifest in `dir`, in order of file name.
+fn read_dir(dir: &Path) -> Result<Arc<[Value]>> {
+    let mut paths = std::fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| format!("reading {}", dir.display()))?;
+    paths.retain(|p| {
+        p.extension()
+            .map_or(false, |ext| ext == "yaml" || ext == "yml" || ext == "json")
+    });
+    paths.sort();
+
+    let mut resources = Vec::new();
+    for path in paths {
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        resources.extend(parse(&contents).with_context(|| format!("parsing {}", path.display()))?);
+    }
+    Ok(resources.into())
+}
+
+/// Parses the documents in a manifest, expanding lists into their items.
+fn parse(contents: &str) -> Result<Vec<Value>> {
+    let mut resources = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        let value = Value::deserialize(document)?;
+        push(&mut resources, value);
+    }
+    Ok(resources)
+}
+
+fn push(resources: &mut Vec<Value>, mut value: Value) {
+    if value.is_null() {
+        return;
+    }
+    if value["kind"]
+        .as_str()
+        .map_or(false, |kind| kind.ends_with("List"))
+    {
+        if let Value::Array(items) = value["items"].take() {
+            for item in items {
+                push(resources, item);
+            }
+        }
+        return;
+    }
+    if value["kind"] == "HTTPRoute" && value["status"].is_null() {
+        accept_route(&mut value);
+    }
+    resources.push(value);
+}
+
+/// Sets the status of a route to that which the status controller would write
+/// if it accepted the route for each of its parents.
+fn accept_route(route: &mut Value) {
+    let parents = route["spec"]["parentRefs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|parent_ref| {
+            json!({
+                "parentRef": parent_ref,
+                "controllerName": "linkerd.io/policy-controller",
+                "conditions": [{
+                    "type": "Accepted",
+                    "status": "True",
+                    "reason": "Accepted",
+                    "message": "",
+                    "lastTransitionTime": "1970-01-01T00:00:00Z",
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+    route["status"] = json!({ "parents": parents });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::k8s;
+
+    const MANIFESTS: &str = r#"
+apiVersion: policy.linkerd.io/v1beta2
+kind: Server
+metadata:
+  namespace: ns-0
+  name: srv-0
+spec:
+  podSelector:
+    matchLabels:
+      app: app-0
+  port: 8080
+---
+apiVersion: v1
+kind: List
+items:
+- apiVersion: v1
+  kind: Service
+  metadata:
+    namespace: ns-0
+    name: svc-0
+  spec:
+    ports:
+    - port: 8080
+- apiVersion: v1
+  kind: Service
+  metadata:
+    namespace: ns-0
+    name: svc-1
+---
+apiVersion: gateway.networking.k8s.io/v1
+kind: HTTPRoute
+metadata:
+  namespace: ns-0
+  name: route-0
+spec:
+  parentRefs:
+  - kind: Service
+    group: core
+    name: svc-0
+    port: 8080
+"#;
+
+    fn names<T: kube::Resource>(event: watcher::Event<T>) -> Vec<String> {
+        match event {
+            watcher::Event::Restarted(resources) => resources
+                .into_iter()
+                .map(|r| r.meta().name.clone().unwrap())
+                .collect(),
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    #[tokio::test]
+    async fn watches_manifests() {
+        let dir = std::env::temp_dir().join(format!("manifests-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ns-0.yaml"), MANIFESTS).unwrap();
+        std::fs::write(dir.join("README.md"), "not a manifest").unwrap();
+
+        let (manifests, _task) = Manifests::load(dir.clone(), time::Duration::from_secs(1))
+            .expect("manifests must load");
+
+        let mut servers = Box::pin(manifests.watch::<k8s::policy::Server>());
+        assert_eq!(names(servers.next().await.unwrap()), ["srv-0"]);
+        let mut services = Box::pin(manifests.watch::<k8s::Service>());
+        assert_eq!(names(services.next().await.unwrap()), ["svc-0", "svc-1"]);
+
+        // Routes are read in the version of the API that the controller
+        // watches, and are accepted by their parents.
+        let mut routes = Box::pin(manifests.watch::<k8s_gateway_api::HttpRoute>());
+        match routes.next().await.unwrap() {
+            watcher::Event::Restarted(routes) => {
+                let parents = &routes[0].status.as_ref().unwrap().inner.parents;
+                assert_eq!(parents.len(), 1);
+                assert_eq!(parents[0].parent_ref.name, "svc-0");
+                assert_eq!(parents[0].conditions[0].type_, "Accepted");
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        let dir = std::env::temp_dir().join(format!("manifests-invalid-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("invalid.yaml"), "kind: [").unwrap();
+
+        assert!(Manifests::load(dir.clone(), time::Duration::from_secs(1)).is_err());
+        assert!(Manifests::load(dir.join("missing"), time::Duration::from_secs(1)).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,73 @@
+use prometheus_client::registry::Registry;
+
+/// Registers metrics describing the memory held by the process's allocator.
+///
+/// Allocator statistics are only available when jemalloc is the global
+/// allocator.
+pub fn register(reg: &mut Registry) {
+    #[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu"))]
+    reg.register_collector(Box::new(jemalloc::Stats));
+
+    #[cfg(not(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu")))]
+    let _ = reg;
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu"))]
+mod jemalloc {
+    use jemalloc_ctl::{epoch, stats};
+    use prometheus_client::{
+        collector::Collector,
+        encoding::{DescriptorEncoder, EncodeMetric},
+        metrics::{gauge::ConstGauge, MetricType},
+        registry::Unit,
+    };
+
+    #[derive(Debug)]
+    pub(super) struct Stats;
+
+    impl Collector for Stats {
+        fn encode(&self, mut encoder: DescriptorEncoder<'_>) -> Result<(), std::fmt::Error> {
+            // Statistics are cached by jemalloc until the epoch is advanced.
+            if let Err(error) = epoch::advance() {
+                tracing::debug!(%error, "Failed to refresh allocator statistics");
+                return Ok(());
+            }
+
+            let stats = [
+                (
+                    "allocated",
+                    "The number of bytes allocated by the application",
+                    stats::allocated::read(),
+                ),
+                (
+                    "active",
+                    "The number of bytes in active pages allocated by the application",
+                    stats::active::read(),
+                ),
+                (
+                    "resident",
+                    "The number of bytes in physically resident pages mapped by the allocator",
+                    stats::resident::read(),
+                ),
+                (
+                    "mapped",
+                    "The number of bytes in active extents mapped by the allocator",
+                    stats::mapped::read(),
+                ),
+                (
+                    "retained",
+                    "The number of bytes in virtual memory mappings retained by the allocator",
+                    stats::retained::read(),
+                ),
+            ];
+            for (name, help, value) in stats {
+                let Ok(value) = value else { continue };
+                let gauge = ConstGauge::new(value as i64);
+                let metric_encoder =
+                    encoder.encode_descriptor(name, help, Some(&Unit::Bytes), MetricType::Gauge)?;
+                gauge.encode(metric_encoder)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::{Context, Result};
+use futures::prelude::*;
+use kube::runtime::{reflector, watcher};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::{fmt, hash::Hash, path::PathBuf};
+use tokio::time;
+
+/// Persists the resources observed by the controller's watches so that, when
+/// the controller restarts, its indexes may be populated from the last known
+/// state before the watches complete their initial list.
+///
+/// Each watch is primed with the resources from the snapshot, which are then
+/// replaced by the watch's initial list. Resources that were deleted while the
+/// controller was not running are removed from the indexes at that point.
+pub struct Snapshot {
+    path: PathBuf,
+    loaded: Map<String, Value>,
+    stores: Vec<(&'static str, Store)>,
+}
+
+/// Serializes the current state of a watched resource.
+type Store = Box<dyn Fn() -> Result<Value> + Send + Sync>;
+
+// === impl Snapshot ===
+
+impl Snapshot {
+    /// Loads the snapshot at `path`, if one exists.
+    ///
+    /// A missing or invalid snapshot is not an error: the indexes are populated
+    /// by the watches as usual.
+    pub fn load(path: PathBuf) -> Self {
+        let loaded = match std::fs::read(&path) {
+            Ok(bytes) => match serde_json::from_slice(&bytes) {
+                Ok(loaded) => {
+                    tracing::info!(path = %path.display(), "Loaded index snapshot");
+                    loaded
+                }
+                Err(error) => {
+                    tracing::warn!(%error, path = %path.display(), "Ignoring invalid index snapshot");
+                    Map::default()
+                }
+            },
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Map::default(),
+            Err(error) => {
+                tracing::warn!(%error, path = %path.display(), "Failed to read index snapshot");
+                Map::default()
+            }
+        };
+        Self {
+            path,
+            loaded,
+            stores: Vec::new(),
+        }
+    }
+
+    /// Primes a watch with the snapshot's resources and records the resources
+    /// that the watch observes in subsequent snapshots.
+    pub fn watch<T, S>(
+        &mut self,
+        resource: &'static str,
+        watch: S,
+    ) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+    where
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+        T: kube::Resource + Clone + DeserializeOwned + Serialize + fmt::Debug,
+        T: Send + Sync + 'static,
+        T::DynamicType: Default + Eq + Hash + Clone,
+    {
+        let primed =
+            self.loaded.remove(resource).and_then(|resources| {
+                match serde_json::from_value::<Vec<T>>(resources) {
+                    Ok(resources) => {
+                        tracing::debug!(resource, count = resources.len(), "Priming watch");
+                        Some(Ok(watcher::Event::Restarted(resources)))
+                    }
+                    Err(error) => {
+                        tracing::warn!(%error, resource, "Ignoring invalid index snapshot");
+                        None
+                    }
+                }
+            });
+
+        let (store, writer) = reflector::store::<T>();
+        self.stores.push((
+            resource,
+            Box::new(move || {
+                let mut resources = Vec::new();
+                for resource in store.state() {
+                    let mut resource = serde_json::to_value(&*resource)?;
+                    // Managed fields are not used by the indexes and account
+                    // for much of a resource's size.
+                    if let Some(meta) = resource.get_mut("metadata").and_then(Value::as_object_mut)
+                    {
+                        meta.remove("managedFields");
+                    }
+                    resources.push(resource);
+                }
+                Ok(Value::Array(resources))
+            }),
+        ));
+
+        reflector::reflector(writer, stream::iter(primed).chain(watch))
+    }
+
+    /// Writes a snapshot of the watched resources at the given interval.
+    pub async fn run(self, interval: time::Duration) {
+        let mut interval = time::interval(interval);
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        // The first tick completes immediately, before the watches have
+        // observed any resources.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match self.write() {
+                Ok(()) => tracing::debug!(path = %self.path.display(), "Wrote index snapshot"),
+                Err(error) => tracing::warn!(%error, "Failed to write index snapshot"),
+            }
+        }
+    }
+
+    /// Writes the snapshot, replacing any prior snapshot atomically.
+    fn write(&self) -> Result<()> {
+        let mut snapshot = Map::default();
+        for (resource, store) in self.stores.iter() {
+            snapshot.insert(resource.to_string(), store()?);
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&snapshot)?)
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path).with_context(|| format!("renaming {}", tmp.display()))?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("path", &self.path)
+            .field(
+                "resources",
+                &self.stores.iter().map(|(r, _)| r).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::k8s;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    fn mk_server(name: &str) -> k8s::policy::Server {
+        k8s::policy::Server {
+            metadata: k8s::ObjectMeta {
+                namespace: Some("ns-0".to_string()),
+                name: Some(name.to_string()),
+                managed_fields: Some(vec![Default::default()]),
+                ..Default::default()
+            },
+            spec: k8s::policy::ServerSpec {
+                selector: k8s::policy::server::Selector::Pod(Default::default()),
+                port: k8s::policy::server::Port::Number(8080.try_into().unwrap()),
+                proxy_protocol: None,
+                detect_timeout: None,
+            },
+        }
+    }
+
+    fn names(event: watcher::Event<k8s::policy::Server>) -> Vec<String> {
+        match event {
+            watcher::Event::Restarted(servers) => servers
+                .into_iter()
+                .map(|s| s.metadata.name.unwrap())
+                .collect(),
+            event => panic!("unexpected event: {event:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn primes_watches_from_snapshot() {
+        let path = std::env::temp_dir().join(format!("index-snapshot-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut snapshot = Snapshot::load(path.clone());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watch = Box::pin(snapshot.watch("servers", UnboundedReceiverStream::new(rx)));
+        tx.send(Ok(watcher::Event::Restarted(vec![mk_server("srv-0")])))
+            .unwrap();
+        let event = watch.next().await.unwrap().unwrap();
+        assert_eq!(names(event), ["srv-0"]);
+        snapshot.write().expect("snapshot must be written");
+
+        let written = serde_json::from_slice::<Value>(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(written["servers"][0]["metadata"].get("managedFields"), None);
+
+        // A restarted controller observes the prior state before its watches
+        // complete their initial list.
+        let mut snapshot = Snapshot::load(path.clone());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watch = Box::pin(snapshot.watch("servers", UnboundedReceiverStream::new(rx)));
+        let event = watch.next().await.unwrap().unwrap();
+        assert_eq!(names(event), ["srv-0"]);
+
+        tx.send(Ok(watcher::Event::Restarted(vec![mk_server("srv-1")])))
+            .unwrap();
+        let event = watch.next().await.unwrap().unwrap();
+        assert_eq!(names(event), ["srv-1"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn primed_watches_are_not_synced() {
+        let path =
+            std::env::temp_dir().join(format!("index-snapshot-sync-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            serde_json::to_vec(&serde_json::json!({ "servers": [mk_server("srv-0")] })).unwrap(),
+        )
+        .unwrap();
+
+        let sync = crate::watches::InitialSync::default();
+        let mut snapshot = Snapshot::load(path.clone());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watch = sync.track("servers", UnboundedReceiverStream::new(rx));
+        let mut watch = Box::pin(snapshot.watch("servers", watch));
+
+        // Resources replayed from the snapshot must not complete the initial
+        // sync, so that lookups are held until the API server lists them.
+        let event = watch.next().await.unwrap().unwrap();
+        assert_eq!(names(event), ["srv-0"]);
+        assert!(!sync.is_synced());
+
+        tx.send(Ok(watcher::Event::Restarted(vec![mk_server("srv-1")])))
+            .unwrap();
+        let event = watch.next().await.unwrap().unwrap();
+        assert_eq!(names(event), ["srv-1"]);
+        assert!(sync.is_synced());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+//! Configures the subscriber that records the controller's logs and, if
+//! configured, exports its spans to an OpenTelemetry collector over OTLP.
+//!
+//! The indexes record spans as resources are applied, as namespaces are
+//! reindexed, and as the status controller reconciles and patches routes; the
+//! gRPC servers record a span for each lookup and for each update published on
+//! a watch. Exporting these spans makes it possible to measure how long a
+//! resource change takes to reach proxies, and which stage is slow.
+//!
+//! `kubert::Runtime::build` always installs its own global subscriber (even
+//! when no log settings are configured), so the subscriber configured here
+//! cannot be the global default. It is instead set as the default for every
+//! thread of the controller's runtime, and kubert's global subscriber, built
+//! with the same filter and format, only records events from other threads.
+//! The subscriber configured here logs exactly as kubert's does, except that
+//! its filter may be changed at runtime via a [`LogLevel`] handle.
+
+use anyhow::{anyhow, Result};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    trace::{self as sdktrace, Sampler},
+    Resource,
+};
+use std::cell::RefCell;
+use tracing::{dispatcher::DefaultGuard, Dispatch};
+use tracing_subscriber::{fmt, prelude::*, reload, Registry};
+
+const SERVICE_NAME: &str = "linkerd-policy-controller";
+
+/// Configures the export of spans to an OpenTelemetry collector.
+#[derive(Clone, Debug)]
+pub struct Exporter {
+    /// The collector's OTLP/gRPC endpoint.
+    pub endpoint: String,
+
+    /// The ratio of traces that are sampled, unless their parent has already
+    /// been sampled.
+    pub sample_ratio: f64,
+}
+
+thread_local! {
+    /// Holds the subscriber's default on each runtime thread until the thread
+    /// stops.
+    static THREAD_DEFAULT: RefCell<Option<DefaultGuard>> = RefCell::new(None);
+}
+
+/// A handle to the filter of the controller's subscriber.
+#[derive(Clone)]
+pub struct LogLevel(reload::Handle<kubert::LogFilter, Registry>);
+
+/// Builds a multi-threaded runtime whose threads all use the given subscriber.
+///
+/// The returned guard sets the subscriber as the default on the calling thread
+/// (on which the runtime blocks) and must be held until the runtime completes.
+pub fn runtime(dispatch: Dispatch) -> Result<(tokio::runtime::Runtime, DefaultGuard)> {
+    let d = dispatch.clone();
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .on_thread_start(move || {
+            let guard = tracing::dispatcher::set_default(&d);
+            THREAD_DEFAULT.with(|default| *default.borrow_mut() = Some(guard));
+        })
+        .on_thread_stop(|| {
+            THREAD_DEFAULT.with(|default| default.borrow_mut().take());
+        })
+        .build()?;
+    let guard = tracing::dispatcher::set_default(&dispatch);
+    Ok((rt, guard))
+}
+
+/// Returns a subscriber that logs with the given filter and format and, if an
+/// exporter is configured, exports spans to an OpenTelemetry collector.
+pub fn dispatch(
+    filter: kubert::LogFilter,
+    format: kubert::LogFormat,
+    exporter: Option<Exporter>,
+) -> Result<(Dispatch, LogLevel)> {
+    let tracer = exporter
+        .map(
+            |Exporter {
+                 endpoint,
+                 sample_ratio,
+             }| spawn_exporter(endpoint, sample_ratio),
+        )
+        .transpose()?;
+    let (filter, handle) = reload::Layer::new(filter);
+    let registry = tracing_subscriber::registry().with(filter);
+    let dispatch = match format {
+        kubert::LogFormat::Plain => registry
+            .with(fmt::layer())
+            .with(tracer.map(|t| tracing_opentelemetry::layer().with_tracer(t)))
+            .into(),
+        kubert::LogFormat::Json => {
+            // Mirrors kubert's JSON logs.
+            let event_fmt = fmt::format()
+                .json()
+                .with_span_list(true)
+                .with_current_span(false);
+            let fmt = fmt::layer()
+                .event_format(event_fmt)
+                .fmt_fields(fmt::format::JsonFields::default());
+            registry
+                .with(fmt)
+                .with(tracer.map(|t| tracing_opentelemetry::layer().with_tracer(t)))
+                .into()
+        }
+    };
+    Ok((dispatch, LogLevel(handle)))
+}
+
+/// Flushes any spans that have not yet been exported.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+// === impl LogLevel ===
+
+impl LogLevel {
+    /// Returns the current filter directives.
+    pub fn get(&self) -> Result<String> {
+        Ok(self.0.with_current(|filter| filter.to_string())?)
+    }
+
+    /// Replaces the filter.
+    ///
+    /// This only applies to the runtime's threads: anything logged on other
+    /// threads is recorded by kubert's global subscriber, which continues to use
+    /// the filter with which the process started.
+    pub fn set(&self, filter: kubert::LogFilter) -> Result<()> {
+        tracing::info!(%filter, "Setting log level");
+        self.0.reload(filter)?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LogLevel").finish()
+    }
+}
+
+/// Spawns the exporter onto a dedicated thread, so that exports are not
+/// themselves traced and are not delayed by a busy controller runtime.
+fn spawn_exporter(endpoint: String, sample_ratio: f64) -> Result<sdktrace::Tracer> {
+    let (tx, rx) = std::sync::mpsc::sync_channel(1);
+    std::thread::Builder::new()
+        .name("otlp".to_string())
+        .spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(error) => {
+                    let _ = tx.send(Err(anyhow!(error)));
+                    return;
+                }
+            };
+            rt.block_on(async move {
+                let tracer = opentelemetry_otlp::new_pipeline()
+                    .tracing()
+                    .with_exporter(
+                        opentelemetry_otlp::new_exporter()
+                            .tonic()
+                            .with_endpoint(endpoint),
+                    )
+                    .with_trace_config(
+                        sdktrace::config()
+                            .with_sampler(Sampler::ParentBased(Box::new(
+                                Sampler::TraceIdRatioBased(sample_ratio),
+                            )))
+                            .with_resource(Resource::new([KeyValue::new(
+                                "service.name",
+                                SERVICE_NAME,
+                            )])),
+                    )
+                    .install_batch(opentelemetry_sdk::runtime::Tokio);
+                let installed = tracer.is_ok();
+                let _ = tx.send(tracer.map_err(Into::into));
+                if installed {
+                    // The batch processor runs on this runtime until the
+                    // process exits.
+                    futures::future::pending::<()>().await;
+                }
+            });
+        })?;
+    rx.recv()?
+}
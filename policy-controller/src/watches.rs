@@ -0,0 +1,1133 @@
+use crate::k8s::labels::{Labels, Selector};
+use futures::prelude::*;
+use kube::runtime::watcher;
+use parking_lot::{Mutex, RwLock};
+use prometheus_client::{
+    collector::Collector,
+    encoding::{DescriptorEncoder, EncodeMetric},
+    metrics::{counter::ConstCounter, gauge::ConstGauge, MetricType},
+    registry::{Registry, Unit},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+};
+use tokio::{sync::watch, time};
+
+/// Tracks whether the controller's resource watches are up-to-date with the
+/// Kubernetes API.
+///
+/// When a watch fails, the indexes continue to serve the last state that was
+/// observed. The watch is considered stale until it yields another event, and
+/// the age of the stale state is exposed as a metric.
+#[derive(Clone, Debug, Default)]
+pub struct WatchHealth {
+    watches: Arc<Mutex<BTreeMap<&'static str, Watch>>>,
+    backoff: Backoff,
+}
+
+/// Configures the delay before a failed watch is restarted.
+///
+/// Delays grow exponentially with each consecutive failure. Once a watch has
+/// failed more times than its error budget allows, either in a row or within
+/// the error window, its circuit opens: it is only retried at the maximum delay
+/// until it recovers, so that a struggling API server is not hammered with
+/// requests. Counting failures within the window detects watches that fail
+/// shortly after each successful restart, whose consecutive failures are
+/// reset by every restart.
+#[derive(Copy, Clone, Debug)]
+pub struct Backoff {
+    min: time::Duration,
+    max: time::Duration,
+    error_budget: u32,
+    error_window: time::Duration,
+}
+
+#[derive(Debug, Default)]
+struct Watch {
+    stale_since: Option<time::Instant>,
+    failures: u32,
+
+    /// The times of the failures within the backoff's error window.
+    recent_failures: VecDeque<time::Instant>,
+
+    /// The time at which the watch last yielded an event. Note that the API
+    /// server does not send events for resources that are not changing.
+    last_event: Option<time::Instant>,
+    errors: u64,
+    restarts: u64,
+    relists: u64,
+
+    /// The time at which the event currently being applied to the indexes was
+    /// yielded by the watch, if any.
+    applying_since: Option<time::Instant>,
+    applied: u64,
+    apply_time: time::Duration,
+}
+
+/// Tracks whether all resource watches have completed their initial sync, so
+/// that lookups may be held until the indexes are fully populated.
+#[derive(Clone, Debug)]
+pub struct InitialSync {
+    pending: Arc<Mutex<BTreeSet<&'static str>>>,
+    synced: Arc<watch::Sender<bool>>,
+}
+
+/// Tracks whether the API server serves an optional resource type (e.g. one
+/// defined by a CRD that may not be installed), so that watches on the
+/// resource may be started and stopped as the type is added and removed.
+#[derive(Clone, Debug)]
+pub struct Served(watch::Receiver<bool>);
+
+/// Allows operators to force resource watches to be rebuilt, so that their
+/// resources are listed again, e.g. when a watch is suspected to have missed
+/// events. Indexes are updated from the relisted resources without dropping
+/// the streams that they serve.
+#[derive(Clone, Debug, Default)]
+pub struct Resync(Arc<Mutex<BTreeMap<&'static str, watch::Sender<()>>>>);
+
+/// Tracks the namespaces selected by a label selector, so that resources in
+/// these namespaces (e.g. ephemeral CI namespaces) may be excluded from the
+/// indexes and, thereby, from the status controller.
+///
+/// Namespaces are indexed by whether they are excluded. When a namespace that
+/// was already known is included or excluded (i.e. its labels change), the
+/// watches must be resynced to add or remove its resources.
+#[derive(Clone, Debug, Default)]
+pub struct ExcludedNamespaces {
+    selector: Option<Arc<Selector>>,
+    namespaces: Arc<RwLock<HashMap<String, bool>>>,
+}
+
+#[derive(Debug)]
+struct Instrumented(WatchHealth);
+
+// === impl WatchHealth ===
+
+impl WatchHealth {
+    pub fn new(backoff: Backoff) -> Self {
+        Self {
+            watches: Default::default(),
+            backoff,
+        }
+    }
+
+    pub fn register(&self, reg: &mut Registry) {
+        reg.register_collector(Box::new(Instrumented(self.clone())));
+    }
+
+    /// Records the outcome of each update from a resource watch.
+    pub fn instrument<T, S>(
+        &self,
+        resource: &'static str,
+        watch: S,
+    ) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+    where
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+    {
+        self.watches.lock().insert(resource, Watch::default());
+        let health = self.clone();
+        watch.inspect(move |res| {
+            let relist = matches!(res, Ok(watcher::Event::Restarted(_)));
+            health.update(resource, res.is_ok(), relist)
+        })
+    }
+
+    /// Logs errors from an instrumented watch, delaying the watch's restart
+    /// according to its recent failures.
+    pub fn retry<T, S>(
+        &self,
+        resource: &'static str,
+        watch: S,
+    ) -> impl Stream<Item = watcher::Event<T>>
+    where
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+    {
+        let health = self.clone();
+        stream::unfold(Box::pin(watch), move |mut watch| {
+            let health = health.clone();
+            async move {
+                loop {
+                    match watch.next().await? {
+                        Ok(event) => return Some((event, watch)),
+                        Err(error) => {
+                            let (failures, delay) = health.retry_delay(resource);
+                            tracing::info!(%error, resource, failures, ?delay, "Watch failed");
+                            time::sleep(delay).await;
+                            health.restarted(resource);
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Records the time taken to apply each event from a watch to the indexes.
+    ///
+    /// Events are applied before the next event is read from the watch, so an
+    /// event is considered applied once the next one is requested. While the
+    /// indexes are slow to apply events (e.g. because their locks are
+    /// contended), the watch falls behind the API server.
+    pub fn measure<T, S>(&self, resource: &'static str, watch: S) -> impl Stream<Item = T>
+    where
+        S: Stream<Item = T>,
+    {
+        let health = self.clone();
+        stream::unfold(Box::pin(watch), move |mut watch| {
+            let health = health.clone();
+            async move {
+                health.applied(resource);
+                let event = watch.next().await?;
+                health.applying(resource);
+                Some((event, watch))
+            }
+        })
+    }
+
+    /// Returns the time since the longest-failing watch became stale, if any
+    /// watch is stale.
+    pub fn staleness(&self) -> Option<time::Duration> {
+        self.watches
+            .lock()
+            .values()
+            .filter_map(|w| w.stale_since)
+            .min()
+            .map(|since| since.elapsed())
+    }
+
+    /// Marks the process as unready while any watch has been stale for longer
+    /// than `threshold`.
+    ///
+    /// Readiness is only restored if it was cleared by this task, so that it
+    /// does not interfere with readiness changes made by the runtime (e.g. at
+    /// shutdown).
+    pub async fn fail_readiness(
+        self,
+        readiness: kubert::admin::Readiness,
+        threshold: time::Duration,
+    ) {
+        let mut interval = time::interval(time::Duration::from_secs(1));
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        let mut cleared = false;
+        loop {
+            interval.tick().await;
+            let stale = self.staleness().map_or(false, |age| age >= threshold);
+            if stale && !cleared && readiness.get() {
+                tracing::warn!(?threshold, "Resource watches are stale; marking unready");
+                readiness.set(false);
+                cleared = true;
+            } else if !stale && cleared {
+                tracing::info!("Resource watches recovered; marking ready");
+                readiness.set(true);
+                cleared = false;
+            }
+        }
+    }
+
+    fn update(&self, resource: &'static str, ok: bool, relist: bool) {
+        let mut watches = self.watches.lock();
+        let watch = watches.entry(resource).or_default();
+        if ok {
+            watch.last_event = Some(time::Instant::now());
+        } else {
+            watch.errors += 1;
+        }
+        if relist {
+            watch.relists += 1;
+        }
+
+        match (ok, watch.stale_since) {
+            (true, Some(since)) => {
+                tracing::info!(resource, stale = ?since.elapsed(), "Watch recovered");
+                watch.stale_since = None;
+            }
+            (false, None) => {
+                tracing::warn!(resource, "Watch failed; serving last known state");
+                watch.stale_since = Some(time::Instant::now());
+            }
+            _ => {}
+        }
+
+        if ok {
+            watch.failures = 0;
+        } else {
+            let was_open = self.backoff.is_open(watch);
+            let now = time::Instant::now();
+            watch.failures = watch.failures.saturating_add(1);
+            watch.recent_failures.push_back(now);
+            while watch.recent_failures.front().map_or(false, |t| {
+                now.duration_since(*t) > self.backoff.error_window
+            }) {
+                watch.recent_failures.pop_front();
+            }
+            if !was_open && self.backoff.is_open(watch) {
+                tracing::error!(
+                    resource,
+                    failures = watch.failures,
+                    recent_failures = watch.recent_failures.len(),
+                    retry = ?self.backoff.max,
+                    "Watch exhausted its error budget; retrying at the maximum delay"
+                );
+            }
+        }
+    }
+
+    fn restarted(&self, resource: &'static str) {
+        if let Some(watch) = self.watches.lock().get_mut(resource) {
+            watch.restarts += 1;
+        }
+    }
+
+    fn applying(&self, resource: &'static str) {
+        if let Some(watch) = self.watches.lock().get_mut(resource) {
+            watch.applying_since = Some(time::Instant::now());
+        }
+    }
+
+    fn applied(&self, resource: &'static str) {
+        if let Some(watch) = self.watches.lock().get_mut(resource) {
+            if let Some(since) = watch.applying_since.take() {
+                watch.applied += 1;
+                watch.apply_time += since.elapsed();
+            }
+        }
+    }
+
+    /// Returns the watch's consecutive failures and the delay before it is
+    /// retried.
+    fn retry_delay(&self, resource: &'static str) -> (u32, time::Duration) {
+        let watches = self.watches.lock();
+        match watches.get(resource) {
+            Some(watch) if self.backoff.is_open(watch) => (watch.failures, self.backoff.max),
+            Some(watch) => (watch.failures, self.backoff.delay(watch.failures)),
+            None => (0, self.backoff.delay(0)),
+        }
+    }
+}
+
+// === impl Backoff ===
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            min: time::Duration::from_secs(1),
+            max: time::Duration::from_secs(60),
+            error_budget: 5,
+            error_window: time::Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    pub fn new(
+        min: time::Duration,
+        max: time::Duration,
+        error_budget: u32,
+        error_window: time::Duration,
+    ) -> anyhow::Result<Self> {
+        if min > max {
+            anyhow::bail!(
+                "the minimum watch backoff ({min:?}) must not exceed the maximum ({max:?})"
+            );
+        }
+        Ok(Self {
+            min,
+            max,
+            error_budget,
+            error_window,
+        })
+    }
+
+    /// Returns the delay before retrying a watch that has failed the given
+    /// number of times in a row, while its circuit is closed.
+    fn delay(&self, failures: u32) -> time::Duration {
+        let exp = failures.saturating_sub(1).min(31);
+        self.min.saturating_mul(1 << exp).min(self.max)
+    }
+
+    /// Returns true if the watch has exhausted its error budget, either by
+    /// failing in a row or by failing repeatedly within the error window.
+    fn is_open(&self, watch: &Watch) -> bool {
+        let recent = watch
+            .recent_failures
+            .iter()
+            .filter(|t| t.elapsed() <= self.error_window)
+            .count();
+        watch.failures > self.error_budget || recent > self.error_budget as usize
+    }
+}
+
+impl Collector for Instrumented {
+    fn encode(&self, mut encoder: DescriptorEncoder<'_>) -> Result<(), std::fmt::Error> {
+        let watches = self.0.watches.lock();
+
+        let mut stale_encoder = encoder.encode_descriptor(
+            "stale",
+            "Whether the index for a resource is stale because its watch is failing",
+            None,
+            MetricType::Gauge,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let stale = ConstGauge::new(watch.stale_since.is_some() as u32);
+            stale.encode(stale_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut age_encoder = encoder.encode_descriptor(
+            "stale_age",
+            "The time since the index for a resource was last known to be up-to-date",
+            Some(&Unit::Seconds),
+            MetricType::Gauge,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let age = watch
+                .stale_since
+                .map_or(0.0, |since| since.elapsed().as_secs_f64());
+            ConstGauge::new(age).encode(age_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut circuit_encoder = encoder.encode_descriptor(
+            "circuit_open",
+            "Whether a resource's watch has exhausted its error budget and is retried at the maximum delay",
+            None,
+            MetricType::Gauge,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let open = self.0.backoff.is_open(watch);
+            ConstGauge::new(open as u32).encode(circuit_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut last_event_encoder = encoder.encode_descriptor(
+            "watch_last_event_age",
+            "The time since a resource's watch last yielded an event",
+            Some(&Unit::Seconds),
+            MetricType::Gauge,
+        )?;
+        for (resource, watch) in watches.iter() {
+            if let Some(last) = watch.last_event {
+                let labels = [("resource", *resource)];
+                let age = ConstGauge::new(last.elapsed().as_secs_f64());
+                age.encode(last_event_encoder.encode_family(&labels)?)?;
+            }
+        }
+
+        let mut errors_encoder = encoder.encode_descriptor(
+            "watch_errors",
+            "The number of errors returned by a resource's watch",
+            None,
+            MetricType::Counter,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let errors = ConstCounter::new(watch.errors);
+            errors.encode(errors_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut restarts_encoder = encoder.encode_descriptor(
+            "watch_restarts",
+            "The number of times a resource's watch was restarted after failing",
+            None,
+            MetricType::Counter,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let restarts = ConstCounter::new(watch.restarts);
+            restarts.encode(restarts_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut relists_encoder = encoder.encode_descriptor(
+            "watch_relists",
+            "The number of times a resource's watch listed all resources",
+            None,
+            MetricType::Counter,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let relists = ConstCounter::new(watch.relists);
+            relists.encode(relists_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut applied_encoder = encoder.encode_descriptor(
+            "index_updates",
+            "The number of events from a resource's watch applied to the indexes",
+            None,
+            MetricType::Counter,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let applied = ConstCounter::new(watch.applied);
+            applied.encode(applied_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut apply_time_encoder = encoder.encode_descriptor(
+            "index_update_duration",
+            "The total time taken to apply events from a resource's watch to the indexes",
+            Some(&Unit::Seconds),
+            MetricType::Counter,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let time = ConstCounter::new(watch.apply_time.as_secs_f64());
+            time.encode(apply_time_encoder.encode_family(&labels)?)?;
+        }
+
+        let mut lag_encoder = encoder.encode_descriptor(
+            "index_update_lag",
+            "The time for which the event currently being applied from a resource's watch has been in progress",
+            Some(&Unit::Seconds),
+            MetricType::Gauge,
+        )?;
+        for (resource, watch) in watches.iter() {
+            let labels = [("resource", *resource)];
+            let lag = watch
+                .applying_since
+                .map_or(0.0, |since| since.elapsed().as_secs_f64());
+            ConstGauge::new(lag).encode(lag_encoder.encode_family(&labels)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+// === impl Served ===
+
+impl Served {
+    /// Checks API discovery for the resource type `T`, returning its current
+    /// state and a task that polls discovery at the given interval.
+    ///
+    /// If discovery fails, the type is assumed to be served, so that errors
+    /// are surfaced by the watch itself.
+    pub async fn discover<T>(
+        client: kube::Client,
+        interval: time::Duration,
+    ) -> (Self, impl Future<Output = ()>)
+    where
+        T: kube::Resource<DynamicType = ()>,
+    {
+        let served = is_served::<T>(&client).await.unwrap_or_else(|error| {
+            tracing::warn!(%error, resource = %T::plural(&()), "Failed to discover API resources");
+            true
+        });
+        tracing::info!(resource = %T::plural(&()), group = %T::group(&()), served, "Discovered API resource");
+        let (tx, rx) = watch::channel(served);
+
+        let task = async move {
+            let mut interval = time::interval(interval);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match is_served::<T>(&client).await {
+                    Ok(served) => {
+                        if tx.send_if_modified(|s| std::mem::replace(s, served) != served) {
+                            tracing::info!(resource = %T::plural(&()), group = %T::group(&()), served, "API resource changed");
+                        }
+                    }
+                    Err(error) => {
+                        tracing::debug!(%error, resource = %T::plural(&()), "Failed to discover API resources");
+                    }
+                }
+            }
+        };
+        (Self(rx), task)
+    }
+
+    /// Runs the watch built by `mk_watch` only while the resource type is
+    /// served. See [`gated`].
+    pub fn while_served<T, S>(
+        &self,
+        mk_watch: impl FnMut() -> S,
+    ) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+    where
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+    {
+        gated(self.0.clone(), |served| *served, mk_watch)
+    }
+}
+
+/// Runs the watch built by `mk_watch` only while `enabled` holds for the
+/// gate's current value.
+///
+/// While the watch is disabled, it is stopped and an empty `Restarted` event
+/// is emitted, so that indexes forget the resources they hold and the watch is
+/// considered synced. Whenever the gate's value changes while the watch is
+/// enabled, the watch is rebuilt so that its resources are listed again. If
+/// the gate is closed, the watch is left in its current state.
+pub fn gated<G, T, S>(
+    gate: watch::Receiver<G>,
+    enabled: impl Fn(&G) -> bool,
+    mk_watch: impl FnMut() -> S,
+) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+where
+    S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+{
+    enum State<S> {
+        Stopped { cleared: bool },
+        Running(std::pin::Pin<Box<S>>),
+    }
+
+    let init = (
+        gate,
+        enabled,
+        mk_watch,
+        State::Stopped { cleared: false },
+        true,
+    );
+    stream::unfold(
+        init,
+        |(mut gate, enabled, mut mk_watch, mut state, mut open)| async move {
+            loop {
+                state = match state {
+                    State::Stopped { cleared } => {
+                        if enabled(&gate.borrow_and_update()) {
+                            State::Running(Box::pin(mk_watch()))
+                        } else if !cleared {
+                            let event = Ok(watcher::Event::Restarted(vec![]));
+                            let state = State::Stopped { cleared: true };
+                            return Some((event, (gate, enabled, mk_watch, state, open)));
+                        } else {
+                            gate.changed().await.ok()?;
+                            State::Stopped { cleared }
+                        }
+                    }
+                    State::Running(mut watch) => {
+                        tokio::select! {
+                            event = watch.next() => {
+                                let state = State::Running(watch);
+                                return Some((event?, (gate, enabled, mk_watch, state, open)));
+                            }
+                            res = gate.changed(), if open => {
+                                if res.is_err() {
+                                    open = false;
+                                    State::Running(watch)
+                                } else if enabled(&gate.borrow_and_update()) {
+                                    tracing::info!("Restarting watch");
+                                    State::Running(Box::pin(mk_watch()))
+                                } else {
+                                    tracing::info!("Stopping disabled watch");
+                                    State::Stopped { cleared: false }
+                                }
+                            }
+                        }
+                    }
+                };
+            }
+        },
+    )
+}
+
+async fn is_served<T>(client: &kube::Client) -> kube::Result<bool>
+where
+    T: kube::Resource<DynamicType = ()>,
+{
+    match client.list_api_group_resources(&T::api_version(&())).await {
+        Ok(list) => Ok(list.resources.iter().any(|r| r.name == T::plural(&()))),
+        Err(kube::Error::Api(rsp)) if rsp.code == 404 => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+// === impl InitialSync ===
+
+impl Default for InitialSync {
+    fn default() -> Self {
+        Self {
+            pending: Default::default(),
+            synced: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl InitialSync {
+    /// Records when a watch has observed the full set of its resources.
+    pub fn track<T, S>(
+        &self,
+        resource: &'static str,
+        watch: S,
+    ) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+    where
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+    {
+        self.pending.lock().insert(resource);
+        let sync = self.clone();
+        watch.inspect(move |res| {
+            if let Ok(watcher::Event::Restarted(_)) = res {
+                sync.synced(resource);
+            }
+        })
+    }
+
+    /// Returns true if all tracked watches have completed their initial sync.
+    pub fn is_synced(&self) -> bool {
+        *self.synced.borrow()
+    }
+
+    /// Waits for all tracked watches to complete their initial sync.
+    pub async fn wait(&self) {
+        // The sender is held by `self`, so the channel cannot be closed.
+        let _ = self.synced.subscribe().wait_for(|synced| *synced).await;
+    }
+
+    fn synced(&self, resource: &'static str) {
+        let mut pending = self.pending.lock();
+        if pending.remove(resource) && pending.is_empty() {
+            tracing::info!("Initial sync of all resource watches complete");
+            self.synced.send_replace(true);
+        }
+    }
+}
+
+// === impl Resync ===
+
+impl Resync {
+    /// Runs the watch built by `mk_watch`, rebuilding it whenever a resync of
+    /// the resource is triggered. See [`gated`].
+    pub fn watch<T, S>(
+        &self,
+        resource: &'static str,
+        mk_watch: impl FnMut() -> S,
+    ) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+    where
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+    {
+        let (tx, rx) = watch::channel(());
+        self.0.lock().insert(resource, tx);
+        gated(rx, |_| true, mk_watch)
+    }
+
+    /// Triggers a resync of the named resource or, if no resource is named,
+    /// of all resources. Returns the resources that are resynced.
+    pub fn trigger(&self, resource: Option<&str>) -> Vec<&'static str> {
+        let watches = self.0.lock();
+        watches
+            .iter()
+            .filter(|(name, _)| resource.map_or(true, |r| r == **name))
+            .map(|(name, tx)| {
+                tracing::info!(resource = %name, "Resyncing watch");
+                tx.send_replace(());
+                *name
+            })
+            .collect()
+    }
+}
+
+// === impl ExcludedNamespaces ===
+
+impl ExcludedNamespaces {
+    pub fn new(selector: Selector) -> Self {
+        Self {
+            selector: Some(Arc::new(selector)),
+            namespaces: Default::default(),
+        }
+    }
+
+    pub fn is_excluded(&self, namespace: &str) -> bool {
+        self.namespaces.read().get(namespace).copied() == Some(true)
+    }
+
+    /// Applies an event from a namespace watch, returning true if a known
+    /// namespace was included or excluded, so that watches must be resynced.
+    ///
+    /// Namespaces that are added or removed do not require a resync: their
+    /// resources are filtered as they are watched.
+    pub fn apply<N: kube::Resource>(&self, event: watcher::Event<N>) -> bool {
+        let Some(selector) = self.selector.as_deref() else {
+            return false;
+        };
+        let excluded = |ns: &N| {
+            let labels = Labels::from(ns.meta().labels.clone());
+            (
+                ns.meta().name.clone().unwrap_or_default(),
+                selector.matches(&labels),
+            )
+        };
+
+        let mut namespaces = self.namespaces.write();
+        match event {
+            watcher::Event::Applied(ns) => {
+                let (name, excluded) = excluded(&ns);
+                namespaces
+                    .insert(name, excluded)
+                    .map_or(false, |prior| prior != excluded)
+            }
+            watcher::Event::Deleted(ns) => {
+                namespaces.remove(&excluded(&ns).0);
+                false
+            }
+            watcher::Event::Restarted(nss) => {
+                let prior = std::mem::replace(&mut *namespaces, nss.iter().map(excluded).collect());
+                prior
+                    .iter()
+                    .any(|(name, excluded)| namespaces.get(name).map_or(false, |e| e != excluded))
+            }
+        }
+    }
+
+    /// Drops resources in excluded namespaces from a watch.
+    ///
+    /// Deletions are always passed through, so that resources that were
+    /// indexed before their namespace was observed are removed.
+    pub fn filter<T, S>(
+        &self,
+        watch: S,
+    ) -> impl Stream<Item = Result<watcher::Event<T>, watcher::Error>>
+    where
+        T: kube::Resource,
+        S: Stream<Item = Result<watcher::Event<T>, watcher::Error>>,
+    {
+        let this = self.clone();
+        watch.filter_map(move |res| {
+            let res = match res {
+                Ok(watcher::Event::Applied(obj)) if this.excludes(&obj) => None,
+                Ok(watcher::Event::Restarted(mut objs)) => {
+                    objs.retain(|obj| !this.excludes(obj));
+                    Some(Ok(watcher::Event::Restarted(objs)))
+                }
+                res => Some(res),
+            };
+            future::ready(res)
+        })
+    }
+
+    fn excludes<T: kube::Resource>(&self, obj: &T) -> bool {
+        self.selector.is_some()
+            && obj
+                .meta()
+                .namespace
+                .as_deref()
+                .map_or(false, |ns| self.is_excluded(ns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    #[tokio::test]
+    async fn tracks_stale_watches() {
+        let health = WatchHealth::default();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watch = Box::pin(
+            health.instrument::<crate::k8s::Pod, _>("pods", UnboundedReceiverStream::new(rx)),
+        );
+        assert_eq!(health.staleness(), None);
+
+        tx.send(Err(watcher::Error::NoResourceVersion)).unwrap();
+        assert!(watch.next().await.unwrap().is_err());
+        assert!(health.staleness().is_some(), "failed watches are stale");
+
+        tx.send(Err(watcher::Error::NoResourceVersion)).unwrap();
+        let stale = health.staleness();
+        assert!(watch.next().await.unwrap().is_err());
+        assert!(
+            health.staleness() >= stale,
+            "repeated failures must not reset staleness"
+        );
+
+        tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+        assert!(watch.next().await.unwrap().is_ok());
+        assert_eq!(health.staleness(), None, "recovered watches are fresh");
+    }
+
+    #[tokio::test]
+    async fn stops_watches_while_not_served() {
+        let (served_tx, served_rx) = watch::channel(false);
+        let served = Served(served_rx);
+        let mut watch = Box::pin(served.while_served(|| {
+            stream::iter([Ok(watcher::Event::Restarted(vec![
+                crate::k8s::Pod::default(),
+            ]))])
+            .chain(stream::pending())
+        }));
+
+        // While the resource is not served, the watch is immediately synced
+        // with no resources.
+        match watch.next().await {
+            Some(Ok(watcher::Event::Restarted(pods))) => assert!(pods.is_empty()),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        served_tx.send_replace(true);
+        match watch.next().await {
+            Some(Ok(watcher::Event::Restarted(pods))) => assert_eq!(pods.len(), 1),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        // When the resource is removed, the resources are cleared.
+        served_tx.send_replace(false);
+        match watch.next().await {
+            Some(Ok(watcher::Event::Restarted(pods))) => assert!(pods.is_empty()),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        drop(served_tx);
+        assert!(watch.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn resyncs_watches() {
+        let resync = Resync::default();
+        let mut builds = 0;
+        let mut pods = Box::pin(resync.watch("pods", || {
+            builds += 1;
+            stream::iter([Ok(watcher::Event::Restarted(vec![
+                crate::k8s::Pod::default(
+                );
+                builds
+            ]))])
+            .chain(stream::pending())
+        }));
+        let _servers = resync.watch::<crate::k8s::policy::Server, _>("servers", stream::pending);
+
+        match pods.next().await {
+            Some(Ok(watcher::Event::Restarted(pods))) => assert_eq!(pods.len(), 1),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        assert!(resync.trigger(Some("nodes")).is_empty());
+        assert_eq!(resync.trigger(Some("pods")), ["pods"]);
+        match pods.next().await {
+            Some(Ok(watcher::Event::Restarted(pods))) => assert_eq!(pods.len(), 2),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        assert_eq!(resync.trigger(None), ["pods", "servers"]);
+        match pods.next().await {
+            Some(Ok(watcher::Event::Restarted(pods))) => assert_eq!(pods.len(), 3),
+            event => panic!("unexpected event: {event:?}"),
+        }
+    }
+
+    #[test]
+    fn backoff_delays() {
+        let backoff = Backoff::new(
+            time::Duration::from_secs(1),
+            time::Duration::from_secs(10),
+            6,
+            time::Duration::from_secs(60),
+        )
+        .unwrap();
+        let delays = (1..=8)
+            .map(|failures| backoff.delay(failures).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, [1, 2, 4, 8, 10, 10, 10, 10]);
+
+        assert!(
+            Backoff::new(
+                time::Duration::from_secs(10),
+                time::Duration::from_secs(1),
+                6,
+                time::Duration::from_secs(60),
+            )
+            .is_err(),
+            "the minimum delay must not exceed the maximum"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn opens_circuit_for_flapping_watches() {
+        let health = WatchHealth::new(
+            Backoff::new(
+                time::Duration::from_secs(1),
+                time::Duration::from_secs(60),
+                2,
+                time::Duration::from_secs(60),
+            )
+            .unwrap(),
+        );
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watch =
+            health.instrument::<crate::k8s::Pod, _>("pods", UnboundedReceiverStream::new(rx));
+        let mut watch = Box::pin(health.retry("pods", watch));
+
+        // Each failure follows a successful relist, so the watch never fails
+        // twice in a row, but it still exhausts its budget within the window.
+        for _ in 0..3 {
+            tx.send(Err(watcher::Error::NoResourceVersion)).unwrap();
+            tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+        }
+        let start = time::Instant::now();
+        for _ in 0..3 {
+            assert!(watch.next().await.is_some());
+        }
+        assert_eq!(
+            start.elapsed(),
+            time::Duration::from_secs(62),
+            "the third failure within the window must be retried at the maximum delay"
+        );
+        assert_eq!(health.retry_delay("pods").0, 0);
+
+        // Once the failures age out of the window, the circuit closes.
+        time::sleep(time::Duration::from_secs(61)).await;
+        assert_eq!(health.retry_delay("pods").1, time::Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_failed_watches() {
+        let health = WatchHealth::new(
+            Backoff::new(
+                time::Duration::from_secs(1),
+                time::Duration::from_secs(60),
+                1,
+                time::Duration::from_secs(60),
+            )
+            .unwrap(),
+        );
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watch =
+            health.instrument::<crate::k8s::Pod, _>("pods", UnboundedReceiverStream::new(rx));
+        let mut watch = Box::pin(health.retry("pods", watch));
+
+        for _ in 0..2 {
+            tx.send(Err(watcher::Error::NoResourceVersion)).unwrap();
+        }
+        tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+
+        // The first failure is retried after the minimum delay; the second
+        // exhausts the error budget and is retried after the maximum delay.
+        let start = time::Instant::now();
+        assert!(matches!(
+            watch.next().await,
+            Some(watcher::Event::Restarted(_))
+        ));
+        assert_eq!(start.elapsed(), time::Duration::from_secs(61));
+        assert_eq!(health.retry_delay("pods").0, 0);
+
+        let watches = health.watches.lock();
+        let pods = &watches["pods"];
+        assert_eq!(pods.errors, 2);
+        assert_eq!(pods.restarts, 2);
+        assert_eq!(pods.relists, 1);
+        assert!(pods.last_event.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn measures_index_updates() {
+        let health = WatchHealth::default();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watch =
+            health.instrument::<crate::k8s::Pod, _>("pods", UnboundedReceiverStream::new(rx));
+        let mut watch = Box::pin(health.measure("pods", watch));
+
+        tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+        tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+        assert!(watch.next().await.is_some());
+        time::sleep(time::Duration::from_secs(2)).await;
+        assert!(health.watches.lock()["pods"].applying_since.is_some());
+
+        assert!(watch.next().await.is_some());
+        let watches = health.watches.lock();
+        let pods = &watches["pods"];
+        assert_eq!(pods.applied, 1);
+        assert_eq!(pods.apply_time, time::Duration::from_secs(2));
+        assert!(pods.applying_since.is_some());
+    }
+
+    #[tokio::test]
+    async fn tracks_initial_sync() {
+        let sync = InitialSync::default();
+        let (pods_tx, pods_rx) = mpsc::unbounded_channel();
+        let mut pods = Box::pin(
+            sync.track::<crate::k8s::Pod, _>("pods", UnboundedReceiverStream::new(pods_rx)),
+        );
+        let (srvs_tx, srvs_rx) = mpsc::unbounded_channel();
+        let mut srvs = Box::pin(sync.track::<crate::k8s::policy::Server, _>(
+            "servers",
+            UnboundedReceiverStream::new(srvs_rx),
+        ));
+
+        pods_tx
+            .send(Err(watcher::Error::NoResourceVersion))
+            .unwrap();
+        assert!(pods.next().await.unwrap().is_err());
+        pods_tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+        assert!(pods.next().await.unwrap().is_ok());
+        assert!(!sync.is_synced(), "all watches must sync");
+
+        srvs_tx.send(Ok(watcher::Event::Restarted(vec![]))).unwrap();
+        assert!(srvs.next().await.unwrap().is_ok());
+        assert!(sync.is_synced());
+        time::timeout(time::Duration::from_secs(1), sync.wait())
+            .await
+            .expect("synced watches must not block");
+    }
+
+    #[tokio::test]
+    async fn excludes_namespaces() {
+        use crate::k8s::{Namespace, ObjectMeta, Pod};
+
+        fn ns(name: &str, labels: Option<(&str, &str)>) -> Namespace {
+            Namespace {
+                metadata: ObjectMeta {
+                    name: Some(name.to_string()),
+                    labels: labels
+                        .map(|(k, v)| Some((k.to_string(), v.to_string())).into_iter().collect()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        }
+        fn pod(ns: &str, name: &str) -> Pod {
+            Pod {
+                metadata: ObjectMeta {
+                    namespace: Some(ns.to_string()),
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        }
+
+        let ci = Some(("ci", "true"));
+        let excluded = ExcludedNamespaces::new(Selector::from_iter(Some(("ci", "true"))));
+        assert!(!excluded.apply(watcher::Event::Restarted(vec![
+            ns("ns-0", None),
+            ns("ci-0", ci)
+        ])));
+        assert!(excluded.is_excluded("ci-0"));
+        assert!(!excluded.is_excluded("ns-0"));
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut pods = Box::pin(excluded.filter(UnboundedReceiverStream::new(rx)));
+        tx.send(Ok(watcher::Event::Restarted(vec![
+            pod("ns-0", "pod-0"),
+            pod("ci-0", "pod-1"),
+        ])))
+        .unwrap();
+        match pods.next().await.unwrap() {
+            Ok(watcher::Event::Restarted(pods)) => {
+                assert_eq!(pods.len(), 1);
+                assert_eq!(pods[0].metadata.name.as_deref(), Some("pod-0"));
+            }
+            _ => panic!("unexpected event"),
+        }
+        tx.send(Ok(watcher::Event::Applied(pod("ci-0", "pod-2"))))
+            .unwrap();
+        tx.send(Ok(watcher::Event::Deleted(pod("ci-0", "pod-1"))))
+            .unwrap();
+        assert!(
+            matches!(pods.next().await.unwrap(), Ok(watcher::Event::Deleted(_))),
+            "deletions must not be filtered"
+        );
+
+        // New namespaces are filtered without a resync.
+        assert!(!excluded.apply(watcher::Event::Applied(ns("ci-1", ci))));
+        assert!(excluded.is_excluded("ci-1"));
+
+        // Relabeling a namespace requires a resync.
+        assert!(excluded.apply(watcher::Event::Applied(ns("ci-0", None))));
+        assert!(!excluded.is_excluded("ci-0"));
+        assert!(excluded.apply(watcher::Event::Restarted(vec![
+            ns("ns-0", ci),
+            ns("ci-1", ci)
+        ])));
+        assert!(excluded.is_excluded("ns-0"));
+
+        assert!(!excluded.apply(watcher::Event::Deleted(ns("ci-1", ci))));
+        assert!(!excluded.is_excluded("ci-1"));
+    }
+}
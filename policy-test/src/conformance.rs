@@ -0,0 +1,145 @@
+//! A runner for the Gateway API mesh (GAMMA) conformance profile.
+//!
+//! The upstream conformance suite tests a mesh by sending traffic through it.
+//! The cases run here instead assert that the policy controller's outbound API
+//! describes the behavior that each upstream test expects, so that the
+//! controller's conformance can be tracked independently of the proxy. Results
+//! are reported per feature, using the upstream feature names.
+
+use anyhow::Result;
+use std::{collections::BTreeMap, fmt, future::Future, pin::Pin, time::Duration};
+
+/// A feature of the mesh conformance profile.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Feature {
+    Mesh,
+    HttpRouteQueryParamMatching,
+    HttpRouteMethodMatching,
+    HttpResponseHeaderModification,
+    HttpRouteSchemeRedirect,
+    HttpRoutePortRedirect,
+    HttpRoutePathRedirect,
+    HttpRoutePathRewrite,
+    HttpRouteRequestMirror,
+}
+
+/// A conformance test, named after the upstream test that it models.
+pub struct Case {
+    pub name: &'static str,
+    pub feature: Feature,
+    pub run: fn(kube::Client, String) -> CaseFuture,
+}
+
+pub type CaseFuture = Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+
+/// The result of each case, by name.
+type CaseResults = Vec<(&'static str, Result<(), String>)>;
+
+/// The results of a conformance run, by feature.
+#[derive(Debug, Default)]
+pub struct Report {
+    results: BTreeMap<Feature, CaseResults>,
+}
+
+// === impl Feature ===
+
+impl Feature {
+    /// The feature's name in the upstream conformance suite.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mesh => "Mesh",
+            Self::HttpRouteQueryParamMatching => "HTTPRouteQueryParamMatching",
+            Self::HttpRouteMethodMatching => "HTTPRouteMethodMatching",
+            Self::HttpResponseHeaderModification => "HTTPResponseHeaderModification",
+            Self::HttpRouteSchemeRedirect => "HTTPRouteSchemeRedirect",
+            Self::HttpRoutePortRedirect => "HTTPRoutePortRedirect",
+            Self::HttpRoutePathRedirect => "HTTPRoutePathRedirect",
+            Self::HttpRoutePathRewrite => "HTTPRoutePathRewrite",
+            Self::HttpRouteRequestMirror => "HTTPRouteRequestMirror",
+        }
+    }
+
+    /// Core features must be supported by every conformant mesh. Other
+    /// features are extended and are reported without being required.
+    pub fn is_core(&self) -> bool {
+        matches!(self, Self::Mesh)
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+// === impl Report ===
+
+impl Report {
+    /// Runs each case in the given namespace, recording its result.
+    ///
+    /// Each case runs on its own task, so that a case that panics or times out
+    /// fails without stopping the run.
+    pub async fn run(client: &kube::Client, ns: &str, cases: &[Case], timeout: Duration) -> Self {
+        let mut report = Self::default();
+        for case in cases {
+            tracing::info!(case = case.name, feature = %case.feature, "Running");
+            let run = tokio::time::timeout(timeout, (case.run)(client.clone(), ns.to_string()));
+            let result = match tokio::spawn(run).await {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(error))) => Err(format!("{error:#}")),
+                Ok(Err(_)) => Err(format!("timed out after {timeout:?}")),
+                Err(error) => Err(format!("panicked: {error}")),
+            };
+            if let Err(error) = &result {
+                tracing::warn!(case = case.name, feature = %case.feature, %error, "Failed");
+            }
+            report
+                .results
+                .entry(case.feature)
+                .or_default()
+                .push((case.name, result));
+        }
+        report
+    }
+
+    /// Returns true if every case of the feature passed.
+    pub fn passed(&self, feature: Feature) -> bool {
+        self.results
+            .get(&feature)
+            .map_or(false, |cases| cases.iter().all(|(_, r)| r.is_ok()))
+    }
+
+    /// Returns the core features that did not pass.
+    pub fn failed_core_features(&self) -> Vec<Feature> {
+        self.results
+            .keys()
+            .copied()
+            .filter(|f| f.is_core() && !self.passed(*f))
+            .collect()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (feature, cases) in &self.results {
+            let status = if self.passed(*feature) {
+                "PASS"
+            } else {
+                "FAIL"
+            };
+            let support = if feature.is_core() {
+                "core"
+            } else {
+                "extended"
+            };
+            writeln!(f, "{status} {feature} ({support})")?;
+            for (name, result) in cases {
+                match result {
+                    Ok(()) => writeln!(f, "    PASS {name}")?,
+                    Err(error) => writeln!(f, "    FAIL {name}: {error}")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,8 +3,11 @@
 
 pub mod admission;
 pub mod bb;
+pub mod conformance;
 pub mod curl;
 pub mod grpc;
+pub mod scale;
+pub mod sim;
 pub mod web;
 
 use linkerd_policy_controller_k8s_api::{self as k8s, ResourceExt};
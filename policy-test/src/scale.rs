@@ -0,0 +1,384 @@
+//! Synthetic load for the policy controller's indexes.
+//!
+//! A [`Scale`] populates in-memory inbound and outbound indexes with a
+//! generated cluster of namespaces, pods, and routes, and then opens many watches
+//! on the policies that they serve, as proxies' streams do. A route is then
+//! added to every namespace, and the run reports how long it takes for every
+//! watch to observe it. Runs at a fixed scale can be compared across releases
+//! to catch scale regressions.
+
+use crate::sim;
+use kubert::index::IndexNamespacedResource;
+use linkerd_policy_controller_core::inbound::HttpRouteRef;
+use linkerd_policy_controller_k8s_api::{self as k8s, policy::server::Port};
+use linkerd_policy_controller_k8s_index::{inbound, outbound};
+use maplit::{btreemap, convert_args};
+use std::{
+    fmt,
+    num::NonZeroU16,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc, watch};
+
+/// The size of a synthetic cluster.
+#[derive(Clone, Debug)]
+pub struct Scale {
+    pub namespaces: usize,
+    pub pods: usize,
+    pub routes: usize,
+    pub watches: usize,
+}
+
+/// The results of a run.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub scale: Scale,
+
+    /// The time taken to index the cluster.
+    pub index: Duration,
+
+    /// The time taken for every watch to observe an update, from when updates
+    /// are first applied.
+    pub convergence: Duration,
+
+    /// The number of watches that observed the update before the run timed out.
+    pub converged: usize,
+
+    /// The growth in the process's resident memory over the run, if it can be
+    /// read. Runs should be made in separate processes for this to be
+    /// meaningful.
+    pub rss_growth: Option<u64>,
+}
+
+const PORT: u16 = 8080;
+
+/// The name of the route that is added to each namespace once the watches are
+/// open.
+const UPDATE: &str = "scale-update";
+
+// === impl Scale ===
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self {
+            namespaces: 10,
+            pods: 10,
+            routes: 10,
+            watches: 1_000,
+        }
+    }
+}
+
+impl Scale {
+    /// Reads the scale from the `POLICY_TEST_SCALE_NAMESPACES`,
+    /// `POLICY_TEST_SCALE_PODS`, `POLICY_TEST_SCALE_ROUTES`, and
+    /// `POLICY_TEST_SCALE_WATCHES` environment variables, using the default for
+    /// each that is unset.
+    pub fn from_env() -> Self {
+        fn var(name: &str, default: usize) -> usize {
+            match std::env::var(name) {
+                Ok(v) => v
+                    .parse()
+                    .unwrap_or_else(|error| panic!("invalid {name}: {error}")),
+                Err(_) => default,
+            }
+        }
+
+        let default = Self::default();
+        Self {
+            namespaces: var("POLICY_TEST_SCALE_NAMESPACES", default.namespaces),
+            pods: var("POLICY_TEST_SCALE_PODS", default.pods),
+            routes: var("POLICY_TEST_SCALE_ROUTES", default.routes),
+            watches: var("POLICY_TEST_SCALE_WATCHES", default.watches),
+        }
+    }
+
+    /// Populates the indexes, opens the watches, and updates each namespace.
+    ///
+    /// Half of the watches are on pods' inbound policies and half are on
+    /// Services' outbound policies, spread evenly across namespaces. Watches
+    /// that have not observed the update within the timeout are not counted as
+    /// converged.
+    pub async fn run(&self, timeout: Duration) -> Report {
+        assert!(self.namespaces > 0, "at least one namespace is required");
+        assert!(self.pods > 0, "at least one pod is required");
+
+        let rss = rss_bytes();
+        let cluster_info = Arc::new(sim::cluster_info());
+        let inbound = inbound::Index::shared(cluster_info.clone());
+        let outbound = outbound::Index::shared(cluster_info);
+
+        let start = Instant::now();
+        for ns in 0..self.namespaces {
+            let ns = namespace(ns);
+            IndexNamespacedResource::apply(&mut *outbound.write(), mk_service(&ns));
+            IndexNamespacedResource::apply(&mut *inbound.write(), mk_server(&ns));
+            for pod in 0..self.pods {
+                IndexNamespacedResource::apply(&mut *inbound.write(), mk_pod(&ns, pod));
+            }
+            for route in 0..self.routes {
+                let route = mk_route(&ns, &format!("route-{route}"));
+                IndexNamespacedResource::apply(&mut *inbound.write(), route.clone());
+                IndexNamespacedResource::apply(&mut *outbound.write(), route);
+            }
+        }
+        let index = start.elapsed();
+        tracing::info!(?index, "Indexed");
+
+        let port = NonZeroU16::new(PORT).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        for watch in 0..self.watches {
+            let ns = namespace(watch % self.namespaces);
+            if watch % 2 == 0 {
+                let pod = format!("pod-{}", (watch / self.namespaces) % self.pods);
+                let server_rx = inbound
+                    .read()
+                    .pod_server_rx(&ns, &pod, port)
+                    .expect("pod must be indexed");
+                spawn_watch(server_rx, tx.clone(), |server| {
+                    server.http_routes.keys().any(|r| match r {
+                        HttpRouteRef::Linkerd(gkn) => &*gkn.name == UPDATE,
+                        HttpRouteRef::Default(_) => false,
+                    })
+                });
+            } else {
+                let policy_rx = outbound
+                    .write()
+                    .outbound_policy_rx("svc-0".to_string(), ns.clone(), port, ns)
+                    .expect("service must be indexed");
+                spawn_watch(policy_rx, tx.clone(), |policy| {
+                    policy.http_routes.keys().any(|gknn| &*gknn.name == UPDATE)
+                });
+            }
+        }
+        drop(tx);
+        tracing::info!(watches = self.watches, "Watching");
+
+        let start = Instant::now();
+        for ns in 0..self.namespaces {
+            let route = mk_route(&namespace(ns), UPDATE);
+            IndexNamespacedResource::apply(&mut *inbound.write(), route.clone());
+            IndexNamespacedResource::apply(&mut *outbound.write(), route);
+        }
+        let mut converged = 0;
+        let _ = tokio::time::timeout(timeout, async {
+            while rx.recv().await.is_some() {
+                converged += 1;
+            }
+        })
+        .await;
+        let convergence = start.elapsed();
+        tracing::info!(?convergence, converged, "Converged");
+
+        Report {
+            scale: self.clone(),
+            index,
+            convergence,
+            converged,
+            rss_growth: rss.zip(rss_bytes()).map(|(a, b)| b.saturating_sub(a)),
+        }
+    }
+}
+
+// === impl Report ===
+
+impl Report {
+    /// Returns true if every watch observed the update.
+    pub fn converged(&self) -> bool {
+        self.converged == self.scale.watches
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Scale {
+            namespaces,
+            pods,
+            routes,
+            watches,
+        } = self.scale;
+        writeln!(
+            f,
+            "{namespaces} namespaces x {pods} pods x {routes} routes, {watches} watches"
+        )?;
+        writeln!(f, "    index:       {:?}", self.index)?;
+        writeln!(
+            f,
+            "    convergence: {:?} ({}/{watches} watches)",
+            self.convergence, self.converged
+        )?;
+        match self.rss_growth {
+            Some(bytes) => writeln!(f, "    rss growth:  {} KiB", bytes / 1024),
+            None => writeln!(f, "    rss growth:  unknown"),
+        }
+    }
+}
+
+/// Reports on `tx` once a watched value satisfies `converged`.
+fn spawn_watch<T: Send + Sync + 'static>(
+    mut rx: watch::Receiver<T>,
+    tx: mpsc::UnboundedSender<()>,
+    converged: impl Fn(&T) -> bool + Send + 'static,
+) {
+    tokio::spawn(async move {
+        loop {
+            if converged(&rx.borrow_and_update()) {
+                let _ = tx.send(());
+                return;
+            }
+            if rx.changed().await.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Reads the process's resident memory, on Linux.
+fn rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib = status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+fn namespace(i: usize) -> String {
+    format!("ns-{i}")
+}
+
+fn mk_service(ns: &str) -> k8s::Service {
+    k8s::Service {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some("svc-0".to_string()),
+            ..Default::default()
+        },
+        spec: Some(k8s::ServiceSpec {
+            ports: Some(vec![k8s::ServicePort {
+                port: PORT.into(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn mk_server(ns: &str) -> k8s::policy::Server {
+    k8s::policy::Server {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some("srv-0".to_string()),
+            ..Default::default()
+        },
+        spec: k8s::policy::ServerSpec {
+            selector: k8s::policy::server::Selector::Pod(
+                Some(("app", "app-0")).into_iter().collect(),
+            ),
+            port: Port::Number(NonZeroU16::new(PORT).unwrap()),
+            proxy_protocol: Some(k8s::policy::server::ProxyProtocol::Http1),
+            detect_timeout: None,
+        },
+    }
+}
+
+fn mk_pod(ns: &str, i: usize) -> k8s::Pod {
+    k8s::Pod {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(format!("pod-{i}")),
+            labels: Some(convert_args!(btreemap!("app" => "app-0"))),
+            ..Default::default()
+        },
+        spec: Some(k8s::PodSpec {
+            containers: vec![k8s::Container {
+                name: "app".to_string(),
+                ports: Some(vec![k8s::ContainerPort {
+                    container_port: PORT.into(),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Builds a route that is attached to both the namespace's Server and its
+/// Service, with the status that the status controller would write.
+fn mk_route(ns: &str, name: &str) -> k8s::policy::HttpRoute {
+    use k8s::policy::httproute as api;
+    let parent_refs = vec![
+        api::ParentReference {
+            group: Some("policy.linkerd.io".to_string()),
+            kind: Some("Server".to_string()),
+            namespace: None,
+            name: "srv-0".to_string(),
+            section_name: None,
+            port: None,
+        },
+        api::ParentReference {
+            group: Some("core".to_string()),
+            kind: Some("Service".to_string()),
+            namespace: Some(ns.to_string()),
+            name: "svc-0".to_string(),
+            section_name: None,
+            port: Some(PORT),
+        },
+    ];
+    let parents = parent_refs
+        .iter()
+        .map(|parent_ref| k8s_gateway_api::RouteParentStatus {
+            parent_ref: parent_ref.clone(),
+            controller_name: "linkerd.io/policy-controller".to_string(),
+            conditions: vec![k8s::Condition {
+                last_transition_time: k8s::Time(
+                    k8s_openapi::chrono::DateTime::<k8s_openapi::chrono::Utc>::MIN_UTC,
+                ),
+                message: "".to_string(),
+                observed_generation: None,
+                reason: "Accepted".to_string(),
+                status: "True".to_string(),
+                type_: "Accepted".to_string(),
+            }],
+        })
+        .collect();
+
+    api::HttpRoute {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        spec: api::HttpRouteSpec {
+            inner: api::CommonRouteSpec {
+                parent_refs: Some(parent_refs),
+            },
+            hostnames: None,
+            rules: Some(vec![api::HttpRouteRule {
+                name: None,
+                matches: Some(vec![api::HttpRouteMatch {
+                    path: Some(api::HttpPathMatch::PathPrefix {
+                        value: format!("/{name}"),
+                    }),
+                    headers: None,
+                    query_params: None,
+                    method: None,
+                }]),
+                filters: None,
+                backend_refs: None,
+                timeouts: None,
+            }]),
+        },
+        status: Some(api::HttpRouteStatus {
+            inner: k8s_gateway_api::RouteStatus { parents },
+        }),
+    }
+}
@@ -0,0 +1,330 @@
+//! A deterministic simulation of the policy controller's indexes.
+//!
+//! A [`Sim`] drives in-memory inbound and outbound indexes with a scripted
+//! sequence of [`Event`]s, so that ordering bugs can be reproduced without a
+//! cluster. Scripts may model watch gaps (events that the controller misses
+//! until its watches are reset) and controller restarts. Tests assert on the
+//! policies that the indexes serve after each step.
+//!
+//! Status updates are not simulated, since the status index writes through a
+//! Kubernetes client.
+
+use kubert::index::{IndexNamespacedResource, NamespacedRemoved};
+use linkerd_policy_controller_core::{inbound::InboundServer, outbound::OutboundPolicy};
+use linkerd_policy_controller_k8s_api::{self as k8s, ResourceExt};
+use linkerd_policy_controller_k8s_index::{inbound, outbound, ClusterInfo, DefaultPolicy};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroU16,
+    sync::Arc,
+    time::Duration,
+};
+
+/// A simulated policy controller and the cluster that it watches.
+pub struct Sim {
+    cluster_info: Arc<ClusterInfo>,
+    indexes: Indexes,
+
+    /// The resources that exist in the simulated cluster.
+    cluster: BTreeMap<Key, Resource>,
+
+    /// The resources that the indexes have observed.
+    observed: BTreeSet<Key>,
+}
+
+/// A step in a simulation script.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// A resource is created or updated.
+    Apply(Resource),
+
+    /// A resource is deleted. Only the resource's metadata is used.
+    Delete(Resource),
+
+    /// An event occurs in the cluster but is not observed by the controller
+    /// until its watches are reset.
+    Missed(Box<Event>),
+
+    /// All of the controller's watches are reset with the resources in the
+    /// cluster, as happens when a watch is disconnected.
+    Resync,
+
+    /// The controller restarts, building new indexes from the resources in the
+    /// cluster.
+    Restart,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    kind: &'static str,
+    namespace: String,
+    name: String,
+}
+
+struct Indexes {
+    inbound: inbound::SharedIndex,
+    outbound: outbound::SharedIndex,
+}
+
+/// Declares the resource types that the simulation indexes and the indexes to
+/// which each is dispatched, mirroring the controller's watches.
+macro_rules! resources {
+    ($($variant:ident($ty:ty) => [$($index:ident),+]),+ $(,)?) => {
+        /// A resource that the simulated controller watches.
+        #[derive(Clone, Debug)]
+        pub enum Resource {
+            $($variant($ty)),+
+        }
+
+        $(
+            impl From<$ty> for Resource {
+                fn from(resource: $ty) -> Self {
+                    Self::$variant(resource)
+                }
+            }
+        )+
+
+        impl Resource {
+            fn key(&self) -> Key {
+                match self {
+                    $(Self::$variant(r) => Key {
+                        kind: stringify!($variant),
+                        namespace: r.namespace().expect("resource must have a namespace"),
+                        name: r.name_unchecked(),
+                    }),+
+                }
+            }
+        }
+
+        impl Indexes {
+            fn apply(&self, resource: Resource) {
+                match resource {
+                    $(Resource::$variant(r) => {
+                        $(IndexNamespacedResource::<$ty>::apply(
+                            &mut *self.$index.write(),
+                            r.clone(),
+                        );)+
+                    }),+
+                }
+            }
+
+            fn delete(&self, key: &Key) {
+                $(if key.kind == stringify!($variant) {
+                    $(IndexNamespacedResource::<$ty>::delete(
+                        &mut *self.$index.write(),
+                        key.namespace.clone(),
+                        key.name.clone(),
+                    );)+
+                })+
+            }
+
+            fn reset(&self, cluster: &BTreeMap<Key, Resource>, removed: &BTreeSet<Key>) {
+                $({
+                    let resources = cluster
+                        .values()
+                        .filter_map(|r| match r {
+                            Resource::$variant(r) => Some(r.clone()),
+                            _ => None,
+                        })
+                        .collect::<Vec<$ty>>();
+                    let mut deleted = NamespacedRemoved::default();
+                    for key in removed.iter().filter(|k| k.kind == stringify!($variant)) {
+                        deleted
+                            .entry(key.namespace.clone())
+                            .or_default()
+                            .insert(key.name.clone());
+                    }
+                    $(IndexNamespacedResource::<$ty>::reset(
+                        &mut *self.$index.write(),
+                        resources.clone(),
+                        deleted.clone(),
+                    );)+
+                })+
+            }
+        }
+    };
+}
+
+resources! {
+    Pod(k8s::Pod) => [inbound],
+    ExternalWorkload(k8s::external_workload::ExternalWorkload) => [inbound],
+    Server(k8s::policy::Server) => [inbound],
+    ServerAuthorization(k8s::policy::ServerAuthorization) => [inbound],
+    AuthorizationPolicy(k8s::policy::AuthorizationPolicy) => [inbound],
+    MeshTLSAuthentication(k8s::policy::MeshTLSAuthentication) => [inbound],
+    NetworkAuthentication(k8s::policy::NetworkAuthentication) => [inbound],
+    HttpRoute(k8s::policy::HttpRoute) => [inbound, outbound],
+    GatewayHttpRoute(k8s_gateway_api::HttpRoute) => [inbound, outbound],
+    Service(k8s::Service) => [outbound],
+    EndpointSlice(k8s::api::discovery::v1::EndpointSlice) => [outbound],
+}
+
+/// The cluster configuration used by default: a cluster on `10.0.0.0/8` whose
+/// default policy allows all traffic.
+pub fn cluster_info() -> ClusterInfo {
+    ClusterInfo {
+        networks: vec!["10.0.0.0/8".parse().unwrap()],
+        control_plane_ns: "linkerd".to_string(),
+        dns_domain: "cluster.local".to_string(),
+        identity_domain: "cluster.local".to_string(),
+        default_policy: DefaultPolicy::Allow {
+            authenticated_only: false,
+            cluster_only: false,
+        },
+        default_detect_timeout: Duration::from_secs(10),
+        default_opaque_ports: Default::default(),
+        probe_networks: vec!["10.0.0.0/8".parse().unwrap()],
+        max_authorizations_per_server: None,
+        meshed_pods_only: false,
+        remote_identity_domains: Default::default(),
+    }
+}
+
+// === impl Sim ===
+
+impl Default for Sim {
+    fn default() -> Self {
+        Self::new(cluster_info())
+    }
+}
+
+impl Sim {
+    pub fn new(cluster_info: ClusterInfo) -> Self {
+        let cluster_info = Arc::new(cluster_info);
+        Self {
+            indexes: Indexes::new(&cluster_info),
+            cluster_info,
+            cluster: BTreeMap::new(),
+            observed: BTreeSet::new(),
+        }
+    }
+
+    /// Runs each event of a script in order.
+    pub fn run(&mut self, script: impl IntoIterator<Item = Event>) {
+        for event in script {
+            self.step(event);
+        }
+    }
+
+    /// Runs a single event.
+    pub fn step(&mut self, event: Event) {
+        tracing::debug!(?event, "Simulating");
+        match event {
+            Event::Apply(resource) => {
+                let key = self.record_apply(resource.clone());
+                self.indexes.apply(resource);
+                self.observed.insert(key);
+            }
+
+            Event::Delete(resource) => {
+                let key = self.record_delete(&resource);
+                self.indexes.delete(&key);
+                self.observed.remove(&key);
+            }
+
+            Event::Missed(event) => match *event {
+                Event::Apply(resource) => {
+                    self.record_apply(resource);
+                }
+                Event::Delete(resource) => {
+                    self.record_delete(&resource);
+                }
+                event => panic!("only resource events may be missed: {event:?}"),
+            },
+
+            Event::Resync => {
+                let removed = self
+                    .observed
+                    .iter()
+                    .filter(|key| !self.cluster.contains_key(key))
+                    .cloned()
+                    .collect();
+                self.indexes.reset(&self.cluster, &removed);
+                self.observed = self.cluster.keys().cloned().collect();
+            }
+
+            Event::Restart => {
+                self.indexes = Indexes::new(&self.cluster_info);
+                self.indexes.reset(&self.cluster, &BTreeSet::new());
+                self.observed = self.cluster.keys().cloned().collect();
+            }
+        }
+    }
+
+    /// Returns the policy currently served for a pod's port, or `None` if the
+    /// pod is not indexed.
+    pub fn inbound(&self, namespace: &str, pod: &str, port: u16) -> Option<InboundServer> {
+        let port = NonZeroU16::new(port).expect("port must not be zero");
+        let rx = self
+            .indexes
+            .inbound
+            .read()
+            .pod_server_rx(namespace, pod, port)
+            .ok()?;
+        let server = rx.borrow().clone();
+        Some(server)
+    }
+
+    /// Returns the policy currently served for a Service's port to clients in
+    /// the Service's namespace.
+    pub fn outbound(&self, namespace: &str, service: &str, port: u16) -> OutboundPolicy {
+        let port = NonZeroU16::new(port).expect("port must not be zero");
+        let rx = self
+            .indexes
+            .outbound
+            .write()
+            .outbound_policy_rx(
+                service.to_string(),
+                namespace.to_string(),
+                port,
+                namespace.to_string(),
+            )
+            .expect("outbound policy must be served");
+        let policy = rx.borrow().clone();
+        policy
+    }
+
+    fn record_apply(&mut self, resource: Resource) -> Key {
+        let key = resource.key();
+        self.cluster.insert(key.clone(), resource);
+        key
+    }
+
+    fn record_delete(&mut self, resource: &Resource) -> Key {
+        let key = resource.key();
+        self.cluster.remove(&key);
+        key
+    }
+}
+
+// === impl Indexes ===
+
+impl Indexes {
+    fn new(cluster_info: &Arc<ClusterInfo>) -> Self {
+        Self {
+            inbound: inbound::Index::shared(cluster_info.clone()),
+            outbound: outbound::Index::shared(cluster_info.clone()),
+        }
+    }
+}
+
+/// Enumerates every interleaving of two scripts that preserves the order of
+/// each, so that a test may assert that all orderings converge.
+pub fn interleavings(a: &[Event], b: &[Event]) -> Vec<Vec<Event>> {
+    match (a.split_first(), b.split_first()) {
+        (None, _) => vec![b.to_vec()],
+        (_, None) => vec![a.to_vec()],
+        (Some((a0, a_rest)), Some((b0, b_rest))) => {
+            let mut scripts = Vec::new();
+            for mut script in interleavings(a_rest, b) {
+                script.insert(0, a0.clone());
+                scripts.push(script);
+            }
+            for mut script in interleavings(a, b_rest) {
+                script.insert(0, b0.clone());
+                scripts.push(script);
+            }
+            scripts
+        }
+    }
+}
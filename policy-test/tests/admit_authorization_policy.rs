@@ -13,11 +13,12 @@ async fn accepts_valid() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "Server".to_string(),
                 name: "api".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
@@ -46,11 +47,12 @@ async fn accepts_targets_namespace() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: None,
                 kind: "Namespace".to_string(),
                 name: ns,
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
@@ -79,11 +81,12 @@ async fn rejects_targets_other_namespace() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: None,
                 kind: "Namespace".to_string(),
                 name: "foobar".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
@@ -112,11 +115,12 @@ async fn accepts_targets_route() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "HttpRoute".to_string(),
                 name: "route-foo".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
@@ -145,11 +149,12 @@ async fn accepts_valid_with_only_meshtls() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "Server".to_string(),
                 name: "api".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![NamespacedTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "MeshTLSAuthentication".to_string(),
@@ -170,11 +175,12 @@ async fn accepts_valid_with_only_network() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "Server".to_string(),
                 name: "api".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![NamespacedTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "NetworkAuthentication".to_string(),
@@ -195,11 +201,12 @@ async fn accepts_empty_required_authentications() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "Server".to_string(),
                 name: "deny".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![],
         },
     })
@@ -255,11 +262,100 @@ async fn rejects_target_ref_deployment() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("apps".to_string()),
                 kind: "Deployment".to_string(),
                 name: "someapp".to_string(),
-            },
+            }),
+            target_refs: None,
+            required_authentication_refs: vec![NamespacedTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "NetworkAuthentication".to_string(),
+                namespace: Some("linkerd".to_string()),
+                name: "cluster-nets".to_string(),
+            }],
+        },
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn accepts_multiple_targets() {
+    admission::accepts(|ns| AuthorizationPolicy {
+        metadata: api::ObjectMeta {
+            namespace: Some(ns),
+            name: Some("test".to_string()),
+            ..Default::default()
+        },
+        spec: AuthorizationPolicySpec {
+            target_ref: None,
+            target_refs: Some(vec![
+                LocalTargetRef {
+                    group: Some("policy.linkerd.io".to_string()),
+                    kind: "Server".to_string(),
+                    name: "api".to_string(),
+                },
+                LocalTargetRef {
+                    group: Some("policy.linkerd.io".to_string()),
+                    kind: "HTTPRoute".to_string(),
+                    name: "admin".to_string(),
+                },
+            ]),
+            required_authentication_refs: vec![NamespacedTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "NetworkAuthentication".to_string(),
+                namespace: Some("linkerd".to_string()),
+                name: "cluster-nets".to_string(),
+            }],
+        },
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rejects_missing_targets() {
+    admission::rejects(|ns| AuthorizationPolicy {
+        metadata: api::ObjectMeta {
+            namespace: Some(ns),
+            name: Some("test".to_string()),
+            ..Default::default()
+        },
+        spec: AuthorizationPolicySpec {
+            target_ref: None,
+            target_refs: Some(vec![]),
+            required_authentication_refs: vec![NamespacedTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "NetworkAuthentication".to_string(),
+                namespace: Some("linkerd".to_string()),
+                name: "cluster-nets".to_string(),
+            }],
+        },
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rejects_target_refs_deployment() {
+    admission::rejects(|ns| AuthorizationPolicy {
+        metadata: api::ObjectMeta {
+            namespace: Some(ns),
+            name: Some("test".to_string()),
+            ..Default::default()
+        },
+        spec: AuthorizationPolicySpec {
+            target_ref: None,
+            target_refs: Some(vec![
+                LocalTargetRef {
+                    group: Some("policy.linkerd.io".to_string()),
+                    kind: "Server".to_string(),
+                    name: "api".to_string(),
+                },
+                LocalTargetRef {
+                    group: Some("apps".to_string()),
+                    kind: "Deployment".to_string(),
+                    name: "someapp".to_string(),
+                },
+            ]),
             required_authentication_refs: vec![NamespacedTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "NetworkAuthentication".to_string(),
@@ -280,11 +376,12 @@ async fn rejects_duplicate_mtls_authns() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "Server".to_string(),
                 name: "some-srv".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
@@ -313,11 +410,12 @@ async fn rejects_duplicate_network_authns() {
             ..Default::default()
         },
         spec: AuthorizationPolicySpec {
-            target_ref: LocalTargetRef {
+            target_ref: Some(LocalTargetRef {
                 group: Some("policy.linkerd.io".to_string()),
                 kind: "Server".to_string(),
                 name: "some-srv".to_string(),
-            },
+            }),
+            target_refs: None,
             required_authentication_refs: vec![
                 NamespacedTargetRef {
                     group: Some("policy.linkerd.io".to_string()),
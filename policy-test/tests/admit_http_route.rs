@@ -31,6 +31,7 @@ async fn rejects_relative_path_match() {
             },
             hostnames: None,
             rules: Some(vec![HttpRouteRule {
+                name: None,
                 matches: Some(vec![HttpRouteMatch {
                     path: Some(HttpPathMatch::Exact {
                         value: "foo/bar".to_string(),
@@ -57,6 +58,7 @@ async fn rejects_relative_redirect_path() {
             },
             hostnames: None,
             rules: Some(vec![HttpRouteRule {
+                name: None,
                 matches: Some(vec![HttpRouteMatch {
                     path: Some(HttpPathMatch::Exact {
                         value: "/foo".to_string(),
@@ -104,6 +106,7 @@ fn meta(ns: impl ToString) -> api::ObjectMeta {
 
 fn rules() -> Vec<HttpRouteRule> {
     vec![HttpRouteRule {
+        name: None,
         matches: Some(vec![HttpRouteMatch {
             path: Some(HttpPathMatch::Exact {
                 value: "/foo".to_string(),
@@ -1,6 +1,9 @@
 use linkerd_policy_controller_k8s_api::{
     self as api,
-    policy::{MeshTLSAuthentication, MeshTLSAuthenticationSpec, NamespacedTargetRef},
+    policy::{
+        meshtls_authentication::RemoteIdentityRef, MeshTLSAuthentication,
+        MeshTLSAuthenticationSpec, NamespacedTargetRef,
+    },
 };
 use linkerd_policy_test::admission;
 
@@ -112,6 +115,55 @@ async fn rejects_both_refs_and_strings() {
                 name: "default".to_string(),
                 namespace: None,
             }]),
+            remote_identity_refs: None,
+        },
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn accepts_remote_ref() {
+    admission::accepts(|ns| MeshTLSAuthentication {
+        metadata: api::ObjectMeta {
+            namespace: Some(ns),
+            name: Some("test".to_string()),
+            ..Default::default()
+        },
+        spec: MeshTLSAuthenticationSpec {
+            remote_identity_refs: Some(vec![RemoteIdentityRef {
+                cluster: "east".to_string(),
+                target: NamespacedTargetRef {
+                    group: None,
+                    kind: "ServiceAccount".to_string(),
+                    name: "default".to_string(),
+                    namespace: None,
+                },
+            }]),
+            ..Default::default()
+        },
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rejects_remote_ref_without_cluster() {
+    admission::rejects(|ns| MeshTLSAuthentication {
+        metadata: api::ObjectMeta {
+            namespace: Some(ns),
+            name: Some("test".to_string()),
+            ..Default::default()
+        },
+        spec: MeshTLSAuthenticationSpec {
+            remote_identity_refs: Some(vec![RemoteIdentityRef {
+                cluster: "".to_string(),
+                target: NamespacedTargetRef {
+                    group: None,
+                    kind: "ServiceAccount".to_string(),
+                    name: "default".to_string(),
+                    namespace: None,
+                },
+            }]),
+            ..Default::default()
         },
     })
     .await;
@@ -0,0 +1,613 @@
+use anyhow::{bail, ensure, Context, Result};
+use k8s_gateway_api as api;
+use kube::ResourceExt;
+use linkerd_policy_controller_k8s_api as k8s;
+use linkerd_policy_test::{
+    conformance::{Case, CaseFuture, Feature, Report},
+    create, create_service, grpc, with_temp_ns,
+};
+use std::time::Duration;
+use tokio::time;
+
+/// Runs the mesh conformance profile against the outbound policy API and
+/// reports the results by feature. Only core features are required to pass.
+///
+/// The report is written to the path in `POLICY_TEST_CONFORMANCE_REPORT`, if
+/// it is set.
+#[tokio::test(flavor = "current_thread")]
+async fn mesh_http_profile() {
+    with_temp_ns(|client, ns| async move {
+        let report = Report::run(&client, &ns, CASES, Duration::from_secs(30)).await;
+        println!("{report}");
+        if let Ok(path) = std::env::var("POLICY_TEST_CONFORMANCE_REPORT") {
+            std::fs::write(path, report.to_string()).expect("failed to write report");
+        }
+
+        let failed = report.failed_core_features();
+        assert!(failed.is_empty(), "core features failed: {failed:?}");
+    })
+    .await;
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "MeshBasic",
+        feature: Feature::Mesh,
+        run: mesh_basic,
+    },
+    Case {
+        name: "MeshHTTPRouteSimpleSameNamespace",
+        feature: Feature::Mesh,
+        run: mesh_same_namespace,
+    },
+    Case {
+        name: "MeshHTTPRouteMatching",
+        feature: Feature::Mesh,
+        run: mesh_matching,
+    },
+    Case {
+        name: "MeshHTTPRouteWeight",
+        feature: Feature::Mesh,
+        run: mesh_weight,
+    },
+    Case {
+        name: "MeshHTTPRouteRequestHeaderModifier",
+        feature: Feature::Mesh,
+        run: mesh_request_header_modifier,
+    },
+    Case {
+        name: "MeshHTTPRouteQueryParamMatching",
+        feature: Feature::HttpRouteQueryParamMatching,
+        run: mesh_query_param_matching,
+    },
+    Case {
+        name: "MeshHTTPRouteMethodMatching",
+        feature: Feature::HttpRouteMethodMatching,
+        run: mesh_method_matching,
+    },
+    Case {
+        name: "MeshHTTPRouteResponseHeaderModifier",
+        feature: Feature::HttpResponseHeaderModification,
+        run: mesh_response_header_modifier,
+    },
+    Case {
+        name: "MeshHTTPRouteRedirectScheme",
+        feature: Feature::HttpRouteSchemeRedirect,
+        run: mesh_redirect_scheme,
+    },
+    Case {
+        name: "MeshHTTPRouteRedirectPort",
+        feature: Feature::HttpRoutePortRedirect,
+        run: mesh_redirect_port,
+    },
+    Case {
+        name: "MeshHTTPRouteRedirectPath",
+        feature: Feature::HttpRoutePathRedirect,
+        run: mesh_redirect_path,
+    },
+    Case {
+        name: "MeshHTTPRouteRewritePath",
+        feature: Feature::HttpRoutePathRewrite,
+        run: mesh_rewrite_path,
+    },
+    Case {
+        name: "MeshHTTPRouteRequestMirror",
+        feature: Feature::HttpRouteRequestMirror,
+        run: mesh_request_mirror,
+    },
+];
+
+fn mesh_basic(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "basic", 80).await;
+        let mut policy_api = grpc::OutboundPolicyClient::port_forwarded(&client).await;
+        let config = policy_api.get(&ns, &svc, 80).await?;
+        let routes = http_routes(&config)?;
+        ensure!(routes.len() == 1, "expected a single default route");
+        let backends = rule_backends(&routes[0])?;
+        ensure!(
+            backends == [(1, authority(&ns, "basic", 80))],
+            "default route must target the Service; got {backends:?}"
+        );
+        Ok(())
+    })
+}
+
+fn mesh_same_namespace(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "same-ns", 80).await;
+        create_service(&client, &ns, "same-ns-v1", 80).await;
+        let rule = mk_rule(None, vec![backend_ref("same-ns-v1", None)]);
+        let route = apply_route(&client, &ns, &svc, "same-ns", rule).await?;
+        let backends = rule_backends(&route)?;
+        ensure!(
+            backends == [(1, authority(&ns, "same-ns-v1", 80))],
+            "route must target its backend; got {backends:?}"
+        );
+        Ok(())
+    })
+}
+
+fn mesh_matching(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "matching", 80).await;
+        let rule = mk_rule(
+            Some(api::HttpRouteMatch {
+                path: Some(api::HttpPathMatch::PathPrefix {
+                    value: "/v2".to_string(),
+                }),
+                headers: Some(vec![api::HttpHeaderMatch::Exact {
+                    name: "version".to_string(),
+                    value: "two".to_string(),
+                }]),
+                query_params: None,
+                method: None,
+            }),
+            vec![],
+        );
+        let route = apply_route(&client, &ns, &svc, "matching", rule).await?;
+        let m = single_match(&route)?;
+        ensure!(
+            m.path
+                == Some(grpc::http_route::PathMatch {
+                    kind: Some(grpc::http_route::path_match::Kind::Prefix(
+                        "/v2".to_string()
+                    )),
+                }),
+            "path match must be served; got {:?}",
+            m.path
+        );
+        ensure!(
+            m.headers
+                == [grpc::http_route::HeaderMatch {
+                    name: "version".to_string(),
+                    value: Some(grpc::http_route::header_match::Value::Exact(
+                        b"two".to_vec()
+                    )),
+                }],
+            "header match must be served; got {:?}",
+            m.headers
+        );
+        Ok(())
+    })
+}
+
+fn mesh_weight(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "weight", 80).await;
+        create_service(&client, &ns, "weight-v1", 80).await;
+        create_service(&client, &ns, "weight-v2", 80).await;
+        let rule = mk_rule(
+            None,
+            vec![
+                backend_ref("weight-v1", Some(70)),
+                backend_ref("weight-v2", Some(30)),
+            ],
+        );
+        let route = apply_route(&client, &ns, &svc, "weight", rule).await?;
+        let backends = rule_backends(&route)?;
+        ensure!(
+            backends
+                == [
+                    (70, authority(&ns, "weight-v1", 80)),
+                    (30, authority(&ns, "weight-v2", 80))
+                ],
+            "backends must be weighted; got {backends:?}"
+        );
+        Ok(())
+    })
+}
+
+fn mesh_request_header_modifier(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "req-header", 80).await;
+        let filter = api::HttpRouteFilter::RequestHeaderModifier {
+            request_header_modifier: header_filter(),
+        };
+        let route = apply_filtered_route(&client, &ns, &svc, "req-header", filter).await?;
+        match single_filter(&route)? {
+            grpc::outbound::http_route::filter::Kind::RequestHeaderModifier(_) => Ok(()),
+            kind => bail!("expected a RequestHeaderModifier filter; got {kind:?}"),
+        }
+    })
+}
+
+fn mesh_query_param_matching(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "query-param", 80).await;
+        let rule = mk_rule(
+            Some(api::HttpRouteMatch {
+                path: None,
+                headers: None,
+                query_params: Some(vec![api::HttpQueryParamMatch::Exact {
+                    name: "animal".to_string(),
+                    value: "whale".to_string(),
+                }]),
+                method: None,
+            }),
+            vec![],
+        );
+        let route = apply_route(&client, &ns, &svc, "query-param", rule).await?;
+        let m = single_match(&route)?;
+        ensure!(
+            m.query_params
+                == [grpc::http_route::QueryParamMatch {
+                    name: "animal".to_string(),
+                    value: Some(grpc::http_route::query_param_match::Value::Exact(
+                        "whale".to_string()
+                    )),
+                }],
+            "query parameter match must be served; got {:?}",
+            m.query_params
+        );
+        Ok(())
+    })
+}
+
+fn mesh_method_matching(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "method", 80).await;
+        let rule = mk_rule(
+            Some(api::HttpRouteMatch {
+                path: None,
+                headers: None,
+                query_params: None,
+                method: Some("POST".to_string()),
+            }),
+            vec![],
+        );
+        let route = apply_route(&client, &ns, &svc, "method", rule).await?;
+        let m = single_match(&route)?;
+        ensure!(
+            m.method
+                == Some(grpc::http_types::HttpMethod {
+                    r#type: Some(grpc::http_types::http_method::Type::Registered(
+                        grpc::http_types::http_method::Registered::Post.into()
+                    )),
+                }),
+            "method match must be served; got {:?}",
+            m.method
+        );
+        Ok(())
+    })
+}
+
+fn mesh_response_header_modifier(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "rsp-header", 80).await;
+        let filter = api::HttpRouteFilter::ResponseHeaderModifier {
+            response_header_modifier: header_filter(),
+        };
+        let route = apply_filtered_route(&client, &ns, &svc, "rsp-header", filter).await?;
+        match single_filter(&route)? {
+            grpc::outbound::http_route::filter::Kind::ResponseHeaderModifier(_) => Ok(()),
+            kind => bail!("expected a ResponseHeaderModifier filter; got {kind:?}"),
+        }
+    })
+}
+
+fn mesh_redirect_scheme(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "redirect-scheme", 80).await;
+        let filter = redirect_filter(api::HttpRequestRedirectFilter {
+            scheme: Some("https".to_string()),
+            hostname: None,
+            path: None,
+            port: None,
+            status_code: None,
+        });
+        let route = apply_filtered_route(&client, &ns, &svc, "redirect-scheme", filter).await?;
+        let redirect = single_redirect(&route)?;
+        ensure!(
+            redirect.scheme
+                == Some(grpc::http_types::Scheme {
+                    r#type: Some(grpc::http_types::scheme::Type::Registered(
+                        grpc::http_types::scheme::Registered::Https.into()
+                    )),
+                }),
+            "redirect scheme must be served; got {:?}",
+            redirect.scheme
+        );
+        ensure!(
+            redirect.port == 443,
+            "redirect port must default to the scheme's port; got {}",
+            redirect.port
+        );
+        Ok(())
+    })
+}
+
+fn mesh_redirect_port(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "redirect-port", 80).await;
+        let filter = redirect_filter(api::HttpRequestRedirectFilter {
+            scheme: None,
+            hostname: None,
+            path: None,
+            port: Some(8083),
+            status_code: None,
+        });
+        let route = apply_filtered_route(&client, &ns, &svc, "redirect-port", filter).await?;
+        let redirect = single_redirect(&route)?;
+        ensure!(
+            redirect.port == 8083,
+            "redirect port must be served; got {}",
+            redirect.port
+        );
+        Ok(())
+    })
+}
+
+fn mesh_redirect_path(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "redirect-path", 80).await;
+        let filter = redirect_filter(api::HttpRequestRedirectFilter {
+            scheme: None,
+            hostname: None,
+            path: Some(api::HttpPathModifier::ReplaceFullPath {
+                replace_full_path: "/one".to_string(),
+            }),
+            port: None,
+            status_code: None,
+        });
+        let route = apply_filtered_route(&client, &ns, &svc, "redirect-path", filter).await?;
+        let redirect = single_redirect(&route)?;
+        ensure!(
+            redirect.path
+                == Some(grpc::http_route::PathModifier {
+                    replace: Some(grpc::http_route::path_modifier::Replace::Full(
+                        "/one".to_string()
+                    )),
+                }),
+            "redirect path must be served; got {:?}",
+            redirect.path
+        );
+        Ok(())
+    })
+}
+
+fn mesh_rewrite_path(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "rewrite-path", 80).await;
+        let filter = api::HttpRouteFilter::URLRewrite {
+            url_rewrite: api::HttpUrlRewriteFilter {
+                hostname: None,
+                path: Some(api::HttpPathModifier::ReplacePrefixMatch {
+                    replace_prefix_match: "/one".to_string(),
+                }),
+            },
+        };
+        let route = apply_filtered_route(&client, &ns, &svc, "rewrite-path", filter).await?;
+        let kind = single_filter(&route)?;
+        bail!("URL rewrites are not served; got {kind:?}")
+    })
+}
+
+fn mesh_request_mirror(client: kube::Client, ns: String) -> CaseFuture {
+    Box::pin(async move {
+        let svc = create_service(&client, &ns, "mirror", 80).await;
+        create_service(&client, &ns, "mirror-v2", 80).await;
+        let filter = api::HttpRouteFilter::RequestMirror {
+            request_mirror: api::HttpRequestMirrorFilter {
+                backend_ref: api::BackendObjectReference {
+                    group: None,
+                    kind: None,
+                    name: "mirror-v2".to_string(),
+                    namespace: None,
+                    port: Some(80),
+                },
+            },
+        };
+        let route = apply_filtered_route(&client, &ns, &svc, "mirror", filter).await?;
+        let kind = single_filter(&route)?;
+        bail!("request mirrors are not served; got {kind:?}")
+    })
+}
+
+/* Helpers */
+
+/// Creates a route with a single rule parented to the given Service and waits
+/// for the outbound API to serve it.
+async fn apply_route(
+    client: &kube::Client,
+    ns: &str,
+    svc: &k8s::Service,
+    name: &str,
+    rule: api::HttpRouteRule,
+) -> Result<grpc::outbound::HttpRoute> {
+    let route = api::HttpRoute {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        spec: api::HttpRouteSpec {
+            inner: api::CommonRouteSpec {
+                parent_refs: Some(vec![api::ParentReference {
+                    group: Some("core".to_string()),
+                    kind: Some("Service".to_string()),
+                    namespace: svc.namespace(),
+                    name: svc.name_unchecked(),
+                    section_name: None,
+                    port: Some(80),
+                }]),
+            },
+            hostnames: None,
+            rules: Some(vec![rule]),
+        },
+        status: None,
+    };
+    create(client, route).await;
+
+    let mut policy_api = grpc::OutboundPolicyClient::port_forwarded(client).await;
+    loop {
+        let config = policy_api.get(ns, svc, 80).await?;
+        let served = http_routes(&config)?
+            .iter()
+            .find(|route| route_name(route) == Some(name))
+            .cloned();
+        if let Some(route) = served {
+            return Ok(route);
+        }
+        time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn apply_filtered_route(
+    client: &kube::Client,
+    ns: &str,
+    svc: &k8s::Service,
+    name: &str,
+    filter: api::HttpRouteFilter,
+) -> Result<grpc::outbound::HttpRoute> {
+    let mut rule = mk_rule(None, vec![]);
+    rule.filters = Some(vec![filter]);
+    apply_route(client, ns, svc, name, rule).await
+}
+
+fn mk_rule(
+    route_match: Option<api::HttpRouteMatch>,
+    backend_refs: Vec<api::HttpBackendRef>,
+) -> api::HttpRouteRule {
+    api::HttpRouteRule {
+        matches: route_match.map(|m| vec![m]),
+        filters: None,
+        backend_refs: Some(backend_refs),
+    }
+}
+
+fn backend_ref(name: &str, weight: Option<u16>) -> api::HttpBackendRef {
+    api::HttpBackendRef {
+        backend_ref: Some(api::BackendRef {
+            weight,
+            inner: api::BackendObjectReference {
+                group: None,
+                kind: None,
+                name: name.to_string(),
+                namespace: None,
+                port: Some(80),
+            },
+        }),
+        filters: None,
+    }
+}
+
+fn header_filter() -> api::HttpRequestHeaderFilter {
+    api::HttpRequestHeaderFilter {
+        set: Some(vec![api::HttpHeader {
+            name: "x-header-set".to_string(),
+            value: "set-overwrites-values".to_string(),
+        }]),
+        add: None,
+        remove: None,
+    }
+}
+
+fn redirect_filter(request_redirect: api::HttpRequestRedirectFilter) -> api::HttpRouteFilter {
+    api::HttpRouteFilter::RequestRedirect { request_redirect }
+}
+
+fn http_routes(config: &grpc::outbound::OutboundPolicy) -> Result<&[grpc::outbound::HttpRoute]> {
+    let kind = config
+        .protocol
+        .as_ref()
+        .and_then(|p| p.kind.as_ref())
+        .context("policy must have a protocol")?;
+    match kind {
+        grpc::outbound::proxy_protocol::Kind::Detect(detect) => Ok(&detect
+            .http1
+            .as_ref()
+            .context("protocol must have an HTTP/1 config")?
+            .routes),
+        kind => bail!("protocol must be detected; got {kind:?}"),
+    }
+}
+
+fn route_name(route: &grpc::outbound::HttpRoute) -> Option<&str> {
+    match route.metadata.as_ref()?.kind.as_ref()? {
+        grpc::meta::metadata::Kind::Resource(resource) => Some(&resource.name),
+        grpc::meta::metadata::Kind::Default(_) => None,
+    }
+}
+
+fn single_rule(route: &grpc::outbound::HttpRoute) -> Result<&grpc::outbound::http_route::Rule> {
+    match route.rules.as_slice() {
+        [rule] => Ok(rule),
+        rules => bail!("expected a single rule; got {}", rules.len()),
+    }
+}
+
+fn single_match(route: &grpc::outbound::HttpRoute) -> Result<&grpc::http_route::HttpRouteMatch> {
+    match single_rule(route)?.matches.as_slice() {
+        [m] => Ok(m),
+        matches => bail!("expected a single match; got {}", matches.len()),
+    }
+}
+
+fn single_filter(
+    route: &grpc::outbound::HttpRoute,
+) -> Result<&grpc::outbound::http_route::filter::Kind> {
+    match single_rule(route)?.filters.as_slice() {
+        [filter] => filter.kind.as_ref().context("filter must have a kind"),
+        filters => bail!("expected a single filter; got {}", filters.len()),
+    }
+}
+
+fn single_redirect(
+    route: &grpc::outbound::HttpRoute,
+) -> Result<&grpc::http_route::RequestRedirect> {
+    match single_filter(route)? {
+        grpc::outbound::http_route::filter::Kind::Redirect(redirect) => Ok(redirect),
+        kind => bail!("expected a RequestRedirect filter; got {kind:?}"),
+    }
+}
+
+/// Returns the weight and destination authority of each of a route's backends.
+fn rule_backends(route: &grpc::outbound::HttpRoute) -> Result<Vec<(u32, String)>> {
+    use grpc::outbound::http_route::distribution::Kind;
+
+    let kind = single_rule(route)?
+        .backends
+        .as_ref()
+        .and_then(|d| d.kind.as_ref())
+        .context("rule must have backends")?;
+    let backends = match kind {
+        Kind::FirstAvailable(dist) => dist
+            .backends
+            .iter()
+            .map(|b| (1, b.backend.as_ref()))
+            .collect::<Vec<_>>(),
+        Kind::RandomAvailable(dist) => dist
+            .backends
+            .iter()
+            .map(|b| {
+                (
+                    b.weight,
+                    b.backend.as_ref().and_then(|b| b.backend.as_ref()),
+                )
+            })
+            .collect(),
+        kind => bail!("unexpected distribution: {kind:?}"),
+    };
+    backends
+        .into_iter()
+        .map(|(weight, backend)| {
+            let backend = backend.context("backend must be set")?;
+            match backend.kind.as_ref() {
+                Some(grpc::outbound::backend::Kind::Balancer(balance)) => {
+                    match balance.discovery.as_ref().and_then(|d| d.kind.as_ref()) {
+                        Some(grpc::outbound::backend::endpoint_discovery::Kind::Dst(dst)) => {
+                            Ok((weight, dst.path.clone()))
+                        }
+                        None => bail!("balancer must have discovery"),
+                    }
+                }
+                kind => bail!("backend must be a balancer; got {kind:?}"),
+            }
+        })
+        .collect()
+}
+
+fn authority(ns: &str, name: &str, port: u16) -> String {
+    format!("{name}.{ns}.svc.cluster.local:{port}")
+}
@@ -570,7 +570,8 @@ fn authz_policy(
             ..Default::default()
         },
         spec: k8s::policy::AuthorizationPolicySpec {
-            target_ref: target,
+            target_ref: Some(target),
+            target_refs: None,
             required_authentication_refs: authns.into_iter().collect(),
         },
     }
@@ -586,6 +587,7 @@ fn all_authenticated(ns: &str) -> k8s::policy::MeshTLSAuthentication {
         spec: k8s::policy::MeshTLSAuthenticationSpec {
             identity_refs: None,
             identities: Some(vec!["*".to_string()]),
+            remote_identity_refs: None,
         },
     }
 }
@@ -605,6 +607,7 @@ fn ns_authenticated(ns: &str) -> k8s::policy::MeshTLSAuthentication {
                 namespace: None,
             }]),
             identities: None,
+            remote_identity_refs: None,
         },
     }
 }
@@ -651,6 +654,7 @@ fn http_route(name: &str, ns: &str, server_name: &str, path: &str) -> k8s::polic
             },
             hostnames: None,
             rules: Some(vec![k8s::policy::httproute::HttpRouteRule {
+                name: None,
                 matches: Some(vec![k8s::policy::httproute::HttpRouteMatch {
                     path: Some(k8s::policy::httproute::HttpPathMatch::Exact {
                         value: path.to_string(),
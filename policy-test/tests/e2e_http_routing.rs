@@ -69,6 +69,7 @@ async fn path_based_routing() {
 
 fn rule(path: String, backend: String) -> k8s::policy::httproute::HttpRouteRule {
     k8s::policy::httproute::HttpRouteRule {
+        name: None,
         matches: Some(vec![k8s::policy::httproute::HttpRouteMatch {
             path: Some(k8s::policy::httproute::HttpPathMatch::Exact { value: path }),
             ..Default::default()
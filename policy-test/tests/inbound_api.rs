@@ -190,7 +190,8 @@ async fn server_with_authorization_policy() {
                     ..Default::default()
                 },
                 spec: k8s::policy::AuthorizationPolicySpec {
-                    target_ref: k8s::policy::LocalTargetRef::from_resource(&server),
+                    target_ref: Some(k8s::policy::LocalTargetRef::from_resource(&server)),
+                    target_refs: None,
                     required_authentication_refs: vec![
                         k8s::policy::NamespacedTargetRef::from_resource(&all_nets),
                     ],
@@ -346,7 +347,8 @@ async fn server_with_http_route() {
                     ..Default::default()
                 },
                 spec: k8s::policy::AuthorizationPolicySpec {
-                    target_ref: k8s::policy::LocalTargetRef::from_resource(&route),
+                    target_ref: Some(k8s::policy::LocalTargetRef::from_resource(&route)),
+                    target_refs: None,
                     required_authentication_refs: vec![
                         k8s::policy::NamespacedTargetRef::from_resource(&all_nets),
                     ],
@@ -542,6 +544,7 @@ fn mk_admin_route(ns: &str, name: &str) -> k8s::policy::HttpRoute {
             },
             hostnames: None,
             rules: Some(vec![api::HttpRouteRule {
+                name: None,
                 matches: Some(vec![api::HttpRouteMatch {
                     path: Some(api::HttpPathMatch::Exact {
                         value: "/metrics".to_string(),
@@ -583,6 +586,7 @@ fn mk_admin_route_with_path(ns: &str, name: &str, path: &str) -> k8s::policy::Ht
             },
             hostnames: None,
             rules: Some(vec![api::HttpRouteRule {
+                name: None,
                 matches: Some(vec![api::HttpRouteMatch {
                     path: Some(api::HttpPathMatch::Exact {
                         value: path.to_string(),
@@ -633,6 +637,7 @@ fn mk_admin_server(ns: &str, name: &str) -> k8s::policy::Server {
             selector: k8s::policy::server::Selector::Pod(k8s::labels::Selector::default()),
             port: k8s::policy::server::Port::Number(4191.try_into().unwrap()),
             proxy_protocol: Some(k8s::policy::server::ProxyProtocol::Http1),
+            detect_timeout: None,
         },
     }
 }
@@ -67,11 +67,12 @@ async fn external_workload_srv_with_authorization_policy() {
                     ..Default::default()
                 },
                 spec: k8s::policy::AuthorizationPolicySpec {
-                    target_ref: LocalTargetRef {
+                    target_ref: Some(LocalTargetRef {
                         group: Some("policy.linkerd.io".to_string()),
                         kind: "server".to_string(),
                         name: server.name_any(),
-                    },
+                    }),
+                    target_refs: None,
                     required_authentication_refs: vec![],
                 },
             },
@@ -203,6 +204,7 @@ async fn external_workload_srv_with_http_route() {
                     },
                     hostnames: None,
                     rules: Some(vec![api::HttpRouteRule {
+                        name: None,
                         matches: Some(vec![api::HttpRouteMatch {
                             path: Some(api::HttpPathMatch::Exact {
                                 value: "/endpoint".to_string(),
@@ -307,7 +309,8 @@ async fn external_workload_srv_with_http_route() {
                     ..Default::default()
                 },
                 spec: k8s::policy::AuthorizationPolicySpec {
-                    target_ref: k8s::policy::LocalTargetRef::from_resource(&created_route),
+                    target_ref: Some(k8s::policy::LocalTargetRef::from_resource(&created_route)),
+                    target_refs: None,
                     required_authentication_refs: vec![
                         k8s::policy::NamespacedTargetRef::from_resource(&all_networks),
                     ],
@@ -459,6 +462,7 @@ fn mk_http_server(ns: &str, name: &str) -> k8s::policy::Server {
             ),
             port: k8s::policy::server::Port::Name("http".to_string()),
             proxy_protocol: Some(k8s::policy::server::ProxyProtocol::Http1),
+            detect_timeout: None,
         },
     }
 }
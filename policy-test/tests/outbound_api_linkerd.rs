@@ -1230,6 +1230,7 @@ fn mk_http_route(ns: &str, name: &str, svc: &k8s::Service, port: Option<u16>) ->
             },
             hostnames: None,
             rules: Some(vec![api::HttpRouteRule {
+                name: None,
                 matches: Some(vec![api::HttpRouteMatch {
                     path: Some(api::HttpPathMatch::Exact {
                         value: "/foo".to_string(),
@@ -100,9 +100,10 @@ async fn no_cluster_ip() {
         let status = await_route_status(&client, &ns, "test-route").await;
         let cond = find_route_condition(&status.parents, "test-service")
             .expect("must have at least one 'Accepted' condition set for parent");
-        // Parent with no ClusterIP should not match.
-        assert_eq!(cond.status, "False");
-        assert_eq!(cond.reason, "NoMatchingParent");
+        // Headless parents are accepted, since their pods are resolved
+        // through their endpoints.
+        assert_eq!(cond.status, "True");
+        assert_eq!(cond.reason, "Accepted");
     })
     .await;
 }
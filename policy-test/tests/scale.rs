@@ -0,0 +1,23 @@
+use linkerd_policy_test::scale::Scale;
+use std::time::Duration;
+
+/// Populates the indexes with a synthetic cluster and reports how long it takes
+/// for every watch to observe an update. This test does not require a cluster.
+///
+/// The scale is read from the environment (see [`Scale::from_env`]). If
+/// `POLICY_TEST_SCALE_MAX_CONVERGENCE_MS` is set, the test fails when
+/// convergence takes longer.
+#[tokio::test(flavor = "current_thread")]
+async fn converges_at_scale() {
+    let report = Scale::from_env().run(Duration::from_secs(120)).await;
+    println!("{report}");
+    assert!(report.converged(), "not all watches converged:\n{report}");
+
+    if let Ok(max) = std::env::var("POLICY_TEST_SCALE_MAX_CONVERGENCE_MS") {
+        let max = Duration::from_millis(max.parse().expect("invalid maximum convergence"));
+        assert!(
+            report.convergence <= max,
+            "convergence exceeded {max:?}:\n{report}"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use linkerd_policy_controller_core::inbound::{AuthorizationRef, ServerRef};
+use linkerd_policy_controller_k8s_api::{self as k8s, policy::server::Port};
+use linkerd_policy_test::sim::{interleavings, Event, Sim};
+use maplit::{btreemap, convert_args};
+
+/// Tests that a pod's policy does not depend on the order in which it and the
+/// resources that authorize it are observed.
+#[test]
+fn authorization_policy_converges_in_any_order() {
+    let pod_events = [
+        Event::Apply(mk_pod("ns-0", "pod-0").into()),
+        Event::Apply(mk_server("ns-0", "srv-8080", 8080).into()),
+    ];
+    let authz_events = [
+        Event::Apply(mk_network_authentication("ns-0", "net-0").into()),
+        Event::Apply(mk_authorization_policy("ns-0", "authz-0", "srv-8080", "net-0").into()),
+    ];
+
+    let mut expected = Sim::default();
+    expected.run(pod_events.iter().chain(&authz_events).cloned());
+    let expected = expected
+        .inbound("ns-0", "pod-0", 8080)
+        .expect("pod must be indexed");
+    assert_eq!(
+        expected.reference,
+        ServerRef::Server("srv-8080".to_string())
+    );
+    assert!(expected
+        .authorizations
+        .contains_key(&AuthorizationRef::AuthorizationPolicy(
+            "authz-0".to_string()
+        )));
+
+    for script in interleavings(&pod_events, &authz_events) {
+        let mut sim = Sim::default();
+        sim.run(script.clone());
+        assert_eq!(
+            sim.inbound("ns-0", "pod-0", 8080).as_ref(),
+            Some(&expected),
+            "{script:#?}"
+        );
+    }
+}
+
+/// Tests that a deletion missed during a watch gap is observed when the
+/// controller's watches are reset.
+#[test]
+fn missed_deletion_observed_on_resync() {
+    let mut sim = Sim::default();
+    sim.run([
+        Event::Apply(mk_pod("ns-0", "pod-0").into()),
+        Event::Apply(mk_server("ns-0", "srv-8080", 8080).into()),
+        Event::Missed(Box::new(Event::Delete(
+            mk_server("ns-0", "srv-8080", 8080).into(),
+        ))),
+    ]);
+    assert_eq!(
+        sim.inbound("ns-0", "pod-0", 8080).unwrap().reference,
+        ServerRef::Server("srv-8080".to_string())
+    );
+
+    sim.step(Event::Resync);
+    assert_eq!(
+        sim.inbound("ns-0", "pod-0", 8080).unwrap().reference,
+        ServerRef::Default("all-unauthenticated")
+    );
+}
+
+/// Tests that a restarted controller serves the same policy as one that
+/// observed each event.
+#[test]
+fn restart_rebuilds_policy() {
+    let mut sim = Sim::default();
+    sim.run([
+        Event::Apply(mk_pod("ns-0", "pod-0").into()),
+        Event::Apply(mk_server("ns-0", "srv-8080", 8080).into()),
+        Event::Apply(mk_network_authentication("ns-0", "net-0").into()),
+        Event::Apply(mk_authorization_policy("ns-0", "authz-0", "srv-8080", "net-0").into()),
+        Event::Apply(mk_service("ns-0", "svc-0", None).into()),
+    ]);
+    let inbound = sim.inbound("ns-0", "pod-0", 8080);
+    let outbound = sim.outbound("ns-0", "svc-0", 80);
+
+    sim.step(Event::Restart);
+    assert_eq!(sim.inbound("ns-0", "pod-0", 8080), inbound);
+    assert_eq!(sim.outbound("ns-0", "svc-0", 80), outbound);
+}
+
+/// Tests that an update missed during a watch gap is applied when the
+/// controller's watches are reset.
+#[test]
+fn missed_update_observed_on_resync() {
+    let mut sim = Sim::default();
+    sim.run([
+        Event::Apply(mk_service("ns-0", "svc-0", None).into()),
+        Event::Missed(Box::new(Event::Apply(
+            mk_service("ns-0", "svc-0", Some("80")).into(),
+        ))),
+    ]);
+    assert!(!sim.outbound("ns-0", "svc-0", 80).opaque);
+
+    sim.step(Event::Resync);
+    assert!(sim.outbound("ns-0", "svc-0", 80).opaque);
+}
+
+fn mk_pod(ns: &str, name: &str) -> k8s::Pod {
+    k8s::Pod {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            labels: Some(convert_args!(btreemap!("app" => "app-0"))),
+            ..Default::default()
+        },
+        spec: Some(k8s::PodSpec {
+            containers: vec![k8s::Container {
+                name: "app".to_string(),
+                ports: Some(vec![k8s::ContainerPort {
+                    container_port: 8080,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn mk_server(ns: &str, name: &str, port: u16) -> k8s::policy::Server {
+    k8s::policy::Server {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        spec: k8s::policy::ServerSpec {
+            selector: k8s::policy::server::Selector::Pod(
+                Some(("app", "app-0")).into_iter().collect(),
+            ),
+            port: Port::Number(port.try_into().unwrap()),
+            proxy_protocol: Some(k8s::policy::server::ProxyProtocol::Http1),
+            detect_timeout: None,
+        },
+    }
+}
+
+fn mk_network_authentication(ns: &str, name: &str) -> k8s::policy::NetworkAuthentication {
+    k8s::policy::NetworkAuthentication {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        spec: k8s::policy::NetworkAuthenticationSpec {
+            networks: vec![k8s::policy::Network {
+                cidr: "10.0.0.0/8".parse().unwrap(),
+                except: None,
+            }],
+        },
+    }
+}
+
+fn mk_authorization_policy(
+    ns: &str,
+    name: &str,
+    server: &str,
+    authn: &str,
+) -> k8s::policy::AuthorizationPolicy {
+    k8s::policy::AuthorizationPolicy {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        spec: k8s::policy::AuthorizationPolicySpec {
+            target_ref: Some(k8s::policy::LocalTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "Server".to_string(),
+                name: server.to_string(),
+            }),
+            target_refs: None,
+            required_authentication_refs: vec![k8s::policy::NamespacedTargetRef {
+                group: Some("policy.linkerd.io".to_string()),
+                kind: "NetworkAuthentication".to_string(),
+                name: authn.to_string(),
+                namespace: None,
+            }],
+        },
+    }
+}
+
+fn mk_service(ns: &str, name: &str, opaque_ports: Option<&str>) -> k8s::Service {
+    k8s::Service {
+        metadata: k8s::ObjectMeta {
+            namespace: Some(ns.to_string()),
+            name: Some(name.to_string()),
+            annotations: opaque_ports
+                .map(|ports| convert_args!(btreemap!("config.linkerd.io/opaque-ports" => ports))),
+            ..Default::default()
+        },
+        spec: Some(k8s::ServiceSpec {
+            cluster_ip: Some("10.1.0.1".to_string()),
+            ports: Some(vec![k8s::ServicePort {
+                port: 80,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}